@@ -0,0 +1,348 @@
+// Persistent content-hash cache
+// Digests keyed by (path, kind) and validated against (dev, inode, size, mtime)
+// so unchanged files are never re-read; bounded by least-recently-used eviction
+#![allow(clippy::needless_raw_string_hashes)]
+
+use rusqlite::{Connection, OptionalExtension, Result, Transaction};
+use std::fs::Metadata;
+use std::path::Path;
+
+/// Default number of cached digests kept before eviction
+pub const DEFAULT_MAX_ENTRIES: usize = 500_000;
+
+/// Which digest of a file is cached
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HashKind {
+    /// SHA-256 of the full content (duplicate detection)
+    Sha256,
+    /// SHA-256 of the head and tail blocks (duplicate pre-filter)
+    Sha256Edges,
+    /// MD5 of the full content (PACS file inventory)
+    Md5,
+}
+
+impl HashKind {
+    const fn as_str(self) -> &'static str {
+        match self {
+            Self::Sha256 => "sha256",
+            Self::Sha256Edges => "sha256_edges",
+            Self::Md5 => "md5",
+        }
+    }
+}
+
+/// File identity and version; a cached digest is only valid while this matches
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileStamp {
+    /// Device the file lives on
+    pub dev: u64,
+    /// Inode number of the file
+    pub inode: u64,
+    /// Apparent size in bytes
+    pub size: u64,
+    /// Last modification time in nanoseconds since the epoch
+    pub mtime_ns: i64,
+}
+
+impl FileStamp {
+    /// Build a stamp from file metadata
+    #[cfg(unix)]
+    #[must_use]
+    pub fn from_metadata(metadata: &Metadata) -> Self {
+        use std::os::unix::fs::MetadataExt;
+
+        Self {
+            dev: metadata.dev(),
+            inode: metadata.ino(),
+            size: metadata.len(),
+            mtime_ns: metadata
+                .mtime()
+                .saturating_mul(1_000_000_000)
+                .saturating_add(metadata.mtime_nsec()),
+        }
+    }
+
+    /// Build a stamp from file metadata (no inode on this platform)
+    #[cfg(not(unix))]
+    #[must_use]
+    pub fn from_metadata(metadata: &Metadata) -> Self {
+        let mtime_ns = metadata
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+            .and_then(|d| i64::try_from(d.as_nanos()).ok())
+            .unwrap_or(0);
+
+        Self {
+            dev: 0,
+            inode: 0,
+            size: metadata.len(),
+            mtime_ns,
+        }
+    }
+}
+
+/// SQLite-backed cache of file content digests shared by all scanners
+pub struct HashCache {
+    conn: Connection,
+    max_entries: usize,
+}
+
+// SQLite integers are signed; u64 identifiers round-trip through a bit cast
+#[allow(clippy::cast_possible_wrap)]
+const fn to_sql_u64(value: u64) -> i64 {
+    value as i64
+}
+
+impl HashCache {
+    /// Open (or create) the hash cache database
+    pub fn new(db_path: &str) -> Result<Self> {
+        let conn = Connection::open(db_path)?;
+
+        conn.execute_batch(
+            r#"
+            PRAGMA journal_mode = WAL;
+            PRAGMA synchronous = NORMAL;
+
+            CREATE TABLE IF NOT EXISTS hash_cache (
+                path TEXT NOT NULL,
+                kind TEXT NOT NULL,
+                dev INTEGER NOT NULL,
+                inode INTEGER NOT NULL,
+                size INTEGER NOT NULL,
+                mtime_ns INTEGER NOT NULL,
+                digest TEXT NOT NULL,
+                last_used INTEGER NOT NULL,
+                PRIMARY KEY (path, kind)
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_hash_cache_last_used
+                ON hash_cache(last_used);
+            "#,
+        )?;
+
+        Ok(Self {
+            conn,
+            max_entries: DEFAULT_MAX_ENTRIES,
+        })
+    }
+
+    /// Set the maximum number of cached digests kept by [`Self::evict_to_limit`]
+    #[must_use]
+    pub const fn with_max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = max_entries;
+        self
+    }
+
+    /// Start a transaction so many lookups and inserts are written in one batch
+    pub fn begin_batch(&self) -> Result<Transaction<'_>> {
+        self.conn.unchecked_transaction()
+    }
+
+    /// Return the cached digest if the file is unchanged since it was hashed
+    pub fn get(&self, path: &Path, kind: HashKind, stamp: &FileStamp) -> Result<Option<String>> {
+        let key = path.to_string_lossy();
+
+        let digest = self
+            .conn
+            .prepare_cached(
+                r#"
+                SELECT digest FROM hash_cache
+                WHERE path = ?1 AND kind = ?2
+                  AND dev = ?3 AND inode = ?4 AND size = ?5 AND mtime_ns = ?6
+                "#,
+            )?
+            .query_row(
+                (
+                    key.as_ref(),
+                    kind.as_str(),
+                    to_sql_u64(stamp.dev),
+                    to_sql_u64(stamp.inode),
+                    to_sql_u64(stamp.size),
+                    stamp.mtime_ns,
+                ),
+                |row| row.get::<_, String>(0),
+            )
+            .optional()?;
+
+        if digest.is_some() {
+            self.conn
+                .prepare_cached(
+                    r#"
+                    UPDATE hash_cache
+                    SET last_used = (SELECT COALESCE(MAX(last_used), 0) + 1 FROM hash_cache)
+                    WHERE path = ?1 AND kind = ?2
+                    "#,
+                )?
+                .execute((key.as_ref(), kind.as_str()))?;
+        }
+
+        Ok(digest)
+    }
+
+    /// Store a digest, replacing any stale entry for the same path and kind
+    pub fn put(&self, path: &Path, kind: HashKind, stamp: &FileStamp, digest: &str) -> Result<()> {
+        self.conn
+            .prepare_cached(
+                r#"
+                INSERT OR REPLACE INTO hash_cache
+                    (path, kind, dev, inode, size, mtime_ns, digest, last_used)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7,
+                    (SELECT COALESCE(MAX(last_used), 0) + 1 FROM hash_cache))
+                "#,
+            )?
+            .execute((
+                path.to_string_lossy().as_ref(),
+                kind.as_str(),
+                to_sql_u64(stamp.dev),
+                to_sql_u64(stamp.inode),
+                to_sql_u64(stamp.size),
+                stamp.mtime_ns,
+                digest,
+            ))?;
+
+        Ok(())
+    }
+
+    /// Forget every digest cached for `path`
+    pub fn invalidate(&self, path: &Path) -> Result<usize> {
+        self.conn.execute(
+            "DELETE FROM hash_cache WHERE path = ?1",
+            [path.to_string_lossy().as_ref()],
+        )
+    }
+
+    /// Forget every digest cached for files under `dir`
+    pub fn invalidate_subtree(&self, dir: &Path) -> Result<usize> {
+        let key = dir.to_string_lossy();
+        let prefix = format!("{}{}", key, std::path::MAIN_SEPARATOR);
+
+        // substr() instead of LIKE so '%' and '_' in paths need no escaping
+        self.conn.execute(
+            "DELETE FROM hash_cache WHERE path = ?1 OR substr(path, 1, length(?2)) = ?2",
+            (key.as_ref(), &prefix),
+        )
+    }
+
+    /// Evict least recently used digests until at most `max_entries` remain
+    pub fn evict_to_limit(&self) -> Result<usize> {
+        let count = self.len()?;
+        if count <= self.max_entries {
+            return Ok(0);
+        }
+
+        let excess = to_sql_u64((count - self.max_entries) as u64);
+        self.conn.execute(
+            r#"
+            DELETE FROM hash_cache WHERE rowid IN (
+                SELECT rowid FROM hash_cache ORDER BY last_used ASC LIMIT ?1
+            )
+            "#,
+            [excess],
+        )
+    }
+
+    /// Number of cached digests
+    pub fn len(&self) -> Result<usize> {
+        self.conn
+            .query_row("SELECT COUNT(*) FROM hash_cache", [], |row| row.get(0))
+    }
+
+    /// Whether the cache holds no digests
+    pub fn is_empty(&self) -> Result<bool> {
+        Ok(self.len()? == 0)
+    }
+
+    /// Drop every cached digest
+    pub fn clear(&self) -> Result<()> {
+        self.conn.execute_batch("DELETE FROM hash_cache;")
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+mod tests {
+    use super::*;
+    use tempfile::NamedTempFile;
+
+    fn stamp(size: u64) -> FileStamp {
+        FileStamp {
+            dev: 1,
+            inode: 7,
+            size,
+            mtime_ns: 1_700_000_000_000_000_000,
+        }
+    }
+
+    #[test]
+    fn test_hit_requires_matching_stamp_and_kind() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let cache = HashCache::new(temp_file.path().to_str().unwrap()).unwrap();
+        let path = Path::new("/data/image.iso");
+
+        cache
+            .put(path, HashKind::Sha256, &stamp(10), "abc")
+            .unwrap();
+
+        assert_eq!(
+            cache.get(path, HashKind::Sha256, &stamp(10)).unwrap(),
+            Some("abc".to_string())
+        );
+        assert_eq!(cache.get(path, HashKind::Sha256, &stamp(11)).unwrap(), None);
+        assert_eq!(cache.get(path, HashKind::Md5, &stamp(10)).unwrap(), None);
+
+        cache.invalidate(path).unwrap();
+        assert!(cache.is_empty().unwrap());
+    }
+
+    #[test]
+    fn test_eviction_drops_least_recently_used() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let cache = HashCache::new(temp_file.path().to_str().unwrap())
+            .unwrap()
+            .with_max_entries(2);
+
+        cache
+            .put(Path::new("/a"), HashKind::Sha256, &stamp(1), "a")
+            .unwrap();
+        cache
+            .put(Path::new("/b"), HashKind::Sha256, &stamp(1), "b")
+            .unwrap();
+        cache
+            .get(Path::new("/a"), HashKind::Sha256, &stamp(1))
+            .unwrap();
+        cache
+            .put(Path::new("/c"), HashKind::Sha256, &stamp(1), "c")
+            .unwrap();
+
+        assert_eq!(cache.evict_to_limit().unwrap(), 1);
+        assert!(cache
+            .get(Path::new("/a"), HashKind::Sha256, &stamp(1))
+            .unwrap()
+            .is_some());
+        assert!(cache
+            .get(Path::new("/b"), HashKind::Sha256, &stamp(1))
+            .unwrap()
+            .is_none());
+        assert!(cache
+            .get(Path::new("/c"), HashKind::Sha256, &stamp(1))
+            .unwrap()
+            .is_some());
+    }
+
+    #[test]
+    fn test_invalidate_subtree() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let cache = HashCache::new(temp_file.path().to_str().unwrap()).unwrap();
+
+        cache
+            .put(Path::new("/w/p/a"), HashKind::Md5, &stamp(1), "a")
+            .unwrap();
+        cache
+            .put(Path::new("/w/p2/b"), HashKind::Md5, &stamp(1), "b")
+            .unwrap();
+
+        assert_eq!(cache.invalidate_subtree(Path::new("/w/p")).unwrap(), 1);
+        assert_eq!(cache.len().unwrap(), 1);
+    }
+}
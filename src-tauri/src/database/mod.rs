@@ -6,6 +6,19 @@ use chrono::{DateTime, Utc};
 use rusqlite::{Connection, Result};
 use serde::{Deserialize, Serialize};
 
+/// Content-hash cache shared by duplicate detection and PACS.
+pub mod hash_cache;
+pub use hash_cache::HashCache;
+/// Incremental scan index for skipping unchanged directories on rescans.
+pub mod scan_index;
+pub use scan_index::ScanIndex;
+/// Background scan jobs and their results, kept across restarts.
+pub mod scan_jobs;
+pub use scan_jobs::ScanJobStore;
+/// Persisted size trees for drill-down views across restarts.
+pub mod size_tree;
+pub use size_tree::SizeTreeStore;
+
 /// Project monitoring database with OSM-lite migration support
 pub struct ProjectDatabase {
     conn: Connection,
@@ -0,0 +1,416 @@
+// Persistent incremental scan index
+// Per-directory listings keyed by (dev, inode, mtime, ctime) so rescans can
+// skip reading directories whose metadata has not changed
+#![allow(clippy::needless_raw_string_hashes)]
+
+use crate::utils::disk_usage::FileUsage;
+use rusqlite::{Connection, OptionalExtension, Result, Transaction};
+use std::fs::Metadata;
+use std::path::Path;
+
+/// Directory identity and change stamp used to decide whether a listing is stale
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DirStamp {
+    /// Device the directory lives on
+    pub dev: u64,
+    /// Inode number of the directory
+    pub inode: u64,
+    /// Last modification time in nanoseconds since the epoch
+    pub mtime_ns: i64,
+    /// Last status change time in nanoseconds since the epoch
+    pub ctime_ns: i64,
+}
+
+impl DirStamp {
+    /// Build a stamp from directory metadata
+    #[cfg(unix)]
+    #[must_use]
+    pub fn from_metadata(metadata: &Metadata) -> Self {
+        use std::os::unix::fs::MetadataExt;
+
+        Self {
+            dev: metadata.dev(),
+            inode: metadata.ino(),
+            mtime_ns: metadata
+                .mtime()
+                .saturating_mul(1_000_000_000)
+                .saturating_add(metadata.mtime_nsec()),
+            ctime_ns: metadata
+                .ctime()
+                .saturating_mul(1_000_000_000)
+                .saturating_add(metadata.ctime_nsec()),
+        }
+    }
+
+    /// Build a stamp from directory metadata (no inode or ctime on this platform)
+    #[cfg(not(unix))]
+    #[must_use]
+    pub fn from_metadata(metadata: &Metadata) -> Self {
+        let mtime_ns = metadata
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+            .and_then(|d| i64::try_from(d.as_nanos()).ok())
+            .unwrap_or(0);
+
+        Self {
+            dev: 0,
+            inode: 0,
+            mtime_ns,
+            ctime_ns: 0,
+        }
+    }
+}
+
+/// A regular file recorded in a directory listing
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexedFile {
+    /// File name within its directory
+    pub name: String,
+    /// Apparent and allocated size, inode identity and link count
+    pub usage: FileUsage,
+    /// Last modification time in seconds since the epoch
+    pub last_modified: u64,
+}
+
+/// Cached listing of a single directory (direct children only)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IndexedDir {
+    /// Regular files directly inside the directory
+    pub files: Vec<IndexedFile>,
+    /// Names of direct subdirectories
+    pub subdirs: Vec<String>,
+}
+
+impl IndexedDir {
+    /// Number of regular files directly inside the directory
+    #[must_use]
+    pub fn file_count(&self) -> usize {
+        self.files.len()
+    }
+
+    /// Total apparent size of the files directly inside the directory
+    #[must_use]
+    pub fn total_bytes(&self) -> u64 {
+        self.files.iter().map(|f| f.usage.apparent).sum()
+    }
+}
+
+/// Bumped whenever the stored listing format changes
+const SCHEMA_VERSION: i32 = 2;
+
+/// SQLite-backed index of directory listings for incremental rescans
+pub struct ScanIndex {
+    conn: Connection,
+}
+
+// SQLite integers are signed; u64 identifiers round-trip through a bit cast
+#[allow(clippy::cast_possible_wrap)]
+const fn to_sql_u64(value: u64) -> i64 {
+    value as i64
+}
+
+#[allow(clippy::cast_sign_loss)]
+const fn from_sql_u64(value: i64) -> u64 {
+    value as u64
+}
+
+impl ScanIndex {
+    /// Open (or create) the scan index database
+    pub fn new(db_path: &str) -> Result<Self> {
+        let conn = Connection::open(db_path)?;
+
+        conn.execute_batch(
+            r#"
+            PRAGMA journal_mode = WAL;
+            PRAGMA synchronous = NORMAL;
+            "#,
+        )?;
+
+        // The index is only a cache, so an outdated one is dropped and rebuilt
+        let version: i32 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        if version < SCHEMA_VERSION {
+            conn.execute_batch(
+                r#"
+                DROP TABLE IF EXISTS scan_index_entries;
+                DROP TABLE IF EXISTS scan_index_dirs;
+                "#,
+            )?;
+        }
+
+        conn.execute_batch(
+            r#"
+            -- One row per indexed directory with its change stamp and aggregates
+            CREATE TABLE IF NOT EXISTS scan_index_dirs (
+                path TEXT PRIMARY KEY,
+                dev INTEGER NOT NULL,
+                inode INTEGER NOT NULL,
+                mtime_ns INTEGER NOT NULL,
+                ctime_ns INTEGER NOT NULL,
+                file_count INTEGER NOT NULL,
+                total_bytes INTEGER NOT NULL,
+                updated_at TEXT DEFAULT CURRENT_TIMESTAMP
+            );
+
+            -- Direct children of each indexed directory
+            CREATE TABLE IF NOT EXISTS scan_index_entries (
+                dir_path TEXT NOT NULL,
+                name TEXT NOT NULL,
+                is_dir INTEGER NOT NULL,
+                size INTEGER NOT NULL DEFAULT 0,
+                allocated INTEGER NOT NULL DEFAULT 0,
+                dev INTEGER NOT NULL DEFAULT 0,
+                inode INTEGER NOT NULL DEFAULT 0,
+                nlink INTEGER NOT NULL DEFAULT 1,
+                last_modified INTEGER NOT NULL DEFAULT 0,
+                PRIMARY KEY (dir_path, name)
+            );
+            "#,
+        )?;
+        conn.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+
+        Ok(Self { conn })
+    }
+
+    /// Start a transaction so a whole rescan is written in one batch
+    pub fn begin_batch(&self) -> Result<Transaction<'_>> {
+        self.conn.unchecked_transaction()
+    }
+
+    /// Return the cached listing for `dir` if its stamp still matches
+    pub fn lookup(&self, dir: &Path, stamp: &DirStamp) -> Result<Option<IndexedDir>> {
+        let key = dir.to_string_lossy();
+
+        let stored = self
+            .conn
+            .prepare_cached(
+                "SELECT dev, inode, mtime_ns, ctime_ns FROM scan_index_dirs WHERE path = ?1",
+            )?
+            .query_row([key.as_ref()], |row| {
+                Ok(DirStamp {
+                    dev: from_sql_u64(row.get(0)?),
+                    inode: from_sql_u64(row.get(1)?),
+                    mtime_ns: row.get(2)?,
+                    ctime_ns: row.get(3)?,
+                })
+            })
+            .optional()?;
+
+        if stored.as_ref() != Some(stamp) {
+            return Ok(None);
+        }
+
+        let mut listing = IndexedDir::default();
+        let mut stmt = self.conn.prepare_cached(
+            r#"
+            SELECT name, is_dir, size, allocated, dev, inode, nlink, last_modified
+            FROM scan_index_entries WHERE dir_path = ?1
+            "#,
+        )?;
+        let rows = stmt.query_map([key.as_ref()], |row| {
+            let file = IndexedFile {
+                name: row.get(0)?,
+                usage: FileUsage {
+                    apparent: from_sql_u64(row.get(2)?),
+                    allocated: from_sql_u64(row.get(3)?),
+                    dev: from_sql_u64(row.get(4)?),
+                    inode: from_sql_u64(row.get(5)?),
+                    nlink: from_sql_u64(row.get(6)?),
+                },
+                last_modified: from_sql_u64(row.get(7)?),
+            };
+            Ok((row.get::<_, bool>(1)?, file))
+        })?;
+
+        for row in rows {
+            let (is_dir, file) = row?;
+            if is_dir {
+                listing.subdirs.push(file.name);
+            } else {
+                listing.files.push(file);
+            }
+        }
+
+        Ok(Some(listing))
+    }
+
+    /// Store a fresh listing for `dir`, dropping subtrees of vanished subdirectories
+    pub fn record(&self, dir: &Path, stamp: &DirStamp, listing: &IndexedDir) -> Result<()> {
+        let key = dir.to_string_lossy();
+
+        let previous_subdirs: Vec<String> = self
+            .conn
+            .prepare_cached(
+                "SELECT name FROM scan_index_entries WHERE dir_path = ?1 AND is_dir = 1",
+            )?
+            .query_map([key.as_ref()], |row| row.get(0))?
+            .collect::<Result<_>>()?;
+        for name in previous_subdirs {
+            if !listing.subdirs.contains(&name) {
+                self.remove_subtree(&dir.join(&name))?;
+            }
+        }
+
+        self.conn
+            .prepare_cached("DELETE FROM scan_index_entries WHERE dir_path = ?1")?
+            .execute([key.as_ref()])?;
+
+        self.conn
+            .prepare_cached(
+                r#"
+                INSERT OR REPLACE INTO scan_index_dirs
+                    (path, dev, inode, mtime_ns, ctime_ns, file_count, total_bytes, updated_at)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, CURRENT_TIMESTAMP)
+                "#,
+            )?
+            .execute((
+                key.as_ref(),
+                to_sql_u64(stamp.dev),
+                to_sql_u64(stamp.inode),
+                stamp.mtime_ns,
+                stamp.ctime_ns,
+                to_sql_u64(listing.file_count() as u64),
+                to_sql_u64(listing.total_bytes()),
+            ))?;
+
+        let mut insert = self.conn.prepare_cached(
+            r#"
+            INSERT INTO scan_index_entries
+                (dir_path, name, is_dir, size, allocated, dev, inode, nlink, last_modified)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+            "#,
+        )?;
+        for file in &listing.files {
+            insert.execute((
+                key.as_ref(),
+                &file.name,
+                false,
+                to_sql_u64(file.usage.apparent),
+                to_sql_u64(file.usage.allocated),
+                to_sql_u64(file.usage.dev),
+                to_sql_u64(file.usage.inode),
+                to_sql_u64(file.usage.nlink),
+                to_sql_u64(file.last_modified),
+            ))?;
+        }
+        for name in &listing.subdirs {
+            insert.execute((key.as_ref(), name, true, 0i64, 0i64, 0i64, 0i64, 1i64, 0i64))?;
+        }
+
+        Ok(())
+    }
+
+    /// Forget `dir` and everything indexed beneath it
+    pub fn remove_subtree(&self, dir: &Path) -> Result<usize> {
+        let key = dir.to_string_lossy();
+        let prefix = format!("{}{}", key, std::path::MAIN_SEPARATOR);
+
+        // substr() instead of LIKE so '%' and '_' in paths need no escaping
+        let removed = self.conn.execute(
+            r#"
+            DELETE FROM scan_index_dirs
+            WHERE path = ?1 OR substr(path, 1, length(?2)) = ?2
+            "#,
+            (key.as_ref(), &prefix),
+        )?;
+        self.conn.execute(
+            r#"
+            DELETE FROM scan_index_entries
+            WHERE dir_path = ?1 OR substr(dir_path, 1, length(?2)) = ?2
+            "#,
+            (key.as_ref(), &prefix),
+        )?;
+
+        Ok(removed)
+    }
+
+    /// Number of directories currently indexed
+    pub fn dir_count(&self) -> Result<usize> {
+        self.conn
+            .query_row("SELECT COUNT(*) FROM scan_index_dirs", [], |row| row.get(0))
+    }
+
+    /// Drop every indexed directory, forcing the next scan to be cold
+    pub fn clear(&self) -> Result<()> {
+        self.conn
+            .execute_batch("DELETE FROM scan_index_entries; DELETE FROM scan_index_dirs;")
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+mod tests {
+    use super::*;
+    use tempfile::NamedTempFile;
+
+    fn stamp(mtime_ns: i64) -> DirStamp {
+        DirStamp {
+            dev: 1,
+            inode: 42,
+            mtime_ns,
+            ctime_ns: mtime_ns,
+        }
+    }
+
+    fn listing() -> IndexedDir {
+        IndexedDir {
+            files: vec![IndexedFile {
+                name: "a.txt".to_string(),
+                usage: FileUsage {
+                    apparent: 10,
+                    allocated: 4096,
+                    dev: 1,
+                    inode: 43,
+                    nlink: 2,
+                },
+                last_modified: 1_700_000_000,
+            }],
+            subdirs: vec!["child".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_lookup_requires_matching_stamp() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let index = ScanIndex::new(temp_file.path().to_str().unwrap()).unwrap();
+        let dir = Path::new("/work/project");
+
+        index.record(dir, &stamp(100), &listing()).unwrap();
+
+        assert_eq!(index.lookup(dir, &stamp(100)).unwrap(), Some(listing()));
+        assert_eq!(index.lookup(dir, &stamp(101)).unwrap(), None);
+        assert_eq!(index.dir_count().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_record_drops_vanished_subtrees() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let index = ScanIndex::new(temp_file.path().to_str().unwrap()).unwrap();
+        let dir = Path::new("/work/project");
+
+        index.record(dir, &stamp(100), &listing()).unwrap();
+        index
+            .record(&dir.join("child"), &stamp(100), &IndexedDir::default())
+            .unwrap();
+        index
+            .record(
+                &dir.join("child_sibling"),
+                &stamp(100),
+                &IndexedDir::default(),
+            )
+            .unwrap();
+        assert_eq!(index.dir_count().unwrap(), 3);
+
+        let without_child = IndexedDir {
+            subdirs: Vec::new(),
+            ..listing()
+        };
+        index.record(dir, &stamp(200), &without_child).unwrap();
+
+        assert_eq!(index.lookup(&dir.join("child"), &stamp(100)).unwrap(), None);
+        assert!(index
+            .lookup(&dir.join("child_sibling"), &stamp(100))
+            .unwrap()
+            .is_some());
+    }
+}
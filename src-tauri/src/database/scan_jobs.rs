@@ -0,0 +1,178 @@
+// Persistent scan jobs
+// State and serialized results of background scans, so results can be fetched
+// by job ID after the window closed or the app restarted
+#![allow(clippy::needless_raw_string_hashes)]
+
+use crate::utils::scan_jobs::{ScanJobInfo, ScanJobStatus};
+use rusqlite::{Connection, OptionalExtension, Result, Row};
+
+/// SQLite-backed record of scan jobs and their results
+pub struct ScanJobStore {
+    conn: Connection,
+}
+
+/// Columns selected for every job query, in the order `read_job` expects
+const JOB_COLUMNS: &str =
+    "id, scan_type, root, status, started_at, finished_at, error, progress_json";
+
+// SQLite integers are signed; u64 sizes round-trip through a bit cast
+#[allow(clippy::cast_possible_wrap)]
+const fn to_sql_u64(value: u64) -> i64 {
+    value as i64
+}
+
+#[allow(clippy::cast_sign_loss)]
+const fn from_sql_u64(value: i64) -> u64 {
+    value as u64
+}
+
+impl ScanJobStore {
+    /// Open (or create) the scan job database
+    pub fn new(db_path: &str) -> Result<Self> {
+        let conn = Connection::open(db_path)?;
+
+        conn.execute_batch(
+            r#"
+            PRAGMA journal_mode = WAL;
+            PRAGMA synchronous = NORMAL;
+
+            -- One row per job; result_json is set once the job completed or was cancelled
+            CREATE TABLE IF NOT EXISTS scan_jobs (
+                id TEXT PRIMARY KEY,
+                scan_type TEXT NOT NULL,
+                root TEXT NOT NULL,
+                status TEXT NOT NULL,
+                started_at TEXT NOT NULL,
+                finished_at TEXT,
+                error TEXT,
+                bytes_processed INTEGER,
+                progress_json TEXT,
+                result_json TEXT
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_scan_jobs_root
+                ON scan_jobs(root, status, finished_at DESC);
+            "#,
+        )?;
+
+        Ok(Self { conn })
+    }
+
+    /// Insert or update a job, with its serialized result once it finished
+    pub fn save(&self, info: &ScanJobInfo, result_json: Option<&str>) -> Result<()> {
+        let progress_json = info
+            .progress
+            .as_ref()
+            .and_then(|progress| serde_json::to_string(progress).ok());
+        let bytes_processed = info
+            .progress
+            .as_ref()
+            .map(|progress| to_sql_u64(progress.bytes_processed));
+
+        self.conn.execute(
+            r#"
+            INSERT OR REPLACE INTO scan_jobs
+                (id, scan_type, root, status, started_at, finished_at, error,
+                 bytes_processed, progress_json, result_json)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+            "#,
+            (
+                &info.id,
+                &info.scan_type,
+                &info.root,
+                info.status.as_str(),
+                &info.started_at,
+                &info.finished_at,
+                &info.error,
+                bytes_processed,
+                progress_json,
+                result_json,
+            ),
+        )?;
+        Ok(())
+    }
+
+    /// Mark jobs that were still running when the app quit as interrupted
+    ///
+    /// **Returns:** Number of jobs marked
+    pub fn mark_interrupted(&self) -> Result<usize> {
+        self.conn.execute(
+            r#"
+            UPDATE scan_jobs
+            SET status = ?1, error = 'The app quit before the scan finished'
+            WHERE status = ?2
+            "#,
+            (
+                ScanJobStatus::Interrupted.as_str(),
+                ScanJobStatus::Running.as_str(),
+            ),
+        )
+    }
+
+    /// State of a stored job
+    pub fn job(&self, id: &str) -> Result<Option<ScanJobInfo>> {
+        self.conn
+            .prepare_cached(&format!(
+                "SELECT {JOB_COLUMNS} FROM scan_jobs WHERE id = ?1"
+            ))?
+            .query_row([id], read_job)
+            .optional()
+    }
+
+    /// Serialized result of a completed or cancelled job
+    pub fn result(&self, id: &str) -> Result<Option<String>> {
+        Ok(self
+            .conn
+            .prepare_cached("SELECT result_json FROM scan_jobs WHERE id = ?1")?
+            .query_row([id], |row| row.get::<_, Option<String>>(0))
+            .optional()?
+            .flatten())
+    }
+
+    /// Most recent jobs, newest first
+    pub fn list(&self, limit: usize) -> Result<Vec<ScanJobInfo>> {
+        let limit = i64::try_from(limit).unwrap_or(i64::MAX);
+        self.conn
+            .prepare_cached(&format!(
+                "SELECT {JOB_COLUMNS} FROM scan_jobs ORDER BY started_at DESC LIMIT ?1"
+            ))?
+            .query_map([limit], read_job)?
+            .collect()
+    }
+
+    /// Bytes processed by the last completed job over `root`, for ETA estimates
+    pub fn last_bytes_processed(&self, root: &str) -> Result<Option<u64>> {
+        Ok(self
+            .conn
+            .prepare_cached(
+                r#"
+                SELECT bytes_processed FROM scan_jobs
+                WHERE root = ?1 AND status = ?2 AND bytes_processed > 0
+                ORDER BY finished_at DESC
+                LIMIT 1
+                "#,
+            )?
+            .query_row((root, ScanJobStatus::Completed.as_str()), |row| {
+                row.get::<_, i64>(0)
+            })
+            .optional()?
+            .map(from_sql_u64))
+    }
+}
+
+/// Read a job row selected with [`JOB_COLUMNS`]
+fn read_job(row: &Row<'_>) -> Result<ScanJobInfo> {
+    let status: String = row.get(3)?;
+    let progress_json: Option<String> = row.get(7)?;
+
+    Ok(ScanJobInfo {
+        id: row.get(0)?,
+        scan_type: row.get(1)?,
+        root: row.get(2)?,
+        status: ScanJobStatus::parse(&status),
+        started_at: row.get(4)?,
+        finished_at: row.get(5)?,
+        error: row.get(6)?,
+        progress: progress_json.and_then(|json| serde_json::from_str(&json).ok()),
+    })
+}
@@ -0,0 +1,265 @@
+// Persistent size tree
+// Cumulative sizes of every directory and file of a scanned root, so drill-down
+// views can list the children of any directory after the app restarts
+#![allow(clippy::needless_raw_string_hashes)]
+
+use crate::models::{SizeTreeChildren, SizeTreeNode};
+use crate::utils::size_tree::{page_children, NodeTotals, SizeTree};
+use rusqlite::{Connection, OptionalExtension, Result, Row};
+use std::path::Path;
+
+/// SQLite-backed store of size trees, queried one directory at a time
+pub struct SizeTreeStore {
+    conn: Connection,
+}
+
+// SQLite integers are signed; u64 sizes round-trip through a bit cast
+#[allow(clippy::cast_possible_wrap)]
+const fn to_sql_u64(value: u64) -> i64 {
+    value as i64
+}
+
+#[allow(clippy::cast_sign_loss)]
+const fn from_sql_u64(value: i64) -> u64 {
+    value as u64
+}
+
+/// Columns selected for every node query, in the order `read_node` expects
+const NODE_COLUMNS: &str =
+    "path, name, is_dir, apparent, allocated, file_count, dir_count, child_count";
+
+impl SizeTreeStore {
+    /// Open (or create) the size tree database
+    pub fn new(db_path: &str) -> Result<Self> {
+        let conn = Connection::open(db_path)?;
+
+        conn.execute_batch(
+            r#"
+            PRAGMA journal_mode = WAL;
+            PRAGMA synchronous = NORMAL;
+
+            -- Roots of the stored trees and when they were scanned
+            CREATE TABLE IF NOT EXISTS size_tree_roots (
+                root TEXT PRIMARY KEY,
+                scanned_at TEXT DEFAULT CURRENT_TIMESTAMP
+            );
+
+            -- One row per directory or file with cumulative totals
+            CREATE TABLE IF NOT EXISTS size_tree_nodes (
+                path TEXT PRIMARY KEY,
+                parent_path TEXT,
+                name TEXT NOT NULL,
+                is_dir INTEGER NOT NULL,
+                apparent INTEGER NOT NULL,
+                allocated INTEGER NOT NULL,
+                file_count INTEGER NOT NULL,
+                dir_count INTEGER NOT NULL,
+                child_count INTEGER NOT NULL
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_size_tree_parent
+                ON size_tree_nodes(parent_path, apparent DESC);
+            "#,
+        )?;
+
+        Ok(Self { conn })
+    }
+
+    /// Store `tree`, replacing any stored nodes at or below its root.
+    ///
+    /// A tree saved inside an already stored root replaces that subtree, so it
+    /// shows up when drilling down from the outer root; the outer root's
+    /// cumulative totals keep their values from its own scan.
+    pub fn save(&self, tree: &SizeTree) -> Result<()> {
+        let root = tree.root().to_string_lossy();
+        let prefix = format!("{}{}", root, std::path::MAIN_SEPARATOR);
+        let tx = self.conn.unchecked_transaction()?;
+
+        // substr() instead of LIKE so '%' and '_' in paths need no escaping
+        tx.execute(
+            r#"
+            DELETE FROM size_tree_nodes
+            WHERE path = ?1 OR substr(path, 1, length(?2)) = ?2
+            "#,
+            (root.as_ref(), &prefix),
+        )?;
+        tx.execute(
+            r#"
+            DELETE FROM size_tree_roots
+            WHERE root = ?1 OR substr(root, 1, length(?2)) = ?2
+            "#,
+            (root.as_ref(), &prefix),
+        )?;
+        tx.execute(
+            "INSERT INTO size_tree_roots (root, scanned_at) VALUES (?1, CURRENT_TIMESTAMP)",
+            [root.as_ref()],
+        )?;
+
+        {
+            let mut insert = tx.prepare_cached(
+                r#"
+                INSERT OR REPLACE INTO size_tree_nodes
+                    (path, parent_path, name, is_dir, apparent, allocated,
+                     file_count, dir_count, child_count)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+                "#,
+            )?;
+            for node in tree.flatten() {
+                insert.execute((
+                    node.path.to_string_lossy(),
+                    node.parent_path
+                        .as_deref()
+                        .or_else(|| tree.root().parent())
+                        .map(Path::to_string_lossy),
+                    node.name,
+                    node.is_dir,
+                    to_sql_u64(node.totals.apparent),
+                    to_sql_u64(node.totals.allocated),
+                    to_sql_u64(node.totals.file_count),
+                    to_sql_u64(node.totals.dir_count),
+                    to_sql_u64(node.child_count as u64),
+                ))?;
+            }
+        }
+
+        tx.commit()
+    }
+
+    /// Stored root that `path` lies in, if any
+    pub fn root_containing(&self, path: &Path) -> Result<Option<String>> {
+        let roots: Vec<String> = self
+            .conn
+            .prepare_cached("SELECT root FROM size_tree_roots")?
+            .query_map([], |row| row.get(0))?
+            .collect::<Result<_>>()?;
+
+        Ok(roots.into_iter().find(|root| path.starts_with(root)))
+    }
+
+    /// Describe the stored node at `path`
+    pub fn node(&self, path: &Path) -> Result<Option<SizeTreeNode>> {
+        Ok(self.node_with_bytes(path)?.map(|(node, _)| node))
+    }
+
+    /// Children of the stored directory at `path`, largest first
+    ///
+    /// **Parameters:**
+    /// - `path` - Directory in a stored tree
+    /// - `limit` - Maximum number of children to return; the rest are summarized
+    ///
+    /// **Returns:** The directory and its children, or `None` if `path` is not stored
+    pub fn children(&self, path: &Path, limit: Option<usize>) -> Result<Option<SizeTreeChildren>> {
+        let Some((parent, parent_bytes)) = self.node_with_bytes(path)? else {
+            return Ok(None);
+        };
+
+        let limit = limit.map_or(-1, |limit| i64::try_from(limit).unwrap_or(i64::MAX));
+        let children = self
+            .conn
+            .prepare_cached(&format!(
+                r#"
+                SELECT {NODE_COLUMNS} FROM size_tree_nodes
+                WHERE parent_path = ?1
+                ORDER BY apparent DESC, name
+                LIMIT ?2
+                "#
+            ))?
+            .query_map((path.to_string_lossy(), limit), read_node)?
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Some(page_children(parent, parent_bytes, children)))
+    }
+
+    /// Forget every stored tree
+    pub fn clear(&self) -> Result<()> {
+        self.conn
+            .execute_batch("DELETE FROM size_tree_nodes; DELETE FROM size_tree_roots;")
+    }
+
+    fn node_with_bytes(&self, path: &Path) -> Result<Option<(SizeTreeNode, u64)>> {
+        self.conn
+            .prepare_cached(&format!(
+                "SELECT {NODE_COLUMNS} FROM size_tree_nodes WHERE path = ?1"
+            ))?
+            .query_row([path.to_string_lossy()], read_node)
+            .optional()
+    }
+}
+
+/// Read a node row selected with [`NODE_COLUMNS`], with its apparent bytes
+fn read_node(row: &Row<'_>) -> Result<(SizeTreeNode, u64)> {
+    let totals = NodeTotals {
+        apparent: from_sql_u64(row.get(3)?),
+        allocated: from_sql_u64(row.get(4)?),
+        file_count: from_sql_u64(row.get(5)?),
+        dir_count: from_sql_u64(row.get(6)?),
+    };
+    #[allow(clippy::cast_possible_truncation)]
+    let child_count = from_sql_u64(row.get(7)?) as usize;
+
+    Ok((
+        totals.to_node(row.get(0)?, row.get(1)?, row.get(2)?, child_count),
+        totals.apparent,
+    ))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::{NamedTempFile, TempDir};
+
+    #[test]
+    fn test_stored_children_match_in_memory_tree() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let base = temp_dir.path();
+        fs::create_dir_all(base.join("a/deep")).unwrap();
+        fs::write(base.join("a/deep/x.bin"), vec![1u8; 40_000]).unwrap();
+        fs::write(base.join("a/y.bin"), vec![1u8; 10_000]).unwrap();
+        fs::write(base.join("z.txt"), vec![1u8; 20_000]).unwrap();
+        let tree = SizeTree::build(base, false, false).unwrap();
+
+        let db_file = NamedTempFile::new().unwrap();
+        let store = SizeTreeStore::new(db_file.path().to_str().unwrap()).unwrap();
+        store.save(&tree).unwrap();
+
+        for dir in [base.to_path_buf(), base.join("a"), base.join("a/deep")] {
+            assert_eq!(
+                store.children(&dir, None).unwrap(),
+                tree.children(&dir, None)
+            );
+        }
+        assert_eq!(
+            store.children(base, Some(1)).unwrap(),
+            tree.children(base, Some(1))
+        );
+        assert_eq!(
+            store.root_containing(&base.join("a/deep")).unwrap(),
+            Some(base.to_string_lossy().into_owned())
+        );
+        assert!(store.children(&base.join("nope"), None).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_save_replaces_previous_tree() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let base = temp_dir.path();
+        fs::create_dir_all(base.join("old")).unwrap();
+        fs::write(base.join("old/file"), b"data").unwrap();
+
+        let db_file = NamedTempFile::new().unwrap();
+        let store = SizeTreeStore::new(db_file.path().to_str().unwrap()).unwrap();
+        store
+            .save(&SizeTree::build(base, false, false).unwrap())
+            .unwrap();
+
+        fs::remove_dir_all(base.join("old")).unwrap();
+        store
+            .save(&SizeTree::build(base, false, false).unwrap())
+            .unwrap();
+
+        assert!(store.node(&base.join("old")).unwrap().is_none());
+        assert_eq!(store.node(base).unwrap().unwrap().child_count, 0);
+    }
+}
@@ -22,7 +22,7 @@ pub mod pacs;
 pub mod utils;
 
 use arch_viz::{ArchVizConfig, ArchVizEngine, ArchitectureAnalysis};
-use database::{ProjectDatabase, ProjectMonitorConfig, ProjectScanResult};
+use database::{ProjectDatabase, ProjectMonitorConfig, ProjectScanResult, ScanIndex, SizeTreeStore};
 pub use error::{ScannerError, ScannerResult};
 pub use models::*;
 use pacs::{DeepProjectScanner, PACSConfig, ProjectAuditReport, ProjectBaseline};
@@ -31,10 +31,19 @@ use std::collections::HashMap;
 use std::path::Path;
 use std::sync::{Arc, Mutex};
 use tauri::{AppHandle, Emitter, Manager};
+use utils::cache_cleanup;
+use utils::cache_versions;
+use utils::cargo_target;
+use utils::git_maintenance::{self, GitAction};
+use utils::git_artifacts;
+use utils::git_status;
 use utils::cleanup;
+use utils::detector_rules::{self, DetectorRules, RuleFile, RuleListing};
 use utils::path::validate_scan_path;
 use utils::scan;
-use utils::scan_progress::CancellationToken;
+use utils::scan_jobs::{ScanJobEvent, ScanJobInfo, ScanJobKind, ScanJobListener, ScanJobs};
+use utils::scan_progress::{CancellationToken, ProgressTracker};
+use utils::size_tree::SizeTree;
 use error::{retry_with_config, RetryConfig};
 
 // Global cancellation token manager for scan operations (BEAD-010)
@@ -86,6 +95,12 @@ struct ScanProgressEvent {
 // Tauri state for cancellation manager (BEAD-010)
 struct CancellationState(Mutex<ScanCancellationManager>);
 
+// Tauri state holding the most recently built size tree for drill-down
+struct SizeTreeState(Mutex<Option<SizeTree>>);
+
+// Tauri state running background scan jobs started by `start_scan`
+struct ScanJobState(ScanJobs);
+
 /// Emit progress event to frontend (BEAD-011)
 fn emit_progress(
     app: &AppHandle,
@@ -246,6 +261,8 @@ async fn get_system_info() -> Result<SystemInfoResponse, String> {
 /// - `opts.root` - Root directory path to scan (must not be a protected system directory)
 /// - `opts.min_bytes` - Minimum file size threshold in bytes (default: 1GB)
 /// - `opts.follow_symlinks` - Whether to follow symbolic links during traversal
+/// - `opts.one_file_system` - Whether to stay on the root's filesystem (skip mount points)
+/// - `opts.limits` - Thread, hashing read-rate and priority limits (default: none)
 ///
 /// **Returns:** Vector of `LargeFileEntry` objects sorted by size (largest first),
 /// each containing file path, size in MB, and last modification timestamp.
@@ -255,7 +272,13 @@ async fn scan_large_files(opts: ScanOpts) -> Result<Vec<LargeFileEntry>, String>
     let validated_path = validate_scan_path(&opts.root)?;
     log::info!("Scanning large files in: {}", validated_path.display());
 
-    scan::scan_large_files(&validated_path, opts.min_bytes, opts.follow_symlinks)
+    let detectors = scan::ScanDetectors {
+        large_files: true,
+        ..scan::ScanDetectors::none()
+    };
+    let options = scan::UnifiedScanOptions::from_scan_opts(detectors, &opts);
+
+    Ok(scan::scan_all(&validated_path, &options)?.large_files)
 }
 
 /// Scans a directory to identify bloat-prone directories (caches, logs, temporary files).
@@ -263,18 +286,30 @@ async fn scan_large_files(opts: ScanOpts) -> Result<Vec<LargeFileEntry>, String>
 /// **Parameters:**
 /// - `opts.root` - Root directory path to scan (must not be a protected system directory)
 /// - `opts.follow_symlinks` - Whether to follow symbolic links during traversal
+/// - `opts.one_file_system` - Whether to stay on the root's filesystem (skip mount points)
+/// - `opts.limits` - Thread, hashing read-rate and priority limits (default: none)
+/// - `opts.stale_after_days` - Recommend deleting only artifacts of projects inactive
+///   for more than this many days (default: recommend nothing)
 ///
 /// **Returns:** Vector of `BloatCategory` objects, each containing:
 /// - Category ID and display name
-/// - List of bloat entries with paths and sizes (MB)
-/// - Total category size sorted by size (largest first)
+/// - List of bloat entries with paths, sizes (MB), project root, last source
+///   modification and last commit, recommended entries first
+/// - Total and recommended category sizes, sorted by recommended size, then total size
 #[tauri::command]
 async fn scan_bloat(opts: ScanOpts) -> Result<Vec<BloatCategory>, String> {
     // Validate the scan path to prevent system directory access
     let validated_path = validate_scan_path(&opts.root)?;
     log::info!("Scanning bloat in: {}", validated_path.display());
 
-    scan::scan_bloat(&validated_path, opts.follow_symlinks)
+    let detectors = scan::ScanDetectors {
+        bloat: true,
+        ..scan::ScanDetectors::none()
+    };
+    let options = scan::UnifiedScanOptions::from_scan_opts(detectors, &opts)
+        .with_rules(Arc::new(DetectorRules::load_for_root(&validated_path)));
+
+    Ok(scan::scan_all(&validated_path, &options)?.bloat)
 }
 
 /// Scans a directory to find duplicate files by comparing SHA-256 file hashes.
@@ -282,10 +317,14 @@ async fn scan_bloat(opts: ScanOpts) -> Result<Vec<BloatCategory>, String> {
 /// **Parameters:**
 /// - `opts.root` - Root directory path to scan (must not be a protected system directory)
 /// - `opts.follow_symlinks` - Whether to follow symbolic links during traversal
+/// - `opts.one_file_system` - Whether to stay on the root's filesystem (skip mount points)
+/// - `opts.limits` - Thread, hashing read-rate and priority limits (default: none)
 ///
 /// **Behavior:**
-/// - Files smaller than 1KB or larger than 100MB are skipped
-/// - Only files with same-size siblings are hashed for efficiency
+/// - Files smaller than 1KB are skipped; there is no upper size limit
+/// - Only files with same-size siblings are hashed, first by head and tail blocks,
+///   then in full when those also match
+/// - Hashes of unchanged files are reused from the shared hash cache
 /// - Duplicates are sorted by potential storage savings (largest first)
 ///
 /// **Returns:** Vector of `DuplicateSet` objects containing:
@@ -298,7 +337,53 @@ async fn scan_duplicates(opts: ScanOpts) -> Result<Vec<DuplicateSet>, String> {
     let validated_path = validate_scan_path(&opts.root)?;
     log::info!("Scanning duplicates in: {}", validated_path.display());
 
-    scan::scan_duplicates(&validated_path, opts.follow_symlinks)
+    // Reuse hashes of files that are unchanged since a previous scan
+    std::fs::create_dir_all("./data")
+        .map_err(|e| format!("Failed to create data directory: {e}"))?;
+    let detectors = scan::ScanDetectors {
+        duplicates: true,
+        ..scan::ScanDetectors::none()
+    };
+    let options = scan::UnifiedScanOptions::from_scan_opts(detectors, &opts)
+        .with_hash_cache("./data/hash_cache.db");
+
+    Ok(scan::scan_all(&validated_path, &options)?.duplicates)
+}
+
+/// Scans a directory for duplicated directory trees, such as `project-old/` or `project (copy)/`.
+///
+/// **Parameters:**
+/// - `opts.root` - Root directory path to scan (must not be a protected system directory)
+/// - `opts.follow_symlinks` - Whether to follow symbolic links during traversal
+/// - `opts.one_file_system` - Whether to stay on the root's filesystem (skip mount points)
+/// - `opts.limits` - Thread, hashing read-rate and priority limits (default: none)
+///
+/// **Behavior:**
+/// - Directories smaller than 1MB are skipped
+/// - Identical trees are confirmed by a Merkle fingerprint of child names and content hashes
+/// - Trees with similar names sharing at least 90% of their bytes are reported as near-identical
+/// - Only the topmost duplicated directory is reported, never its duplicated subdirectories
+/// - Hashes of unchanged files are reused from the shared hash cache
+///
+/// **Returns:** Vector of `DuplicateDirSet` objects containing:
+/// - Fingerprint and similarity (1.0 for identical trees)
+/// - Total space that could be saved by keeping a single tree (MB)
+/// - All directories in the group with sizes and file counts
+#[tauri::command]
+async fn scan_duplicate_dirs(opts: ScanOpts) -> Result<Vec<DuplicateDirSet>, String> {
+    let validated_path = validate_scan_path(&opts.root)?;
+    log::info!("Scanning duplicate directories in: {}", validated_path.display());
+
+    std::fs::create_dir_all("./data")
+        .map_err(|e| format!("Failed to create data directory: {e}"))?;
+    let detectors = scan::ScanDetectors {
+        duplicate_dirs: true,
+        ..scan::ScanDetectors::none()
+    };
+    let options = scan::UnifiedScanOptions::from_scan_opts(detectors, &opts)
+        .with_hash_cache("./data/hash_cache.db");
+
+    Ok(scan::scan_all(&validated_path, &options)?.duplicate_dirs)
 }
 
 /// Scans a directory for junk files matching known patterns (temp files, backups, OS artifacts).
@@ -306,6 +391,8 @@ async fn scan_duplicates(opts: ScanOpts) -> Result<Vec<DuplicateSet>, String> {
 /// **Parameters:**
 /// - `opts.root` - Root directory path to scan (must not be a protected system directory)
 /// - `opts.follow_symlinks` - Whether to follow symbolic links during traversal
+/// - `opts.one_file_system` - Whether to stay on the root's filesystem (skip mount points)
+/// - `opts.limits` - Thread, hashing read-rate and priority limits (default: none)
 ///
 /// **Returns:** Vector of `JunkCategory` objects containing:
 /// - Category ID, display name, and safety level ("safe", "caution")
@@ -317,7 +404,53 @@ async fn scan_junk_files(opts: ScanOpts) -> Result<Vec<JunkCategory>, String> {
     let validated_path = validate_scan_path(&opts.root)?;
     log::info!("Scanning junk files in: {}", validated_path.display());
 
-    scan::scan_junk_files(&validated_path, opts.follow_symlinks)
+    let detectors = scan::ScanDetectors {
+        junk: true,
+        ..scan::ScanDetectors::none()
+    };
+    let options = scan::UnifiedScanOptions::from_scan_opts(detectors, &opts)
+        .with_rules(Arc::new(DetectorRules::load_for_root(&validated_path)));
+
+    Ok(scan::scan_all(&validated_path, &options)?.junk)
+}
+
+/// Runs every detector in one pass, reusing the persistent scan index so only
+/// directories whose metadata changed since the last scan are re-read.
+///
+/// **Parameters:**
+/// - `opts.root` - Root directory path to scan (must not be a protected system directory)
+/// - `opts.min_bytes` - Minimum size for large files in bytes (default: 1GB)
+/// - `opts.follow_symlinks` - Whether to follow symbolic links during traversal
+/// - `opts.one_file_system` - Whether to stay on the root's filesystem (skip mount points)
+/// - `opts.limits` - Thread, hashing read-rate and priority limits (default: none)
+///
+/// **Returns:** `CombinedScanResults` with large files, bloat, duplicates,
+/// duplicated directories, junk, developer caches and Git repositories,
+/// identical to a cold scan, plus any
+/// mount points skipped in one-filesystem mode.
+#[tauri::command]
+async fn scan_all_incremental(opts: ScanOpts) -> Result<CombinedScanResults, String> {
+    // Validate the scan path to prevent system directory access
+    let validated_path = validate_scan_path(&opts.root)?;
+    log::info!("Incremental scan of: {}", validated_path.display());
+
+    let db_path = "./data/scan_index.db";
+    std::fs::create_dir_all("./data")
+        .map_err(|e| format!("Failed to create data directory: {e}"))?;
+    let index = ScanIndex::new(db_path).map_err(|e| format!("Database error: {e}"))?;
+
+    let options = scan::UnifiedScanOptions::from_scan_opts(scan::ScanDetectors::all(), &opts)
+        .with_hash_cache("./data/hash_cache.db")
+        .with_rules(Arc::new(DetectorRules::load_for_root(&validated_path)));
+
+    let (results, stats) = scan::scan_all_indexed(&validated_path, &options, &index)?;
+    log::info!(
+        "Incremental scan reused {} directories, rescanned {}",
+        stats.dirs_reused,
+        stats.dirs_rescanned
+    );
+
+    Ok(results)
 }
 
 /// Deletes files and directories from the file system with optional dry-run and trash support.
@@ -326,21 +459,28 @@ async fn scan_junk_files(opts: ScanOpts) -> Result<Vec<JunkCategory>, String> {
 /// - `req.paths` - Vector of file/directory paths to delete (max 10,000 paths, max 100GB total)
 /// - `req.dry_run` - If true, returns what would be deleted without performing actual deletion
 /// - `req.trash` - If true, moves files to trash; if false, permanently deletes them
+/// - `req.allow_unsaved_work` - If true, deletes even when Git work would be lost (see `check_unsaved_work`)
 ///
 /// **Safety Limits:**
 /// - Maximum 10,000 files per operation (enforced by cleanup module)
 /// - Maximum 100GB per operation (enforced by cleanup module)
 /// - Validates deletion request before executing
+/// - Refuses paths holding uncommitted, untracked, unpushed or stashed Git work unless overridden
 ///
 /// **Returns:** `CleanupResult` containing:
 /// - `deleted` - Vector of successfully deleted paths
 /// - `skipped` - Vector of files that were already deleted or not found
 /// - `errors` - Vector of error messages for failed deletions
+/// - `freed_mb` - Disk space freed by the deleted paths, counting hardlinked files
+///   only when every link was deleted (space moved to trash is freed once emptied)
 #[tauri::command]
 async fn cleanup_dirs(req: CleanupReq) -> Result<CleanupResult, String> {
     // Validate deletion request using cleanup module
     cleanup::validate_deletion_request(&req).map_err(|e| e.to_string())?;
 
+    // Measure before deleting; the files are gone afterwards
+    let estimate = cleanup::DeletionEstimate::measure(&req.paths);
+
     // Execute deletion using cleanup module with retry logic for transient failures (BEAD-014)
     let retry_config = RetryConfig::new(2, 200) // 2 attempts, 200ms initial delay
         .with_backoff_multiplier(1.5)
@@ -352,43 +492,181 @@ async fn cleanup_dirs(req: CleanupReq) -> Result<CleanupResult, String> {
             .map_err(|e| ScannerError::DeletionFailed(e.to_string()))
     }).await.map_err(|e| e.to_string())?;
 
+    let freed_mb = estimate.freed(&deleted).reclaimable_mb();
+
     Ok(CleanupResult {
         deleted,
         skipped,
         errors,
+        freed_mb,
     })
 }
 
+// ============================================================================
+// Size Tree (Drill-Down) Commands
+// ============================================================================
+
+/// Builds a directory tree with cumulative sizes for treemap and drill-down views.
+///
+/// **Parameters:**
+/// - `opts.root` - Root directory path to scan (must not be a protected system directory)
+/// - `opts.follow_symlinks` - Whether to follow symbolic links during traversal
+/// - `opts.one_file_system` - Whether to stay on the root's filesystem (skip mount points)
+/// - `persist` - Also save the tree so drill-down works after a restart (default: false)
+///
+/// **Behavior:**
+/// - The tree replaces the previously built one; use `get_size_tree_children` to drill down
+/// - Hardlinked files are counted once, at the first path reached
+///
+/// **Returns:** The root `SizeTreeNode` with cumulative size, file count and directory count
+#[tauri::command]
+async fn build_size_tree(
+    app: AppHandle,
+    opts: ScanOpts,
+    persist: Option<bool>,
+) -> Result<SizeTreeNode, String> {
+    let validated_path = validate_scan_path(&opts.root)?;
+    log::info!("Building size tree of: {}", validated_path.display());
+
+    let tree = SizeTree::build(&validated_path, opts.follow_symlinks, opts.one_file_system)?;
+    let root = tree
+        .node(tree.root())
+        .ok_or_else(|| format!("Size tree of {} is empty", validated_path.display()))?;
+
+    if persist.unwrap_or(false) {
+        std::fs::create_dir_all("./data")
+            .map_err(|e| format!("Failed to create data directory: {e}"))?;
+        let store = SizeTreeStore::new("./data/size_tree.db")
+            .map_err(|e| format!("Database error: {e}"))?;
+        store.save(&tree).map_err(|e| format!("Database error: {e}"))?;
+    }
+
+    let state = app.state::<SizeTreeState>();
+    *state
+        .0
+        .lock()
+        .map_err(|e| format!("Failed to acquire size tree lock: {e}"))? = Some(tree);
+
+    Ok(root)
+}
+
+/// Lists the children of a size tree directory, largest first, like ncdu.
+///
+/// **Parameters:**
+/// - `path` - Directory at or below the root of a built size tree
+/// - `limit` - Maximum number of children to return (default: all)
+///
+/// **Behavior:**
+/// - Served from the tree built in this session when it covers `path`
+/// - Otherwise served from a persisted tree whose root encloses `path`
+///
+/// **Returns:** `SizeTreeChildren` containing:
+/// - The directory itself with cumulative totals
+/// - Its children sorted by size (largest first), up to `limit`
+/// - Count and combined size of the children left out by `limit`
+#[tauri::command]
+async fn get_size_tree_children(
+    app: AppHandle,
+    path: String,
+    limit: Option<usize>,
+) -> Result<SizeTreeChildren, String> {
+    let path = Path::new(&path);
+
+    {
+        let state = app.state::<SizeTreeState>();
+        let tree = state
+            .0
+            .lock()
+            .map_err(|e| format!("Failed to acquire size tree lock: {e}"))?;
+        if let Some(children) = tree.as_ref().and_then(|tree| tree.children(path, limit)) {
+            return Ok(children);
+        }
+    }
+
+    let db_path = "./data/size_tree.db";
+    if Path::new(db_path).exists() {
+        let store = SizeTreeStore::new(db_path).map_err(|e| format!("Database error: {e}"))?;
+        if let Some(children) = store
+            .children(path, limit)
+            .map_err(|e| format!("Database error: {e}"))?
+        {
+            return Ok(children);
+        }
+    }
+
+    Err(format!(
+        "No size tree covers {}; build one first",
+        path.display()
+    ))
+}
+
 // ============================================================================
 // Scan Cancellation Commands (BEAD-010)
 // ============================================================================
 
-/// Starts a new scan with a unique scan ID and cancellation token.
+/// Starts a scan of any type as a background job.
 ///
 /// **Parameters:**
-/// - `scan_type` - Type of scan ("dev_caches" or "git_repos")
-/// - `opts` - Scan options including root path and symlinks setting
+/// - `scan_type` - Type of scan ("large_files", "bloat", "duplicates", "duplicate_dirs",
+///   "junk", "dev_caches", "git_repos" or "all")
+/// - `opts` - Scan options including root path, size threshold, symlinks setting and
+///   resource limits
 ///
-/// **Returns:** Unique scan ID that can be used to cancel the scan
+/// **Behavior:**
+/// - Emits `scan-job-progress` (`ScanJobInfo` with live `ScanProgress` and ETA) about
+///   twice a second, plus the legacy `scan-progress` event
+/// - Emits `scan-results-chunk` (`ScanResultsChunk` with the job ID) with large files,
+///   bloat, junk and caches found since the previous chunk, so they can be acted on
+///   before the scan completes
+/// - Emits `scan-job-finished` once the job completed, failed or was cancelled
+/// - Results are stored in ./data/scan_jobs.db and can be fetched with
+///   `get_scan_result` even after the window was closed or the app restarted
+///
+/// **Returns:** Unique scan ID that can be used to cancel the scan or fetch its result
 #[tauri::command]
 async fn start_scan(app: AppHandle, scan_type: String, opts: ScanOpts) -> Result<String, String> {
-    use std::sync::atomic::{AtomicU64, Ordering};
-    static SCAN_COUNTER: AtomicU64 = AtomicU64::new(0);
-    
-    // Generate unique scan ID
-    let scan_id = format!("scan_{}_{}", scan_type, SCAN_COUNTER.fetch_add(1, Ordering::SeqCst));
-    
-    // Get cancellation manager from app state
-    let cancellation_state = app.state::<CancellationState>();
-    let mut manager = cancellation_state.0.lock()
-        .map_err(|e| format!("Failed to acquire cancellation lock: {}", e))?;
-    
-    // Create cancellation token for this scan
-    let _token = manager.create_token(&scan_id);
-    
-    log::info!("Started scan with ID: {}", scan_id);
-    
-    Ok(scan_id)
+    let kind = ScanJobKind::parse(&scan_type)?;
+    let validated_path = validate_scan_path(&opts.root)?;
+
+    let mut options = scan::UnifiedScanOptions::from_scan_opts(kind.detectors(), &opts)
+        .with_rules(Arc::new(DetectorRules::load_for_root(&validated_path)));
+    if kind.hashes_files() {
+        // Reuse hashes of files that are unchanged since a previous scan
+        std::fs::create_dir_all("./data")
+            .map_err(|e| format!("Failed to create data directory: {e}"))?;
+        options = options.with_hash_cache("./data/hash_cache.db");
+    }
+
+    let handle = app.clone();
+    let listener: ScanJobListener = Arc::new(move |event: ScanJobEvent<'_>| {
+        let emitted = match event {
+            ScanJobEvent::Progress(job) => {
+                if let Some(progress) = &job.progress {
+                    emit_progress(
+                        &handle,
+                        Path::new(&progress.current_path),
+                        progress.files_scanned,
+                        progress.percentage,
+                        &format!("Scanning ({})...", progress.phase),
+                        progress.eta_seconds,
+                    );
+                }
+                handle.emit("scan-job-progress", job)
+            }
+            ScanJobEvent::Results(chunk) => handle.emit("scan-results-chunk", chunk),
+            ScanJobEvent::Finished(job) => handle.emit("scan-job-finished", job),
+        };
+        if let Err(e) = emitted {
+            log::error!("Failed to emit scan job event: {}", e);
+        }
+    });
+
+    let job = app
+        .state::<ScanJobState>()
+        .0
+        .start(kind, validated_path, options, listener)?;
+
+    Ok(job.id)
 }
 
 /// Cancels an ongoing scan by ID.
@@ -399,6 +677,11 @@ async fn start_scan(app: AppHandle, scan_type: String, opts: ScanOpts) -> Result
 /// **Returns:** true if scan was cancelled, false if scan not found
 #[tauri::command]
 async fn cancel_scan(app: AppHandle, scan_id: String) -> Result<bool, String> {
+    if app.state::<ScanJobState>().0.cancel(&scan_id)? {
+        log::info!("Cancelled scan job: {}", scan_id);
+        return Ok(true);
+    }
+
     // Get cancellation manager from app state
     let cancellation_state = app.state::<CancellationState>();
     let mut manager = cancellation_state.0.lock()
@@ -425,6 +708,10 @@ async fn cancel_scan(app: AppHandle, scan_id: String) -> Result<bool, String> {
 /// **Returns:** true if scan is still active, false if not found or cancelled
 #[tauri::command]
 async fn is_scan_running(app: AppHandle, scan_id: String) -> Result<bool, String> {
+    if app.state::<ScanJobState>().0.is_running(&scan_id)? {
+        return Ok(true);
+    }
+
     // Get cancellation manager from app state
     let cancellation_state = app.state::<CancellationState>();
     let manager = cancellation_state.0.lock()
@@ -437,6 +724,40 @@ async fn is_scan_running(app: AppHandle, scan_id: String) -> Result<bool, String
     Ok(is_running)
 }
 
+/// Returns the state of a scan job, with live progress while it runs.
+///
+/// **Parameters:**
+/// - `scan_id` - Unique scan ID returned by start_scan, possibly from a previous session
+///
+/// **Returns:** `ScanJobInfo` with status ("running", "completed", "failed",
+/// "cancelled" or "interrupted"), timestamps, error and progress, or `None` if unknown
+#[tauri::command]
+async fn get_scan_job(app: AppHandle, scan_id: String) -> Result<Option<ScanJobInfo>, String> {
+    app.state::<ScanJobState>().0.job(&scan_id)
+}
+
+/// Returns the result of a completed scan job.
+///
+/// **Parameters:**
+/// - `scan_id` - Unique scan ID returned by start_scan, possibly from a previous session
+///
+/// **Returns:** The same value the blocking command for the job's scan type returns
+/// (e.g., `LargeFileEntry[]` for "large_files", `CombinedScanResults` for "all"),
+/// or `None` if the job is unknown or did not complete
+#[tauri::command]
+async fn get_scan_result(app: AppHandle, scan_id: String) -> Result<Option<serde_json::Value>, String> {
+    app.state::<ScanJobState>().0.result(&scan_id)
+}
+
+/// Lists scan jobs of this and previous sessions, newest first.
+///
+/// **Parameters:**
+/// - `limit` - Maximum number of jobs to return (default: 50)
+#[tauri::command]
+async fn list_scan_jobs(app: AppHandle, limit: Option<usize>) -> Result<Vec<ScanJobInfo>, String> {
+    app.state::<ScanJobState>().0.list(limit.unwrap_or(50))
+}
+
 // ============================================================================
 // Developer Caches Scanner Command
 // ============================================================================
@@ -447,11 +768,16 @@ async fn is_scan_running(app: AppHandle, scan_id: String) -> Result<bool, String
 /// - `scan_id` - Unique scan ID for cancellation support (BEAD-010)
 /// - `opts.root` - Root directory path to scan (must not be a protected system directory)
 /// - `opts.follow_symlinks` - Whether to follow symbolic links during traversal
+/// - `opts.one_file_system` - Whether to stay on the root's filesystem (skip mount points)
+/// - `opts.limits` - Thread, hashing read-rate and priority limits (default: none)
 ///
 /// **Analysis Includes:**
 /// - Node.js (npm/yarn) caches from ~/.npm and ~/.cache directories
-/// - Cargo registry cache and build artifacts from ~/.cargo
-/// - Python pip and conda caches from ~/.cache/pip and ~/.conda
+/// - Cargo registry and git checkouts from ~/.cargo
+/// - Python pip HTTP and wheel caches from ~/.cache/pip
+/// - Go module cache from ~/go/pkg/mod
+/// - ccache and sccache compiler caches
+/// - Bazel output base and Hugging Face model caches from ~/.cache
 /// - Maven/Gradle caches from ~/.m2 and ~/.gradle
 /// - Docker build cache, container images, and volumes
 /// - Homebrew cache and temporary files
@@ -461,20 +787,23 @@ async fn is_scan_running(app: AppHandle, scan_id: String) -> Result<bool, String
 ///
 /// **Returns:** Vector of `CacheCategory` objects sorted by total size (largest first),
 /// each containing category name, total size, and individual cache entries with sizes.
+/// If the scan is cancelled, the caches found before cancellation are returned.
 #[tauri::command]
 async fn scan_dev_caches(app: AppHandle, scan_id: String, opts: ScanOpts) -> Result<Vec<CacheCategory>, String> {
     // Validate the scan path to prevent system directory access
     let validated_path = validate_scan_path(&opts.root)?;
     log::info!("Scanning developer caches in: {} (scan_id: {})", validated_path.display(), scan_id);
 
-    // Get cancellation token
+    // Get cancellation token, registering one for IDs not issued by start_scan
     let cancel_token = {
         let cancellation_state = app.state::<CancellationState>();
-        let manager = cancellation_state.0.lock()
+        let mut manager = cancellation_state.0.lock()
             .map_err(|e| format!("Failed to acquire cancellation lock: {}", e))?;
         
-        manager.get_token(&scan_id)
-            .ok_or_else(|| format!("Scan ID not found: {}", scan_id))?
+        match manager.get_token(&scan_id) {
+            Some(token) => token,
+            None => manager.create_token(&scan_id),
+        }
     };
 
     // Check if already cancelled
@@ -491,12 +820,15 @@ async fn scan_dev_caches(app: AppHandle, scan_id: String, opts: ScanOpts) -> Res
         .with_max_delay_ms(5000)
         .with_jitter(true);
     
-    let result = retry_with_config(retry_config, || {
-        scan::scan_dev_caches_async_with_cancellation(&validated_path, opts.follow_symlinks, &cancel_token)
+    let rules = Arc::new(DetectorRules::load_for_root(&validated_path));
+    let progress = Arc::new(ProgressTracker::new());
+    let outcome = retry_with_config(retry_config, || {
+        scan::scan_dev_caches_async_with_cancellation(&validated_path, opts.follow_symlinks, opts.one_file_system, &cancel_token, &progress, opts.limits, &rules)
     }).await?;
     
-    // Emit completion event
-    emit_progress(&app, &validated_path, 0, 100.0, "Cache scan complete", None);
+    // Emit completion event; a cancelled scan still returns what it found
+    let message = if outcome.cancelled { "Cache scan cancelled" } else { "Cache scan complete" };
+    emit_progress(&app, &validated_path, progress.get_files_scanned(), 100.0, message, None);
     
     // Clean up cancellation token
     {
@@ -506,7 +838,97 @@ async fn scan_dev_caches(app: AppHandle, scan_id: String, opts: ScanOpts) -> Res
         manager.remove_token(&scan_id);
     }
     
-    Ok(result)
+    Ok(outcome.results)
+}
+
+/// Cleans a developer cache found by `scan_dev_caches`, using the owning tool's
+/// own cleanup command when there is one.
+///
+/// **Parameters:**
+/// - `path` - Cache directory to clean (an entry path from `scan_dev_caches`)
+/// - `dry_run` - If true, reports what would run without changing anything
+/// - `trash` - If the cache is deleted, move it to trash instead of deleting permanently
+///
+/// **Behavior:**
+/// - Caches with a `cleanup_command` (e.g. `npm cache clean --force`, `go clean -modcache`)
+///   are cleaned by that command when the tool is installed, stopped after 10 minutes
+/// - Other caches, and caches whose tool is not installed, are deleted
+///
+/// **Returns:** `CacheCleanupResult` with the method and command used, the command's
+/// exit code and captured output, and the space freed measured before and after
+#[tauri::command]
+async fn clean_cache(path: String, dry_run: bool, trash: bool) -> Result<CacheCleanupResult, String> {
+    log::info!("Cleaning cache: {path} (dry_run={dry_run}, trash={trash})");
+    tokio::task::spawn_blocking(move || cache_cleanup::clean_cache(Path::new(&path), dry_run, trash))
+        .await
+        .map_err(|e| format!("Task failed: {e}"))?
+}
+
+/// Proposes which cached package versions to prune from a Maven, Gradle, Cargo or npm cache.
+///
+/// **Parameters:**
+/// - `path` - Cache root: `~/.m2/repository`, `~/.gradle/caches/modules-2`,
+///   `~/.cargo/registry` (or its `cache`), or `~/.npm/_cacache`
+/// - `opts.keep_newest` - Versions of each artifact to keep, newest first (default: 2)
+/// - `opts.project_roots` - Directories searched for projects whose lockfiles
+///   (`Cargo.lock`, `package-lock.json`, `gradle.lockfile`) pin versions to keep
+///
+/// **Returns:** `CachePruneReport` with every artifact that has versions to prune,
+/// per-version sizes and keep reasons, largest savings first, and `prune_paths`
+/// to pass to `cleanup_dirs`
+#[tauri::command]
+async fn analyze_cache_versions(path: String, opts: Option<CachePruneOptions>) -> Result<CachePruneReport, String> {
+    let validated_path = validate_scan_path(&path)?;
+    let mut opts = opts.unwrap_or_default();
+    opts.project_roots = opts
+        .project_roots
+        .iter()
+        .map(|root| validate_scan_path(root).map(|root| root.to_string_lossy().to_string()))
+        .collect::<Result<_, _>>()?;
+    log::info!("Analyzing cached versions in: {}", validated_path.display());
+
+    tokio::task::spawn_blocking(move || cache_versions::analyze_cache_versions(&validated_path, &opts))
+        .await
+        .map_err(|e| format!("Task failed: {e}"))?
+}
+
+/// Breaks a Cargo `target/` directory down by build profile and kind of artifact.
+///
+/// **Parameters:**
+/// - `path` - The `target/` directory (or a custom `CARGO_TARGET_DIR`)
+///
+/// **Returns:** `CargoTargetReport` with per-profile sizes of incremental caches,
+/// `deps/` and `build/` output, artifacts of stale toolchains, and `doc/` output
+#[tauri::command]
+async fn analyze_cargo_target(path: String) -> Result<CargoTargetReport, String> {
+    let validated_path = validate_scan_path(&path)?;
+    log::info!("Analyzing Cargo target directory: {}", validated_path.display());
+
+    tokio::task::spawn_blocking(move || cargo_target::analyze_target_dir(&validated_path))
+        .await
+        .map_err(|e| format!("Task failed: {e}"))?
+}
+
+/// Proposes what to prune from a Cargo `target/` directory without a full `cargo clean`.
+///
+/// **Parameters:**
+/// - `path` - The `target/` directory
+/// - `opts.incremental` - Drop incremental compilation caches
+/// - `opts.older_than_days` - Drop units (and incremental caches) unused for this many days
+/// - `opts.stale_toolchains` - Drop artifacts built by another rustc than the current one
+/// - `opts.profiles` - Drop whole profiles, e.g. `release` or `x86_64-pc-windows-gnu/debug`
+/// - `opts.doc` - Drop `doc/` output
+///
+/// **Returns:** `CargoPrunePlan` grouped by reason, with `paths` to pass to `cleanup_dirs`
+#[tauri::command]
+async fn plan_cargo_target_prune(path: String, opts: Option<CargoPruneOptions>) -> Result<CargoPrunePlan, String> {
+    let validated_path = validate_scan_path(&path)?;
+    let opts = opts.unwrap_or_default();
+    log::info!("Planning Cargo target prune: {} ({opts:?})", validated_path.display());
+
+    tokio::task::spawn_blocking(move || cargo_target::plan_target_prune(&validated_path, &opts))
+        .await
+        .map_err(|e| format!("Task failed: {e}"))?
 }
 
 // ============================================================================
@@ -519,6 +941,8 @@ async fn scan_dev_caches(app: AppHandle, scan_id: String, opts: ScanOpts) -> Res
 /// - `scan_id` - Unique scan ID for cancellation support (BEAD-010)
 /// - `opts.root` - Root directory path to scan (must not be a protected system directory)
 /// - `opts.follow_symlinks` - Whether to follow symbolic links during traversal
+/// - `opts.one_file_system` - Whether to stay on the root's filesystem (skip mount points)
+/// - `opts.limits` - Thread, hashing read-rate and priority limits (default: none)
 ///
 /// **Analysis Includes:**
 /// - Repository path and root directory location
@@ -526,23 +950,30 @@ async fn scan_dev_caches(app: AppHandle, scan_id: String, opts: ScanOpts) -> Res
 /// - Total repository size (all files included)
 /// - Branch information and latest commit details
 /// - File and directory counts within the repository
+/// - Largest blobs in history, read from pack files and loose objects
+/// - Submodule stores, Git LFS objects (unreferenced ones marked `lfs_prunable`) and linked worktrees
+/// - Other clones of the same project (same root commit and remote), with combined size,
+///   which clones are fully pushed, and which could be deleted or replaced by `git worktree`
 ///
 /// **Returns:** Vector of `GitRepository` objects sorted by repository size (largest first),
 /// each containing repository statistics and metadata.
+/// If the scan is cancelled, the repositories found before cancellation are returned.
 #[tauri::command]
 async fn scan_git_repos(app: AppHandle, scan_id: String, opts: ScanOpts) -> Result<Vec<GitRepository>, String> {
     // Validate the scan path to prevent system directory access
     let validated_path = validate_scan_path(&opts.root)?;
     log::info!("Scanning git repositories in: {} (scan_id: {})", validated_path.display(), scan_id);
 
-    // Get cancellation token
+    // Get cancellation token, registering one for IDs not issued by start_scan
     let cancel_token = {
         let cancellation_state = app.state::<CancellationState>();
-        let manager = cancellation_state.0.lock()
+        let mut manager = cancellation_state.0.lock()
             .map_err(|e| format!("Failed to acquire cancellation lock: {}", e))?;
         
-        manager.get_token(&scan_id)
-            .ok_or_else(|| format!("Scan ID not found: {}", scan_id))?
+        match manager.get_token(&scan_id) {
+            Some(token) => token,
+            None => manager.create_token(&scan_id),
+        }
     };
 
     // Check if already cancelled
@@ -559,12 +990,14 @@ async fn scan_git_repos(app: AppHandle, scan_id: String, opts: ScanOpts) -> Resu
         .with_max_delay_ms(5000)
         .with_jitter(true);
     
-    let result = retry_with_config(retry_config, || {
-        scan::scan_git_repos_async_with_cancellation(&validated_path, opts.follow_symlinks, &cancel_token)
+    let progress = Arc::new(ProgressTracker::new());
+    let outcome = retry_with_config(retry_config, || {
+        scan::scan_git_repos_async_with_cancellation(&validated_path, opts.follow_symlinks, opts.one_file_system, &cancel_token, &progress, opts.limits)
     }).await?;
     
-    // Emit completion event
-    emit_progress(&app, &validated_path, 0, 100.0, "Git repository scan complete", None);
+    // Emit completion event; a cancelled scan still returns what it found
+    let message = if outcome.cancelled { "Git repository scan cancelled" } else { "Git repository scan complete" };
+    emit_progress(&app, &validated_path, progress.get_files_scanned(), 100.0, message, None);
     
     // Clean up cancellation token
     {
@@ -574,15 +1007,60 @@ async fn scan_git_repos(app: AppHandle, scan_id: String, opts: ScanOpts) -> Resu
         manager.remove_token(&scan_id);
     }
     
-    Ok(result)
+    Ok(outcome.results)
+}
+
+/// Runs a git maintenance action on a scanned repository, after a dry-run estimate.
+///
+/// **Parameters:**
+/// - `path` - Repository work tree (a `repo_path` from `scan_git_repos`) or `.git` directory
+/// - `action` - "gc", "prune", "repack" (`git repack -a -d`), "`reflog_expire`"
+///   (`git reflog expire --all`) or "`worktree_prune`"
+/// - `dry_run` - If true, only estimates what the action would free
+///
+/// **Behavior:**
+/// - Refuses to run while another git process holds a lock in the repository
+/// - The estimate uses git's own `--dry-run` output where the command has one,
+///   and `git count-objects -v` otherwise
+/// - The command is stopped after 30 minutes
+///
+/// **Returns:** `GitMaintenanceResult` with the estimate, the command's exit code and
+/// captured output, and the size of `.git` measured before and after
+#[tauri::command]
+async fn run_git_maintenance(path: String, action: String, dry_run: bool) -> Result<GitMaintenanceResult, String> {
+    let validated_path = validate_scan_path(&path)?;
+    let action = GitAction::from_id(&action)?;
+    log::info!("Git maintenance `{}` on {} (dry_run={dry_run})", action.id(), validated_path.display());
+
+    tokio::task::spawn_blocking(move || git_maintenance::run_git_maintenance(&validated_path, action, dry_run))
+        .await
+        .map_err(|e| format!("Task failed: {e}"))?
+}
+
+/// Finds build artifacts and junk files a repository commits or does not ignore.
+///
+/// **Parameters:**
+/// - `path` - Repository work tree (a `repo_path` from `scan_git_repos`)
+///
+/// **Behavior:**
+/// - Matches the index and untracked, not ignored files against the bloat and junk patterns
+/// - Measures what every version of a committed artifact costs in history
+/// - Proposes a patch adding the missing patterns to the root `.gitignore`
+///
+/// **Returns:** `GitArtifactReport`, including a `git rm --cached` command for each committed artifact
+#[tauri::command]
+async fn analyze_git_artifacts(path: String) -> Result<GitArtifactReport, String> {
+    let validated_path = validate_scan_path(&path)?;
+    log::info!("Analyzing committed artifacts in: {}", validated_path.display());
+
+    tokio::task::spawn_blocking(move || git_artifacts::analyze_git_artifacts(&validated_path))
+        .await
+        .map_err(|e| format!("Task failed: {e}"))?
 }
 
 /// Get lightweight git status for a repository path
 #[tauri::command]
-#[allow(clippy::too_many_lines)]
 async fn get_git_repo_status(path: String) -> Result<GitRepoStatus, String> {
-    use std::process::Command;
-
     // Validate the path to prevent system directory access
     let validated_path = validate_scan_path(&path)?;
     log::info!("Getting git status for: {}", validated_path.display());
@@ -599,108 +1077,28 @@ async fn get_git_repo_status(path: String) -> Result<GitRepoStatus, String> {
     } else {
         repo_path.clone()
     };
-    let work_dir = work_dir_path.to_string_lossy().to_string();
-
-    // Branch name
-    let branch = match Command::new("git")
-        .arg("-C")
-        .arg(&work_dir)
-        .args(["rev-parse", "--abbrev-ref", "HEAD"])
-        .output()
-    {
-        Ok(out) if out.status.success() => String::from_utf8_lossy(&out.stdout).trim().to_string(),
-        Ok(out) => {
-            let err = String::from_utf8_lossy(&out.stderr);
-            log::warn!("git rev-parse failed: {err}");
-            "unknown".to_string()
-        }
-        Err(e) => {
-            log::warn!("git not available: {e}");
-            "unknown".to_string()
-        }
-    };
-
-    // Detect upstream presence
-    let has_upstream = match Command::new("git")
-        .arg("-C")
-        .arg(&work_dir)
-        .args(["rev-parse", "--abbrev-ref", "--symbolic-full-name", "@{u}"])
-        .output()
-    {
-        Ok(out) => out.status.success(),
-        Err(_) => false,
-    };
-
-    // Ahead/behind relative to upstream
-    let (ahead, behind) = if has_upstream {
-        match Command::new("git")
-            .arg("-C")
-            .arg(&work_dir)
-            .args(["rev-list", "--left-right", "--count", "HEAD...@{upstream}"])
-            .output()
-        {
-            Ok(out) if out.status.success() => {
-                let txt = String::from_utf8_lossy(&out.stdout);
-                let mut parts = txt.split_whitespace();
-                let left = parts
-                    .next()
-                    .and_then(|s| s.parse::<u32>().ok())
-                    .unwrap_or(0);
-                let right = parts
-                    .next()
-                    .and_then(|s| s.parse::<u32>().ok())
-                    .unwrap_or(0);
-                (left, right)
-            }
-            _ => (0, 0),
-        }
-    } else {
-        (0, 0)
-    };
-
-    // Uncommitted/untracked counts via porcelain
-    let (mut uncommitted, mut untracked) = (0u32, 0u32);
-    if let Ok(out) = Command::new("git")
-        .arg("-C")
-        .arg(&work_dir)
-        .args(["status", "--porcelain"]) // simpler to parse
-        .output()
-    {
-        if out.status.success() {
-            let txt = String::from_utf8_lossy(&out.stdout);
-            for line in txt.lines() {
-                if line.starts_with("??") {
-                    untracked += 1;
-                } else if !line.trim().is_empty() {
-                    uncommitted += 1;
-                }
-            }
-        }
-    }
 
-    // Last commit timestamp
-    let last_commit_ts: u64 = match Command::new("git")
-        .arg("-C")
-        .arg(&work_dir)
-        .args(["log", "-1", "--format=%ct"]) // unix timestamp
-        .output()
-    {
-        Ok(out) if out.status.success() => String::from_utf8_lossy(&out.stdout)
-            .trim()
-            .parse::<u64>()
-            .unwrap_or(0),
-        _ => 0,
-    };
+    tokio::task::spawn_blocking(move || git_status::repo_status(&work_dir_path))
+        .await
+        .map_err(|e| format!("Task failed: {e}"))
+}
 
-    Ok(GitRepoStatus {
-        branch,
-        ahead,
-        behind,
-        uncommitted,
-        untracked,
-        last_commit_ts,
-        has_upstream,
-    })
+/// Lists Git work that deleting the given paths would destroy.
+///
+/// **Parameters:**
+/// - `paths` - Paths about to be passed to `cleanup_dirs`
+///
+/// **Behavior:**
+/// - Counts uncommitted changes and untracked files under each path
+/// - For repositories at or below a path, also counts unpushed commits and stashes
+/// - Reports nothing if git is not installed
+///
+/// **Returns:** One `UnsavedWork` per affected path and repository; empty if nothing would be lost
+#[tauri::command]
+async fn check_unsaved_work(paths: Vec<String>) -> Result<Vec<UnsavedWork>, String> {
+    tokio::task::spawn_blocking(move || git_status::find_unsaved_work(&paths))
+        .await
+        .map_err(|e| format!("Task failed: {e}"))
 }
 
 // Initializes and runs the Tauri application with all scanning and cleanup commands.
@@ -714,11 +1112,24 @@ async fn get_git_repo_status(path: String) -> Result<GitRepoStatus, String> {
 // - `scan_large_files` - Scan for large files exceeding size thresholds
 // - `scan_bloat` - Detect bloated files and directories
 // - `scan_duplicates` - Find duplicate files by hash
+// - `scan_duplicate_dirs` - Find identical and near-identical directory trees
 // - `scan_junk_files` - Detect junk files (cache, temp, etc.)
+// - `build_size_tree` - Build a directory tree with cumulative sizes
+// - `get_size_tree_children` - List a size tree directory's children, largest first
+// - `start_scan` - Run any scan type as a background job with progress events
+// - `cancel_scan` - Cancel a background scan job
+// - `get_scan_job` / `get_scan_result` / `list_scan_jobs` - Inspect jobs and fetch results
 // - `scan_dev_caches` - Analyze developer tool caches
+// - `clean_cache` - Clean a developer cache with its tool's own command, or delete it
+// - `analyze_cache_versions` - Propose old package versions to prune from a package cache
+// - `analyze_cargo_target` / `plan_cargo_target_prune` - Break down and selectively prune a Cargo target directory
 // - `scan_git_repos` - Find and analyze Git repositories
+// - `run_git_maintenance` - Run `git gc`, `prune`, `repack`, `reflog expire` or `worktree prune` on a repository
+// - `list_detector_rules` / `save_detector_rules` - List and edit user-defined detector rules
 // - `cleanup_dirs` - Safely delete selected files and directories
 // - `get_git_repo_status` - Get lightweight git status for a repository
+// - `check_unsaved_work` - List Git work a deletion would destroy
+// - `analyze_git_artifacts` - Find committed build artifacts and propose a .gitignore patch
 // - `store_project_scan` - Store project scan result in database
 // - `get_project_history` - Get project scan history
 // - `configure_project_monitoring` - Configure project monitoring
@@ -842,7 +1253,8 @@ async fn run_pacs_scan(
     log::info!("Starting PACS scan for: {project_path}");
 
     let config = config.unwrap_or_default();
-    let mut scanner = DeepProjectScanner::new(&project_path, config);
+    let mut scanner =
+        DeepProjectScanner::new(&project_path, config).with_hash_cache("./data/hash_cache.db");
 
     // Load existing baseline if available
     scanner
@@ -928,7 +1340,8 @@ async fn create_project_baseline(
     log::info!("Creating baseline '{version}' for project: {project_path}");
 
     let config = PACSConfig::default();
-    let mut scanner = DeepProjectScanner::new(&project_path, config);
+    let mut scanner =
+        DeepProjectScanner::new(&project_path, config).with_hash_cache("./data/hash_cache.db");
 
     // Load existing baseline if available
     scanner
@@ -1262,6 +1675,45 @@ async fn export_all_diagrams(
     Ok(exported_files)
 }
 
+// ============================================================================
+// Detector Rule Commands
+// ============================================================================
+
+/// Lists the detector rules: built-in patterns, the global rule file and,
+/// for a scan root, its `.disk-bloat-rules.toml`.
+///
+/// **Parameters:**
+/// - `root` - Optional scan root whose rule file should be listed as well
+///
+/// **Returns:** `RuleListing` with the built-in, global and root rules and the
+/// paths of the rule files
+#[tauri::command]
+async fn list_detector_rules(root: Option<String>) -> Result<RuleListing, String> {
+    let root = root.map(|root| validate_scan_path(&root)).transpose()?;
+    RuleListing::load(root.as_deref())
+}
+
+/// Validates and saves user-defined bloat, junk and cache rules.
+///
+/// **Parameters:**
+/// - `rules` - The complete rule file contents; replaces the existing file
+/// - `root` - Scan root to save the rules for, or `None` for the global rule file
+///
+/// **Behavior:**
+/// - Rules with missing ids, names or globs, invalid globs, unknown safety
+///   levels or duplicate ids are rejected with every problem listed
+/// - New rules apply from the next scan on
+#[tauri::command]
+async fn save_detector_rules(rules: RuleFile, root: Option<String>) -> Result<(), String> {
+    let path = match root {
+        Some(root) => detector_rules::root_rules_path(&validate_scan_path(&root)?),
+        None => detector_rules::global_rules_path()
+            .ok_or_else(|| "Cannot determine the config directory".to_string())?,
+    };
+    log::info!("Saving detector rules to: {}", path.display());
+    rules.save(&path)
+}
+
 /// Open a directory or file in the default editor or specified application
 #[tauri::command]
 async fn open_command(path: String, app: Option<String>) -> Result<(), String> {
@@ -1306,20 +1758,40 @@ pub fn run() {
         .plugin(tauri_plugin_log::Builder::new().build())
         .plugin(tauri_plugin_dialog::init())
         .manage(CancellationState(Mutex::new(ScanCancellationManager::new())))
+        .manage(SizeTreeState(Mutex::new(None)))
+        .manage(ScanJobState(ScanJobs::new(
+            std::fs::create_dir_all("./data")
+                .map(|()| std::path::PathBuf::from("./data/scan_jobs.db"))
+                .ok(),
+        )))
         .invoke_handler(tauri::generate_handler![
             get_disk_info,
             get_system_info,
             scan_large_files,
             scan_bloat,
             scan_duplicates,
+            scan_duplicate_dirs,
             scan_junk_files,
+            scan_all_incremental,
+            build_size_tree,
+            get_size_tree_children,
             start_scan,
             cancel_scan,
             is_scan_running,
+            get_scan_job,
+            get_scan_result,
+            list_scan_jobs,
             scan_dev_caches,
+            clean_cache,
+            analyze_cache_versions,
+            analyze_cargo_target,
+            plan_cargo_target_prune,
             scan_git_repos,
+            run_git_maintenance,
             cleanup_dirs,
             get_git_repo_status,
+            check_unsaved_work,
+            analyze_git_artifacts,
             store_project_scan,
             get_project_history,
             configure_project_monitoring,
@@ -1337,6 +1809,8 @@ pub fn run() {
             update_archviz_config,
             generate_diagram,
             export_all_diagrams,
+            list_detector_rules,
+            save_detector_rules,
             open_command
         ]);
 
@@ -65,6 +65,32 @@ pub struct ScanOpts {
     pub min_bytes: Option<u64>,
     /// Whether to follow symbolic links
     pub follow_symlinks: bool,
+    /// Stay on the root's filesystem instead of descending into other mounts
+    #[serde(default)]
+    pub one_file_system: bool,
+    /// Thread, read-rate and priority limits for the scan
+    #[serde(default)]
+    pub limits: ScanResourceLimits,
+    /// Recommend deleting build artifacts only of projects inactive for more
+    /// than this many days, and rank those first (None = recommend nothing)
+    #[serde(default)]
+    pub stale_after_days: Option<u64>,
+}
+
+/// Limits that keep a scan from saturating the machine
+///
+/// The default imposes no limits: hashing uses every core at full speed.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct ScanResourceLimits {
+    /// Maximum number of threads the scan may use (None = one per core)
+    pub max_threads: Option<usize>,
+    /// Maximum bytes per second read for hashing (None = unlimited)
+    pub max_read_bytes_per_sec: Option<u64>,
+    /// Lower the I/O priority of the scan threads (Linux only)
+    pub low_io_priority: bool,
+    /// Lower the CPU priority of the scan threads (Linux only)
+    pub low_cpu_priority: bool,
 }
 
 /// Cleanup request specifying files to delete
@@ -76,6 +102,9 @@ pub struct CleanupReq {
     pub dry_run: bool,
     /// If true, move to trash instead of permanent deletion
     pub trash: bool,
+    /// If true, delete even if uncommitted, unpushed or stashed Git work would be lost
+    #[serde(default)]
+    pub allow_unsaved_work: bool,
 }
 
 /// Result of a cleanup operation
@@ -87,6 +116,38 @@ pub struct CleanupResult {
     pub skipped: Vec<String>,
     /// Error messages for paths that failed
     pub errors: Vec<String>,
+    /// Disk space freed (MB); hardlinked files only count when every link was deleted.
+    /// In dry-run mode this is the space that would be freed.
+    pub freed_mb: f32,
+}
+
+/// Result of cleaning one developer cache
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CacheCleanupResult {
+    /// Cache directory that was cleaned
+    pub path: String,
+    /// "native" if the tool's cleanup command ran, "delete" if the directory was deleted
+    pub method: String,
+    /// Command that ran (or would run in dry-run mode), for the native method
+    pub command: Option<String>,
+    /// Whether the cleanup completed without errors
+    pub success: bool,
+    /// Exit code of the cleanup command, if it exited
+    pub exit_code: Option<i32>,
+    /// Whether the cleanup command was killed after running too long
+    pub timed_out: bool,
+    /// Captured standard output of the cleanup command
+    pub stdout: String,
+    /// Captured standard error of the cleanup command
+    pub stderr: String,
+    /// Error message if the cleanup failed
+    pub error: Option<String>,
+    /// Space allocated by the cache before cleaning (MB)
+    pub before_mb: f32,
+    /// Space allocated by the cache after cleaning (MB); equals `before_mb` in dry-run mode
+    pub after_mb: f32,
+    /// Space freed, measured as the difference (MB); 0 in dry-run mode
+    pub freed_mb: f32,
 }
 
 // ============================================================================
@@ -100,6 +161,10 @@ pub struct LargeFileEntry {
     pub path: String,
     /// File size in megabytes
     pub size_mb: f32,
+    /// Space allocated on disk (MB)
+    pub allocated_mb: f32,
+    /// Space freed by deleting this path (MB); zero if other hardlinks remain
+    pub reclaimable_mb: f32,
     /// Last modification timestamp (Unix epoch seconds)
     pub last_modified: u64,
 }
@@ -113,8 +178,31 @@ pub struct LargeFileEntry {
 pub struct BloatEntry {
     /// Absolute path to bloat file/directory
     pub path: String,
-    /// Size in megabytes
+    /// Apparent size in megabytes, each hardlinked file counted once
     pub size_mb: f32,
+    /// Space allocated on disk (MB)
+    pub allocated_mb: f32,
+    /// Space freed by deleting this directory (MB); excludes files hardlinked from outside it
+    pub reclaimable_mb: f32,
+    /// How certain the match is: "high" (confirmed by a marker file or an unambiguous
+    /// name) or "medium" (a telltale name without a marker file next to it)
+    pub confidence: String,
+    /// Why the directory was matched (e.g., "target next to Cargo.toml")
+    pub reason: String,
+    /// Safety level of deleting it: "safe", "caution", or "dangerous"
+    pub safety: String,
+    /// Project directory the artifact belongs to
+    pub project_root: String,
+    /// Newest modification of a source file in the project (Unix seconds),
+    /// ignoring build artifacts and `.git`
+    pub project_last_modified: Option<u64>,
+    /// Date of the last git commit touching the project (Unix seconds)
+    pub last_commit: Option<u64>,
+    /// Whole days since the project's sources or git history last changed
+    pub inactive_days: Option<u64>,
+    /// Deleting is recommended: the project has been inactive for longer than
+    /// `ScanOpts::stale_after_days` (always false without a threshold)
+    pub recommended: bool,
 }
 
 /// Grouped collection of bloat entries by category
@@ -126,7 +214,13 @@ pub struct BloatCategory {
     pub display_name: String,
     /// Total size of all entries in this category (MB)
     pub total_size_mb: f32,
-    /// List of individual bloat entries
+    /// Total space allocated on disk by all entries (MB)
+    pub total_allocated_mb: f32,
+    /// Total space freed by deleting all entries (MB)
+    pub total_reclaimable_mb: f32,
+    /// Space freed by deleting only the recommended entries (MB)
+    pub recommended_reclaimable_mb: f32,
+    /// Bloat entries, recommended first, then largest first
     pub entries: Vec<BloatEntry>,
 }
 
@@ -141,6 +235,8 @@ pub struct DuplicateEntry {
     pub path: String,
     /// File size in megabytes
     pub size_mb: f32,
+    /// Space allocated on disk (MB)
+    pub allocated_mb: f32,
     /// Last modification timestamp (Unix epoch seconds)
     pub last_modified: u64,
 }
@@ -150,12 +246,70 @@ pub struct DuplicateEntry {
 pub struct DuplicateSet {
     /// SHA256 hash of file content
     pub hash: String,
-    /// Total size that could be saved by keeping only one copy (MB)
+    /// Disk space freed by keeping only one copy (MB). Hardlinks to one inode are a
+    /// single copy, and an inode still linked from outside the set frees nothing.
     pub total_savable_mb: f32,
     /// List of all files with this same content
     pub entries: Vec<DuplicateEntry>,
 }
 
+/// Directory that belongs to a group of duplicated directory trees
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DuplicateDirEntry {
+    /// Absolute directory path
+    pub path: String,
+    /// Total size of the files below the directory (MB)
+    pub size_mb: f32,
+    /// Space allocated on disk (MB)
+    pub allocated_mb: f32,
+    /// Space freed by deleting the directory (MB); hardlinks to files outside it free nothing
+    pub reclaimable_mb: f32,
+    /// Number of files below the directory
+    pub file_count: usize,
+}
+
+/// Collection of directory trees with identical or nearly identical content
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DuplicateDirSet {
+    /// Merkle fingerprint shared by identical trees (SHA256 over child names and
+    /// content hashes); for near-identical trees, a hash of the largest tree's
+    /// file names and sizes
+    pub fingerprint: String,
+    /// Share of bytes the trees have in common: 1.0 for identical trees, less
+    /// for near-identical ones (lowest pairwise similarity in the group)
+    pub similarity: f32,
+    /// Disk space freed by keeping only one tree (MB). For near-identical trees
+    /// the largest is kept, so files only present in the others would be lost.
+    pub total_savable_mb: f32,
+    /// Every directory in the group
+    pub entries: Vec<DuplicateDirEntry>,
+}
+
+/// How many candidates each duplicate detection stage eliminated
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DuplicateScanStats {
+    /// Files large enough to be considered
+    pub files_considered: usize,
+    /// Total size of the considered files (bytes)
+    pub bytes_considered: u64,
+    /// Files dropped because no other file had the same size
+    pub size_unique_files: usize,
+    /// Files whose head and tail blocks were read and hashed
+    pub edge_hashed_files: usize,
+    /// Files dropped because their head/tail hash was unique
+    pub edge_unique_files: usize,
+    /// Files whose full content was read and hashed
+    pub full_hashed_files: usize,
+    /// Digests taken from the hash cache instead of reading the file
+    pub cache_hits: usize,
+    /// Bytes read while hashing head and tail blocks
+    pub bytes_edge_hashed: u64,
+    /// Bytes read while hashing full file contents
+    pub bytes_full_hashed: u64,
+    /// Bytes of considered files that were never read
+    pub bytes_hash_avoided: u64,
+}
+
 // ============================================================================
 // Junk Files Scan Results
 // ============================================================================
@@ -167,6 +321,10 @@ pub struct JunkFileEntry {
     pub path: String,
     /// File size in kilobytes
     pub size_kb: f32,
+    /// Space allocated on disk (KB)
+    pub allocated_kb: f32,
+    /// Space freed by deleting this file (KB); zero if other hardlinks remain
+    pub reclaimable_kb: f32,
     /// Pattern name that matched (e.g., ".DS_Store", "*.log")
     pub pattern: String,
     /// Category ID for grouping (e.g., "system", "logs")
@@ -184,6 +342,10 @@ pub struct JunkCategory {
     pub display_name: String,
     /// Total size of all files in this category (KB)
     pub total_size_kb: f32,
+    /// Total space allocated on disk by all files (KB)
+    pub total_allocated_kb: f32,
+    /// Total space freed by deleting all files (KB)
+    pub total_reclaimable_kb: f32,
     /// Count of files in this category
     pub file_count: usize,
     /// Overall safety level for category
@@ -201,14 +363,22 @@ pub struct JunkCategory {
 pub struct CacheEntry {
     /// Absolute path to cache file/directory
     pub path: String,
-    /// Size in megabytes
+    /// Apparent size in megabytes, each hardlinked file counted once
     pub size_mb: f32,
+    /// Space allocated on disk (MB)
+    pub allocated_mb: f32,
+    /// Space freed by deleting this cache (MB); excludes files hardlinked from outside it
+    pub reclaimable_mb: f32,
     /// Type of cache (e.g., "npm", "pip", "gradle")
     pub cache_type: String,
     /// Safety level: "safe", "caution", or "dangerous"
     pub safety: String,
     /// Human-readable description of cache
     pub description: String,
+    /// The tool's own cleanup command (e.g. `npm cache clean --force`), used by
+    /// `clean_cache` instead of deleting the directory when the tool is installed
+    #[serde(default)]
+    pub cleanup_command: Option<String>,
 }
 
 /// Grouped collection of cache entries by type
@@ -220,6 +390,10 @@ pub struct CacheCategory {
     pub display_name: String,
     /// Total size of all caches in this category (MB)
     pub total_size_mb: f32,
+    /// Total space allocated on disk by all caches (MB)
+    pub total_allocated_mb: f32,
+    /// Total space freed by deleting all caches (MB)
+    pub total_reclaimable_mb: f32,
     /// Number of cache entries
     pub entry_count: usize,
     /// Overall safety level for category
@@ -228,6 +402,159 @@ pub struct CacheCategory {
     pub entries: Vec<CacheEntry>,
 }
 
+/// Options for proposing which cached package versions to prune
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(default)]
+pub struct CachePruneOptions {
+    /// Keep this many of the newest versions of every artifact
+    pub keep_newest: usize,
+    /// Directories searched for projects whose lockfiles pin versions to keep
+    pub project_roots: Vec<String>,
+}
+
+impl Default for CachePruneOptions {
+    fn default() -> Self {
+        Self {
+            keep_newest: 2,
+            project_roots: Vec::new(),
+        }
+    }
+}
+
+/// One cached version of an artifact
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CachedVersion {
+    /// Version string as found in the cache
+    pub version: String,
+    /// Files and directories holding this version
+    pub paths: Vec<String>,
+    /// Apparent size in megabytes
+    pub size_mb: f32,
+    /// Space freed by deleting this version (MB)
+    pub reclaimable_mb: f32,
+    /// Whether the version is kept
+    pub keep: bool,
+    /// Why the version is kept or pruned (e.g. "newest 2", "locked by /work/app/Cargo.lock")
+    pub reason: String,
+}
+
+/// All cached versions of one artifact, newest first
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CachedArtifact {
+    /// Artifact name: crate or package name, or `group:artifact` for Maven and Gradle
+    pub artifact: String,
+    /// Cached versions, newest first
+    pub versions: Vec<CachedVersion>,
+    /// Total size of all versions (MB)
+    pub total_size_mb: f32,
+    /// Space freed by pruning the versions not kept (MB)
+    pub prunable_mb: f32,
+}
+
+/// Proposed pruning of a package manager cache
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CachePruneReport {
+    /// Cache directory that was analyzed
+    pub cache_root: String,
+    /// Cache layout: "maven", "gradle", "cargo" or "npm"
+    pub layout: String,
+    /// Artifacts with versions to prune, by savings (largest first)
+    pub artifacts: Vec<CachedArtifact>,
+    /// Space freed by pruning every proposed version (MB)
+    pub total_prunable_mb: f32,
+    /// Paths of every version not kept, for `cleanup_dirs` (at most 10,000 paths per call)
+    pub prune_paths: Vec<String>,
+    /// Lockfiles whose versions were kept
+    pub lockfiles: Vec<String>,
+}
+
+// ============================================================================
+// Cargo Target Directory Analysis
+// ============================================================================
+
+/// Space used by one build profile of a Cargo `target/` directory
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CargoProfileUsage {
+    /// Profile name, prefixed by the target triple for cross builds (e.g. "debug",
+    /// "x86_64-unknown-linux-gnu/release")
+    pub name: String,
+    /// Absolute path of the profile directory
+    pub path: String,
+    /// Total size of the profile (MB)
+    pub size_mb: f32,
+    /// Incremental compilation caches (`incremental/`, MB)
+    pub incremental_mb: f32,
+    /// Compiled dependencies and test binaries (`deps/`, MB)
+    pub deps_mb: f32,
+    /// Build script binaries and outputs (`build/`, MB)
+    pub build_mb: f32,
+    /// Everything else: fingerprints, examples and final binaries (MB)
+    pub other_mb: f32,
+    /// Artifacts built by another toolchain than the most recently used one (MB)
+    pub stale_toolchain_mb: f32,
+    /// Number of compilation units
+    pub unit_count: usize,
+    /// Last time any unit of the profile was used (Unix seconds)
+    pub last_used: u64,
+}
+
+/// Breakdown of a Cargo `target/` directory
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CargoTargetReport {
+    /// Absolute path of the `target/` directory
+    pub target_dir: String,
+    /// Total size (MB)
+    pub size_mb: f32,
+    /// Build profiles, largest first
+    pub profiles: Vec<CargoProfileUsage>,
+    /// Documentation generated by `cargo doc` (`doc/`, MB)
+    pub doc_mb: f32,
+    /// Everything outside the profiles and `doc/` (`package/`, `tmp/`, ..., MB)
+    pub other_mb: f32,
+    /// Number of distinct toolchains that built artifacts
+    pub toolchain_count: usize,
+}
+
+/// What to prune from a Cargo `target/` directory
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct CargoPruneOptions {
+    /// Drop incremental compilation caches of every profile
+    pub incremental: bool,
+    /// Drop compilation units not used for more than this many days (like `cargo sweep --time`)
+    pub older_than_days: Option<u64>,
+    /// Drop artifacts of toolchains other than the most recently used one
+    pub stale_toolchains: bool,
+    /// Drop these whole profiles (names as in `CargoProfileUsage::name`)
+    pub profiles: Vec<String>,
+    /// Drop `cargo doc` output
+    pub doc: bool,
+}
+
+/// Paths pruned for one reason
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CargoPruneGroup {
+    /// Why these paths are pruned (e.g. "incremental (debug)", "unused for 30 days")
+    pub reason: String,
+    /// Files and directories to delete
+    pub paths: Vec<String>,
+    /// Space freed by deleting them (MB)
+    pub reclaimable_mb: f32,
+}
+
+/// Selective pruning proposed for a Cargo `target/` directory
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CargoPrunePlan {
+    /// Absolute path of the `target/` directory
+    pub target_dir: String,
+    /// Paths grouped by reason; each path appears once
+    pub groups: Vec<CargoPruneGroup>,
+    /// Every path to delete, for `cleanup_dirs` (at most 10,000 paths per call)
+    pub paths: Vec<String>,
+    /// Space freed by deleting every path (MB)
+    pub reclaimable_mb: f32,
+}
+
 // ============================================================================
 // Git Repository Scan Results
 // ============================================================================
@@ -235,11 +562,15 @@ pub struct CacheCategory {
 /// Information about a problematic file within a Git repository
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct GitEntry {
-    /// Absolute path to file within repository
+    /// Absolute path to file within repository; LFS entries append
+    /// `#referenced` or `#prunable` to the shared store path
     pub path: String,
-    /// Size in megabytes
+    /// Size in megabytes, each hardlinked file counted once
     pub size_mb: f32,
-    /// Type of git object: "large_file", "loose_object", "pack_file", "reflog"
+    /// Space allocated on disk (MB)
+    pub allocated_mb: f32,
+    /// Type of git storage: "objects", "refs", "reflog", "`pack_file`", "file",
+    /// "submodule", "`lfs_objects`", "`lfs_prunable`" or "worktree"
     pub entry_type: String,
     /// Human-readable description of what this is
     pub description: String,
@@ -256,10 +587,157 @@ pub struct GitRepository {
     pub repo_path: String,
     /// Total size of problematic entries (MB)
     pub total_size_mb: f32,
+    /// Total space allocated on disk by the entries (MB)
+    pub total_allocated_mb: f32,
     /// Number of problematic entries found
     pub entry_count: usize,
     /// List of individual problematic entries
     pub entries: Vec<GitEntry>,
+    /// Largest blobs in the repository's history, largest first
+    #[serde(default)]
+    pub large_blobs: Vec<GitBlob>,
+    /// Other clones of the same project found by the scan, if any
+    #[serde(default)]
+    pub clone_group: Option<GitCloneGroup>,
+}
+
+/// Clones of one project, matched by root commit and remote URLs
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct GitCloneGroup {
+    /// Root commits reachable from HEAD (hex), shared by every clone
+    pub root_commits: Vec<String>,
+    /// Remote URLs of the clones, normalized to `host/path`
+    pub remote_urls: Vec<String>,
+    /// Combined size of the clones' work trees, `.git` included (MB)
+    pub total_size_mb: f32,
+    /// Combined space allocated on disk, hardlinked objects counted once (MB)
+    pub total_allocated_mb: f32,
+    /// Space freed by deleting every clone suggested for deletion (MB)
+    pub deletable_mb: f32,
+    /// The clones, the one to keep first
+    pub clones: Vec<GitClone>,
+}
+
+/// One clone in a [`GitCloneGroup`] and what to do with it
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct GitClone {
+    /// Work tree path
+    pub repo_path: String,
+    /// Checked-out branch ("HEAD" when detached)
+    pub branch: String,
+    /// Size of the work tree, `.git` included (MB)
+    pub size_mb: f32,
+    /// Space allocated on disk by the work tree (MB)
+    pub allocated_mb: f32,
+    /// No uncommitted changes, untracked files, unpushed commits or stashes
+    pub fully_pushed: bool,
+    /// Work that exists only in this clone
+    pub unsaved: UnsavedWork,
+    /// "keep", "delete", "worktree" (replace with `git worktree add` from the kept clone)
+    /// or "review" (holds changes that cannot be moved automatically)
+    pub suggestion: String,
+    /// Why the suggestion was made
+    pub reason: String,
+}
+
+/// A large blob in a repository's object store and the commit that introduced it
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct GitBlob {
+    /// Object id (hex)
+    pub id: String,
+    /// Uncompressed size (MB)
+    pub size_mb: f32,
+    /// Space its copies take in pack files and loose objects (MB)
+    pub disk_mb: f32,
+    /// Path the blob was first committed at, if a ref reaches it
+    pub path: Option<String>,
+    /// Oldest commit reachable from a ref that contains the blob (hex)
+    pub commit: Option<String>,
+    /// Committer time of that commit (Unix seconds)
+    pub commit_time: Option<u64>,
+    /// First line of that commit's message
+    pub commit_summary: Option<String>,
+}
+
+/// Build artifacts and junk files a repository commits or fails to ignore
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct GitArtifactReport {
+    /// Work tree that was analyzed
+    pub repo_path: String,
+    /// Committed and untracked artifacts, largest first
+    pub artifacts: Vec<GitArtifact>,
+    /// Work tree size of the committed artifacts (MB)
+    pub tracked_mb: f32,
+    /// Uncompressed size of every version of the committed artifacts in history (MB)
+    pub history_mb: f32,
+    /// Space those versions take in pack files and loose objects (MB)
+    pub history_disk_mb: f32,
+    /// Size of the untracked artifacts that are not ignored (MB)
+    pub untracked_mb: f32,
+    /// Unified diff adding the missing patterns to the root `.gitignore`, if any are missing
+    pub gitignore_patch: Option<String>,
+}
+
+/// A build artifact directory, or the junk files matching one pattern, in a repository
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct GitArtifact {
+    /// "bloat" (build artifact directory) or "junk" (junk files)
+    pub kind: String,
+    /// Category identifier from the bloat or junk patterns
+    pub category_id: String,
+    /// Display name of the category
+    pub display_name: String,
+    /// Paths relative to the work tree: one directory, or every matching junk file
+    pub paths: Vec<String>,
+    /// Whether the paths are committed (in the index) rather than untracked
+    pub tracked: bool,
+    /// Number of files under the paths
+    pub file_count: usize,
+    /// Size in the work tree (MB)
+    pub size_mb: f32,
+    /// Uncompressed size of every version in history (MB); 0 when untracked
+    pub history_mb: f32,
+    /// Space those versions take in pack files and loose objects (MB); 0 when untracked
+    pub history_disk_mb: f32,
+    /// `.gitignore` pattern that covers the paths
+    pub ignore_pattern: String,
+    /// Command that stops tracking the paths without deleting them; `None` when untracked
+    pub untrack_command: Option<String>,
+}
+
+/// Result of a git maintenance action (`git gc`, `git prune`, ...) on one repository
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct GitMaintenanceResult {
+    /// Repository the action ran against
+    pub repo_path: String,
+    /// Action id: "gc", "prune", "repack", "`reflog_expire`" or "`worktree_prune`"
+    pub action: String,
+    /// Git command that ran (or would run in dry-run mode)
+    pub command: String,
+    /// Whether only the estimate was made
+    pub dry_run: bool,
+    /// Space the dry-run estimate expects the action to free (MB)
+    pub estimated_mb: f32,
+    /// What the estimate found, e.g. "3 unreachable loose objects"
+    pub estimate: String,
+    /// Whether the action completed without errors
+    pub success: bool,
+    /// Exit code of the git command, if it exited
+    pub exit_code: Option<i32>,
+    /// Whether the git command was killed after running too long
+    pub timed_out: bool,
+    /// Captured standard output of the git command
+    pub stdout: String,
+    /// Captured standard error of the git command
+    pub stderr: String,
+    /// Error message if the action failed
+    pub error: Option<String>,
+    /// Space allocated by `.git` before the action (MB)
+    pub before_mb: f32,
+    /// Space allocated by `.git` after the action (MB); equals `before_mb` in dry-run mode
+    pub after_mb: f32,
+    /// Space freed, measured as the difference (MB); 0 in dry-run mode
+    pub freed_mb: f32,
 }
 
 /// Lightweight repository status for UI
@@ -279,6 +757,138 @@ pub struct GitRepoStatus {
     pub last_commit_ts: u64,
     /// Whether an upstream is configured
     pub has_upstream: bool,
+    /// Commits on local branches not contained in any remote-tracking branch
+    #[serde(default)]
+    pub unpushed: u32,
+    /// Count of stash entries
+    #[serde(default)]
+    pub stashes: u32,
+}
+
+/// Git work a deletion would destroy, for one requested path and repository
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct UnsavedWork {
+    /// Path requested for deletion
+    pub path: String,
+    /// Work tree holding the work
+    pub repo_path: String,
+    /// Staged/unstaged changes that would be lost
+    pub uncommitted: u32,
+    /// Untracked (not ignored) files or directories that would be lost
+    pub untracked: u32,
+    /// Commits that exist on no remote (only when the repository itself is deleted)
+    pub unpushed: u32,
+    /// Stash entries (only when the repository itself is deleted)
+    pub stashes: u32,
+}
+
+// ============================================================================
+// Size Tree (Drill-Down) Results
+// ============================================================================
+
+/// One directory or file in a hierarchical size tree
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SizeTreeNode {
+    /// Absolute path
+    pub path: String,
+    /// File or directory name (the full path for the tree root)
+    pub name: String,
+    /// Whether the node is a directory
+    pub is_dir: bool,
+    /// Cumulative apparent size in megabytes, each hardlinked file counted once
+    pub size_mb: f32,
+    /// Cumulative space allocated on disk (MB)
+    pub allocated_mb: f32,
+    /// Number of files at or below this node
+    pub file_count: u64,
+    /// Number of directories below this node
+    pub dir_count: u64,
+    /// Number of direct children (0 for files)
+    pub child_count: usize,
+}
+
+/// Direct children of a size tree directory, largest first
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SizeTreeChildren {
+    /// The directory whose children are listed
+    pub parent: SizeTreeNode,
+    /// Children sorted by size (largest first), up to the requested limit
+    pub children: Vec<SizeTreeNode>,
+    /// Number of children left out by the limit
+    pub omitted_count: usize,
+    /// Combined size of the children left out by the limit (MB)
+    pub omitted_size_mb: f32,
+}
+
+// ============================================================================
+// Combined Scan Results
+// ============================================================================
+
+/// Results of a single-pass scan that fed every enabled detector
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct CombinedScanResults {
+    /// Files above the large file threshold, largest first
+    pub large_files: Vec<LargeFileEntry>,
+    /// Build artifact categories, largest first
+    pub bloat: Vec<BloatCategory>,
+    /// Duplicate sets, most savable space first
+    pub duplicates: Vec<DuplicateSet>,
+    /// Per-stage statistics for duplicate detection
+    pub duplicate_stats: DuplicateScanStats,
+    /// Duplicated directory trees, topmost only, most savable space first
+    pub duplicate_dirs: Vec<DuplicateDirSet>,
+    /// Junk file categories, most files first
+    pub junk: Vec<JunkCategory>,
+    /// Developer cache categories, largest first
+    pub dev_caches: Vec<CacheCategory>,
+    /// Git repositories, largest first
+    pub git_repos: Vec<GitRepository>,
+    /// Mount points not descended into because the scan stayed on one filesystem
+    pub skipped_mounts: Vec<SkippedMount>,
+    /// The scan was cancelled; the lists hold what was found before it stopped
+    pub cancelled: bool,
+}
+
+/// Results of a single scan type that may have been cancelled part way
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct ScanOutcome<T> {
+    /// What the scan found, sorted like a complete scan; partial if `cancelled`
+    pub results: T,
+    /// The scan was cancelled before it finished
+    pub cancelled: bool,
+}
+
+/// A directory on another filesystem that a one-filesystem scan did not enter
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct SkippedMount {
+    /// Absolute path of the mount point
+    pub path: String,
+    /// Filesystem type (e.g., "nfs4", "fuse.sshfs", "ext4"), or "unknown"
+    pub fs_type: String,
+    /// Mounted device or remote source, empty if unknown
+    pub source: String,
+    /// Whether the filesystem is a network share
+    pub is_network: bool,
+}
+
+/// Entries found since the previous chunk of a running background scan.
+///
+/// Categories hold only the entries of this chunk, with totals over those
+/// entries; the job's final result has the complete, sorted lists.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct ScanResultsChunk {
+    /// Job that found the entries
+    pub job_id: String,
+    /// Position of the chunk within the job, starting at 0
+    pub sequence: u64,
+    /// Files above the large file threshold, largest first
+    pub large_files: Vec<LargeFileEntry>,
+    /// Build artifact directories, grouped by category
+    pub bloat: Vec<BloatCategory>,
+    /// Junk files, grouped by category
+    pub junk: Vec<JunkCategory>,
+    /// Developer caches, grouped by category
+    pub dev_caches: Vec<CacheCategory>,
 }
 
 // ============================================================================
@@ -292,8 +902,18 @@ pub struct BloatPattern {
     pub category_id: &'static str,
     /// Display name for UI presentation
     pub display_name: &'static str,
-    /// Directory names to match (e.g., "node_modules", "target")
+    /// Directory names to match (e.g., "node_modules", "target"); a trailing
+    /// `*` matches any suffix (e.g., "cmake-build-*")
     pub dir_names: &'static [&'static str],
+    /// Files that must sit next to the directory to confirm it is generated
+    /// (e.g., "Cargo.toml" for "target", "*.tf" for ".terraform"); empty when
+    /// the name alone is unambiguous
+    pub markers: &'static [&'static str],
+    /// Confidence for a directory without any marker next to it, or `None` to
+    /// skip such directories
+    pub unmarked_confidence: Option<&'static str>,
+    /// Safety level of deleting a match: "safe", "caution", or "dangerous"
+    pub safety: &'static str,
 }
 
 /// Pattern definition for detecting junk files
@@ -332,16 +952,35 @@ mod tests {
             root: "/home/user".to_string(),
             min_bytes: Some(1024 * 1024), // 1MB
             follow_symlinks: false,
+            one_file_system: false,
+            limits: ScanResourceLimits::default(),
+            stale_after_days: None,
         };
         assert_eq!(opts.min_bytes, Some(1024 * 1024));
     }
 
+    #[test]
+    fn test_scan_opts_limits_default_to_unlimited() {
+        let json = r#"{"root": "/home/user", "min_bytes": null, "follow_symlinks": false,
+            "limits": {"max_threads": 2}}"#;
+        let opts: ScanOpts = serde_json::from_str(json).unwrap();
+        assert_eq!(opts.limits.max_threads, Some(2));
+        assert_eq!(opts.limits.max_read_bytes_per_sec, None);
+        assert!(!opts.limits.low_io_priority);
+
+        let json = r#"{"root": "/home/user", "min_bytes": null, "follow_symlinks": false}"#;
+        let opts: ScanOpts = serde_json::from_str(json).unwrap();
+        assert_eq!(opts.limits, ScanResourceLimits::default());
+        assert_eq!(opts.stale_after_days, None);
+    }
+
     #[test]
     fn test_cleanup_result_structure() {
         let result = CleanupResult {
             deleted: vec!["/path/to/file1".to_string()],
             skipped: vec!["/path/to/file2".to_string()],
             errors: vec!["Permission denied".to_string()],
+            freed_mb: 0.0,
         };
         assert_eq!(result.deleted.len(), 1);
         assert_eq!(result.skipped.len(), 1);
@@ -354,14 +993,37 @@ mod tests {
             category_id: "node_modules".to_string(),
             display_name: "Node.js".to_string(),
             total_size_mb: 1000.0,
+            total_allocated_mb: 1000.0,
+            total_reclaimable_mb: 1000.0,
+            recommended_reclaimable_mb: 500.0,
             entries: vec![
                 BloatEntry {
                     path: "/project1/node_modules".to_string(),
                     size_mb: 500.0,
+                    allocated_mb: 500.0,
+                    reclaimable_mb: 500.0,
+                    confidence: "high".to_string(),
+                    reason: "node_modules next to package.json".to_string(),
+                    safety: "safe".to_string(),
+                    project_root: "/project1".to_string(),
+                    project_last_modified: None,
+                    last_commit: None,
+                    inactive_days: Some(400),
+                    recommended: true,
                 },
                 BloatEntry {
                     path: "/project2/node_modules".to_string(),
                     size_mb: 500.0,
+                    allocated_mb: 500.0,
+                    reclaimable_mb: 500.0,
+                    confidence: "high".to_string(),
+                    reason: "node_modules next to package.json".to_string(),
+                    safety: "safe".to_string(),
+                    project_root: "/project2".to_string(),
+                    project_last_modified: None,
+                    last_commit: None,
+                    inactive_days: Some(3),
+                    recommended: false,
                 },
             ],
         };
@@ -374,6 +1036,8 @@ mod tests {
         let safe_junk = JunkFileEntry {
             path: "/home/.DS_Store".to_string(),
             size_kb: 10.0,
+            allocated_kb: 12.0,
+            reclaimable_kb: 12.0,
             pattern: ".DS_Store".to_string(),
             category: "system".to_string(),
             safety: "safe".to_string(),
@@ -11,10 +11,16 @@ pub mod compliance;
 
 // Re-export core types for disk scanning operations
 pub use core::{
-    DiskInfoResponse, SystemInfoResponse, ScanOpts, CleanupReq, CleanupResult,
-    LargeFileEntry, BloatEntry, BloatCategory, DuplicateEntry, DuplicateSet,
+    DiskInfoResponse, SystemInfoResponse, ScanOpts, ScanResourceLimits, CleanupReq, CleanupResult,
+    CacheCleanupResult,
+    LargeFileEntry, BloatEntry, BloatCategory, DuplicateEntry, DuplicateSet, DuplicateScanStats,
+    DuplicateDirEntry, DuplicateDirSet,
     JunkFileEntry, JunkCategory, CacheEntry, CacheCategory, GitEntry,
-    GitRepository, GitRepoStatus, BloatPattern, JunkPattern,
+    CachePruneOptions, CachedVersion, CachedArtifact, CachePruneReport,
+    CargoProfileUsage, CargoTargetReport, CargoPruneOptions, CargoPruneGroup, CargoPrunePlan,
+    GitRepository, GitBlob, GitCloneGroup, GitClone, GitArtifactReport, GitArtifact, GitMaintenanceResult, GitRepoStatus, UnsavedWork, BloatPattern, JunkPattern, CombinedScanResults, ScanOutcome, ScanResultsChunk,
+    SkippedMount,
+    SizeTreeNode, SizeTreeChildren,
 };
 
 // Re-export PACS project audit types
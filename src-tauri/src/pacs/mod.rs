@@ -25,6 +25,7 @@ pub struct DeepProjectScanner {
     config: PACSConfig,
     findings: Vec<ComplianceFinding>,
     baseline: Option<ProjectBaseline>,
+    hash_cache_path: Option<PathBuf>,
 }
 
 /// PACS Configuration
@@ -170,9 +171,17 @@ impl DeepProjectScanner {
             config,
             findings: Vec::new(),
             baseline: None,
+            hash_cache_path: None,
         }
     }
 
+    /// Reuse file hashes of unchanged files from the shared hash cache at `path`
+    #[must_use]
+    pub fn with_hash_cache(mut self, path: impl AsRef<Path>) -> Self {
+        self.hash_cache_path = Some(path.as_ref().to_path_buf());
+        self
+    }
+
     /// Load existing baseline if available
     pub fn load_baseline(&mut self) -> Result<(), Box<dyn std::error::Error>> {
         let baseline_path = self.project_path.join(".pacs").join("baseline.json");
@@ -253,11 +262,23 @@ impl DeepProjectScanner {
     async fn inventory_files(
         &self,
     ) -> Result<HashMap<String, FileMetadata>, Box<dyn std::error::Error>> {
+        use crate::database::hash_cache::{FileStamp, HashCache, HashKind};
         use std::fs;
         use walkdir::WalkDir;
 
         let mut inventory = HashMap::new();
 
+        // Unchanged files keep their hash from the shared cache instead of being re-read
+        let hash_cache = self.hash_cache_path.as_ref().and_then(|path| {
+            HashCache::new(&path.to_string_lossy())
+                .map_err(|e| log::warn!("Hash cache unavailable at {}: {}", path.display(), e))
+                .ok()
+        });
+        let batch = match &hash_cache {
+            Some(cache) => Some(cache.begin_batch()?),
+            None => None,
+        };
+
         for entry in WalkDir::new(&self.project_path)
             .max_depth(self.config.max_depth)
             .into_iter()
@@ -276,8 +297,24 @@ impl DeepProjectScanner {
                 }
 
                 let metadata = fs::metadata(path)?;
-                let content = fs::read(path).unwrap_or_default();
-                let hash = format!("{:x}", md5::compute(&content));
+                let stamp = FileStamp::from_metadata(&metadata);
+                let cached = hash_cache
+                    .as_ref()
+                    .and_then(|cache| cache.get(path, HashKind::Md5, &stamp).ok().flatten());
+                let hash = cached.unwrap_or_else(|| {
+                    let content = fs::read(path);
+                    let hash = format!(
+                        "{:x}",
+                        md5::compute(content.as_deref().unwrap_or_default())
+                    );
+                    // Unreadable files are hashed as empty but never cached
+                    if let (Some(cache), Ok(_)) = (&hash_cache, &content) {
+                        if let Err(e) = cache.put(path, HashKind::Md5, &stamp, &hash) {
+                            log::debug!("Cannot cache hash for {}: {}", path.display(), e);
+                        }
+                    }
+                    hash
+                });
 
                 let file_metadata = FileMetadata {
                     path: relative_path.clone(),
@@ -292,6 +329,13 @@ impl DeepProjectScanner {
             }
         }
 
+        if let (Some(cache), Some(batch)) = (&hash_cache, batch) {
+            batch.commit()?;
+            if let Err(e) = cache.evict_to_limit() {
+                log::warn!("Hash cache eviction failed: {}", e);
+            }
+        }
+
         Ok(inventory)
     }
 
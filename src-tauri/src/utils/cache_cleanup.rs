@@ -0,0 +1,351 @@
+//! Cleaning developer caches with the tools that own them.
+//!
+//! Deleting `~/.npm` or `~/.cargo/registry` works, but can leave the tool's
+//! index, lock or metadata files inconsistent. Cache patterns with a
+//! [`NativeCleanup`] are therefore cleaned by running the tool's own command
+//! (`npm cache clean --force`, `go clean -modcache`, ...), with a timeout and
+//! captured output. Caches without one, and caches of tools that are not
+//! installed, are deleted like any other directory.
+//!
+//! The space a cleanup frees is measured, not estimated: the cache's
+//! allocated size is read before and after, since a tool may keep part of
+//! its cache (`cargo cache -a` keeps the compressed downloads).
+
+use crate::models::{CacheCleanupResult, CleanupReq};
+use crate::utils::cleanup::{delete_files, validate_deletion_request};
+use crate::utils::deletion_log::{log_deletion, DeletionRecord};
+use crate::utils::disk_usage::path_usage;
+use crate::utils::patterns::{match_cache_pattern, NativeCleanup, CACHE_PATTERNS};
+use crate::utils::process::{find_program, run_with_timeout};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+/// How long a cleanup command may run before it is killed
+pub const NATIVE_CLEANUP_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+/// Clean the developer cache at `path`.
+///
+/// **Behavior:**
+/// - If `path` is the root of a cache with a native cleanup command and the
+///   tool is installed, the command runs (at most [`NATIVE_CLEANUP_TIMEOUT`])
+/// - Otherwise the directory is deleted, moved to the trash if `use_trash`
+/// - In dry-run mode nothing runs; the result names the method and command
+///
+/// **Returns:** What ran, its output, and the space freed measured before and after
+///
+/// # Errors
+/// Returns an error if `path` may not be deleted (e.g. a system directory)
+pub fn clean_cache(
+    path: &Path,
+    dry_run: bool,
+    use_trash: bool,
+) -> Result<CacheCleanupResult, String> {
+    validate_deletion_request(&CleanupReq {
+        paths: vec![path.to_string_lossy().to_string()],
+        dry_run,
+        trash: use_trash,
+        allow_unsaved_work: false,
+    })
+    .map_err(|e| e.to_string())?;
+
+    Ok(clean_with(
+        path,
+        native_cleanup_for(path),
+        dry_run,
+        use_trash,
+        NATIVE_CLEANUP_TIMEOUT,
+    ))
+}
+
+/// The native cleanup of the cache rooted at `path`, and the directory its
+/// command runs in (the one the cache pattern starts in).
+///
+/// Paths inside a cache get none: the command would clean the whole cache.
+#[must_use]
+pub fn native_cleanup_for(path: &Path) -> Option<(&'static NativeCleanup, PathBuf)> {
+    let (pattern, .., native) = CACHE_PATTERNS.get(match_cache_pattern(path)?)?;
+    let native = (*native)?;
+    if !path.ends_with(pattern) {
+        return None;
+    }
+    let work_dir = path
+        .ancestors()
+        .nth(Path::new(pattern).components().count())?;
+    Some((native, work_dir.to_path_buf()))
+}
+
+/// Clean `path` with `native` if its tool is installed, else by deleting it
+fn clean_with(
+    path: &Path,
+    native: Option<(&NativeCleanup, PathBuf)>,
+    dry_run: bool,
+    use_trash: bool,
+    timeout: Duration,
+) -> CacheCleanupResult {
+    let before = path_usage(path).allocated_bytes;
+    let mut result = CacheCleanupResult {
+        path: path.to_string_lossy().to_string(),
+        method: "delete".to_string(),
+        command: None,
+        success: false,
+        exit_code: None,
+        timed_out: false,
+        stdout: String::new(),
+        stderr: String::new(),
+        error: None,
+        before_mb: bytes_to_mb(before),
+        after_mb: bytes_to_mb(before),
+        freed_mb: 0.0,
+    };
+
+    let installed = native.and_then(|(native, work_dir)| {
+        let program = find_tool(native);
+        if program.is_none() {
+            log::info!(
+                "{} is not installed; deleting {} instead",
+                native
+                    .requires
+                    .or_else(|| native.command.first().copied())
+                    .unwrap_or_default(),
+                path.display()
+            );
+        }
+        program.map(|program| (native, program, work_dir))
+    });
+
+    if let Some((native, program, work_dir)) = installed {
+        result.method = "native".to_string();
+        result.command = Some(native.command.join(" "));
+        if dry_run {
+            result.success = true;
+            return result;
+        }
+        run_native(native, &program, &work_dir, timeout, &mut result);
+    } else {
+        let paths = [result.path.clone()];
+        match delete_files(&paths, dry_run, use_trash) {
+            Ok((_, _, errors)) if errors.is_empty() => result.success = true,
+            Ok((_, _, errors)) => result.error = Some(errors.join("; ")),
+            Err(e) => result.error = Some(e.to_string()),
+        }
+        if dry_run {
+            return result;
+        }
+    }
+
+    let after = path_usage(path).allocated_bytes;
+    result.after_mb = bytes_to_mb(after);
+    result.freed_mb = bytes_to_mb(before.saturating_sub(after));
+    if result.method == "native" && result.success {
+        let record = DeletionRecord::new(
+            result.path.clone(),
+            before.saturating_sub(after),
+            "cache".to_string(),
+            "native".to_string(),
+        );
+        if let Err(e) = log_deletion(&record) {
+            log::warn!("Failed to log cache cleanup: {e}");
+        }
+    }
+    result
+}
+
+/// Run a native cleanup command in `work_dir`, recording its outcome in `result`
+fn run_native(
+    native: &NativeCleanup,
+    program: &Path,
+    work_dir: &Path,
+    timeout: Duration,
+    result: &mut CacheCleanupResult,
+) {
+    let command_line = native.command.join(" ");
+    let mut command = Command::new(program);
+    command
+        .args(native.command.iter().skip(1))
+        .current_dir(work_dir)
+        .stdin(Stdio::null());
+    for (name, relative) in native.env {
+        command.env(name, work_dir.join(relative));
+    }
+
+    log::info!("Running `{command_line}` in {}", work_dir.display());
+    match run_with_timeout(command, timeout) {
+        Ok(output) => {
+            result.exit_code = output.exit_code;
+            result.timed_out = output.timed_out;
+            result.stdout = output.stdout;
+            result.stderr = output.stderr;
+            result.success = !output.timed_out && output.exit_code == Some(0);
+            if output.timed_out {
+                result.error = Some(format!(
+                    "`{command_line}` did not finish within {} seconds and was stopped",
+                    timeout.as_secs()
+                ));
+            } else if !result.success {
+                result.error = Some(format!(
+                    "`{command_line}` failed with exit code {}",
+                    output
+                        .exit_code
+                        .map_or_else(|| "none".to_string(), |code| code.to_string())
+                ));
+            }
+        }
+        Err(e) => result.error = Some(e),
+    }
+}
+
+/// Path of the cleanup command's program, if it and any required helper are installed
+fn find_tool(native: &NativeCleanup) -> Option<PathBuf> {
+    let program = find_program(native.command.first()?)?;
+    match native.requires {
+        Some(required) => find_program(required).map(|_| program),
+        None => Some(program),
+    }
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn bytes_to_mb(bytes: u64) -> f32 {
+    bytes as f32 / 1_048_576.0
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::time::Instant;
+
+    fn fake_cache(base: &Path) -> PathBuf {
+        let cache = base.join("fake-cache");
+        fs::create_dir_all(&cache).unwrap();
+        fs::write(cache.join("blob"), vec![1u8; 64 * 1024]).unwrap();
+        cache
+    }
+
+    #[test]
+    fn test_native_cleanup_only_for_cache_roots() {
+        let (native, work_dir) = native_cleanup_for(Path::new("/home/dev/.npm")).unwrap();
+        assert_eq!(native.command, ["npm", "cache", "clean", "--force"]);
+        assert_eq!(work_dir, Path::new("/home/dev"));
+
+        let (native, work_dir) =
+            native_cleanup_for(Path::new("/home/dev/.cargo/registry")).unwrap();
+        assert_eq!(native.requires, Some("cargo-cache"));
+        assert_eq!(work_dir, Path::new("/home/dev"));
+
+        assert!(native_cleanup_for(Path::new("/home/dev/.npm/_cacache")).is_none());
+        assert!(native_cleanup_for(Path::new("/home/dev/.gradle")).is_none());
+    }
+
+    #[test]
+    fn test_pip_http_cleanup_keeps_wheels() {
+        let dir = tempfile::tempdir().unwrap();
+        let pip = dir.path().join(".cache/pip");
+        for sub in ["http", "wheels"] {
+            fs::create_dir_all(pip.join(sub)).unwrap();
+            fs::write(pip.join(sub).join("blob"), vec![1u8; 64 * 1024]).unwrap();
+        }
+        let http = pip.join("http");
+
+        let result = clean_with(
+            &http,
+            native_cleanup_for(&http),
+            false,
+            false,
+            Duration::from_secs(30),
+        );
+        assert_eq!(result.method, "delete");
+        assert!(result.success, "{:?}", result.error);
+        assert!(!http.exists());
+        assert!(pip.join("wheels/blob").exists());
+    }
+
+    #[test]
+    fn test_missing_tool_falls_back_to_deletion() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = fake_cache(dir.path());
+        let native = NativeCleanup {
+            command: &["disk-bloat-scanner-missing-tool", "clean"],
+            env: &[],
+            requires: None,
+        };
+
+        let dry = clean_with(
+            &cache,
+            Some((&native, dir.path().to_path_buf())),
+            true,
+            false,
+            Duration::from_secs(30),
+        );
+        assert_eq!(dry.method, "delete");
+        assert!(cache.exists());
+
+        let result = clean_with(
+            &cache,
+            Some((&native, dir.path().to_path_buf())),
+            false,
+            false,
+            Duration::from_secs(30),
+        );
+        assert_eq!(result.method, "delete");
+        assert!(result.success);
+        assert!(!cache.exists());
+        assert!(result.freed_mb > 0.0);
+        assert!(result.after_mb.abs() < f32::EPSILON);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_native_cleanup_runs_in_cache_root_with_env() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = fake_cache(dir.path());
+        let native = NativeCleanup {
+            command: &["sh", "-c", "rm -f \"$FAKE_CACHE\"/blob && echo cleaned"],
+            env: &[("FAKE_CACHE", "fake-cache")],
+            requires: None,
+        };
+
+        let result = clean_with(
+            &cache,
+            Some((&native, dir.path().to_path_buf())),
+            false,
+            false,
+            Duration::from_secs(30),
+        );
+        assert_eq!(result.method, "native");
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(result.exit_code, Some(0));
+        assert_eq!(result.stdout.trim(), "cleaned");
+        // The tool empties its cache; the directory itself stays
+        assert!(cache.exists());
+        assert!(result.freed_mb > 0.0);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_native_cleanup_is_stopped_after_timeout() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = fake_cache(dir.path());
+        let native = NativeCleanup {
+            command: &["sh", "-c", "echo started >&2; exec sleep 10"],
+            env: &[],
+            requires: None,
+        };
+
+        let started = Instant::now();
+        let result = clean_with(
+            &cache,
+            Some((&native, dir.path().to_path_buf())),
+            false,
+            false,
+            Duration::from_millis(300),
+        );
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert!(result.timed_out);
+        assert!(!result.success);
+        assert!(result.error.is_some());
+        assert_eq!(result.stderr.trim(), "started");
+        assert!(cache.join("blob").exists());
+    }
+}
@@ -0,0 +1,828 @@
+//! Version-aware pruning of package manager caches.
+//!
+//! `~/.m2/repository`, `~/.gradle/caches/modules-2`, `~/.cargo/registry` and
+//! `~/.npm/_cacache` keep every version ever downloaded. This module parses
+//! their layouts into artifacts and versions and proposes keeping only the
+//! newest versions of each artifact, plus any version pinned by a lockfile of
+//! a project on disk. The paths of the other versions go to the regular
+//! cleanup flow (`cleanup_dirs`).
+//!
+//! Lockfiles read: `Cargo.lock` (Cargo), `package-lock.json` and
+//! `npm-shrinkwrap.json` (npm), and `gradle.lockfile` (Gradle and Maven, which
+//! share coordinates). Maven projects without Gradle lockfiles only keep the
+//! newest versions.
+
+use crate::error::compare_f32_safe;
+use crate::models::{CachePruneOptions, CachePruneReport, CachedArtifact, CachedVersion};
+use crate::utils::disk_usage::paths_usage;
+use crate::utils::patterns::detect_bloat_category;
+use base64::Engine;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Key prefix of npm's cached HTTP responses
+const NPM_CACHE_KEY_PREFIX: &str = "make-fetch-happen:request-cache:";
+
+/// Package cache layouts the analyzer understands
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CacheLayout {
+    /// `~/.m2/repository/<group path>/<artifact>/<version>/`
+    Maven,
+    /// `~/.gradle/caches/modules-2/files-2.1/<group>/<artifact>/<version>/`
+    Gradle,
+    /// `~/.cargo/registry/{cache,src}/<index>/<name>-<version>[.crate]`
+    Cargo,
+    /// `~/.npm/_cacache`, content-addressed with an index of request keys
+    Npm,
+}
+
+impl CacheLayout {
+    /// Recognize a cache root by its path
+    #[must_use]
+    pub fn detect(root: &Path) -> Option<Self> {
+        let name = root.file_name()?.to_string_lossy();
+        let parent = root
+            .parent()
+            .and_then(Path::file_name)
+            .map(|name| name.to_string_lossy());
+        match (name.as_ref(), parent.as_deref()) {
+            ("repository", Some(".m2")) => Some(Self::Maven),
+            ("modules-2", _) | ("files-2.1", Some("modules-2")) => Some(Self::Gradle),
+            ("registry", Some(".cargo")) | ("cache" | "src", Some("registry")) => Some(Self::Cargo),
+            ("_cacache", _) => Some(Self::Npm),
+            _ => None,
+        }
+    }
+
+    /// Identifier used in reports
+    #[must_use]
+    pub const fn id(self) -> &'static str {
+        match self {
+            Self::Maven => "maven",
+            Self::Gradle => "gradle",
+            Self::Cargo => "cargo",
+            Self::Npm => "npm",
+        }
+    }
+
+    /// Ecosystem whose lockfiles pin this cache's versions
+    const fn ecosystem(self) -> Ecosystem {
+        match self {
+            Self::Maven | Self::Gradle => Ecosystem::Jvm,
+            Self::Cargo => Ecosystem::Cargo,
+            Self::Npm => Ecosystem::Npm,
+        }
+    }
+}
+
+/// Cached versions by artifact, then version, with the paths holding each
+pub type ArtifactVersions = BTreeMap<String, BTreeMap<String, Vec<PathBuf>>>;
+
+/// Analyze the package cache at `root` and propose versions to prune.
+///
+/// **Behavior:**
+/// - The newest `keep_newest` versions of every artifact are kept
+/// - Versions pinned by lockfiles under `project_roots` are kept
+/// - Every other version is proposed for pruning, with its size
+///
+/// **Returns:** Artifacts with versions to prune, largest savings first, and
+/// the paths to pass to `cleanup_dirs`
+///
+/// # Errors
+/// Returns an error if `root` is not a Maven, Gradle, Cargo or npm cache
+pub fn analyze_cache_versions(
+    root: &Path,
+    options: &CachePruneOptions,
+) -> Result<CachePruneReport, String> {
+    let layout = CacheLayout::detect(root).ok_or_else(|| {
+        format!(
+            "{} is not a Maven, Gradle, Cargo or npm package cache",
+            root.display()
+        )
+    })?;
+    let roots: Vec<PathBuf> = options.project_roots.iter().map(PathBuf::from).collect();
+    let locked = LockedVersions::find(&roots);
+
+    let mut artifacts: Vec<CachedArtifact> = collect_versions(layout, root)
+        .into_iter()
+        .map(|(artifact, versions)| {
+            plan_artifact(
+                artifact,
+                versions,
+                options.keep_newest,
+                locked.versions(layout.ecosystem()),
+            )
+        })
+        .filter(|artifact| artifact.versions.iter().any(|version| !version.keep))
+        .collect();
+    artifacts.sort_by(|a, b| {
+        compare_f32_safe(a.prunable_mb, b.prunable_mb).then_with(|| a.artifact.cmp(&b.artifact))
+    });
+
+    let prune_paths: Vec<String> = artifacts
+        .iter()
+        .flat_map(|artifact| &artifact.versions)
+        .filter(|version| !version.keep)
+        .flat_map(|version| version.paths.iter().cloned())
+        .collect();
+    let total_prunable_mb = artifacts.iter().map(|artifact| artifact.prunable_mb).sum();
+    log::info!(
+        "{} cache {}: {} paths ({total_prunable_mb:.1} MB) can be pruned",
+        layout.id(),
+        root.display(),
+        prune_paths.len()
+    );
+
+    Ok(CachePruneReport {
+        cache_root: root.to_string_lossy().to_string(),
+        layout: layout.id().to_string(),
+        artifacts,
+        total_prunable_mb,
+        prune_paths,
+        lockfiles: locked.lockfiles,
+    })
+}
+
+/// Decide which versions of one artifact to keep
+fn plan_artifact(
+    artifact: String,
+    versions: BTreeMap<String, Vec<PathBuf>>,
+    keep_newest: usize,
+    locked: Option<&PinnedVersions>,
+) -> CachedArtifact {
+    let locked = locked.and_then(|locked| locked.get(&artifact));
+    let mut versions: Vec<(String, Vec<PathBuf>)> = versions.into_iter().collect();
+    versions.sort_by(|a, b| compare_versions(&b.0, &a.0));
+
+    let versions: Vec<CachedVersion> = versions
+        .into_iter()
+        .enumerate()
+        .map(|(rank, (version, paths))| {
+            let lockfile = locked.and_then(|locked| locked.get(&version));
+            let (keep, reason) = if rank < keep_newest {
+                (true, format!("newest {keep_newest}"))
+            } else if let Some(lockfile) = lockfile {
+                (true, format!("locked by {}", lockfile.display()))
+            } else {
+                (false, format!("older than the newest {keep_newest}"))
+            };
+            let usage = paths_usage(paths.iter().map(PathBuf::as_path));
+            CachedVersion {
+                version,
+                paths: paths
+                    .iter()
+                    .map(|path| path.to_string_lossy().to_string())
+                    .collect(),
+                size_mb: usage.apparent_mb(),
+                reclaimable_mb: usage.reclaimable_mb(),
+                keep,
+                reason,
+            }
+        })
+        .collect();
+
+    CachedArtifact {
+        total_size_mb: versions.iter().map(|version| version.size_mb).sum(),
+        prunable_mb: versions
+            .iter()
+            .filter(|version| !version.keep)
+            .map(|version| version.reclaimable_mb)
+            .sum(),
+        artifact,
+        versions,
+    }
+}
+
+// ============================================================================
+// Cache Layouts
+// ============================================================================
+
+/// Parse the cache at `root` into artifacts and versions
+#[must_use]
+pub fn collect_versions(layout: CacheLayout, root: &Path) -> ArtifactVersions {
+    let mut versions = ArtifactVersions::new();
+    match layout {
+        CacheLayout::Maven => collect_maven(root, &mut versions),
+        CacheLayout::Gradle => {
+            let files = if root.ends_with("modules-2") {
+                root.join("files-2.1")
+            } else {
+                root.to_path_buf()
+            };
+            collect_gradle(&files, &mut versions);
+        }
+        CacheLayout::Cargo => {
+            let registry = if root.ends_with("registry") {
+                root
+            } else {
+                root.parent().unwrap_or(root)
+            };
+            collect_cargo(registry, &mut versions);
+        }
+        CacheLayout::Npm => collect_npm(root, &mut versions),
+    }
+    versions
+}
+
+/// Add `path` to the paths of `artifact`'s `version`
+fn add_version(versions: &mut ArtifactVersions, artifact: String, version: String, path: PathBuf) {
+    versions
+        .entry(artifact)
+        .or_default()
+        .entry(version)
+        .or_default()
+        .push(path);
+}
+
+/// Subdirectories of `dir` with their names
+fn subdirs(dir: &Path) -> impl Iterator<Item = (String, PathBuf)> {
+    fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_ok_and(|file_type| file_type.is_dir()))
+        .map(|entry| {
+            (
+                entry.file_name().to_string_lossy().to_string(),
+                entry.path(),
+            )
+        })
+}
+
+/// A Maven version directory holds `<artifact>-<version>.pom` or `.jar`
+fn collect_maven(root: &Path, versions: &mut ArtifactVersions) {
+    let mut walker = WalkDir::new(root).min_depth(3).into_iter();
+    while let Some(entry) = walker.next() {
+        let Ok(entry) = entry else { continue };
+        if !entry.file_type().is_dir() {
+            continue;
+        }
+        let dir = entry.path();
+        let (Some(version), Some(artifact_dir)) = (dir.file_name(), dir.parent()) else {
+            continue;
+        };
+        let (Some(artifact), Some(group_dir)) = (artifact_dir.file_name(), artifact_dir.parent())
+        else {
+            continue;
+        };
+        let (version, artifact) = (version.to_string_lossy(), artifact.to_string_lossy());
+        let is_version_dir = ["pom", "jar"].iter().any(|extension| {
+            dir.join(format!("{artifact}-{version}.{extension}"))
+                .is_file()
+        });
+        if !is_version_dir {
+            continue;
+        }
+
+        let Ok(group_path) = group_dir.strip_prefix(root) else {
+            continue;
+        };
+        let group: Vec<_> = group_path
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect();
+        add_version(
+            versions,
+            format!("{}:{artifact}", group.join(".")),
+            version.to_string(),
+            dir.to_path_buf(),
+        );
+        walker.skip_current_dir();
+    }
+}
+
+/// Gradle's `files-2.1` is `<group>/<artifact>/<version>/<sha1>/<file>`
+fn collect_gradle(files: &Path, versions: &mut ArtifactVersions) {
+    for (group, group_dir) in subdirs(files) {
+        for (artifact, artifact_dir) in subdirs(&group_dir) {
+            for (version, version_dir) in subdirs(&artifact_dir) {
+                add_version(
+                    versions,
+                    format!("{group}:{artifact}"),
+                    version,
+                    version_dir,
+                );
+            }
+        }
+    }
+}
+
+/// Cargo keeps `.crate` archives in `cache/<index>/` and their extracted
+/// sources in `src/<index>/`; both count towards the version
+fn collect_cargo(registry: &Path, versions: &mut ArtifactVersions) {
+    for kind in ["cache", "src"] {
+        for (_, index_dir) in subdirs(&registry.join(kind)) {
+            let Ok(entries) = fs::read_dir(&index_dir) else {
+                continue;
+            };
+            for entry in entries.filter_map(Result::ok) {
+                let file_name = entry.file_name().to_string_lossy().to_string();
+                let stem = file_name.strip_suffix(".crate").unwrap_or(&file_name);
+                if let Some((name, version)) = split_name_version(stem) {
+                    add_version(
+                        versions,
+                        name.to_string(),
+                        version.to_string(),
+                        entry.path(),
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Split `<name>-<version>` where the name may itself contain dashes and
+/// digits (`sha-1-0.10.1` is `sha-1` version `0.10.1`)
+#[must_use]
+pub fn split_name_version(stem: &str) -> Option<(&str, &str)> {
+    stem.match_indices('-').find_map(|(index, _)| {
+        let (name, version) = (&stem[..index], &stem[index + 1..]);
+        let mut parts = version.splitn(3, '.');
+        let major = parts.next().unwrap_or_default();
+        let starts_numeric = |part: Option<&str>| {
+            part.is_some_and(|part| part.starts_with(|c: char| c.is_ascii_digit()))
+        };
+        let is_version = !major.is_empty()
+            && major.chars().all(|c| c.is_ascii_digit())
+            && starts_numeric(parts.next())
+            && starts_numeric(parts.next());
+        (!name.is_empty() && is_version).then_some((name, version))
+    })
+}
+
+/// npm's `_cacache` stores tarballs by content hash; the index maps request
+/// keys (tarball URLs) to hashes. A version's paths are its index bucket and
+/// its content file.
+fn collect_npm(root: &Path, versions: &mut ArtifactVersions) {
+    let index_files = WalkDir::new(root.join("index-v5"))
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_file());
+
+    for index_file in index_files {
+        let Ok(content) = fs::read_to_string(index_file.path()) else {
+            continue;
+        };
+        // Later lines for a key supersede earlier ones; a null integrity deletes it
+        let mut live: HashMap<String, Option<String>> = HashMap::new();
+        for line in content.lines() {
+            let Some((_, json)) = line.split_once('\t') else {
+                continue;
+            };
+            let Ok(entry) = serde_json::from_str::<serde_json::Value>(json) else {
+                continue;
+            };
+            let Some(key) = entry.get("key").and_then(serde_json::Value::as_str) else {
+                continue;
+            };
+            let integrity = entry
+                .get("integrity")
+                .and_then(serde_json::Value::as_str)
+                .map(str::to_string);
+            live.insert(key.to_string(), integrity);
+        }
+
+        for (key, integrity) in live {
+            let (Some((name, version)), Some(integrity)) = (npm_tarball(&key), integrity) else {
+                continue;
+            };
+            let mut paths = vec![index_file.path().to_path_buf()];
+            paths.extend(npm_content_path(root, &integrity).filter(|path| path.is_file()));
+            for path in paths {
+                add_version(versions, name.clone(), version.clone(), path);
+            }
+        }
+    }
+}
+
+/// Package name and version of a cached tarball request key
+/// (`.../@scope/pkg/-/pkg-1.2.3.tgz` is `@scope/pkg` version `1.2.3`)
+#[must_use]
+pub fn npm_tarball(key: &str) -> Option<(String, String)> {
+    let url = key.strip_prefix(NPM_CACHE_KEY_PREFIX).unwrap_or(key);
+    let (package_url, file) = url.rsplit_once("/-/")?;
+    let mut segments = package_url.rsplit('/');
+    let base = segments.next()?;
+    let name = match segments.next() {
+        Some(scope) if scope.starts_with('@') => format!("{scope}/{base}"),
+        _ => base.to_string(),
+    };
+    let version = file
+        .strip_suffix(".tgz")?
+        .strip_prefix(base)?
+        .strip_prefix('-')?;
+    Some((name, version.to_string()))
+}
+
+/// Content file of an integrity string (`sha512-<base64>`)
+fn npm_content_path(root: &Path, integrity: &str) -> Option<PathBuf> {
+    let (algorithm, digest) = integrity.split_whitespace().next()?.split_once('-')?;
+    let digest = base64::engine::general_purpose::STANDARD
+        .decode(digest)
+        .ok()?;
+    let hex = digest.iter().fold(String::new(), |mut hex, byte| {
+        let _ = write!(hex, "{byte:02x}");
+        hex
+    });
+    if hex.len() < 5 {
+        return None;
+    }
+    Some(
+        root.join("content-v2")
+            .join(algorithm)
+            .join(&hex[..2])
+            .join(&hex[2..4])
+            .join(&hex[4..]),
+    )
+}
+
+// ============================================================================
+// Lockfiles
+// ============================================================================
+
+/// Ecosystems whose lockfiles pin cached versions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Ecosystem {
+    Jvm,
+    Cargo,
+    Npm,
+}
+
+/// Artifact -> version -> first lockfile pinning it
+type PinnedVersions = HashMap<String, HashMap<String, PathBuf>>;
+
+/// Reads (artifact, version) pairs from a lockfile's contents
+type LockfileParser = fn(&str) -> Vec<(String, String)>;
+
+/// Versions pinned by lockfiles of projects on disk
+#[derive(Debug, Default)]
+struct LockedVersions {
+    /// Pinned versions by ecosystem
+    pinned: HashMap<Ecosystem, PinnedVersions>,
+    /// Every lockfile read
+    lockfiles: Vec<String>,
+}
+
+impl LockedVersions {
+    /// Read the lockfiles of every project under `roots`, skipping hidden
+    /// directories (where caches live) and build artifact directories
+    fn find(roots: &[PathBuf]) -> Self {
+        let mut locked = Self::default();
+        for root in roots {
+            let files = WalkDir::new(root)
+                .into_iter()
+                .filter_entry(|entry| {
+                    entry.depth() == 0
+                        || !entry.file_type().is_dir()
+                        || !(entry.file_name().to_string_lossy().starts_with('.')
+                            || detect_bloat_category(entry.path()).is_some())
+                })
+                .filter_map(Result::ok)
+                .filter(|entry| entry.file_type().is_file());
+            for file in files {
+                locked.read(file.path());
+            }
+        }
+        locked
+    }
+
+    /// Pinned versions of one ecosystem
+    fn versions(&self, ecosystem: Ecosystem) -> Option<&PinnedVersions> {
+        self.pinned.get(&ecosystem)
+    }
+
+    /// Read `path` if it is a lockfile
+    fn read(&mut self, path: &Path) {
+        let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+            return;
+        };
+        let (ecosystem, parse): (Ecosystem, LockfileParser) = match name {
+            "Cargo.lock" => (Ecosystem::Cargo, parse_cargo_lock),
+            "package-lock.json" | "npm-shrinkwrap.json" => (Ecosystem::Npm, parse_package_lock),
+            "gradle.lockfile" => (Ecosystem::Jvm, parse_gradle_lockfile),
+            _ => return,
+        };
+        let Ok(content) = fs::read_to_string(path) else {
+            return;
+        };
+
+        let pinned = self.pinned.entry(ecosystem).or_default();
+        for (artifact, version) in parse(&content) {
+            pinned
+                .entry(artifact)
+                .or_default()
+                .entry(version)
+                .or_insert_with(|| path.to_path_buf());
+        }
+        self.lockfiles.push(path.to_string_lossy().to_string());
+    }
+}
+
+/// `[[package]]` names and versions of a `Cargo.lock`
+fn parse_cargo_lock(content: &str) -> Vec<(String, String)> {
+    let Ok(lock) = content.parse::<toml::Table>() else {
+        return Vec::new();
+    };
+    lock.get("package")
+        .and_then(toml::Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|package| {
+            let name = package.get("name")?.as_str()?;
+            let version = package.get("version")?.as_str()?;
+            Some((name.to_string(), version.to_string()))
+        })
+        .collect()
+}
+
+/// Package names and versions of a `package-lock.json` (lockfile v1 to v3)
+fn parse_package_lock(content: &str) -> Vec<(String, String)> {
+    fn dependencies(deps: &serde_json::Value, pinned: &mut Vec<(String, String)>) {
+        let Some(deps) = deps.as_object() else { return };
+        for (name, dep) in deps {
+            if let Some(version) = dep.get("version").and_then(serde_json::Value::as_str) {
+                pinned.push((name.clone(), version.to_string()));
+            }
+            if let Some(nested) = dep.get("dependencies") {
+                dependencies(nested, pinned);
+            }
+        }
+    }
+
+    let Ok(lock) = serde_json::from_str::<serde_json::Value>(content) else {
+        return Vec::new();
+    };
+    let mut pinned = Vec::new();
+    if let Some(packages) = lock.get("packages").and_then(serde_json::Value::as_object) {
+        for (path, package) in packages {
+            let Some((_, name)) = path.rsplit_once("node_modules/") else {
+                continue;
+            };
+            if let Some(version) = package.get("version").and_then(serde_json::Value::as_str) {
+                pinned.push((name.to_string(), version.to_string()));
+            }
+        }
+    } else if let Some(deps) = lock.get("dependencies") {
+        dependencies(deps, &mut pinned);
+    }
+    pinned
+}
+
+/// `group:artifact:version=configurations` lines of a `gradle.lockfile`
+fn parse_gradle_lockfile(content: &str) -> Vec<(String, String)> {
+    content
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| {
+            let coordinates = line.split('=').next()?;
+            let (artifact, version) = coordinates.rsplit_once(':')?;
+            artifact
+                .contains(':')
+                .then(|| (artifact.to_string(), version.to_string()))
+        })
+        .collect()
+}
+
+// ============================================================================
+// Version Ordering
+// ============================================================================
+
+/// Compare two version strings, newest greater.
+///
+/// Versions are split into runs of digits and of other characters, ignoring
+/// `.`, `-`, `_` and `+`. Numbers compare numerically, and a number is newer
+/// than text at the same position. When one version runs out, a following
+/// number makes the other newer (`1.0.1` > `1.0`) but following text makes it
+/// older (`1.0-beta` and `1.0-SNAPSHOT` < `1.0`).
+#[must_use]
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    let (a, b) = (version_tokens(a), version_tokens(b));
+    for index in 0..a.len().max(b.len()) {
+        let ordering = match (a.get(index), b.get(index)) {
+            (Some(VersionToken::Number(x)), Some(VersionToken::Number(y))) => x.cmp(y),
+            (Some(VersionToken::Text(x)), Some(VersionToken::Text(y))) => {
+                x.to_ascii_lowercase().cmp(&y.to_ascii_lowercase())
+            }
+            (Some(VersionToken::Number(_)), Some(VersionToken::Text(_)) | None)
+            | (None, Some(VersionToken::Text(_))) => Ordering::Greater,
+            (Some(VersionToken::Text(_)) | None, Some(VersionToken::Number(_)))
+            | (Some(VersionToken::Text(_)), None) => Ordering::Less,
+            (None, None) => Ordering::Equal,
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    Ordering::Equal
+}
+
+/// Part of a version string
+#[derive(Debug, PartialEq, Eq)]
+enum VersionToken<'a> {
+    Number(u64),
+    Text(&'a str),
+}
+
+/// Split a version into numbers and text
+fn version_tokens(version: &str) -> Vec<VersionToken<'_>> {
+    let is_separator = |c: char| matches!(c, '.' | '-' | '_' | '+');
+    let mut tokens = Vec::new();
+    let mut rest = version.trim_start_matches(is_separator);
+    while let Some(first) = rest.chars().next() {
+        let digits = first.is_ascii_digit();
+        let end = rest
+            .find(|c: char| is_separator(c) || c.is_ascii_digit() != digits)
+            .unwrap_or(rest.len());
+        let token = &rest[..end];
+        tokens.push(if digits {
+            token
+                .parse()
+                .map_or(VersionToken::Text(token), VersionToken::Number)
+        } else {
+            VersionToken::Text(token)
+        });
+        rest = rest[end..].trim_start_matches(is_separator);
+    }
+    tokens
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn write(path: &Path, size: usize) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, vec![1u8; size]).unwrap();
+    }
+
+    #[test]
+    fn test_compare_versions() {
+        let mut versions = vec![
+            "1.10.0",
+            "1.2.0",
+            "1.2.0-beta.1",
+            "2.0.0-SNAPSHOT",
+            "1.2",
+            "2.0.0",
+            "1.9.12",
+        ];
+        versions.sort_by(|a, b| compare_versions(a, b));
+        assert_eq!(
+            versions,
+            [
+                "1.2",
+                "1.2.0-beta.1",
+                "1.2.0",
+                "1.9.12",
+                "1.10.0",
+                "2.0.0-SNAPSHOT",
+                "2.0.0"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_split_name_version() {
+        assert_eq!(
+            split_name_version("serde-1.0.200"),
+            Some(("serde", "1.0.200"))
+        );
+        assert_eq!(
+            split_name_version("sha-1-0.10.1"),
+            Some(("sha-1", "0.10.1"))
+        );
+        assert_eq!(
+            split_name_version("tokio-macros-2.0.0-rc.1"),
+            Some(("tokio-macros", "2.0.0-rc.1"))
+        );
+        assert_eq!(split_name_version("no-version"), None);
+    }
+
+    #[test]
+    fn test_cargo_keeps_newest_and_locked_versions() {
+        let dir = tempfile::tempdir().unwrap();
+        let registry = dir.path().join(".cargo/registry");
+        let index = "index.crates.io-6f17d22bba15001f";
+        for version in ["1.0.100", "1.0.150", "1.0.200", "1.0.210"] {
+            write(
+                &registry.join(format!("cache/{index}/serde-{version}.crate")),
+                4096,
+            );
+            write(
+                &registry.join(format!("src/{index}/serde-{version}/src/lib.rs")),
+                4096,
+            );
+        }
+        write(
+            &registry.join(format!("cache/{index}/sha-1-0.10.1.crate")),
+            4096,
+        );
+        // A project pins an old version; its vendored copy in .cargo is ignored
+        let project = dir.path().join("work/app");
+        fs::create_dir_all(&project).unwrap();
+        fs::write(
+            project.join("Cargo.lock"),
+            "version = 3\n\n[[package]]\nname = \"serde\"\nversion = \"1.0.100\"\n",
+        )
+        .unwrap();
+
+        let options = CachePruneOptions {
+            keep_newest: 2,
+            project_roots: vec![dir.path().to_string_lossy().to_string()],
+        };
+        let report = analyze_cache_versions(&registry.join("cache"), &options).unwrap();
+
+        assert_eq!(report.layout, "cargo");
+        assert_eq!(report.artifacts.len(), 1);
+        let serde = &report.artifacts[0];
+        assert_eq!(serde.artifact, "serde");
+        let plan: Vec<(&str, bool)> = serde
+            .versions
+            .iter()
+            .map(|version| (version.version.as_str(), version.keep))
+            .collect();
+        assert_eq!(
+            plan,
+            [
+                ("1.0.210", true),
+                ("1.0.200", true),
+                ("1.0.150", false),
+                ("1.0.100", true)
+            ]
+        );
+        assert!(serde.versions[3].reason.ends_with("Cargo.lock"));
+        // The archive and the extracted sources of 1.0.150 are pruned
+        assert_eq!(report.prune_paths.len(), 2);
+        assert!(report.total_prunable_mb > 0.0);
+        assert_eq!(report.lockfiles.len(), 1);
+    }
+
+    #[test]
+    fn test_maven_and_gradle_layouts() {
+        let dir = tempfile::tempdir().unwrap();
+        let maven = dir.path().join(".m2/repository");
+        for version in ["31.1-jre", "32.1.2-jre", "33.0.0-jre"] {
+            write(
+                &maven.join(format!(
+                    "com/google/guava/guava/{version}/guava-{version}.jar"
+                )),
+                1024,
+            );
+        }
+        let versions = collect_versions(CacheLayout::detect(&maven).unwrap(), &maven);
+        assert_eq!(
+            versions["com.google.guava:guava"]
+                .keys()
+                .collect::<Vec<_>>(),
+            ["31.1-jre", "32.1.2-jre", "33.0.0-jre"]
+        );
+
+        let gradle = dir.path().join(".gradle/caches/modules-2");
+        write(
+            &gradle.join("files-2.1/org.slf4j/slf4j-api/2.0.9/abc123/slf4j-api-2.0.9.jar"),
+            1024,
+        );
+        let versions = collect_versions(CacheLayout::detect(&gradle).unwrap(), &gradle);
+        assert!(versions["org.slf4j:slf4j-api"].contains_key("2.0.9"));
+        assert_eq!(
+            parse_gradle_lockfile("# comment\norg.slf4j:slf4j-api:2.0.9=runtimeClasspath\nempty="),
+            [("org.slf4j:slf4j-api".to_string(), "2.0.9".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_npm_cacache_index() {
+        let dir = tempfile::tempdir().unwrap();
+        let cacache = dir.path().join(".npm/_cacache");
+        let digest = [0xab_u8; 64];
+        let integrity = format!(
+            "sha512-{}",
+            base64::engine::general_purpose::STANDARD.encode(digest)
+        );
+        let hex = "ab".repeat(64);
+        let content = cacache.join(format!(
+            "content-v2/sha512/{}/{}/{}",
+            &hex[..2],
+            &hex[2..4],
+            &hex[4..]
+        ));
+        write(&content, 2048);
+        let key = "make-fetch-happen:request-cache:https://registry.npmjs.org/@types/node/-/node-20.1.0.tgz";
+        let line = serde_json::json!({ "key": key, "integrity": integrity, "size": 2048 });
+        let index = cacache.join("index-v5/12/34/5678");
+        fs::create_dir_all(index.parent().unwrap()).unwrap();
+        fs::write(&index, format!("deadbeef\t{line}\n")).unwrap();
+
+        let versions = collect_versions(CacheLayout::Npm, &cacache);
+        assert_eq!(versions["@types/node"]["20.1.0"], [index, content]);
+        assert_eq!(
+            npm_tarball("https://registry.npmjs.org/lodash/-/lodash-4.17.21.tgz"),
+            Some(("lodash".to_string(), "4.17.21".to_string()))
+        );
+    }
+}
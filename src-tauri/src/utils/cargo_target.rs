@@ -0,0 +1,586 @@
+//! Breakdown and selective pruning of Cargo `target/` directories.
+//!
+//! `scan_bloat` reports a `target/` directory as one entry, and deleting it
+//! forces a full rebuild. This module splits it into build profiles
+//! (`debug/`, `release/`, custom profiles and `<triple>/<profile>/` for cross
+//! builds), `doc/` output and, inside each profile, incremental caches,
+//! `deps/` artifacts and `build/` script outputs.
+//!
+//! Within a profile, every compilation unit has a `.fingerprint/<name>-<hash>`
+//! directory, and its artifacts in `deps/`, `build/` and `examples/` carry the
+//! same hash. The fingerprint records a hash of the rustc version that built
+//! the unit; units built by another toolchain than the most recently used one
+//! are stale and will never be reused by it. Like `cargo sweep`, a unit is
+//! last used when any of its files was last modified or read (atime, which
+//! `relatime` mounts update at most daily).
+//!
+//! Pruning only proposes paths; they are deleted through `cleanup_dirs`.
+
+use crate::error::compare_f32_safe;
+use crate::models::{
+    CargoProfileUsage, CargoPruneGroup, CargoPruneOptions, CargoPrunePlan, CargoTargetReport,
+};
+use crate::utils::disk_usage::{path_usage, paths_usage};
+use crate::utils::project_activity::now_secs;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use walkdir::WalkDir;
+
+/// Seconds in a day, for `older_than_days`
+const SECS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Directories of a profile holding artifacts named after their unit's hash
+const UNIT_ARTIFACT_DIRS: &[&str] = &["deps", "build", "examples"];
+
+/// One compilation unit and every file it produced
+#[derive(Debug)]
+struct Unit {
+    /// The fingerprint directory and the unit's artifacts
+    paths: Vec<PathBuf>,
+    /// Hash of the rustc version that built the unit
+    rustc: Option<u64>,
+    /// Last modification or access of any of its files (Unix seconds)
+    last_used: u64,
+}
+
+/// A build profile directory and its compilation units
+#[derive(Debug)]
+struct Profile {
+    name: String,
+    path: PathBuf,
+    units: Vec<Unit>,
+}
+
+/// A parsed `target/` directory
+#[derive(Debug)]
+struct TargetDir {
+    profiles: Vec<Profile>,
+    /// rustc hash of the most recently used unit
+    current_rustc: Option<u64>,
+}
+
+impl TargetDir {
+    /// Parse the profiles and units of `target`
+    fn read(target: &Path) -> Result<Self, String> {
+        let profiles: Vec<Profile> = find_profiles(target)
+            .into_iter()
+            .map(|(name, path)| Profile {
+                units: read_units(&path),
+                name,
+                path,
+            })
+            .collect();
+        if profiles.is_empty() && !target.join("CACHEDIR.TAG").is_file() {
+            return Err(format!(
+                "{} is not a Cargo target directory",
+                target.display()
+            ));
+        }
+
+        let current_rustc = profiles
+            .iter()
+            .flat_map(|profile| &profile.units)
+            .filter(|unit| unit.rustc.is_some())
+            .max_by_key(|unit| unit.last_used)
+            .and_then(|unit| unit.rustc);
+        Ok(Self {
+            profiles,
+            current_rustc,
+        })
+    }
+
+    /// Whether `unit` was built by another toolchain than the current one
+    const fn is_stale(&self, unit: &Unit) -> bool {
+        matches!((unit.rustc, self.current_rustc), (Some(rustc), Some(current)) if rustc != current)
+    }
+}
+
+/// Break a Cargo `target/` directory down by profile and kind of artifact.
+///
+/// **Returns:** Sizes per profile (largest first) with incremental, `deps/`,
+/// `build/` and stale-toolchain shares, plus `doc/` and other output
+///
+/// # Errors
+/// Returns an error if `target` is not a Cargo target directory
+pub fn analyze_target_dir(target: &Path) -> Result<CargoTargetReport, String> {
+    let parsed = TargetDir::read(target)?;
+    let mb = |bytes: u64| {
+        #[allow(clippy::cast_precision_loss)]
+        let mb = bytes as f32 / 1_048_576.0;
+        mb
+    };
+
+    let mut profile_bytes = 0;
+    let mut profiles: Vec<CargoProfileUsage> = parsed
+        .profiles
+        .iter()
+        .map(|profile| {
+            let total = path_usage(&profile.path).apparent_bytes;
+            let [incremental, deps, build] = ["incremental", "deps", "build"]
+                .map(|dir| path_usage(&profile.path.join(dir)).apparent_bytes);
+            let stale = paths_usage(
+                profile
+                    .units
+                    .iter()
+                    .filter(|unit| parsed.is_stale(unit))
+                    .flat_map(|unit| &unit.paths)
+                    .map(PathBuf::as_path),
+            );
+            profile_bytes += total;
+
+            CargoProfileUsage {
+                name: profile.name.clone(),
+                path: profile.path.to_string_lossy().to_string(),
+                size_mb: mb(total),
+                incremental_mb: mb(incremental),
+                deps_mb: mb(deps),
+                build_mb: mb(build),
+                other_mb: mb(total.saturating_sub(incremental + deps + build)),
+                stale_toolchain_mb: stale.apparent_mb(),
+                unit_count: profile.units.len(),
+                last_used: profile
+                    .units
+                    .iter()
+                    .map(|unit| unit.last_used)
+                    .max()
+                    .unwrap_or(0),
+            }
+        })
+        .collect();
+    profiles.sort_by(|a, b| compare_f32_safe(a.size_mb, b.size_mb));
+
+    let total = path_usage(target).apparent_bytes;
+    let doc = path_usage(&target.join("doc")).apparent_bytes;
+    let toolchain_count = parsed
+        .profiles
+        .iter()
+        .flat_map(|profile| &profile.units)
+        .filter_map(|unit| unit.rustc)
+        .collect::<HashSet<_>>()
+        .len();
+
+    Ok(CargoTargetReport {
+        target_dir: target.to_string_lossy().to_string(),
+        size_mb: mb(total),
+        profiles,
+        doc_mb: mb(doc),
+        other_mb: mb(total.saturating_sub(profile_bytes + doc)),
+        toolchain_count,
+    })
+}
+
+/// Propose what to delete from a Cargo `target/` directory.
+///
+/// **Behavior:**
+/// - Whole profiles and `doc/` are dropped first when requested
+/// - Then incremental caches, artifacts of stale toolchains, and units (and
+///   incremental caches) unused for more than `older_than_days`
+/// - Every path is listed once, under the first reason that selects it
+///
+/// **Returns:** Paths grouped by reason with the space each group frees
+///
+/// # Errors
+/// Returns an error if `target` is not a Cargo target directory or a
+/// requested profile does not exist
+pub fn plan_target_prune(
+    target: &Path,
+    options: &CargoPruneOptions,
+) -> Result<CargoPrunePlan, String> {
+    let parsed = TargetDir::read(target)?;
+    if let Some(unknown) = options
+        .profiles
+        .iter()
+        .find(|name| !parsed.profiles.iter().any(|profile| &&profile.name == name))
+    {
+        return Err(format!(
+            "Unknown profile '{unknown}' in {}",
+            target.display()
+        ));
+    }
+
+    let mut plan = PlanBuilder::default();
+    let kept: Vec<&Profile> = parsed
+        .profiles
+        .iter()
+        .filter(|profile| {
+            let dropped = options.profiles.contains(&profile.name);
+            if dropped {
+                plan.add(format!("profile {}", profile.name), [profile.path.clone()]);
+            }
+            !dropped
+        })
+        .collect();
+
+    if options.doc && target.join("doc").is_dir() {
+        plan.add("doc".to_string(), [target.join("doc")]);
+    }
+    if options.incremental {
+        for profile in &kept {
+            let incremental = profile.path.join("incremental");
+            if incremental.is_dir() {
+                plan.add(format!("incremental ({})", profile.name), [incremental]);
+            }
+        }
+    }
+    if options.stale_toolchains {
+        let stale = kept
+            .iter()
+            .flat_map(|profile| &profile.units)
+            .filter(|unit| parsed.is_stale(unit))
+            .flat_map(|unit| unit.paths.iter().cloned());
+        plan.add("built by another toolchain".to_string(), stale);
+    }
+    if let Some(days) = options.older_than_days {
+        let cutoff = now_secs().saturating_sub(days.saturating_mul(SECS_PER_DAY));
+        let mut unused: Vec<PathBuf> = kept
+            .iter()
+            .flat_map(|profile| &profile.units)
+            .filter(|unit| unit.last_used < cutoff)
+            .flat_map(|unit| unit.paths.iter().cloned())
+            .collect();
+        if !options.incremental {
+            for profile in &kept {
+                unused.extend(
+                    subdirs(&profile.path.join("incremental"))
+                        .filter(|dir| last_used(dir, true) < cutoff),
+                );
+            }
+        }
+        plan.add(format!("unused for {days} days"), unused);
+    }
+
+    let reclaimable_mb = paths_usage(plan.paths.iter().map(PathBuf::as_path)).reclaimable_mb();
+    log::info!(
+        "Pruning {} would free {reclaimable_mb:.1} MB in {} paths",
+        target.display(),
+        plan.paths.len()
+    );
+    Ok(CargoPrunePlan {
+        target_dir: target.to_string_lossy().to_string(),
+        groups: plan.groups,
+        paths: plan
+            .paths
+            .iter()
+            .map(|path| path.to_string_lossy().to_string())
+            .collect(),
+        reclaimable_mb,
+    })
+}
+
+/// Collects prune groups, listing every path once
+#[derive(Default)]
+struct PlanBuilder {
+    groups: Vec<CargoPruneGroup>,
+    paths: Vec<PathBuf>,
+    seen: HashSet<PathBuf>,
+}
+
+impl PlanBuilder {
+    /// Add the paths not selected yet under `reason`
+    fn add(&mut self, reason: String, paths: impl IntoIterator<Item = PathBuf>) {
+        let new: Vec<PathBuf> = paths
+            .into_iter()
+            .filter(|path| self.seen.insert(path.clone()))
+            .collect();
+        if new.is_empty() {
+            return;
+        }
+        self.groups.push(CargoPruneGroup {
+            reason,
+            paths: new
+                .iter()
+                .map(|path| path.to_string_lossy().to_string())
+                .collect(),
+            reclaimable_mb: paths_usage(new.iter().map(PathBuf::as_path)).reclaimable_mb(),
+        });
+        self.paths.extend(new);
+    }
+}
+
+// ============================================================================
+// Target Directory Layout
+// ============================================================================
+
+/// Profile directories of `target`: `<profile>/` and `<triple>/<profile>/`
+fn find_profiles(target: &Path) -> Vec<(String, PathBuf)> {
+    let mut profiles = Vec::new();
+    for dir in subdirs(target) {
+        let name = dir_name(&dir);
+        if name == "doc" {
+            continue;
+        }
+        if is_profile_dir(&dir) {
+            profiles.push((name, dir));
+        } else {
+            for profile in subdirs(&dir).filter(|dir| is_profile_dir(dir)) {
+                profiles.push((format!("{name}/{}", dir_name(&profile)), profile));
+            }
+        }
+    }
+    profiles.sort();
+    profiles
+}
+
+/// Whether `dir` is a build profile (it has compiled units)
+fn is_profile_dir(dir: &Path) -> bool {
+    dir.join("deps").is_dir() || dir.join(".fingerprint").is_dir()
+}
+
+/// Compilation units of a profile, with the artifacts carrying their hashes
+fn read_units(profile: &Path) -> Vec<Unit> {
+    let mut artifacts: HashMap<String, Vec<PathBuf>> = HashMap::new();
+    for dir in UNIT_ARTIFACT_DIRS {
+        for entry in fs::read_dir(profile.join(dir))
+            .into_iter()
+            .flatten()
+            .filter_map(Result::ok)
+        {
+            if let Some(hash) = unit_hash(&entry.file_name().to_string_lossy()) {
+                artifacts
+                    .entry(hash.to_string())
+                    .or_default()
+                    .push(entry.path());
+            }
+        }
+    }
+
+    subdirs(&profile.join(".fingerprint"))
+        .filter_map(|fingerprint| {
+            let hash = unit_hash(&dir_name(&fingerprint))?.to_string();
+            let artifacts = artifacts.remove(&hash).unwrap_or_default();
+            // Reading the fingerprint below bumps its atime, so only its mtime counts
+            let last_used = artifacts
+                .iter()
+                .map(|path| last_used(path, true))
+                .chain([last_used(&fingerprint, false)])
+                .max()
+                .unwrap_or(0);
+            let rustc = fingerprint_rustc(&fingerprint);
+            let mut paths = vec![fingerprint];
+            paths.extend(artifacts);
+            Some(Unit {
+                paths,
+                rustc,
+                last_used,
+            })
+        })
+        .collect()
+}
+
+/// The unit hash in an artifact or fingerprint name
+/// (`libserde-1a2b3c4d5e6f7a8b.rlib` has `1a2b3c4d5e6f7a8b`)
+#[must_use]
+pub fn unit_hash(file_name: &str) -> Option<&str> {
+    let stem = file_name.split('.').next()?;
+    let (_, hash) = stem.rsplit_once('-')?;
+    (hash.len() == 16 && hash.bytes().all(|byte| byte.is_ascii_hexdigit())).then_some(hash)
+}
+
+/// Hash of the rustc version recorded in a unit's fingerprint JSON
+fn fingerprint_rustc(fingerprint: &Path) -> Option<u64> {
+    fs::read_dir(fingerprint)
+        .ok()?
+        .filter_map(Result::ok)
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "json"))
+        .find_map(|entry| {
+            let content = fs::read_to_string(entry.path()).ok()?;
+            let json: serde_json::Value = serde_json::from_str(&content).ok()?;
+            json.get("rustc")?.as_u64()
+        })
+}
+
+/// Newest modification (or, with `accessed`, access) time of the files under
+/// `path`, in Unix seconds
+fn last_used(path: &Path, accessed: bool) -> u64 {
+    WalkDir::new(path)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| entry.metadata().ok())
+        .flat_map(|metadata| {
+            [
+                metadata.modified().ok(),
+                metadata.accessed().ok().filter(|_| accessed),
+            ]
+        })
+        .flatten()
+        .filter_map(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map(|since_epoch| since_epoch.as_secs())
+        .max()
+        .unwrap_or(0)
+}
+
+/// Subdirectories of `dir`
+fn subdirs(dir: &Path) -> impl Iterator<Item = PathBuf> {
+    fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_ok_and(|file_type| file_type.is_dir()))
+        .map(|entry| entry.path())
+}
+
+/// Last component of `path` as a string
+fn dir_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use std::fs::FileTimes;
+    use std::time::{Duration, SystemTime};
+
+    const FOO: &str = "0123456789abcdef";
+    const BAR: &str = "fedcba9876543210";
+
+    fn write(path: &Path, size: usize) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, vec![1u8; size]).unwrap();
+    }
+
+    fn age(path: &Path, days: u64) {
+        let time = SystemTime::now() - Duration::from_secs(days * SECS_PER_DAY);
+        for entry in WalkDir::new(path).into_iter().filter_map(Result::ok) {
+            if entry.file_type().is_file() {
+                fs::File::options()
+                    .write(true)
+                    .open(entry.path())
+                    .unwrap()
+                    .set_times(FileTimes::new().set_accessed(time).set_modified(time))
+                    .unwrap();
+            }
+        }
+    }
+
+    /// `debug/` with a current unit (foo), a unit from an older toolchain
+    /// built 60 days ago (bar) and incremental data; `release/`, a cross
+    /// profile and `doc/`
+    fn fake_target(base: &Path) -> PathBuf {
+        let target = base.join("target");
+        let debug = target.join("debug");
+        write(&target.join("CACHEDIR.TAG"), 10);
+        for (name, hash, rustc) in [("foo", FOO, 111), ("bar", BAR, 222)] {
+            write(
+                &debug.join(format!(".fingerprint/{name}-{hash}/lib-{name}.json")),
+                0,
+            );
+            fs::write(
+                debug.join(format!(".fingerprint/{name}-{hash}/lib-{name}.json")),
+                format!("{{\"rustc\":{rustc},\"features\":\"[]\"}}"),
+            )
+            .unwrap();
+            write(
+                &debug.join(format!("deps/lib{name}-{hash}.rlib")),
+                64 * 1024,
+            );
+            write(&debug.join(format!("deps/{name}-{hash}.d")), 100);
+        }
+        write(
+            &debug.join(format!("build/bar-{BAR}/out/generated.rs")),
+            8192,
+        );
+        write(
+            &debug.join("incremental/foo-1abc/s-xyz/query-cache.bin"),
+            32 * 1024,
+        );
+        for path in [
+            debug.join(format!(".fingerprint/bar-{BAR}")),
+            debug.join(format!("deps/libbar-{BAR}.rlib")),
+            debug.join(format!("deps/bar-{BAR}.d")),
+            debug.join(format!("build/bar-{BAR}")),
+        ] {
+            age(&path, 60);
+        }
+        write(
+            &target.join(format!("release/deps/libfoo-{FOO}.rlib")),
+            16 * 1024,
+        );
+        write(
+            &target.join(format!(
+                "x86_64-pc-windows-gnu/debug/deps/libfoo-{FOO}.rlib"
+            )),
+            4096,
+        );
+        write(&target.join("doc/foo/index.html"), 4096);
+        target
+    }
+
+    #[test]
+    fn test_unit_hash() {
+        assert_eq!(unit_hash(&format!("libserde-{FOO}.rlib")), Some(FOO));
+        assert_eq!(unit_hash(&format!("build-script-build-{FOO}")), Some(FOO));
+        assert_eq!(unit_hash("myapp"), None);
+        assert_eq!(unit_hash("libfoo-xyz.rlib"), None);
+    }
+
+    #[test]
+    fn test_analyze_target_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = fake_target(dir.path());
+
+        let report = analyze_target_dir(&target).unwrap();
+        let names: Vec<&str> = report.profiles.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["debug", "release", "x86_64-pc-windows-gnu/debug"]);
+        let debug = &report.profiles[0];
+        assert_eq!(debug.unit_count, 2);
+        assert!(debug.incremental_mb > 0.0);
+        assert!(debug.deps_mb > debug.build_mb);
+        // bar was built by the older toolchain
+        assert!(debug.stale_toolchain_mb > 0.06 && debug.stale_toolchain_mb < 0.1);
+        assert_eq!(report.toolchain_count, 2);
+        assert!(report.doc_mb > 0.0);
+
+        assert!(analyze_target_dir(&target.join("doc")).is_err());
+    }
+
+    #[test]
+    fn test_plan_incremental_and_stale_toolchains() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = fake_target(dir.path());
+        let options = CargoPruneOptions {
+            incremental: true,
+            stale_toolchains: true,
+            ..CargoPruneOptions::default()
+        };
+
+        let plan = plan_target_prune(&target, &options).unwrap();
+        let reasons: Vec<&str> = plan.groups.iter().map(|g| g.reason.as_str()).collect();
+        assert_eq!(
+            reasons,
+            ["incremental (debug)", "built by another toolchain"]
+        );
+        // Fingerprint, rlib, dep-info and build script output of bar
+        assert_eq!(plan.groups[1].paths.len(), 4);
+        assert!(plan.paths.iter().all(|path| !path.contains("libfoo")));
+        assert!(plan.reclaimable_mb > 0.0);
+    }
+
+    #[test]
+    fn test_plan_profiles_doc_and_unused_units() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = fake_target(dir.path());
+        let options = CargoPruneOptions {
+            older_than_days: Some(30),
+            profiles: vec!["release".to_string()],
+            doc: true,
+            ..CargoPruneOptions::default()
+        };
+
+        let plan = plan_target_prune(&target, &options).unwrap();
+        let reasons: Vec<&str> = plan.groups.iter().map(|g| g.reason.as_str()).collect();
+        assert_eq!(reasons, ["profile release", "doc", "unused for 30 days"]);
+        assert!(plan.groups[2].paths.iter().all(|path| path.contains(BAR)));
+
+        let unknown = CargoPruneOptions {
+            profiles: vec!["bench".to_string()],
+            ..CargoPruneOptions::default()
+        };
+        assert!(plan_target_prune(&target, &unknown).is_err());
+    }
+}
@@ -6,10 +6,16 @@
 //! - Trash/recycle bin support for non-permanent deletion
 //! - Comprehensive error handling and logging
 //! - Audit trail logging for all deletions
+//! - Freed-space estimates that count hardlinked files only once all links go
 
+use std::collections::HashMap;
 use std::path::Path;
 
+use walkdir::WalkDir;
+
 use super::deletion_log::{log_deletion, DeletionRecord};
+use super::disk_usage::{DiskUsage, FileUsage, UsageAccumulator};
+use super::git_status::{describe_unsaved_work, find_unsaved_work};
 use super::path::validate_scan_path;
 use super::undo::{UndoHistory, OperationType};
 use crate::{models::CleanupReq, ScannerResult};
@@ -58,6 +64,8 @@ fn infer_deletion_category(path: &str) -> String {
 /// 1. The number of files doesn't exceed `MAX_BATCH_DELETE_COUNT` (10,000)
 /// 2. The total size doesn't exceed `MAX_BATCH_DELETE_SIZE` (100GB)
 /// 3. All paths are validated for security (no system directories)
+/// 4. No uncommitted changes, untracked files, unpushed commits or stashes
+///    would be lost, unless `allow_unsaved_work` is set
 ///
 /// # Arguments
 /// * `req` - The cleanup request containing paths to delete
@@ -74,6 +82,7 @@ fn infer_deletion_category(path: &str) -> String {
 ///     paths: vec!["/tmp/file.txt".to_string()],
 ///     dry_run: false,
 ///     trash: true,
+///     allow_unsaved_work: false,
 /// };
 /// match validate_deletion_request(&req) {
 ///     Ok(_) => println!("Deletion request is valid"),
@@ -118,9 +127,63 @@ pub fn validate_deletion_request(req: &CleanupReq) -> ScannerResult<()> {
         return Err(format!("Cannot delete {total} at once (maximum: {max})").into());
     }
 
+    // Work that exists nowhere else needs an explicit override
+    if !req.allow_unsaved_work {
+        let unsaved = find_unsaved_work(&req.paths);
+        if !unsaved.is_empty() {
+            return Err(format!(
+                "Deletion would destroy unsaved Git work:\n{}",
+                describe_unsaved_work(&unsaved)
+            )
+            .into());
+        }
+    }
+
     Ok(())
 }
 
+/// Sizes of every file under each requested path, measured before deletion so the
+/// space freed can be reported for exactly the paths that were removed.
+#[derive(Debug, Default)]
+pub struct DeletionEstimate {
+    files: HashMap<String, Vec<FileUsage>>,
+}
+
+impl DeletionEstimate {
+    /// Record the usage of every regular file under each path
+    #[must_use]
+    pub fn measure(paths: &[String]) -> Self {
+        let files = paths
+            .iter()
+            .map(|path| {
+                let usages = WalkDir::new(path)
+                    .into_iter()
+                    .filter_map(Result::ok)
+                    .filter(|entry| entry.file_type().is_file())
+                    .filter_map(|entry| entry.metadata().ok())
+                    .map(|metadata| FileUsage::from_metadata(&metadata))
+                    .collect();
+                (path.clone(), usages)
+            })
+            .collect();
+
+        Self { files }
+    }
+
+    /// Usage of the `deleted` paths taken together. Its `reclaimable_bytes` is the
+    /// space freed: a hardlinked file only counts once every link was deleted.
+    #[must_use]
+    pub fn freed(&self, deleted: &[String]) -> DiskUsage {
+        let mut usage = UsageAccumulator::new();
+        for path in deleted {
+            for file in self.files.get(path).into_iter().flatten() {
+                usage.add(file);
+            }
+        }
+        usage.usage()
+    }
+}
+
 /// Safely deletes files and directories with optional trash support.
 ///
 /// This function handles batch deletion with the following features:
@@ -384,6 +447,7 @@ mod tests {
             paths: vec!["/tmp/test.txt".to_string()],
             dry_run: false,
             trash: true,
+            allow_unsaved_work: false,
         };
         assert!(validate_deletion_request(&req).is_ok());
     }
@@ -394,6 +458,7 @@ mod tests {
             paths: vec!["/tmp/file.txt".to_string()],
             dry_run: false,
             trash: false,
+            allow_unsaved_work: false,
         };
         // Should pass validation (may fail on file existence but not security)
         if let Err(e) = validate_deletion_request(&req) {
@@ -410,6 +475,7 @@ mod tests {
             paths,
             dry_run: false,
             trash: true,
+            allow_unsaved_work: false,
         };
         // Should pass validation (may fail on file existence but not security)
         if let Err(e) = validate_deletion_request(&req) {
@@ -426,6 +492,7 @@ mod tests {
             paths,
             dry_run: false,
             trash: true,
+            allow_unsaved_work: false,
         };
         let result = validate_deletion_request(&req);
         assert!(result.is_err());
@@ -441,6 +508,7 @@ mod tests {
             paths,
             dry_run: false,
             trash: true,
+            allow_unsaved_work: false,
         };
         // Should pass validation (may fail on file existence but not security)
         if let Err(e) = validate_deletion_request(&req) {
@@ -456,6 +524,7 @@ mod tests {
             paths: vec![],
             dry_run: false,
             trash: true,
+            allow_unsaved_work: false,
         };
         // Empty request should be valid (nothing to delete)
         assert!(validate_deletion_request(&req).is_ok());
@@ -629,6 +698,7 @@ mod tests {
             paths: vec!["/tmp/test".to_string(); MAX_BATCH_DELETE_COUNT + 1],
             dry_run: false,
             trash: true,
+            allow_unsaved_work: false,
         };
 
         let result = validate_deletion_request(&req);
@@ -665,6 +735,7 @@ mod tests {
             paths: vec!["/System/Library/test.txt".to_string()],
             dry_run: false,
             trash: true,
+            allow_unsaved_work: false,
         };
         let result = validate_deletion_request(&req);
         assert!(result.is_err());
@@ -679,6 +750,7 @@ mod tests {
             paths: vec!["/bin/some_file".to_string()],
             dry_run: false,
             trash: true,
+            allow_unsaved_work: false,
         };
         let result = validate_deletion_request(&req);
         assert!(result.is_err());
@@ -693,6 +765,7 @@ mod tests {
             paths: vec!["/usr/bin/critical_tool".to_string()],
             dry_run: false,
             trash: true,
+            allow_unsaved_work: false,
         };
         let result = validate_deletion_request(&req);
         assert!(result.is_err());
@@ -709,6 +782,7 @@ mod tests {
                 paths: vec![format!("{}/test_file.txt", home)],
                 dry_run: false,
                 trash: true,
+                allow_unsaved_work: false,
             };
             // This should pass security validation (may fail on size/count limits with real files)
             let result = validate_deletion_request(&req);
@@ -726,6 +800,7 @@ mod tests {
             paths: vec!["/tmp/safe_file.txt".to_string()],
             dry_run: false,
             trash: true,
+            allow_unsaved_work: false,
         };
         let result = validate_deletion_request(&req);
         // Should not be a security error
@@ -744,6 +819,7 @@ mod tests {
             ],
             dry_run: false,
             trash: true,
+            allow_unsaved_work: false,
         };
         let result = validate_deletion_request(&req);
         assert!(result.is_err());
@@ -751,4 +827,25 @@ mod tests {
         assert!(err_msg.contains("Security validation failed"));
         assert!(err_msg.contains("System/Library"));
     }
+
+    #[test]
+    fn test_deletion_estimate_counts_hardlinks_once() {
+        let temp_dir = TempDir::new().unwrap();
+        let original = temp_dir.path().join("store.bin");
+        let link = temp_dir.path().join("link.bin");
+        fs::write(&original, vec![1u8; 16 * 1024]).unwrap();
+        fs::hard_link(&original, &link).unwrap();
+
+        let paths = vec![
+            original.to_string_lossy().to_string(),
+            link.to_string_lossy().to_string(),
+        ];
+        let estimate = DeletionEstimate::measure(&paths);
+
+        // Removing one link frees nothing; removing both frees the inode once
+        assert_eq!(estimate.freed(&paths[..1]).reclaimable_bytes, 0);
+        let both = estimate.freed(&paths);
+        assert_eq!(both.apparent_bytes, 16 * 1024);
+        assert_eq!(both.reclaimable_bytes, both.allocated_bytes);
+    }
 }
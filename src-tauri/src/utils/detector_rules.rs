@@ -0,0 +1,652 @@
+//! User-defined bloat, junk and cache rules.
+//!
+//! The built-in tables in [`crate::utils::patterns`] are compile-time
+//! constants. Rules for anything else (a company-internal build directory, a
+//! tool's cache) live in TOML rule files: a global one in the app's config
+//! directory and an optional [`ROOT_RULES_FILE_NAME`] at the root of a scan.
+//!
+//! ```toml
+//! [[bloat]]
+//! id = "acme_out"
+//! name = "ACME Build Output"
+//! globs = ["acme-out", "acme-out-*"]
+//! markers = ["acme.toml"]
+//! min_size_mb = 50
+//! min_age_days = 14
+//! safety = "safe"
+//! description = "Output of the ACME build tool"
+//! ```
+//!
+//! Globs without a `/` match an entry's name; globs with a `/` match the end
+//! of its path (`.cache/acme` matches `~/.cache/acme`). Markers are file names
+//! (or `*.ext` patterns) that must sit next to the matched entry. Rules are
+//! validated when loaded or saved and checked before the built-ins: rules of
+//! the scan root first, then global rules.
+
+use crate::utils::patterns::{has_marker, BLOAT_PATTERNS, CACHE_PATTERNS, JUNK_PATTERNS};
+use globset::{Glob, GlobBuilder, GlobSet, GlobSetBuilder};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Name of the per-scan-root rule file
+pub const ROOT_RULES_FILE_NAME: &str = ".disk-bloat-rules.toml";
+
+/// Name of the global rule file in the app's config directory
+pub const GLOBAL_RULES_FILE_NAME: &str = "detector_rules.toml";
+
+/// Safety levels a rule may declare
+const SAFETY_LEVELS: &[&str] = &["safe", "caution", "dangerous"];
+
+/// Seconds in a day, for `min_age_days`
+const SECS_PER_DAY: u64 = 24 * 60 * 60;
+
+// ============================================================================
+// Rule Files
+// ============================================================================
+
+/// One user-defined detector rule
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct DetectorRule {
+    /// Category identifier, unique among the file's rules of the same kind
+    pub id: String,
+    /// Display name for UI presentation
+    pub name: String,
+    /// Name globs (`acme-out-*`) or path globs (`.cache/acme`)
+    pub globs: Vec<String>,
+    /// Files (or `*.ext` patterns) that must sit next to a match
+    #[serde(default)]
+    pub markers: Vec<String>,
+    /// Only report matches of at least this size (MB)
+    #[serde(default)]
+    pub min_size_mb: Option<f32>,
+    /// Only report matches whose newest file is at least this many days old
+    #[serde(default)]
+    pub min_age_days: Option<u64>,
+    /// Safety level of deleting a match: "safe", "caution", or "dangerous"
+    pub safety: String,
+    /// What the matched entries are
+    #[serde(default)]
+    pub description: String,
+}
+
+impl DetectorRule {
+    /// Whether a match of `size_mb` whose newest file was modified at
+    /// `last_modified` (Unix seconds) meets the size and age conditions at `now`
+    #[must_use]
+    pub fn admits(&self, size_mb: f32, last_modified: u64, now: u64) -> bool {
+        let big_enough = self.min_size_mb.map_or(true, |min| size_mb >= min);
+        let old_enough = self.min_age_days.map_or(true, |days| {
+            now.saturating_sub(last_modified) >= days.saturating_mul(SECS_PER_DAY)
+        });
+        big_enough && old_enough
+    }
+
+    /// Why an entry matched, for bloat entry reasons
+    fn describe(&self, marker: Option<&str>) -> String {
+        let what = if self.description.is_empty() {
+            &self.name
+        } else {
+            &self.description
+        };
+        let next_to = marker
+            .map(|marker| format!(" next to {marker}"))
+            .unwrap_or_default();
+        format!("{what}{next_to} (rule {})", self.id)
+    }
+}
+
+/// Contents of a TOML rule file
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct RuleFile {
+    /// Build artifact directory rules
+    pub bloat: Vec<DetectorRule>,
+    /// Junk file rules
+    pub junk: Vec<DetectorRule>,
+    /// Cache directory rules
+    pub cache: Vec<DetectorRule>,
+}
+
+impl RuleFile {
+    /// Parse and validate rule file contents
+    pub fn parse(content: &str) -> Result<Self, String> {
+        let rules: Self = toml::from_str(content).map_err(|e| format!("Invalid rule file: {e}"))?;
+        rules.validate()?;
+        Ok(rules)
+    }
+
+    /// Load a rule file, or no rules if it does not exist
+    pub fn load(path: &Path) -> Result<Self, String> {
+        match fs::read_to_string(path) {
+            Ok(content) => Self::parse(&content).map_err(|e| format!("{}: {e}", path.display())),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(format!("Failed to read {}: {err}", path.display())),
+        }
+    }
+
+    /// Validate and write the rules to `path`, creating its directory
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        self.validate()?;
+        let content =
+            toml::to_string_pretty(self).map_err(|e| format!("Failed to serialize rules: {e}"))?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {e}", parent.display()))?;
+        }
+        fs::write(path, content).map_err(|e| format!("Failed to write {}: {e}", path.display()))
+    }
+
+    /// Check every rule, reporting all problems at once
+    pub fn validate(&self) -> Result<(), String> {
+        let mut problems = Vec::new();
+        for (kind, rules) in self.by_kind() {
+            let mut ids = HashSet::new();
+            for rule in rules {
+                let label = format!("{kind} rule '{}'", rule.id);
+                if rule.id.trim().is_empty() {
+                    problems.push(format!("{kind} rule without an id"));
+                } else if !ids.insert(rule.id.as_str()) {
+                    problems.push(format!("{label} is defined twice"));
+                }
+                if rule.name.trim().is_empty() {
+                    problems.push(format!("{label} has no name"));
+                }
+                if rule.globs.is_empty() {
+                    problems.push(format!("{label} has no globs"));
+                }
+                for glob in &rule.globs {
+                    if let Err(err) = compile_glob(glob) {
+                        problems.push(format!("{label} has an invalid glob '{glob}': {err}"));
+                    }
+                }
+                for marker in &rule.markers {
+                    if marker.is_empty() || marker.contains('/') || marker.contains('\\') {
+                        problems.push(format!(
+                            "{label} has marker '{marker}', which is not a file name"
+                        ));
+                    }
+                }
+                if rule
+                    .min_size_mb
+                    .is_some_and(|mb| !mb.is_finite() || mb < 0.0)
+                {
+                    problems.push(format!("{label} has a negative or invalid min_size_mb"));
+                }
+                if !SAFETY_LEVELS.contains(&rule.safety.as_str()) {
+                    problems.push(format!(
+                        "{label} has safety '{}' (expected safe, caution or dangerous)",
+                        rule.safety
+                    ));
+                }
+            }
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(problems.join("; "))
+        }
+    }
+
+    fn by_kind(&self) -> [(&'static str, &[DetectorRule]); 3] {
+        [
+            ("bloat", &self.bloat),
+            ("junk", &self.junk),
+            ("cache", &self.cache),
+        ]
+    }
+}
+
+/// Path of the global rule file, if the config directory is known
+#[must_use]
+pub fn global_rules_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("disk-bloat-scanner").join(GLOBAL_RULES_FILE_NAME))
+}
+
+/// Path of the rule file for scans of `root`
+#[must_use]
+pub fn root_rules_path(root: &Path) -> PathBuf {
+    root.join(ROOT_RULES_FILE_NAME)
+}
+
+/// The built-in patterns, in rule form, for listing next to user rules
+#[must_use]
+pub fn builtin_rules() -> RuleFile {
+    let strings = |items: &[&str]| items.iter().map(|s| (*s).to_string()).collect();
+    RuleFile {
+        bloat: BLOAT_PATTERNS
+            .iter()
+            .map(|pattern| DetectorRule {
+                id: pattern.category_id.to_string(),
+                name: pattern.display_name.to_string(),
+                globs: strings(pattern.dir_names),
+                markers: strings(pattern.markers),
+                min_size_mb: None,
+                min_age_days: None,
+                safety: pattern.safety.to_string(),
+                description: String::new(),
+            })
+            .collect(),
+        junk: JUNK_PATTERNS
+            .iter()
+            .map(|pattern| DetectorRule {
+                id: pattern.category_id.to_string(),
+                name: pattern.display_name.to_string(),
+                globs: vec![pattern.pattern.to_string()],
+                markers: Vec::new(),
+                min_size_mb: None,
+                min_age_days: None,
+                safety: pattern.safety.to_string(),
+                description: String::new(),
+            })
+            .collect(),
+        cache: CACHE_PATTERNS
+            .iter()
+            .map(
+                |(pattern, category_id, display_name, safety, description, _)| DetectorRule {
+                    id: (*category_id).to_string(),
+                    name: (*display_name).to_string(),
+                    globs: vec![(*pattern).to_string()],
+                    markers: Vec::new(),
+                    min_size_mb: None,
+                    min_age_days: None,
+                    safety: (*safety).to_string(),
+                    description: (*description).to_string(),
+                },
+            )
+            .collect(),
+    }
+}
+
+/// Built-in, global and scan-root rules, as listed for editing
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct RuleListing {
+    /// Built-in patterns (read-only)
+    pub builtin: RuleFile,
+    /// Rules of the global rule file
+    pub global: RuleFile,
+    /// Path of the global rule file, if the config directory is known
+    pub global_path: Option<String>,
+    /// Rules of the scan root's rule file, if a root was given
+    pub root: Option<RuleFile>,
+    /// Path of the scan root's rule file, if a root was given
+    pub root_path: Option<String>,
+}
+
+impl RuleListing {
+    /// List the built-in and global rules, plus the rules of `root` if given
+    pub fn load(root: Option<&Path>) -> Result<Self, String> {
+        let global_path = global_rules_path();
+        let global = match &global_path {
+            Some(path) => RuleFile::load(path)?,
+            None => RuleFile::default(),
+        };
+        let root_path = root.map(root_rules_path);
+        let root = root_path.as_deref().map(RuleFile::load).transpose()?;
+        let display = |path: PathBuf| path.to_string_lossy().to_string();
+
+        Ok(Self {
+            builtin: builtin_rules(),
+            global,
+            global_path: global_path.map(display),
+            root,
+            root_path: root_path.map(display),
+        })
+    }
+}
+
+// ============================================================================
+// Compiled Rules
+// ============================================================================
+
+/// A rule with its globs compiled for matching
+#[derive(Debug, Clone)]
+struct CompiledRule {
+    rule: DetectorRule,
+    /// Globs matched against the entry's name
+    names: GlobSet,
+    /// Globs matched against the entry's full path
+    paths: GlobSet,
+}
+
+impl CompiledRule {
+    fn new(rule: DetectorRule) -> Result<Self, String> {
+        let mut names = GlobSetBuilder::new();
+        let mut paths = GlobSetBuilder::new();
+        for glob in &rule.globs {
+            let compiled = compile_glob(glob)
+                .map_err(|e| format!("Invalid glob '{glob}' in rule '{}': {e}", rule.id))?;
+            if glob.contains('/') {
+                paths.add(compiled);
+            } else {
+                names.add(compiled);
+            }
+        }
+        let build = |set: GlobSetBuilder| {
+            set.build()
+                .map_err(|e| format!("Invalid globs in rule '{}': {e}", rule.id))
+        };
+        Ok(Self {
+            names: build(names)?,
+            paths: build(paths)?,
+            rule,
+        })
+    }
+
+    /// Match `path` against the globs and, if the rule has markers, require one next to it
+    fn matches(&self, index: usize, path: &Path) -> Option<RuleMatch<'_>> {
+        let name_matches = path
+            .file_name()
+            .is_some_and(|name| self.names.is_match(name));
+        if !name_matches && !self.paths.is_match(path) {
+            return None;
+        }
+
+        let marker = if self.rule.markers.is_empty() {
+            None
+        } else {
+            let parent = path.parent()?;
+            let marker = self
+                .rule
+                .markers
+                .iter()
+                .find(|marker| has_marker(parent, marker))?;
+            Some(marker.as_str())
+        };
+        Some(RuleMatch {
+            index,
+            rule: &self.rule,
+            marker,
+        })
+    }
+}
+
+/// Compile a rule glob; globs with a `/` match the end of a path
+fn compile_glob(glob: &str) -> Result<Glob, globset::Error> {
+    let anchored = if glob.contains('/') && !glob.starts_with('/') && !glob.starts_with("**/") {
+        format!("**/{glob}")
+    } else {
+        glob.to_string()
+    };
+    GlobBuilder::new(&anchored).literal_separator(true).build()
+}
+
+/// A matched user rule and why it matched
+#[derive(Debug, Clone, Copy)]
+pub struct RuleMatch<'a> {
+    /// Index of the rule, for [`DetectorRules::bloat_rule`] and friends
+    pub index: usize,
+    /// The matched rule
+    pub rule: &'a DetectorRule,
+    /// Marker file found next to the entry, if the rule has markers
+    pub marker: Option<&'a str>,
+}
+
+impl RuleMatch<'_> {
+    /// Why the entry matched: the rule's description, the marker found and the rule id
+    #[must_use]
+    pub fn reason(&self) -> String {
+        self.rule.describe(self.marker)
+    }
+}
+
+/// User rules merged in precedence order, ready to match during a scan
+#[derive(Debug, Clone, Default)]
+pub struct DetectorRules {
+    bloat: Vec<CompiledRule>,
+    junk: Vec<CompiledRule>,
+    cache: Vec<CompiledRule>,
+}
+
+impl DetectorRules {
+    /// No user rules; scans use the built-ins only
+    #[must_use]
+    pub fn none() -> Self {
+        Self::default()
+    }
+
+    /// Merge rule files; rules of earlier files are checked first
+    pub fn compile(files: &[RuleFile]) -> Result<Self, String> {
+        let mut rules = Self::default();
+        for file in files {
+            file.validate()?;
+            for (target, source) in [
+                (&mut rules.bloat, &file.bloat),
+                (&mut rules.junk, &file.junk),
+                (&mut rules.cache, &file.cache),
+            ] {
+                for rule in source {
+                    target.push(CompiledRule::new(rule.clone())?);
+                }
+            }
+        }
+        Ok(rules)
+    }
+
+    /// Load the rules for a scan of `root`: its own rule file, then the global one
+    ///
+    /// A file that cannot be read or holds invalid rules is skipped with a
+    /// warning, so a bad rule file never stops a scan.
+    #[must_use]
+    pub fn load_for_root(root: &Path) -> Self {
+        let mut rules = Self::default();
+        for path in std::iter::once(root_rules_path(root)).chain(global_rules_path()) {
+            match RuleFile::load(&path).and_then(|file| Self::compile(&[file])) {
+                Ok(file_rules) => {
+                    rules.bloat.extend(file_rules.bloat);
+                    rules.junk.extend(file_rules.junk);
+                    rules.cache.extend(file_rules.cache);
+                }
+                Err(err) => log::warn!("Ignoring rule file {}: {err}", path.display()),
+            }
+        }
+        if !rules.is_empty() {
+            log::info!(
+                "Loaded {} bloat, {} junk and {} cache rules for {}",
+                rules.bloat.len(),
+                rules.junk.len(),
+                rules.cache.len(),
+                root.display()
+            );
+        }
+        rules
+    }
+
+    /// Whether there are no user rules
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.bloat.is_empty() && self.junk.is_empty() && self.cache.is_empty()
+    }
+
+    /// First bloat rule matching a directory
+    #[must_use]
+    pub fn match_bloat(&self, path: &Path) -> Option<RuleMatch<'_>> {
+        first_match(&self.bloat, path)
+    }
+
+    /// First junk rule matching a file
+    #[must_use]
+    pub fn match_junk(&self, path: &Path) -> Option<RuleMatch<'_>> {
+        first_match(&self.junk, path)
+    }
+
+    /// First cache rule matching a directory
+    #[must_use]
+    pub fn match_cache(&self, path: &Path) -> Option<RuleMatch<'_>> {
+        first_match(&self.cache, path)
+    }
+
+    /// Bloat rule at `index`, as returned in a [`RuleMatch`]
+    #[must_use]
+    pub fn bloat_rule(&self, index: usize) -> Option<&DetectorRule> {
+        self.bloat.get(index).map(|compiled| &compiled.rule)
+    }
+
+    /// Cache rule at `index`, as returned in a [`RuleMatch`]
+    #[must_use]
+    pub fn cache_rule(&self, index: usize) -> Option<&DetectorRule> {
+        self.cache.get(index).map(|compiled| &compiled.rule)
+    }
+}
+
+fn first_match<'a>(rules: &'a [CompiledRule], path: &Path) -> Option<RuleMatch<'a>> {
+    rules
+        .iter()
+        .enumerate()
+        .find_map(|(index, compiled)| compiled.matches(index, path))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    const RULES: &str = r#"
+        [[bloat]]
+        id = "acme_out"
+        name = "ACME Build Output"
+        globs = ["acme-out", "acme-out-*"]
+        markers = ["acme.toml"]
+        min_size_mb = 1.5
+        safety = "safe"
+        description = "Output of the ACME build tool"
+
+        [[junk]]
+        id = "acme_logs"
+        name = "ACME Logs"
+        globs = ["*.acmelog"]
+        min_age_days = 7
+        safety = "safe"
+
+        [[cache]]
+        id = "acme_cache"
+        name = "ACME Cache"
+        globs = [".cache/acme"]
+        safety = "caution"
+    "#;
+
+    #[test]
+    fn test_parse_and_match_rules() {
+        let file = RuleFile::parse(RULES).unwrap();
+        assert_eq!(file.bloat.len(), 1);
+        let rules = DetectorRules::compile(&[file]).unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("acme-out-debug");
+        assert!(rules.match_bloat(&out).is_none());
+        std::fs::write(dir.path().join("acme.toml"), "").unwrap();
+        let found = rules.match_bloat(&out).unwrap();
+        assert_eq!(found.rule.id, "acme_out");
+        assert_eq!(
+            found.reason(),
+            "Output of the ACME build tool next to acme.toml (rule acme_out)"
+        );
+
+        assert!(rules.match_junk(Path::new("/w/build.acmelog")).is_some());
+        assert!(rules.match_junk(Path::new("/w/build.log")).is_none());
+        assert!(rules
+            .match_cache(Path::new("/home/u/.cache/acme"))
+            .is_some());
+        assert!(rules
+            .match_cache(Path::new("/home/u/.cache/acme/x"))
+            .is_none());
+    }
+
+    #[test]
+    fn test_rule_conditions() {
+        let file = RuleFile::parse(RULES).unwrap();
+        let (bloat, junk) = (&file.bloat[0], &file.junk[0]);
+        let now = 100 * SECS_PER_DAY;
+
+        assert!(!bloat.admits(1.0, 0, now));
+        assert!(bloat.admits(2.0, now, now));
+        assert!(junk.admits(0.0, 90 * SECS_PER_DAY, now));
+        assert!(!junk.admits(0.0, 95 * SECS_PER_DAY, now));
+    }
+
+    #[test]
+    fn test_validation_reports_every_problem() {
+        let err = RuleFile::parse(
+            r#"
+            [[bloat]]
+            id = "x"
+            name = ""
+            globs = ["[unclosed"]
+            markers = ["sub/file"]
+            safety = "yolo"
+
+            [[bloat]]
+            id = "x"
+            name = "Again"
+            globs = []
+            safety = "safe"
+            "#,
+        )
+        .unwrap_err();
+
+        for expected in [
+            "has no name",
+            "invalid glob",
+            "not a file name",
+            "safety 'yolo'",
+            "defined twice",
+            "has no globs",
+        ] {
+            assert!(err.contains(expected), "{expected} missing from {err}");
+        }
+        assert!(RuleFile::parse("[[bloat]]\nid = 1").is_err());
+        assert!(RuleFile::parse("[unknown]").is_err());
+    }
+
+    #[test]
+    fn test_root_rules_take_precedence() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = RuleFile::parse(RULES).unwrap();
+        let mut root_file = file.clone();
+        root_file.bloat[0].markers.clear();
+        root_file.bloat[0].id = "root_acme".to_string();
+        root_file.save(&root_rules_path(dir.path())).unwrap();
+
+        let loaded = RuleFile::load(&root_rules_path(dir.path())).unwrap();
+        assert_eq!(loaded, root_file);
+        assert_eq!(
+            RuleFile::load(&dir.path().join("missing.toml")).unwrap(),
+            RuleFile::default()
+        );
+
+        let rules = DetectorRules::compile(&[root_file, file]).unwrap();
+        let found = rules.match_bloat(&dir.path().join("acme-out")).unwrap();
+        assert_eq!(found.rule.id, "root_acme");
+        assert_eq!(found.index, 0);
+    }
+
+    #[test]
+    fn test_bad_rule_file_falls_back_to_builtins() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(root_rules_path(dir.path()), "[[bloat]]\nid = 1").unwrap();
+
+        let rules = DetectorRules::load_for_root(dir.path());
+        assert!(rules.match_bloat(&dir.path().join("acme-out")).is_none());
+    }
+
+    #[test]
+    fn test_builtin_rules_are_valid() {
+        let builtins = builtin_rules();
+        assert_eq!(builtins.bloat.len(), BLOAT_PATTERNS.len());
+        assert_eq!(builtins.cache.len(), CACHE_PATTERNS.len());
+        // Built-in ids repeat across patterns of one category, so only globs are checked
+        for rule in builtins
+            .bloat
+            .iter()
+            .chain(&builtins.junk)
+            .chain(&builtins.cache)
+        {
+            for glob in &rule.globs {
+                assert!(compile_glob(glob).is_ok(), "{glob}");
+            }
+        }
+    }
+}
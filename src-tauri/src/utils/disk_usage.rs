@@ -0,0 +1,260 @@
+//! Hardlink-aware and allocation-aware size accounting.
+//!
+//! `metadata.len()` is the apparent size of a file. It overstates sparse files,
+//! understates the block overhead of small files, and counts a hardlinked file
+//! once per link. This module reports three numbers for any set of files:
+//! - **apparent** - sum of `len()`, each inode counted once
+//! - **allocated** - on-disk blocks (`st_blocks * 512`), each inode counted once
+//! - **reclaimable** - allocated bytes that deleting the set would actually free:
+//!   a hardlinked inode only counts when every one of its links is in the set
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::Metadata;
+use std::path::Path;
+use walkdir::WalkDir;
+
+/// Size facts for a single file
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FileUsage {
+    /// Apparent size in bytes (`len()`)
+    pub apparent: u64,
+    /// Bytes allocated on disk
+    pub allocated: u64,
+    /// Device the file lives on
+    pub dev: u64,
+    /// Inode number of the file
+    pub inode: u64,
+    /// Number of hardlinks to the inode
+    pub nlink: u64,
+}
+
+impl FileUsage {
+    /// Read size facts from file metadata
+    #[cfg(unix)]
+    #[must_use]
+    pub fn from_metadata(metadata: &Metadata) -> Self {
+        use std::os::unix::fs::MetadataExt;
+
+        Self {
+            apparent: metadata.len(),
+            // st_blocks is always in 512-byte units, regardless of the filesystem block size
+            allocated: metadata.blocks().saturating_mul(512),
+            dev: metadata.dev(),
+            inode: metadata.ino(),
+            nlink: metadata.nlink(),
+        }
+    }
+
+    /// Read size facts from file metadata (allocation and links unknown on this platform)
+    #[cfg(not(unix))]
+    #[must_use]
+    pub fn from_metadata(metadata: &Metadata) -> Self {
+        Self {
+            apparent: metadata.len(),
+            allocated: metadata.len(),
+            dev: 0,
+            inode: 0,
+            nlink: 1,
+        }
+    }
+
+    /// Whether other paths share this file's inode
+    #[must_use]
+    pub const fn is_hardlinked(&self) -> bool {
+        self.nlink > 1 && self.inode != 0
+    }
+
+    /// Bytes freed by deleting just this path
+    #[must_use]
+    pub const fn reclaimable(&self) -> u64 {
+        if self.is_hardlinked() {
+            0
+        } else {
+            self.allocated
+        }
+    }
+}
+
+/// Apparent, allocated and reclaimable bytes of a set of files
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DiskUsage {
+    /// Sum of apparent sizes, each inode counted once
+    pub apparent_bytes: u64,
+    /// Sum of allocated sizes, each inode counted once
+    pub allocated_bytes: u64,
+    /// Allocated bytes that deleting every file in the set would free
+    pub reclaimable_bytes: u64,
+}
+
+impl DiskUsage {
+    /// Usage of a single path on its own
+    #[must_use]
+    pub const fn of_file(usage: &FileUsage) -> Self {
+        Self {
+            apparent_bytes: usage.apparent,
+            allocated_bytes: usage.allocated,
+            reclaimable_bytes: usage.reclaimable(),
+        }
+    }
+
+    /// Apparent size in megabytes
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub const fn apparent_mb(&self) -> f32 {
+        self.apparent_bytes as f32 / 1_048_576.0
+    }
+
+    /// Allocated size in megabytes
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub const fn allocated_mb(&self) -> f32 {
+        self.allocated_bytes as f32 / 1_048_576.0
+    }
+
+    /// Reclaimable size in megabytes
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub const fn reclaimable_mb(&self) -> f32 {
+        self.reclaimable_bytes as f32 / 1_048_576.0
+    }
+}
+
+impl std::ops::AddAssign for DiskUsage {
+    fn add_assign(&mut self, other: Self) {
+        self.apparent_bytes += other.apparent_bytes;
+        self.allocated_bytes += other.allocated_bytes;
+        self.reclaimable_bytes += other.reclaimable_bytes;
+    }
+}
+
+/// Links seen so far for one hardlinked inode
+#[derive(Debug, Clone, Copy)]
+struct LinkCount {
+    seen: u64,
+    nlink: u64,
+    allocated: u64,
+}
+
+/// Accumulates [`DiskUsage`] over files, deduplicating hardlinks by (dev, inode)
+#[derive(Debug, Default)]
+pub struct UsageAccumulator {
+    apparent: u64,
+    allocated: u64,
+    unlinked_reclaimable: u64,
+    hardlinks: HashMap<(u64, u64), LinkCount>,
+}
+
+impl UsageAccumulator {
+    /// Create an empty accumulator
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add one path; further links to an inode already seen add no bytes
+    pub fn add(&mut self, usage: &FileUsage) {
+        if !usage.is_hardlinked() {
+            self.apparent += usage.apparent;
+            self.allocated += usage.allocated;
+            self.unlinked_reclaimable += usage.allocated;
+            return;
+        }
+
+        let links = self
+            .hardlinks
+            .entry((usage.dev, usage.inode))
+            .or_insert_with(|| LinkCount {
+                seen: 0,
+                nlink: usage.nlink,
+                allocated: usage.allocated,
+            });
+        if links.seen == 0 {
+            self.apparent += usage.apparent;
+            self.allocated += usage.allocated;
+        }
+        links.seen += 1;
+    }
+
+    /// Totals for everything added so far
+    #[must_use]
+    pub fn usage(&self) -> DiskUsage {
+        let linked_reclaimable: u64 = self
+            .hardlinks
+            .values()
+            .filter(|links| links.seen >= links.nlink)
+            .map(|links| links.allocated)
+            .sum();
+
+        DiskUsage {
+            apparent_bytes: self.apparent,
+            allocated_bytes: self.allocated,
+            reclaimable_bytes: self.unlinked_reclaimable + linked_reclaimable,
+        }
+    }
+}
+
+/// Measure every regular file under `path` (or `path` itself if it is a file)
+#[must_use]
+pub fn path_usage(path: &Path) -> DiskUsage {
+    paths_usage(std::iter::once(path))
+}
+
+/// Measure several paths together, so hardlinks between them are freed as a set
+#[must_use]
+pub fn paths_usage<'a>(paths: impl IntoIterator<Item = &'a Path>) -> DiskUsage {
+    let mut accumulator = UsageAccumulator::new();
+
+    for path in paths {
+        for entry in WalkDir::new(path).into_iter().filter_map(Result::ok) {
+            if entry.file_type().is_file() {
+                if let Ok(metadata) = entry.metadata() {
+                    accumulator.add(&FileUsage::from_metadata(&metadata));
+                }
+            }
+        }
+    }
+
+    accumulator.usage()
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_hardlinks_counted_once_and_freed_only_as_a_set() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let base = temp_dir.path();
+        fs::create_dir_all(base.join("store")).unwrap();
+        fs::create_dir_all(base.join("project")).unwrap();
+        fs::write(base.join("store/pkg.js"), vec![1u8; 64 * 1024]).unwrap();
+        fs::hard_link(base.join("store/pkg.js"), base.join("project/pkg.js")).unwrap();
+        fs::hard_link(base.join("store/pkg.js"), base.join("project/pkg2.js")).unwrap();
+
+        let project = path_usage(&base.join("project"));
+        assert_eq!(project.apparent_bytes, 64 * 1024);
+        // The store still links the inode, so deleting the project frees nothing
+        assert_eq!(project.reclaimable_bytes, 0);
+
+        let both = paths_usage([base.join("project").as_path(), base.join("store").as_path()]);
+        assert_eq!(both.apparent_bytes, 64 * 1024);
+        assert_eq!(both.reclaimable_bytes, both.allocated_bytes);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_sparse_file_allocates_less_than_apparent() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let path = temp_dir.path().join("sparse.img");
+        let file = fs::File::create(&path).unwrap();
+        file.set_len(64 * 1024 * 1024).unwrap();
+
+        let usage = path_usage(&path);
+        assert_eq!(usage.apparent_bytes, 64 * 1024 * 1024);
+        assert!(usage.allocated_bytes < usage.apparent_bytes);
+    }
+}
@@ -0,0 +1,864 @@
+//! Duplicate directory tree detection.
+//!
+//! Most wasted space comes from whole copied folders (`project-old/`,
+//! `project (copy)/`, unpacked release trees) rather than single files. Every
+//! directory gets a Merkle-style fingerprint built from its children:
+//! 1. A cheap *shape* fingerprint over child names and file sizes selects
+//!    directories that may be copies of each other (no file is read)
+//! 2. A *content* fingerprint over child names and file content hashes confirms
+//!    identical trees; digests come from the shared hash cache when possible
+//! 3. Remaining directories whose names share a stem (`project`, `project-old`,
+//!    `project-2.1`) are compared file by file and reported as near-identical
+//!    when most of their bytes match
+//!
+//! Only the topmost duplicated directory is reported: duplicates nested inside
+//! an already reported directory are implied by it.
+
+use crate::database::hash_cache::{HashCache, HashKind};
+use crate::error::compare_f32_safe;
+use crate::models::{DuplicateDirEntry, DuplicateDirSet};
+use crate::utils::disk_usage::{FileUsage, UsageAccumulator};
+use crate::utils::duplicates::{hash_file, hash_stage};
+use crate::utils::resource_limits::ReadThrottle;
+use crate::utils::scan_progress::CancellationToken;
+use sha2::{Digest, Sha256};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::ffi::{OsStr, OsString};
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+
+/// Directories with fewer bytes than this are not reported
+pub const DUPLICATE_DIR_MIN_BYTES: u64 = 1024 * 1024;
+
+/// Share of bytes two trees must have in common to be near-identical
+pub const NEAR_IDENTICAL_SIMILARITY: f32 = 0.9;
+
+/// Directories sharing a name stem are only compared pairwise up to this many
+const MAX_NEAR_GROUP: usize = 32;
+
+/// (node index, file index) of a file in a [`DirTree`]
+type FileRef = (usize, usize);
+
+// ============================================================================
+// Directory Tree
+// ============================================================================
+
+#[derive(Debug)]
+struct TreeFile {
+    name: OsString,
+    usage: FileUsage,
+}
+
+#[derive(Debug)]
+struct DirNode {
+    path: PathBuf,
+    parent: Option<usize>,
+    files: Vec<TreeFile>,
+    children: Vec<usize>,
+}
+
+impl DirNode {
+    fn name(&self) -> &OsStr {
+        self.path.file_name().unwrap_or_default()
+    }
+}
+
+/// Directories and files recorded during a walk.
+///
+/// Nodes are stored in the order the walk enters them, so a child always comes
+/// after its parent and a reverse pass visits children first.
+#[derive(Debug, Default)]
+pub struct DirTree {
+    nodes: Vec<DirNode>,
+    /// Nodes on the path from the root to the directory being walked
+    open: Vec<usize>,
+}
+
+impl DirTree {
+    /// Create an empty tree
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a directory at `depth` below the scan root (the root has depth 0)
+    pub fn enter_dir(&mut self, path: &Path, depth: usize) {
+        self.open.truncate(depth);
+        // The parent was never entered (unreadable or skipped), so neither is this
+        if self.open.len() != depth {
+            return;
+        }
+
+        let index = self.nodes.len();
+        let parent = self.open.last().copied();
+        if let Some(parent) = parent {
+            self.nodes[parent].children.push(index);
+        }
+        self.nodes.push(DirNode {
+            path: path.to_path_buf(),
+            parent,
+            files: Vec::new(),
+            children: Vec::new(),
+        });
+        self.open.push(index);
+    }
+
+    /// Record a regular file at `depth` below the scan root
+    pub fn add_file(&mut self, path: &Path, depth: usize, usage: FileUsage) {
+        self.open.truncate(depth);
+        if self.open.len() != depth {
+            return;
+        }
+        if let (Some(&dir), Some(name)) = (self.open.last(), path.file_name()) {
+            self.nodes[dir].files.push(TreeFile {
+                name: name.to_os_string(),
+                usage,
+            });
+        }
+    }
+
+    fn file(&self, (node, file): FileRef) -> &TreeFile {
+        &self.nodes[node].files[file]
+    }
+
+    fn file_path(&self, file_ref: FileRef) -> PathBuf {
+        self.nodes[file_ref.0].path.join(&self.file(file_ref).name)
+    }
+
+    /// Files of a node with their indices, sorted by name
+    fn sorted_files(&self, index: usize) -> Vec<(usize, &TreeFile)> {
+        let mut files: Vec<(usize, &TreeFile)> =
+            self.nodes[index].files.iter().enumerate().collect();
+        files.sort_by(|a, b| a.1.name.cmp(&b.1.name));
+        files
+    }
+
+    /// Child directories of a node, sorted by name
+    fn sorted_children(&self, index: usize) -> Vec<usize> {
+        let mut children = self.nodes[index].children.clone();
+        children.sort_by(|&a, &b| self.nodes[a].name().cmp(self.nodes[b].name()));
+        children
+    }
+
+    /// A node and every directory below it
+    fn subtree(&self, root: usize) -> Vec<usize> {
+        let mut nodes = Vec::new();
+        let mut stack = vec![root];
+        while let Some(index) = stack.pop() {
+            nodes.push(index);
+            stack.extend(&self.nodes[index].children);
+        }
+        nodes
+    }
+
+    /// Every file below a node, keyed by its path relative to the node
+    fn relative_files(&self, root: usize) -> HashMap<PathBuf, FileRef> {
+        let mut files = HashMap::new();
+        let mut stack = vec![(root, PathBuf::new())];
+        while let Some((index, prefix)) = stack.pop() {
+            let node = &self.nodes[index];
+            for (file_index, file) in node.files.iter().enumerate() {
+                files.insert(prefix.join(&file.name), (index, file_index));
+            }
+            for &child in &node.children {
+                stack.push((child, prefix.join(self.nodes[child].name())));
+            }
+        }
+        files
+    }
+
+    fn has_ancestor_in(&self, index: usize, nodes: &HashSet<usize>) -> bool {
+        let mut current = self.nodes[index].parent;
+        while let Some(parent) = current {
+            if nodes.contains(&parent) {
+                return true;
+            }
+            current = self.nodes[parent].parent;
+        }
+        false
+    }
+
+    fn is_ancestor(&self, ancestor: usize, index: usize) -> bool {
+        self.has_ancestor_in(index, &HashSet::from([ancestor]))
+    }
+
+    fn depth(&self, index: usize) -> usize {
+        let mut depth = 0;
+        let mut current = self.nodes[index].parent;
+        while let Some(parent) = current {
+            depth += 1;
+            current = self.nodes[parent].parent;
+        }
+        depth
+    }
+
+    /// Total apparent bytes and shape fingerprint of every node
+    fn summarize(&self) -> Vec<NodeSummary> {
+        let mut summaries = vec![NodeSummary::default(); self.nodes.len()];
+
+        for index in (0..self.nodes.len()).rev() {
+            let mut hasher = DefaultHasher::new();
+            let mut bytes = 0;
+            for (_, file) in self.sorted_files(index) {
+                0u8.hash(&mut hasher);
+                file.name.hash(&mut hasher);
+                file.usage.apparent.hash(&mut hasher);
+                bytes += file.usage.apparent;
+            }
+            for child in self.sorted_children(index) {
+                1u8.hash(&mut hasher);
+                self.nodes[child].name().hash(&mut hasher);
+                summaries[child].shape.hash(&mut hasher);
+                bytes += summaries[child].bytes;
+            }
+            summaries[index] = NodeSummary {
+                bytes,
+                shape: hasher.finish(),
+            };
+        }
+
+        summaries
+    }
+
+    /// Content fingerprint of every `needed` node, or `None` if a file below it
+    /// could not be hashed. Children of a needed node must be needed too.
+    fn content_fingerprints(
+        &self,
+        needed: &[bool],
+        digests: &HashMap<FileRef, String>,
+    ) -> Vec<Option<String>> {
+        let mut content: Vec<Option<String>> = vec![None; self.nodes.len()];
+
+        for index in (0..self.nodes.len()).rev() {
+            if !needed[index] {
+                continue;
+            }
+            content[index] = self.content_of(index, digests, &content);
+        }
+
+        content
+    }
+
+    fn content_of(
+        &self,
+        index: usize,
+        digests: &HashMap<FileRef, String>,
+        content: &[Option<String>],
+    ) -> Option<String> {
+        let mut hasher = Sha256::new();
+        for (file_index, file) in self.sorted_files(index) {
+            let digest = digests.get(&(index, file_index))?;
+            hasher.update(b"f");
+            hasher.update(file.name.as_encoded_bytes());
+            hasher.update([0]);
+            hasher.update(digest.as_bytes());
+        }
+        for child in self.sorted_children(index) {
+            let fingerprint = content[child].as_ref()?;
+            hasher.update(b"d");
+            hasher.update(self.nodes[child].name().as_encoded_bytes());
+            hasher.update([0]);
+            hasher.update(fingerprint.as_bytes());
+        }
+        Some(format!("{:x}", hasher.finalize()))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct NodeSummary {
+    /// Apparent bytes of every file below the node
+    bytes: u64,
+    /// Hash over child names, file sizes and child shapes
+    shape: u64,
+}
+
+/// Directories reported together, before sizes are measured
+#[derive(Debug)]
+struct DirGroup {
+    nodes: Vec<usize>,
+    fingerprint: String,
+    similarity: f32,
+    identical: bool,
+}
+
+// ============================================================================
+// Detection
+// ============================================================================
+
+/// Find identical and near-identical directory trees in `tree`.
+///
+/// When a `hash_cache` is given, content digests of unchanged files are taken
+/// from it instead of re-reading the files, and new digests are stored. Files
+/// not yet hashed when `cancel` is set count as unreadable, so their directories
+/// are not reported. Every read goes through `throttle`.
+///
+/// **Returns:** Topmost duplicated directories grouped into sets, sorted by
+/// savable space (largest first)
+pub fn find_duplicate_dirs(
+    tree: &DirTree,
+    hash_cache: Option<&HashCache>,
+    cancel: &CancellationToken,
+    throttle: &ReadThrottle,
+) -> Result<Vec<DuplicateDirSet>, String> {
+    let batch = hash_cache
+        .map(HashCache::begin_batch)
+        .transpose()
+        .map_err(|e| format!("Hash cache error: {e}"))?;
+
+    let summaries = tree.summarize();
+    let mut digests = HashMap::new();
+    let hashing = (hash_cache, cancel, throttle);
+    let identical = find_identical(tree, &summaries, hashing, &mut digests);
+    let near = find_near_identical(tree, &summaries, &identical, hashing, &mut digests);
+
+    let mut result: Vec<DuplicateDirSet> = keep_topmost(tree, identical.into_iter().chain(near))
+        .iter()
+        .filter_map(|group| build_set(tree, &summaries, group))
+        .collect();
+    result.sort_by(|a, b| compare_f32_safe(a.total_savable_mb, b.total_savable_mb));
+
+    if let (Some(cache), Some(batch)) = (hash_cache, batch) {
+        batch
+            .commit()
+            .map_err(|e| format!("Hash cache error: {e}"))?;
+        if let Err(e) = cache.evict_to_limit() {
+            log::warn!("Hash cache eviction failed: {e}");
+        }
+    }
+
+    log::info!(
+        "Duplicate directories: {} directories considered, {} files hashed, {} sets reported",
+        tree.nodes.len(),
+        digests.len(),
+        result.len()
+    );
+
+    Ok(result)
+}
+
+/// Group directories whose shape and then content fingerprints match
+fn find_identical(
+    tree: &DirTree,
+    summaries: &[NodeSummary],
+    hashing: Hashing<'_>,
+    digests: &mut HashMap<FileRef, String>,
+) -> Vec<DirGroup> {
+    let mut by_shape: HashMap<u64, Vec<usize>> = HashMap::new();
+    for (index, summary) in summaries.iter().enumerate() {
+        if summary.bytes >= DUPLICATE_DIR_MIN_BYTES {
+            by_shape.entry(summary.shape).or_default().push(index);
+        }
+    }
+    let mut candidates: Vec<usize> = by_shape
+        .into_values()
+        .filter(|group| group.len() > 1)
+        .flatten()
+        .collect();
+    if candidates.is_empty() {
+        return Vec::new();
+    }
+
+    // Parents come first, so a nested candidate's subtree is already marked
+    candidates.sort_unstable();
+    let mut needed = vec![false; tree.nodes.len()];
+    for &candidate in &candidates {
+        if !needed[candidate] {
+            for index in tree.subtree(candidate) {
+                needed[index] = true;
+            }
+        }
+    }
+
+    let files: Vec<FileRef> = needed
+        .iter()
+        .enumerate()
+        .filter(|(_, is_needed)| **is_needed)
+        .flat_map(|(index, _)| (0..tree.nodes[index].files.len()).map(move |file| (index, file)))
+        .collect();
+    digests.extend(hash_files(tree, files, hashing));
+
+    let content = tree.content_fingerprints(&needed, digests);
+    let mut by_content: HashMap<&str, Vec<usize>> = HashMap::new();
+    for &candidate in &candidates {
+        if let Some(fingerprint) = &content[candidate] {
+            by_content.entry(fingerprint).or_default().push(candidate);
+        }
+    }
+
+    by_content
+        .into_iter()
+        .filter(|(_, nodes)| nodes.len() > 1)
+        .map(|(fingerprint, nodes)| DirGroup {
+            nodes,
+            fingerprint: fingerprint.to_string(),
+            similarity: 1.0,
+            identical: true,
+        })
+        .collect()
+}
+
+/// Compare directories sharing a name stem file by file and cluster the ones
+/// whose bytes mostly match
+fn find_near_identical(
+    tree: &DirTree,
+    summaries: &[NodeSummary],
+    identical: &[DirGroup],
+    hashing: Hashing<'_>,
+    digests: &mut HashMap<FileRef, String>,
+) -> Vec<DirGroup> {
+    // Directories inside identical trees are already accounted for
+    let mut covered = vec![false; tree.nodes.len()];
+    for &node in identical.iter().flat_map(|group| &group.nodes) {
+        for index in tree.subtree(node) {
+            covered[index] = true;
+        }
+    }
+
+    let mut by_stem: HashMap<String, Vec<usize>> = HashMap::new();
+    for (index, summary) in summaries.iter().enumerate() {
+        if summary.bytes >= DUPLICATE_DIR_MIN_BYTES && !covered[index] {
+            let stem = name_stem(&tree.nodes[index].name().to_string_lossy());
+            by_stem.entry(stem).or_default().push(index);
+        }
+    }
+
+    // Pairs worth comparing, with the files that match by relative path and size
+    let mut listings: HashMap<usize, HashMap<PathBuf, FileRef>> = HashMap::new();
+    let mut pairs: Vec<(usize, usize, Vec<FileRef>)> = Vec::new();
+    for (stem, nodes) in &by_stem {
+        if nodes.len() > MAX_NEAR_GROUP {
+            log::debug!(
+                "Not comparing {} directories named like '{stem}' pairwise",
+                nodes.len()
+            );
+            continue;
+        }
+        for (i, &a) in nodes.iter().enumerate() {
+            for &b in &nodes[i + 1..] {
+                if let Some(matches) = match_listings(tree, summaries, &mut listings, a, b) {
+                    pairs.push((a, b, matches));
+                }
+            }
+        }
+    }
+
+    let unhashed: Vec<FileRef> = pairs
+        .iter()
+        .flat_map(|(_, _, matches)| matches)
+        .copied()
+        .filter(|file_ref| !digests.contains_key(file_ref))
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+    digests.extend(hash_files(tree, unhashed, hashing));
+
+    // Union similar pairs into clusters, remembering the weakest link
+    let mut clusters = Clusters::default();
+    for (a, b, matches) in pairs {
+        let matched: u64 = matches
+            .chunks_exact(2)
+            .filter(|pair| {
+                matches!((digests.get(&pair[0]), digests.get(&pair[1])), (Some(x), Some(y)) if x == y)
+            })
+            .map(|pair| tree.file(pair[0]).usage.apparent)
+            .sum();
+        let similarity = ratio(matched, summaries[a].bytes.max(summaries[b].bytes));
+        if similarity >= NEAR_IDENTICAL_SIMILARITY {
+            clusters.join(a, b, similarity);
+        }
+    }
+
+    clusters
+        .into_groups()
+        .into_iter()
+        .map(|(nodes, similarity)| {
+            let largest = nodes
+                .iter()
+                .copied()
+                .max_by_key(|&node| summaries[node].bytes)
+                .unwrap_or_default();
+            DirGroup {
+                fingerprint: format!("{:016x}", summaries[largest].shape),
+                nodes,
+                similarity,
+                identical: false,
+            }
+        })
+        .collect()
+}
+
+/// Files of `a` and `b` matching by relative path and size, flattened as
+/// `[a_file, b_file, ...]`, or `None` if even a full match of those files could
+/// not make the trees near-identical
+fn match_listings(
+    tree: &DirTree,
+    summaries: &[NodeSummary],
+    listings: &mut HashMap<usize, HashMap<PathBuf, FileRef>>,
+    a: usize,
+    b: usize,
+) -> Option<Vec<FileRef>> {
+    let largest = summaries[a].bytes.max(summaries[b].bytes);
+    let smallest = summaries[a].bytes.min(summaries[b].bytes);
+    if ratio(smallest, largest) < NEAR_IDENTICAL_SIMILARITY {
+        return None;
+    }
+    // A tree cannot be a copy of its own subdirectory
+    if tree.is_ancestor(a, b) || tree.is_ancestor(b, a) {
+        return None;
+    }
+
+    for node in [a, b] {
+        listings
+            .entry(node)
+            .or_insert_with(|| tree.relative_files(node));
+    }
+    let (files_a, files_b) = (listings.get(&a)?, listings.get(&b)?);
+
+    let mut matches = Vec::new();
+    let mut potential = 0;
+    for (relative, &file_a) in files_a {
+        if let Some(&file_b) = files_b.get(relative) {
+            let size = tree.file(file_a).usage.apparent;
+            if size == tree.file(file_b).usage.apparent {
+                matches.extend([file_a, file_b]);
+                potential += size;
+            }
+        }
+    }
+
+    (ratio(potential, largest) >= NEAR_IDENTICAL_SIMILARITY).then_some(matches)
+}
+
+/// Hash cache to consult, token that stops hashing and throttle for reads
+type Hashing<'a> = (
+    Option<&'a HashCache>,
+    &'a CancellationToken,
+    &'a ReadThrottle,
+);
+
+/// Hash files in full, taking unchanged ones from the cache
+fn hash_files(
+    tree: &DirTree,
+    files: Vec<FileRef>,
+    (hash_cache, cancel, throttle): Hashing<'_>,
+) -> Vec<(FileRef, String)> {
+    let items: Vec<(FileRef, PathBuf)> = files
+        .into_iter()
+        .map(|file_ref| (file_ref, tree.file_path(file_ref)))
+        .collect();
+
+    hash_stage(
+        items,
+        hash_cache,
+        cancel,
+        |(_, path)| (path.as_path(), HashKind::Sha256),
+        |(_, path)| hash_file(path, throttle),
+    )
+    .into_iter()
+    .map(|((file_ref, _), digest, _)| (file_ref, digest))
+    .collect()
+}
+
+/// Union-find over directory nodes, tracking each cluster's lowest similarity
+#[derive(Debug, Default)]
+struct Clusters {
+    parent: HashMap<usize, usize>,
+    similarity: HashMap<usize, f32>,
+}
+
+impl Clusters {
+    fn root(&mut self, node: usize) -> usize {
+        let mut root = node;
+        while let Some(&parent) = self.parent.get(&root) {
+            if parent == root {
+                break;
+            }
+            root = parent;
+        }
+        self.parent.insert(node, root);
+        root
+    }
+
+    fn join(&mut self, a: usize, b: usize, similarity: f32) {
+        let (root_a, root_b) = (self.root(a), self.root(b));
+        let lowest = [
+            similarity,
+            self.similarity.remove(&root_a).unwrap_or(1.0),
+            self.similarity.remove(&root_b).unwrap_or(1.0),
+        ]
+        .into_iter()
+        .fold(1.0, f32::min);
+        self.parent.insert(root_b, root_a);
+        self.similarity.insert(root_a, lowest);
+    }
+
+    /// Every cluster's nodes and lowest pairwise similarity
+    fn into_groups(mut self) -> Vec<(Vec<usize>, f32)> {
+        let nodes: Vec<usize> = self.parent.keys().copied().collect();
+        let mut groups: HashMap<usize, Vec<usize>> = HashMap::new();
+        for node in nodes {
+            let root = self.root(node);
+            groups.entry(root).or_default().push(node);
+        }
+        groups
+            .into_iter()
+            .map(|(root, nodes)| (nodes, self.similarity.get(&root).copied().unwrap_or(1.0)))
+            .collect()
+    }
+}
+
+/// Drop groups whose directories all lie inside directories of a shallower group
+fn keep_topmost(tree: &DirTree, groups: impl Iterator<Item = DirGroup>) -> Vec<DirGroup> {
+    let mut groups: Vec<(usize, DirGroup)> = groups
+        .map(|group| {
+            let depth = group
+                .nodes
+                .iter()
+                .map(|&node| tree.depth(node))
+                .min()
+                .unwrap_or_default();
+            (depth, group)
+        })
+        .collect();
+    groups.sort_by_key(|(depth, _)| *depth);
+
+    let mut reported = HashSet::new();
+    let mut kept = Vec::new();
+    for (_, group) in groups {
+        if group
+            .nodes
+            .iter()
+            .all(|&node| reported.contains(&node) || tree.has_ancestor_in(node, &reported))
+        {
+            continue;
+        }
+        reported.extend(group.nodes.iter().copied());
+        kept.push(group);
+    }
+    kept
+}
+
+/// Measure a group's directories, or `None` if deleting copies would free nothing
+fn build_set(
+    tree: &DirTree,
+    summaries: &[NodeSummary],
+    group: &DirGroup,
+) -> Option<DuplicateDirSet> {
+    let mut nodes = group.nodes.clone();
+    nodes.sort_by(|&a, &b| tree.nodes[a].path.cmp(&tree.nodes[b].path));
+
+    let mut entries = Vec::with_capacity(nodes.len());
+    let mut reclaimable = Vec::with_capacity(nodes.len());
+    for &node in &nodes {
+        let mut accumulator = UsageAccumulator::new();
+        let mut file_count = 0;
+        for index in tree.subtree(node) {
+            for file in &tree.nodes[index].files {
+                accumulator.add(&file.usage);
+                file_count += 1;
+            }
+        }
+        let usage = accumulator.usage();
+        reclaimable.push(usage.reclaimable_bytes);
+        entries.push(DuplicateDirEntry {
+            path: tree.nodes[node].path.to_string_lossy().to_string(),
+            size_mb: usage.apparent_mb(),
+            allocated_mb: usage.allocated_mb(),
+            reclaimable_mb: usage.reclaimable_mb(),
+            file_count,
+        });
+    }
+
+    // Identical trees keep the copy that frees least; near-identical trees keep
+    // the largest, which is most likely the complete one
+    let kept = if group.identical {
+        reclaimable.iter().copied().min().unwrap_or(0)
+    } else {
+        nodes
+            .iter()
+            .zip(&reclaimable)
+            .max_by_key(|(node, _)| summaries[**node].bytes)
+            .map_or(0, |(_, &bytes)| bytes)
+    };
+    let savable = reclaimable.iter().sum::<u64>() - kept;
+    if savable == 0 {
+        return None;
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    let total_savable_mb = savable as f32 / 1_048_576.0;
+
+    Some(DuplicateDirSet {
+        fingerprint: group.fingerprint.clone(),
+        similarity: group.similarity,
+        total_savable_mb,
+        entries,
+    })
+}
+
+#[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation)]
+fn ratio(part: u64, whole: u64) -> f32 {
+    if whole == 0 {
+        0.0
+    } else {
+        (part as f64 / whole as f64) as f32
+    }
+}
+
+/// Lowercase name without copy markers, version numbers and counters, so that
+/// `project`, `Project (copy)`, `project-old` and `project-v2.1` share a stem
+fn name_stem(name: &str) -> String {
+    const MARKERS: [&str; 7] = ["copy", "backup", "bak", "old", "orig", "new", "v"];
+    let is_separator = |c: char| matches!(c, ' ' | '-' | '_' | '.' | '(' | ')' | '[' | ']');
+
+    let lower = name.to_lowercase();
+    let mut stem = lower.as_str();
+    loop {
+        let mut next = stem.trim_end_matches(|c: char| c.is_ascii_digit() || is_separator(c));
+        for marker in MARKERS {
+            if let Some(rest) = next.strip_suffix(marker) {
+                if rest.ends_with(is_separator) {
+                    next = rest;
+                }
+            }
+        }
+        if next == stem {
+            break;
+        }
+        stem = next;
+    }
+
+    if stem.is_empty() {
+        lower
+    } else {
+        stem.to_string()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+    use walkdir::WalkDir;
+
+    fn tree_of(root: &Path) -> DirTree {
+        let mut tree = DirTree::new();
+        for entry in WalkDir::new(root).into_iter().filter_map(Result::ok) {
+            if entry.file_type().is_dir() {
+                tree.enter_dir(entry.path(), entry.depth());
+            } else if entry.file_type().is_file() {
+                let usage = FileUsage::from_metadata(&entry.metadata().unwrap());
+                tree.add_file(entry.path(), entry.depth(), usage);
+            }
+        }
+        tree
+    }
+
+    fn write_project(dir: &Path) {
+        fs::create_dir_all(dir.join("src/nested")).unwrap();
+        fs::write(dir.join("src/main.rs"), vec![1u8; 512 * 1024]).unwrap();
+        fs::write(dir.join("src/nested/lib.rs"), vec![2u8; 512 * 1024]).unwrap();
+        fs::write(dir.join("data.bin"), vec![3u8; 512 * 1024]).unwrap();
+        fs::write(dir.join("README.md"), b"readme").unwrap();
+    }
+
+    #[test]
+    fn test_identical_trees_report_topmost_only() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let base = temp_dir.path();
+        write_project(&base.join("project"));
+        write_project(&base.join("archive/project (copy)"));
+        // Same names and sizes but different content: not a copy
+        write_project(&base.join("other"));
+        fs::write(base.join("other/src/main.rs"), vec![4u8; 512 * 1024]).unwrap();
+
+        let sets = find_duplicate_dirs(
+            &tree_of(base),
+            None,
+            &CancellationToken::new(),
+            &ReadThrottle::new(None),
+        )
+        .unwrap();
+
+        // Both `src` directories are identical too, but implied by their projects
+        assert_eq!(sets.len(), 1);
+        let paths: Vec<&str> = sets[0].entries.iter().map(|e| e.path.as_str()).collect();
+        assert!(paths[0].ends_with("archive/project (copy)"));
+        assert!(paths[1].ends_with("project"));
+        assert!((sets[0].similarity - 1.0).abs() < f32::EPSILON);
+        assert_eq!(sets[0].entries[0].file_count, 4);
+        assert!((sets[0].total_savable_mb - sets[0].entries[0].reclaimable_mb).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_near_identical_trees_with_similar_names() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let base = temp_dir.path();
+        write_project(&base.join("project"));
+        write_project(&base.join("project-old"));
+        fs::write(base.join("project-old/README.md"), b"an older readme").unwrap();
+        // Mostly the same files under an unrelated name are not compared
+        write_project(&base.join("unrelated"));
+        fs::write(base.join("unrelated/src/main.rs"), vec![4u8; 512 * 1024]).unwrap();
+
+        let sets = find_duplicate_dirs(
+            &tree_of(base),
+            None,
+            &CancellationToken::new(),
+            &ReadThrottle::new(None),
+        )
+        .unwrap();
+
+        assert_eq!(sets.len(), 1);
+        assert_eq!(sets[0].entries.len(), 2);
+        assert!(sets[0].similarity >= NEAR_IDENTICAL_SIMILARITY);
+        assert!(sets[0].similarity < 1.0);
+        assert!(sets[0].entries[1].path.ends_with("project-old"));
+    }
+
+    #[test]
+    fn test_hardlinked_tree_frees_nothing() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let base = temp_dir.path();
+        write_project(&base.join("project"));
+        for dir in ["", "src", "src/nested"] {
+            fs::create_dir_all(base.join("linked").join(dir)).unwrap();
+        }
+        for file in ["src/main.rs", "src/nested/lib.rs", "data.bin", "README.md"] {
+            fs::hard_link(
+                base.join("project").join(file),
+                base.join("linked").join(file),
+            )
+            .unwrap();
+        }
+
+        assert!(find_duplicate_dirs(
+            &tree_of(base),
+            None,
+            &CancellationToken::new(),
+            &ReadThrottle::new(None)
+        )
+        .unwrap()
+        .is_empty());
+    }
+
+    #[test]
+    fn test_name_stem() {
+        for name in [
+            "project",
+            "Project (copy)",
+            "project-old",
+            "project_backup_2",
+            "project copy 3",
+            "project-v2.1.0",
+            "project (1)",
+        ] {
+            assert_eq!(name_stem(name), "project", "{name}");
+        }
+        assert_eq!(name_stem("folder"), "folder");
+        assert_eq!(name_stem("2024"), "2024");
+    }
+}
@@ -0,0 +1,598 @@
+//! Staged duplicate detection.
+//!
+//! Candidate files are narrowed down in three stages so that full-content
+//! hashing is only spent on files that are very likely duplicates:
+//! 1. Group by exact size (free - sizes come from the walk)
+//! 2. Hash the head and tail blocks of each same-size candidate
+//! 3. Fully hash only files whose size and head/tail hash both collide
+//!
+//! There is no upper size limit: large ISOs, VM images and videos are read in
+//! full only when their head and tail blocks already match another file.
+//!
+//! Hardlinks are collapsed before hashing: paths sharing an inode are the same
+//! file, so they are hashed once, listed together, and never count as savable
+//! copies of each other.
+//!
+//! Both digests can be served from the persistent
+//! [`HashCache`](crate::database::hash_cache::HashCache), so files that have not
+//! changed since a previous scan are not read at all.
+//!
+//! Once the scan's cancellation token is set, no further files are read; sets
+//! are reported only from the files hashed before that.
+
+use crate::database::hash_cache::{FileStamp, HashCache, HashKind};
+use crate::error::compare_f32_safe;
+use crate::models::{DuplicateEntry, DuplicateScanStats, DuplicateSet};
+use crate::utils::disk_usage::FileUsage;
+use crate::utils::resource_limits::ReadThrottle;
+use crate::utils::scan_progress::CancellationToken;
+use rayon::prelude::*;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+/// Smallest file considered for duplicate detection
+pub const DUPLICATE_MIN_FILE_SIZE: u64 = 1024;
+
+/// Size of the head and tail blocks hashed in the second stage
+const EDGE_BLOCK_SIZE: u64 = 64 * 1024;
+
+/// A file that may have duplicates
+#[derive(Debug, Clone)]
+pub struct DuplicateCandidate {
+    /// Path to the file
+    pub path: PathBuf,
+    /// Apparent and allocated size, and inode identity
+    pub usage: FileUsage,
+    /// Last modification timestamp (Unix epoch seconds)
+    pub last_modified: u64,
+}
+
+impl DuplicateCandidate {
+    /// Apparent size in bytes
+    #[must_use]
+    pub const fn size(&self) -> u64 {
+        self.usage.apparent
+    }
+
+    fn entry(&self) -> DuplicateEntry {
+        #[allow(clippy::cast_precision_loss)]
+        DuplicateEntry {
+            path: self.path.to_string_lossy().to_string(),
+            size_mb: self.usage.apparent as f32 / 1_048_576.0,
+            allocated_mb: self.usage.allocated as f32 / 1_048_576.0,
+            last_modified: self.last_modified,
+        }
+    }
+}
+
+/// One inode in a duplicate set, with every candidate path linking to it
+struct InodeCopies {
+    usage: FileUsage,
+    entries: Vec<DuplicateEntry>,
+}
+
+impl InodeCopies {
+    /// Allocated bytes freed by deleting every listed path; an inode with links
+    /// outside the candidate set stays on disk
+    fn freeable(&self) -> u64 {
+        let links = self.entries.len() as u64;
+        if self.usage.is_hardlinked() && links < self.usage.nlink {
+            0
+        } else {
+            self.usage.allocated
+        }
+    }
+}
+
+/// Result of the head/tail stage for one file
+struct EdgeHash {
+    candidate: DuplicateCandidate,
+    digest: String,
+    /// The head and tail blocks covered the whole file, so `digest` is the full hash
+    is_full: bool,
+}
+
+/// Group candidates with identical content and report how much hashing each stage avoided.
+///
+/// When a `hash_cache` is given, head/tail and full digests of unchanged files are
+/// taken from it instead of re-reading the files, and new digests are stored.
+/// Files not yet hashed when `cancel` is set are left out. Every read goes
+/// through `throttle`.
+///
+/// **Returns:** Duplicate sets sorted by savable space (largest first) and stage statistics
+pub fn find_duplicates(
+    candidates: Vec<DuplicateCandidate>,
+    hash_cache: Option<&HashCache>,
+    cancel: &CancellationToken,
+    throttle: &ReadThrottle,
+) -> Result<(Vec<DuplicateSet>, DuplicateScanStats), String> {
+    let files_considered = candidates.len();
+    let (candidates, mut extra_links) = collapse_hardlinks(candidates);
+    let mut stats = DuplicateScanStats {
+        files_considered,
+        bytes_considered: candidates.iter().map(DuplicateCandidate::size).sum(),
+        ..DuplicateScanStats::default()
+    };
+
+    let batch = hash_cache
+        .map(HashCache::begin_batch)
+        .transpose()
+        .map_err(|e| format!("Hash cache error: {e}"))?;
+
+    // Stage 1: group by size
+    let candidates_len = candidates.len();
+    let mut size_groups: HashMap<u64, Vec<DuplicateCandidate>> = HashMap::new();
+    for candidate in candidates {
+        size_groups
+            .entry(candidate.size())
+            .or_default()
+            .push(candidate);
+    }
+    let same_size: Vec<DuplicateCandidate> = size_groups
+        .into_values()
+        .filter(|group| group.len() > 1)
+        .flatten()
+        .collect();
+    stats.size_unique_files = candidates_len - same_size.len();
+
+    // Stage 2: hash head and tail blocks (small files are hashed in full)
+    let edge_hashes = hash_stage(
+        same_size,
+        hash_cache,
+        cancel,
+        |candidate| (candidate.path.as_path(), edge_kind(candidate.size())),
+        |candidate| hash_edges(&candidate.path, candidate.size(), throttle),
+    );
+    let edge_total = edge_hashes.len();
+    let mut edge_groups: HashMap<(u64, String), Vec<EdgeHash>> = HashMap::new();
+    for (candidate, digest, was_read) in edge_hashes {
+        if was_read {
+            stats.edge_hashed_files += 1;
+            stats.bytes_edge_hashed += edge_bytes(candidate.size());
+        } else {
+            stats.cache_hits += 1;
+        }
+        edge_groups
+            .entry((candidate.size(), digest.clone()))
+            .or_default()
+            .push(EdgeHash {
+                is_full: edge_kind(candidate.size()) == HashKind::Sha256,
+                candidate,
+                digest,
+            });
+    }
+    let (complete, partial): (Vec<EdgeHash>, Vec<EdgeHash>) = edge_groups
+        .into_values()
+        .filter(|group| group.len() > 1)
+        .flatten()
+        .partition(|edge| edge.is_full);
+    stats.edge_unique_files = edge_total - complete.len() - partial.len();
+
+    // Stage 3: full hash for survivors (small files were already read in full)
+    let full_hashes = hash_stage(
+        partial.into_iter().map(|edge| edge.candidate).collect(),
+        hash_cache,
+        cancel,
+        |candidate| (candidate.path.as_path(), HashKind::Sha256),
+        |candidate| hash_file(&candidate.path, throttle),
+    );
+
+    for (candidate, _, was_read) in &full_hashes {
+        if *was_read {
+            stats.full_hashed_files += 1;
+            stats.bytes_full_hashed += candidate.size();
+        } else {
+            stats.cache_hits += 1;
+        }
+    }
+
+    let mut hash_groups: HashMap<String, Vec<InodeCopies>> = HashMap::new();
+    let complete = complete
+        .into_iter()
+        .map(|edge| (edge.candidate, edge.digest, false));
+    for (candidate, digest, _) in full_hashes.into_iter().chain(complete) {
+        let mut entries = vec![candidate.entry()];
+        if let Some(links) = extra_links.remove(&inode_key(&candidate.usage)) {
+            entries.extend(links.iter().map(DuplicateCandidate::entry));
+        }
+        hash_groups.entry(digest).or_default().push(InodeCopies {
+            usage: candidate.usage,
+            entries,
+        });
+    }
+
+    stats.bytes_hash_avoided = stats
+        .bytes_considered
+        .saturating_sub(stats.bytes_edge_hashed + stats.bytes_full_hashed);
+
+    let result = build_duplicate_sets(hash_groups);
+
+    if let (Some(cache), Some(batch)) = (hash_cache, batch) {
+        batch
+            .commit()
+            .map_err(|e| format!("Hash cache error: {e}"))?;
+        if let Err(e) = cache.evict_to_limit() {
+            log::warn!("Hash cache eviction failed: {e}");
+        }
+    }
+
+    log_stage_stats(&stats);
+
+    Ok((result, stats))
+}
+
+/// (dev, inode) identifying a hardlinked file
+type InodeKey = (u64, u64);
+
+const fn inode_key(usage: &FileUsage) -> InodeKey {
+    (usage.dev, usage.inode)
+}
+
+/// Keep one candidate per hardlinked inode; the other paths are returned by inode
+fn collapse_hardlinks(
+    candidates: Vec<DuplicateCandidate>,
+) -> (
+    Vec<DuplicateCandidate>,
+    HashMap<InodeKey, Vec<DuplicateCandidate>>,
+) {
+    let mut primaries = Vec::with_capacity(candidates.len());
+    let mut seen = std::collections::HashSet::new();
+    let mut extra_links: HashMap<InodeKey, Vec<DuplicateCandidate>> = HashMap::new();
+
+    for candidate in candidates {
+        if !candidate.usage.is_hardlinked() || seen.insert(inode_key(&candidate.usage)) {
+            primaries.push(candidate);
+        } else {
+            extra_links
+                .entry(inode_key(&candidate.usage))
+                .or_default()
+                .push(candidate);
+        }
+    }
+
+    (primaries, extra_links)
+}
+
+/// Log how many candidates each stage eliminated
+fn log_stage_stats(stats: &DuplicateScanStats) {
+    log::info!(
+        "Duplicate stages: {} considered, {} unique by size, {} unique by head/tail, {} fully hashed, {} cache hits ({} bytes not hashed)",
+        stats.files_considered,
+        stats.size_unique_files,
+        stats.edge_unique_files,
+        stats.full_hashed_files,
+        stats.cache_hits,
+        stats.bytes_hash_avoided
+    );
+}
+
+/// Digest every item, taking unchanged files from the cache and hashing the rest
+/// in parallel. New digests are stored in the cache. Once `cancel` is set, the
+/// remaining items are skipped.
+///
+/// **Returns:** `(item, digest, was_read)` for every item that could be hashed
+pub fn hash_stage<T, K, H>(
+    items: Vec<T>,
+    cache: Option<&HashCache>,
+    cancel: &CancellationToken,
+    key: K,
+    compute: H,
+) -> Vec<(T, String, bool)>
+where
+    T: Send,
+    K: Fn(&T) -> (&Path, HashKind) + Sync,
+    H: Fn(&T) -> std::io::Result<String> + Sync,
+{
+    let mut hashed = Vec::with_capacity(items.len());
+    let mut pending = Vec::new();
+
+    // Cache lookups stay on this thread; the connection is not shared
+    for item in items {
+        let (path, kind) = key(&item);
+        let stamp = cache
+            .and_then(|_| std::fs::metadata(path).ok())
+            .map(|m| FileStamp::from_metadata(&m));
+        let cached = match (cache, &stamp) {
+            (Some(cache), Some(stamp)) => cache.get(path, kind, stamp).ok().flatten(),
+            _ => None,
+        };
+        match cached {
+            Some(digest) => hashed.push((item, digest, false)),
+            None => pending.push((item, stamp)),
+        }
+    }
+
+    let computed: Vec<(T, Option<FileStamp>, String)> = pending
+        .into_par_iter()
+        .filter_map(|(item, stamp)| {
+            if cancel.is_cancelled() {
+                return None;
+            }
+            match compute(&item) {
+                Ok(digest) => Some((item, stamp, digest)),
+                Err(err) => {
+                    log::debug!("Cannot hash {}: {err}", key(&item).0.display());
+                    None
+                }
+            }
+        })
+        .collect();
+
+    for (item, stamp, digest) in computed {
+        if let (Some(cache), Some(stamp)) = (cache, stamp) {
+            let (path, kind) = key(&item);
+            if let Err(err) = cache.put(path, kind, &stamp, &digest) {
+                log::debug!("Cannot cache hash for {}: {err}", path.display());
+            }
+        }
+        hashed.push((item, digest, true));
+    }
+
+    hashed
+}
+
+/// Keep groups with more than one inode, sorted by savable space (largest first)
+fn build_duplicate_sets(hash_groups: HashMap<String, Vec<InodeCopies>>) -> Vec<DuplicateSet> {
+    let mut result: Vec<DuplicateSet> = hash_groups
+        .into_iter()
+        .filter(|(_, copies)| copies.len() > 1) // Hardlinks alone are not duplicates
+        .map(|(hash, copies)| {
+            // Keep the copy whose deletion would free the least, delete the rest
+            let freeable: Vec<u64> = copies.iter().map(InodeCopies::freeable).collect();
+            let kept = freeable.iter().copied().min().unwrap_or(0);
+            let savable = freeable.iter().sum::<u64>() - kept;
+            #[allow(clippy::cast_precision_loss)]
+            let total_savable_mb = savable as f32 / 1_048_576.0;
+
+            DuplicateSet {
+                hash,
+                total_savable_mb,
+                entries: copies.into_iter().flat_map(|c| c.entries).collect(),
+            }
+        })
+        .collect();
+
+    // Sort by savable space (largest first)
+    result.sort_by(|a, b| compare_f32_safe(a.total_savable_mb, b.total_savable_mb));
+
+    result
+}
+
+/// Bytes read by the head/tail stage for a file of `size` bytes
+const fn edge_bytes(size: u64) -> u64 {
+    if size <= 2 * EDGE_BLOCK_SIZE {
+        size
+    } else {
+        2 * EDGE_BLOCK_SIZE
+    }
+}
+
+/// Cache kind of the stage-two digest: files no larger than two blocks are
+/// read in full, so their digest is the full content hash
+const fn edge_kind(size: u64) -> HashKind {
+    if size <= 2 * EDGE_BLOCK_SIZE {
+        HashKind::Sha256
+    } else {
+        HashKind::Sha256Edges
+    }
+}
+
+/// SHA-256 of the head and tail blocks (of the whole file if it is small)
+fn hash_edges(path: &Path, size: u64, throttle: &ReadThrottle) -> std::io::Result<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+
+    if size <= 2 * EDGE_BLOCK_SIZE {
+        std::io::copy(&mut throttle.reader(file), &mut hasher)?;
+        return Ok(format!("{:x}", hasher.finalize()));
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    let mut block = vec![0u8; EDGE_BLOCK_SIZE as usize];
+    file.read_exact(&mut block)?;
+    hasher.update(&block);
+    file.seek(SeekFrom::Start(size - EDGE_BLOCK_SIZE))?;
+    file.read_exact(&mut block)?;
+    hasher.update(&block);
+    throttle.consume(2 * EDGE_BLOCK_SIZE);
+
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// SHA-256 of the whole file, read at the rate `throttle` allows
+pub fn hash_file(path: &Path, throttle: &ReadThrottle) -> std::io::Result<String> {
+    let mut reader = BufReader::with_capacity(1024 * 1024, throttle.reader(File::open(path)?));
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut reader, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn candidate(path: PathBuf) -> DuplicateCandidate {
+        let usage = FileUsage::from_metadata(&fs::metadata(&path).unwrap());
+        DuplicateCandidate {
+            path,
+            usage,
+            last_modified: 0,
+        }
+    }
+
+    #[test]
+    fn test_stages_eliminate_before_full_hash() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let base = temp_dir.path();
+        let big = 512 * 1024;
+
+        // Two identical large files, one differing only in the middle, one unique size
+        let content = vec![5u8; big];
+        let mut middle = content.clone();
+        middle[big / 2] = 6;
+        let mut head = content.clone();
+        head[0] = 7;
+        fs::write(base.join("a.iso"), &content).unwrap();
+        fs::write(base.join("b.iso"), &content).unwrap();
+        fs::write(base.join("c.iso"), &middle).unwrap();
+        fs::write(base.join("d.iso"), &head).unwrap();
+        fs::write(base.join("e.iso"), vec![5u8; big + 1]).unwrap();
+
+        let candidates = ["a.iso", "b.iso", "c.iso", "d.iso", "e.iso"]
+            .iter()
+            .map(|name| candidate(base.join(name)))
+            .collect();
+        let (sets, stats) = find_duplicates(
+            candidates,
+            None,
+            &CancellationToken::new(),
+            &ReadThrottle::new(None),
+        )
+        .unwrap();
+
+        assert_eq!(sets.len(), 1);
+        assert_eq!(sets[0].entries.len(), 2);
+        assert_eq!(stats.files_considered, 5);
+        assert_eq!(stats.size_unique_files, 1);
+        assert_eq!(stats.edge_hashed_files, 4);
+        assert_eq!(stats.edge_unique_files, 1);
+        // c.iso matches a/b on head and tail, so it is fully hashed and then dropped
+        assert_eq!(stats.full_hashed_files, 3);
+        assert!(stats.bytes_hash_avoided > 0);
+    }
+
+    #[test]
+    fn test_small_files_are_not_read_twice() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let base = temp_dir.path();
+        fs::write(base.join("a.txt"), vec![1u8; 4096]).unwrap();
+        fs::write(base.join("b.txt"), vec![1u8; 4096]).unwrap();
+
+        let candidates = vec![candidate(base.join("a.txt")), candidate(base.join("b.txt"))];
+        let (sets, stats) = find_duplicates(
+            candidates,
+            None,
+            &CancellationToken::new(),
+            &ReadThrottle::new(None),
+        )
+        .unwrap();
+
+        assert_eq!(sets.len(), 1);
+        assert_eq!(stats.full_hashed_files, 0);
+        assert_eq!(
+            sets[0].hash,
+            hash_file(&base.join("a.txt"), &ReadThrottle::new(None)).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_cache_avoids_rereading_unchanged_files() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let base = temp_dir.path();
+        let big = 256 * 1024;
+        fs::write(base.join("a.img"), vec![3u8; big]).unwrap();
+        fs::write(base.join("b.img"), vec![3u8; big]).unwrap();
+        fs::write(base.join("c.txt"), vec![4u8; 2048]).unwrap();
+        fs::write(base.join("d.txt"), vec![4u8; 2048]).unwrap();
+
+        let db_file = tempfile::NamedTempFile::new().unwrap();
+        let cache = HashCache::new(db_file.path().to_str().unwrap()).unwrap();
+        let candidates = || {
+            ["a.img", "b.img", "c.txt", "d.txt"]
+                .iter()
+                .map(|name| candidate(base.join(name)))
+                .collect::<Vec<_>>()
+        };
+
+        let (first, first_stats) = find_duplicates(
+            candidates(),
+            Some(&cache),
+            &CancellationToken::new(),
+            &ReadThrottle::new(None),
+        )
+        .unwrap();
+        assert_eq!(first.len(), 2);
+        assert_eq!(first_stats.cache_hits, 0);
+
+        let (second, second_stats) = find_duplicates(
+            candidates(),
+            Some(&cache),
+            &CancellationToken::new(),
+            &ReadThrottle::new(None),
+        )
+        .unwrap();
+        assert_eq!(second.len(), 2);
+        assert_eq!(second_stats.edge_hashed_files, 0);
+        assert_eq!(second_stats.full_hashed_files, 0);
+        assert_eq!(second_stats.cache_hits, 6);
+        assert_eq!(
+            second_stats.bytes_hash_avoided,
+            second_stats.bytes_considered
+        );
+    }
+
+    #[test]
+    fn test_cancelled_token_stops_hashing() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let base = temp_dir.path();
+        fs::write(base.join("a.bin"), vec![4u8; 4096]).unwrap();
+        fs::write(base.join("b.bin"), vec![4u8; 4096]).unwrap();
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+
+        let candidates = vec![candidate(base.join("a.bin")), candidate(base.join("b.bin"))];
+        let (sets, stats) =
+            find_duplicates(candidates, None, &cancel, &ReadThrottle::new(None)).unwrap();
+
+        assert!(sets.is_empty());
+        assert_eq!(stats.edge_hashed_files, 0);
+    }
+
+    #[test]
+    fn test_hardlinks_are_not_duplicates() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let base = temp_dir.path();
+        fs::write(base.join("a.bin"), vec![2u8; 8192]).unwrap();
+        fs::hard_link(base.join("a.bin"), base.join("a-link.bin")).unwrap();
+
+        let (sets, stats) = find_duplicates(
+            vec![
+                candidate(base.join("a.bin")),
+                candidate(base.join("a-link.bin")),
+            ],
+            None,
+            &CancellationToken::new(),
+            &ReadThrottle::new(None),
+        )
+        .unwrap();
+        assert!(sets.is_empty());
+        assert_eq!(stats.files_considered, 2);
+        assert_eq!(stats.edge_hashed_files, 0);
+
+        // A real copy forms a set, and only one inode's blocks are savable
+        fs::write(base.join("b.bin"), vec![2u8; 8192]).unwrap();
+        let candidates = ["a.bin", "a-link.bin", "b.bin"]
+            .iter()
+            .map(|name| candidate(base.join(name)))
+            .collect();
+        let (sets, _) = find_duplicates(
+            candidates,
+            None,
+            &CancellationToken::new(),
+            &ReadThrottle::new(None),
+        )
+        .unwrap();
+        assert_eq!(sets.len(), 1);
+        assert_eq!(sets[0].entries.len(), 3);
+        let copy = FileUsage::from_metadata(&fs::metadata(base.join("b.bin")).unwrap());
+        #[allow(clippy::cast_precision_loss)]
+        let expected_mb = copy.allocated as f32 / 1_048_576.0;
+        assert!((sets[0].total_savable_mb - expected_mb).abs() < f32::EPSILON);
+    }
+}
@@ -0,0 +1,524 @@
+//! Build artifacts and junk files that a repository commits or fails to ignore.
+//!
+//! Tracked files (the index) and untracked, not ignored files are matched
+//! against [`BLOAT_PATTERNS`] and [`JUNK_PATTERNS`]:
+//! - A committed artifact directory only counts when its pattern is "safe";
+//!   committed `vendor`, `dist` or `.terraform` directories are often kept on
+//!   purpose. Its history cost is every object stored under it by any commit.
+//! - An untracked artifact counts whenever it matches, since nothing ignores it.
+//!
+//! The proposed `.gitignore` patch adds the patterns that the root
+//! `.gitignore` does not list yet. Ignoring does not untrack committed files,
+//! so each committed artifact also gets a `git rm --cached` command.
+
+use crate::error::compare_f32_safe;
+use crate::models::{GitArtifact, GitArtifactReport};
+use crate::utils::disk_usage::{FileUsage, UsageAccumulator};
+use crate::utils::git_status::{git, git_with_input};
+use crate::utils::patterns::{
+    classify_bloat_dir, matches_junk_pattern, BloatMatch, BLOAT_PATTERNS, JUNK_PATTERNS,
+};
+use crate::utils::process::find_program;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::Write as _;
+use std::path::Path;
+use walkdir::WalkDir;
+
+/// Comment line above the patterns the patch adds
+const PATCH_COMMENT: &str = "# Build artifacts and junk files";
+
+/// Artifacts found so far, keyed by directory path or junk pattern
+#[derive(Default)]
+struct Findings {
+    artifacts: BTreeMap<(bool, String), Artifact>,
+}
+
+struct Artifact {
+    report: GitArtifact,
+    usage: UsageAccumulator,
+}
+
+impl Findings {
+    fn entry(
+        &mut self,
+        tracked: bool,
+        key: String,
+        new: impl FnOnce() -> GitArtifact,
+    ) -> &mut Artifact {
+        self.artifacts
+            .entry((tracked, key))
+            .or_insert_with(|| Artifact {
+                report: new(),
+                usage: UsageAccumulator::new(),
+            })
+    }
+
+    fn add_bloat(&mut self, tracked: bool, dir: &str, found: &BloatMatch) -> &mut Artifact {
+        self.entry(tracked, dir.to_string(), || GitArtifact {
+            kind: "bloat".to_string(),
+            category_id: found.category_id.to_string(),
+            display_name: found.display_name.to_string(),
+            paths: vec![dir.to_string()],
+            tracked,
+            file_count: 0,
+            size_mb: 0.0,
+            history_mb: 0.0,
+            history_disk_mb: 0.0,
+            ignore_pattern: ignore_pattern(dir),
+            untrack_command: None,
+        })
+    }
+
+    fn add_junk(&mut self, tracked: bool, file: &str, path: &Path) {
+        let name = file.rsplit('/').next().unwrap_or(file);
+        let Some(pattern) = JUNK_PATTERNS.iter().find(|pattern| {
+            pattern.safety != "dangerous" && matches_junk_pattern(name, pattern.pattern)
+        }) else {
+            return;
+        };
+        let artifact = self.entry(tracked, format!("junk:{}", pattern.pattern), || {
+            GitArtifact {
+                kind: "junk".to_string(),
+                category_id: pattern.category_id.to_string(),
+                display_name: pattern.display_name.to_string(),
+                paths: Vec::new(),
+                tracked,
+                file_count: 0,
+                size_mb: 0.0,
+                history_mb: 0.0,
+                history_disk_mb: 0.0,
+                ignore_pattern: pattern.pattern.to_string(),
+                untrack_command: None,
+            }
+        });
+        artifact.report.paths.push(file.to_string());
+        artifact.add_file(path);
+    }
+}
+
+impl Artifact {
+    fn add_file(&mut self, path: &Path) {
+        if let Ok(metadata) = std::fs::symlink_metadata(path) {
+            self.usage.add(&FileUsage::from_metadata(&metadata));
+        }
+        self.report.file_count += 1;
+    }
+}
+
+/// Find committed and unignored build artifacts and junk in a work tree.
+///
+/// **Parameters:**
+/// - `work_dir` - Work tree root (a `repo_path` from `scan_git_repos`)
+///
+/// **Returns:** `GitArtifactReport` with the artifacts costliest first (work
+/// tree size plus packed history), or an error if git is missing or
+/// `work_dir` is not a work tree root
+pub fn analyze_git_artifacts(work_dir: &Path) -> Result<GitArtifactReport, String> {
+    if find_program("git").is_none() {
+        return Err("git is not installed".to_string());
+    }
+    if !work_dir.join(".git").exists() {
+        return Err(format!("Not a Git work tree: {}", work_dir.display()));
+    }
+
+    let mut findings = Findings::default();
+    find_tracked(work_dir, &mut findings)?;
+    find_untracked(work_dir, &mut findings)?;
+    add_history_cost(work_dir, &mut findings)?;
+
+    let mut artifacts: Vec<GitArtifact> = findings
+        .artifacts
+        .into_values()
+        .map(|artifact| {
+            let mut report = artifact.report;
+            report.size_mb = artifact.usage.usage().apparent_mb();
+            if report.tracked {
+                report.untrack_command = Some(untrack_command(&report.paths));
+            }
+            report
+        })
+        .collect();
+    artifacts.sort_by(|a, b| {
+        compare_f32_safe(a.size_mb + a.history_disk_mb, b.size_mb + b.history_disk_mb)
+    });
+
+    let sum = |tracked: bool, size: fn(&GitArtifact) -> f32| -> f32 {
+        artifacts
+            .iter()
+            .filter(|artifact| artifact.tracked == tracked)
+            .map(size)
+            .sum()
+    };
+    let existing = std::fs::read_to_string(work_dir.join(".gitignore")).ok();
+    let patterns: Vec<&str> = artifacts
+        .iter()
+        .map(|artifact| artifact.ignore_pattern.as_str())
+        .collect();
+
+    Ok(GitArtifactReport {
+        repo_path: work_dir.to_string_lossy().to_string(),
+        tracked_mb: sum(true, |artifact| artifact.size_mb),
+        history_mb: sum(true, |artifact| artifact.history_mb),
+        history_disk_mb: sum(true, |artifact| artifact.history_disk_mb),
+        untracked_mb: sum(false, |artifact| artifact.size_mb),
+        gitignore_patch: gitignore_patch(existing.as_deref(), &patterns),
+        artifacts,
+    })
+}
+
+/// Match every file in the index against the artifact and junk patterns
+fn find_tracked(work_dir: &Path, findings: &mut Findings) -> Result<(), String> {
+    let files = git(work_dir, &["ls-files", "-z"])
+        .ok_or_else(|| format!("git ls-files failed in {}", work_dir.display()))?;
+
+    let mut bloat_dirs: HashMap<String, Option<BloatMatch>> = HashMap::new();
+    for file in files.split('\0').filter(|file| !file.is_empty()) {
+        let path = work_dir.join(file);
+        // The topmost committed artifact directory holding the file
+        let mut found = None;
+        for (end, _) in file.match_indices('/') {
+            let dir = &file[..end];
+            let matched = bloat_dirs.entry(dir.to_string()).or_insert_with(|| {
+                classify_bloat_dir(&work_dir.join(dir))
+                    .filter(|found| found.safety == "safe" && found.category_id != "git")
+            });
+            if let Some(matched) = matched {
+                found = Some((dir, matched.clone()));
+                break;
+            }
+        }
+
+        match found {
+            Some((dir, matched)) => findings.add_bloat(true, dir, &matched).add_file(&path),
+            None => findings.add_junk(true, file, &path),
+        }
+    }
+    Ok(())
+}
+
+/// Match untracked, not ignored files and directories against the patterns
+fn find_untracked(work_dir: &Path, findings: &mut Findings) -> Result<(), String> {
+    let entries = git(
+        work_dir,
+        &[
+            "ls-files",
+            "-z",
+            "--others",
+            "--exclude-standard",
+            "--directory",
+        ],
+    )
+    .ok_or_else(|| format!("git ls-files failed in {}", work_dir.display()))?;
+
+    for entry in entries.split('\0').filter(|entry| !entry.is_empty()) {
+        let mut walker = WalkDir::new(work_dir.join(entry.trim_end_matches('/'))).into_iter();
+        while let Some(item) = walker.next() {
+            let Ok(item) = item else {
+                continue;
+            };
+            let path = item.path();
+            let Some(relative) = path
+                .strip_prefix(work_dir)
+                .ok()
+                .and_then(|relative| relative.to_str())
+                .map(|relative| relative.replace('\\', "/"))
+            else {
+                continue;
+            };
+
+            if item.file_type().is_dir() {
+                // Nested repositories are analyzed on their own
+                if path.join(".git").exists() {
+                    walker.skip_current_dir();
+                    continue;
+                }
+                let matched = classify_bloat_dir(path)
+                    .filter(|found| found.safety != "dangerous" && found.category_id != "git");
+                if let Some(matched) = matched {
+                    let artifact = findings.add_bloat(false, &relative, &matched);
+                    for file in WalkDir::new(path).into_iter().filter_map(Result::ok) {
+                        if !file.file_type().is_dir() {
+                            artifact.add_file(file.path());
+                        }
+                    }
+                    walker.skip_current_dir();
+                }
+            } else {
+                findings.add_junk(false, &relative, path);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Sum the objects every commit stored under each committed artifact
+fn add_history_cost(work_dir: &Path, findings: &mut Findings) -> Result<(), String> {
+    let mut owners: HashMap<String, (bool, String)> = HashMap::new();
+    for ((tracked, key), artifact) in &findings.artifacts {
+        if *tracked {
+            for path in &artifact.report.paths {
+                owners.insert(path.clone(), (true, key.clone()));
+            }
+        }
+    }
+    if owners.is_empty() {
+        return Ok(());
+    }
+
+    // One line per object, with the path it was first reached at
+    let objects = git(work_dir, &["rev-list", "--objects", "--all"])
+        .ok_or_else(|| format!("git rev-list failed in {}", work_dir.display()))?;
+    let mut owned: HashMap<&str, &(bool, String)> = HashMap::new();
+    for line in objects.lines() {
+        let Some((id, path)) = line.split_once(' ') else {
+            continue;
+        };
+        let artifact = owners.get(path).or_else(|| {
+            path.match_indices('/')
+                .find_map(|(end, _)| owners.get(&path[..end]))
+        });
+        if let Some(artifact) = artifact {
+            owned.insert(id, artifact);
+        }
+    }
+    if owned.is_empty() {
+        return Ok(());
+    }
+
+    let ids = owned.keys().fold(String::new(), |mut ids, id| {
+        let _ = writeln!(ids, "{id}");
+        ids
+    });
+    let sizes = git_with_input(
+        work_dir,
+        &[
+            "cat-file",
+            "--batch-check=%(objectname) %(objectsize) %(objectsize:disk)",
+        ],
+        ids,
+    )?;
+    let sizes = String::from_utf8_lossy(&sizes);
+    let mut seen = HashSet::new();
+    for line in sizes.lines() {
+        let mut fields = line.split(' ');
+        let (Some(id), Some(size), Some(disk)) = (fields.next(), fields.next(), fields.next())
+        else {
+            continue;
+        };
+        let (Some(key), Ok(size), Ok(disk)) =
+            (owned.get(id), size.parse::<u64>(), disk.parse::<u64>())
+        else {
+            continue;
+        };
+        if seen.insert(id) {
+            if let Some(artifact) = findings.artifacts.get_mut(key) {
+                artifact.report.history_mb += to_mb(size);
+                artifact.report.history_disk_mb += to_mb(disk);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// `.gitignore` pattern for an artifact directory: the bloat pattern name
+/// that matched it (e.g., `cmake-build-*/`), which ignores it at any depth
+fn ignore_pattern(dir: &str) -> String {
+    let name = dir.rsplit('/').next().unwrap_or(dir);
+    let pattern = BLOAT_PATTERNS
+        .iter()
+        .flat_map(|pattern| pattern.dir_names)
+        .find(|pattern| matches_junk_pattern(name, pattern))
+        .map_or(name, |pattern| *pattern);
+    format!("{pattern}/")
+}
+
+/// Unified diff appending the patterns missing from the root `.gitignore`
+///
+/// **Parameters:**
+/// - `existing` - Current `.gitignore` content, `None` if there is none
+/// - `patterns` - Patterns the artifacts need; duplicates are dropped
+///
+/// **Returns:** A patch `git apply` accepts, or `None` if every pattern is listed
+#[must_use]
+pub fn gitignore_patch(existing: Option<&str>, patterns: &[&str]) -> Option<String> {
+    let content = existing.unwrap_or("");
+    let listed: HashSet<&str> = content.lines().map(normalize_pattern).collect();
+    let mut missing: Vec<&str> = Vec::new();
+    for pattern in patterns {
+        if !listed.contains(normalize_pattern(pattern)) && !missing.contains(pattern) {
+            missing.push(pattern);
+        }
+    }
+    if missing.is_empty() {
+        return None;
+    }
+
+    let lines: Vec<&str> = content.lines().collect();
+    let mut added: Vec<&str> = Vec::new();
+    if lines.last().is_some_and(|line| !line.trim().is_empty()) {
+        added.push("");
+    }
+    added.push(PATCH_COMMENT);
+    added.extend(missing);
+
+    let mut patch = if existing.is_some() {
+        String::from("--- a/.gitignore\n+++ b/.gitignore\n")
+    } else {
+        String::from("--- /dev/null\n+++ b/.gitignore\n")
+    };
+    if lines.is_empty() {
+        let _ = writeln!(patch, "@@ -0,0 +1,{} @@", added.len());
+    } else {
+        // Up to three lines of context before the insertion
+        let first = lines.len().saturating_sub(3);
+        let kept = lines.len() - first;
+        let _ = writeln!(
+            patch,
+            "@@ -{},{kept} +{},{} @@",
+            first + 1,
+            first + 1,
+            kept + added.len()
+        );
+        let (last, before) = lines[first..].split_last().unwrap_or((&"", &[]));
+        for line in before {
+            let _ = writeln!(patch, " {line}");
+        }
+        if content.ends_with('\n') {
+            let _ = writeln!(patch, " {last}");
+        } else {
+            let _ = writeln!(patch, "-{last}\n\\ No newline at end of file\n+{last}");
+        }
+    }
+    for line in added {
+        let _ = writeln!(patch, "+{line}");
+    }
+    Some(patch)
+}
+
+/// A `.gitignore` line without the anchoring and directory slashes
+fn normalize_pattern(line: &str) -> &str {
+    line.trim().trim_start_matches('/').trim_end_matches('/')
+}
+
+/// `git rm --cached` command that stops tracking the paths
+fn untrack_command(paths: &[String]) -> String {
+    let quoted: Vec<String> = paths.iter().map(|path| shell_quote(path)).collect();
+    format!("git rm -r --cached -- {}", quoted.join(" "))
+}
+
+fn shell_quote(arg: &str) -> String {
+    if !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "._-/+@".contains(c))
+    {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', r"'\''"))
+    }
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn to_mb(bytes: u64) -> f32 {
+    bytes as f32 / 1_048_576.0
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use crate::utils::git_test_support::run_git;
+    use std::fs;
+
+    #[test]
+    fn test_gitignore_patch() {
+        assert_eq!(
+            gitignore_patch(None, &["node_modules/", ".DS_Store", "node_modules/"]).unwrap(),
+            "--- /dev/null\n+++ b/.gitignore\n@@ -0,0 +1,3 @@\n\
+             +# Build artifacts and junk files\n+node_modules/\n+.DS_Store\n"
+        );
+        assert_eq!(
+            gitignore_patch(Some("a\nb\n/target\nc\n"), &["target/", "*.pyc"]).unwrap(),
+            "--- a/.gitignore\n+++ b/.gitignore\n@@ -2,3 +2,6 @@\n b\n /target\n c\n\
+             +\n+# Build artifacts and junk files\n+*.pyc\n"
+        );
+        assert_eq!(
+            gitignore_patch(Some("dist"), &["*.o"]).unwrap(),
+            "--- a/.gitignore\n+++ b/.gitignore\n@@ -1,1 +1,4 @@\n-dist\n\
+             \\ No newline at end of file\n+dist\n+\n+# Build artifacts and junk files\n+*.o\n"
+        );
+        assert!(gitignore_patch(Some("node_modules\n"), &["node_modules/"]).is_none());
+    }
+
+    #[test]
+    fn test_committed_and_unignored_artifacts() {
+        if find_program("git").is_none() {
+            return;
+        }
+        let dir = tempfile::tempdir().unwrap();
+        let repo = dir.path();
+        run_git(repo, &["init", "-q"]);
+        fs::write(repo.join("package.json"), "{}").unwrap();
+        fs::create_dir_all(repo.join("node_modules/pkg")).unwrap();
+        fs::write(repo.join("node_modules/pkg/index.js"), vec![b'x'; 4096]).unwrap();
+        fs::write(repo.join(".DS_Store"), "junk").unwrap();
+        run_git(repo, &["add", "."]);
+        run_git(repo, &["commit", "-q", "-m", "initial"]);
+        // A later version adds to the history cost
+        fs::write(repo.join("node_modules/pkg/index.js"), vec![b'y'; 8192]).unwrap();
+        run_git(repo, &["commit", "-q", "-am", "update"]);
+
+        // Untracked and not ignored; a committed `vendor` is left alone
+        fs::write(repo.join("Cargo.toml"), "[package]").unwrap();
+        fs::create_dir_all(repo.join("target/debug")).unwrap();
+        fs::write(repo.join("target/debug/app"), vec![0u8; 1024]).unwrap();
+        fs::write(repo.join("notes.txt"), "keep").unwrap();
+
+        let report = analyze_git_artifacts(repo).unwrap();
+        let find = |path: &str| {
+            report
+                .artifacts
+                .iter()
+                .find(|artifact| artifact.paths.iter().any(|p| p == path))
+                .unwrap()
+        };
+
+        let modules = find("node_modules");
+        assert!(modules.tracked);
+        assert_eq!(modules.file_count, 1);
+        assert!((modules.history_mb - to_mb(4096 + 8192)).abs() < 0.001);
+        assert!(modules.history_mb > modules.size_mb);
+        assert_eq!(
+            modules.untrack_command.as_deref(),
+            Some("git rm -r --cached -- node_modules")
+        );
+
+        let junk = find(".DS_Store");
+        assert!(junk.tracked);
+        assert_eq!(junk.kind, "junk");
+
+        let target = find("target");
+        assert!(!target.tracked);
+        assert!(target.untrack_command.is_none());
+        assert_eq!(report.artifacts.len(), 3);
+        // Costliest first
+        assert_eq!(report.artifacts[0].paths, ["node_modules"]);
+        let cost = |artifact: &GitArtifact| artifact.size_mb + artifact.history_disk_mb;
+        assert!(report
+            .artifacts
+            .windows(2)
+            .all(|pair| cost(&pair[0]) >= cost(&pair[1])));
+
+        let patch = report.gitignore_patch.unwrap();
+        for pattern in ["+node_modules/", "+.DS_Store", "+target/"] {
+            assert!(patch.contains(pattern), "{patch}");
+        }
+
+        // The patch applies cleanly
+        fs::write(repo.join("ignore.patch"), &patch).unwrap();
+        run_git(repo, &["apply", "ignore.patch"]);
+        assert!(fs::read_to_string(repo.join(".gitignore"))
+            .unwrap()
+            .contains("target/\n"));
+    }
+}
@@ -0,0 +1,423 @@
+//! Detection of several clones of the same repository.
+//!
+//! A repository is fingerprinted by the root commits reachable from HEAD and
+//! by its remote URLs. Repositories with the same root commits are clones of
+//! one project when their remotes overlap, or when either has no remote
+//! (a copy made with `cp -r`, or a clone whose remote was removed). Linked
+//! worktrees have a `.git` file instead of a directory and are never scanned
+//! as repositories, so they are not counted as clones.
+//!
+//! In each group the clone to keep is the one holding the most uncommitted
+//! changes, untracked files and stashes (which cannot be fetched into another
+//! clone), then the most unpushed commits, then the most recently committed.
+//! The others are:
+//! - "delete": fully pushed and on the kept clone's branch
+//! - "worktree": no uncommitted changes or stashes, on another branch or with
+//!   unpushed commits that can be fetched into the kept clone first
+//! - "review": uncommitted changes, untracked files or stashes
+
+use crate::error::compare_f32_safe;
+use crate::models::{GitClone, GitCloneGroup, GitRepoStatus, GitRepository, UnsavedWork};
+use crate::utils::disk_usage::{path_usage, paths_usage};
+use crate::utils::git_status::{git, repo_status};
+use crate::utils::process::find_program;
+use crate::utils::scan_progress::CancellationToken;
+use std::collections::{BTreeSet, HashMap};
+use std::path::Path;
+
+/// Identity of a repository: root commits and normalized remote URLs
+#[derive(Debug, Clone)]
+pub struct RepoFingerprint {
+    /// Root commits reachable from HEAD, sorted
+    pub root_commits: Vec<String>,
+    /// Remote URLs normalized with [`normalize_remote_url`]
+    pub remote_urls: BTreeSet<String>,
+}
+
+impl RepoFingerprint {
+    /// Read the fingerprint of a work tree; `None` if it has no commits
+    #[must_use]
+    pub fn read(work_dir: &Path) -> Option<Self> {
+        let roots = git(work_dir, &["rev-list", "--max-parents=0", "HEAD"])?;
+        let mut root_commits: Vec<String> = roots.lines().map(str::to_string).collect();
+        if root_commits.is_empty() {
+            return None;
+        }
+        root_commits.sort();
+
+        // Exits with 1 when no remote is configured
+        let remote_urls = git(work_dir, &["config", "--get-regexp", r"^remote\..*\.url$"])
+            .map(|out| {
+                out.lines()
+                    .filter_map(|line| line.split_once(' '))
+                    .map(|(_, url)| normalize_remote_url(url))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Some(Self {
+            root_commits,
+            remote_urls,
+        })
+    }
+
+    /// Whether two repositories are clones of the same project
+    #[must_use]
+    pub fn same_project(&self, other: &Self) -> bool {
+        self.root_commits == other.root_commits
+            && (self.remote_urls.is_empty()
+                || other.remote_urls.is_empty()
+                || !self.remote_urls.is_disjoint(&other.remote_urls))
+    }
+}
+
+/// Reduce a remote URL to `host/path`, so SSH, HTTPS and scp-style URLs of
+/// one repository compare equal
+///
+/// `git@github.com:org/app.git`, `ssh://git@github.com/org/app` and
+/// `https://user@github.com/org/app.git/` all become `github.com/org/app`.
+/// Local paths are returned unchanged apart from the trailing `.git`.
+#[must_use]
+pub fn normalize_remote_url(url: &str) -> String {
+    let url = url.trim();
+    let without_scheme = url.split_once("://").map(|(_, rest)| rest);
+    let host_and_path = match without_scheme {
+        Some(rest) if !url.starts_with("file://") => {
+            let rest = rest.split_once('@').map_or(rest, |(_, host)| host);
+            let (host, path) = rest.split_once('/').unwrap_or((rest, ""));
+            // Drop the port; SSH and HTTPS use different ones
+            let host = host.split_once(':').map_or(host, |(host, _)| host);
+            format!("{}/{path}", host.to_lowercase())
+        }
+        Some(path) => path.to_string(),
+        None => match url.split_once(':') {
+            // scp-like `user@host:path`, but not a Windows drive letter
+            Some((host, path)) if host.len() > 1 && !host.contains('/') => {
+                let host = host.split_once('@').map_or(host, |(_, host)| host);
+                format!("{}/{path}", host.to_lowercase())
+            }
+            _ => url.to_string(),
+        },
+    };
+    let trimmed = host_and_path.trim_end_matches('/');
+    trimmed.strip_suffix(".git").unwrap_or(trimmed).to_string()
+}
+
+/// Find repositories that are clones of the same project and attach the
+/// group to each of them
+///
+/// Does nothing if git is not installed. Sizes are measured only for
+/// repositories that have another clone.
+pub fn group_clones(repos: &mut [GitRepository], cancel: &CancellationToken) {
+    if repos.len() < 2 || find_program("git").is_none() {
+        return;
+    }
+
+    let fingerprints: Vec<Option<RepoFingerprint>> = repos
+        .iter()
+        .map(|repo| {
+            if cancel.is_cancelled() {
+                None
+            } else {
+                RepoFingerprint::read(Path::new(&repo.repo_path))
+            }
+        })
+        .collect();
+
+    for members in clone_sets(&fingerprints) {
+        if cancel.is_cancelled() {
+            return;
+        }
+        let group = build_group(repos, &fingerprints, &members);
+        for index in members {
+            repos[index].clone_group = Some(group.clone());
+        }
+    }
+}
+
+/// Indices of repositories forming each group of two or more clones
+fn clone_sets(fingerprints: &[Option<RepoFingerprint>]) -> Vec<Vec<usize>> {
+    let mut by_root: HashMap<&[String], Vec<usize>> = HashMap::new();
+    for (index, fingerprint) in fingerprints.iter().enumerate() {
+        if let Some(fingerprint) = fingerprint {
+            by_root
+                .entry(&fingerprint.root_commits)
+                .or_default()
+                .push(index);
+        }
+    }
+
+    let mut sets = Vec::new();
+    for candidates in by_root.into_values().filter(|c| c.len() > 1) {
+        // Union clones whose remotes overlap; a repository without remotes
+        // joins every clone with the same root commits
+        let mut parent: Vec<usize> = (0..candidates.len()).collect();
+        for a in 0..candidates.len() {
+            for b in a + 1..candidates.len() {
+                let (Some(fa), Some(fb)) =
+                    (&fingerprints[candidates[a]], &fingerprints[candidates[b]])
+                else {
+                    continue;
+                };
+                if fa.same_project(fb) {
+                    let (ra, rb) = (find(&mut parent, a), find(&mut parent, b));
+                    parent[ra.max(rb)] = ra.min(rb);
+                }
+            }
+        }
+        let mut groups: HashMap<usize, Vec<usize>> = HashMap::new();
+        for (position, index) in candidates.iter().enumerate() {
+            groups
+                .entry(find(&mut parent, position))
+                .or_default()
+                .push(*index);
+        }
+        sets.extend(groups.into_values().filter(|group| group.len() > 1));
+    }
+    sets.sort();
+    sets
+}
+
+fn find(parent: &mut [usize], mut node: usize) -> usize {
+    while parent[node] != node {
+        parent[node] = parent[parent[node]];
+        node = parent[node];
+    }
+    node
+}
+
+/// Measure the clones of one project and suggest which to keep
+fn build_group(
+    repos: &[GitRepository],
+    fingerprints: &[Option<RepoFingerprint>],
+    members: &[usize],
+) -> GitCloneGroup {
+    let mut statuses: Vec<(usize, GitRepoStatus)> = members
+        .iter()
+        .map(|&index| (index, repo_status(Path::new(&repos[index].repo_path))))
+        .collect();
+    // Keep the clone with the most work that cannot be fetched elsewhere,
+    // then the most unpushed commits, then the newest commit
+    statuses.sort_by_key(|(index, status)| {
+        (
+            std::cmp::Reverse(status.uncommitted + status.untracked + status.stashes),
+            std::cmp::Reverse(status.unpushed),
+            std::cmp::Reverse(status.last_commit_ts),
+            repos[*index].repo_path.clone(),
+        )
+    });
+
+    let kept_branch = statuses[0].1.branch.clone();
+    let kept_path = repos[statuses[0].0].repo_path.clone();
+    let clones: Vec<GitClone> = statuses
+        .iter()
+        .enumerate()
+        .map(|(position, (index, status))| {
+            let repo_path = repos[*index].repo_path.clone();
+            let usage = path_usage(Path::new(&repo_path));
+            let (suggestion, reason) = if position == 0 {
+                (
+                    "keep",
+                    "Most local-only work or most recent commit".to_string(),
+                )
+            } else {
+                suggest(status, &kept_branch, &kept_path)
+            };
+            GitClone {
+                repo_path: repo_path.clone(),
+                branch: status.branch.clone(),
+                size_mb: usage.apparent_mb(),
+                allocated_mb: usage.allocated_mb(),
+                fully_pushed: unsaved_count(status) == 0,
+                unsaved: UnsavedWork {
+                    path: repo_path.clone(),
+                    repo_path,
+                    uncommitted: status.uncommitted,
+                    untracked: status.untracked,
+                    unpushed: status.unpushed,
+                    stashes: status.stashes,
+                },
+                suggestion: suggestion.to_string(),
+                reason,
+            }
+        })
+        .collect();
+
+    let total = paths_usage(clones.iter().map(|clone| Path::new(&clone.repo_path)));
+    let deletable = paths_usage(
+        clones
+            .iter()
+            .filter(|clone| clone.suggestion == "delete")
+            .map(|clone| Path::new(&clone.repo_path)),
+    );
+    let mut remote_urls = BTreeSet::new();
+    for &index in members {
+        if let Some(fingerprint) = &fingerprints[index] {
+            remote_urls.extend(fingerprint.remote_urls.iter().cloned());
+        }
+    }
+
+    let mut group = GitCloneGroup {
+        root_commits: fingerprints[members[0]]
+            .as_ref()
+            .map(|fingerprint| fingerprint.root_commits.clone())
+            .unwrap_or_default(),
+        remote_urls: remote_urls.into_iter().collect(),
+        total_size_mb: total.apparent_mb(),
+        total_allocated_mb: total.allocated_mb(),
+        deletable_mb: deletable.reclaimable_mb(),
+        clones,
+    };
+    // Largest first after the clone to keep
+    group.clones[1..].sort_by(|a, b| compare_f32_safe(a.size_mb, b.size_mb));
+    group
+}
+
+/// Suggestion and reason for a clone other than the one kept
+fn suggest(status: &GitRepoStatus, kept_branch: &str, kept_path: &str) -> (&'static str, String) {
+    if status.uncommitted + status.untracked + status.stashes > 0 {
+        return (
+            "review",
+            format!(
+                "{} uncommitted changes, {} untracked files and {} stashes exist only here",
+                status.uncommitted, status.untracked, status.stashes
+            ),
+        );
+    }
+    if status.unpushed > 0 {
+        return (
+            "worktree",
+            format!(
+                "Fetch its {} unpushed commits into {kept_path}, then use `git worktree add` there",
+                status.unpushed
+            ),
+        );
+    }
+    if status.branch != kept_branch && status.branch != "HEAD" {
+        return (
+            "worktree",
+            format!(
+                "Fully pushed; `git -C {kept_path} worktree add <path> {}` replaces it",
+                status.branch
+            ),
+        );
+    }
+    (
+        "delete",
+        format!("Fully pushed; {kept_path} has the same project"),
+    )
+}
+
+const fn unsaved_count(status: &GitRepoStatus) -> u32 {
+    status.uncommitted + status.untracked + status.unpushed + status.stashes
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use crate::utils::git_test_support::run_git;
+    use std::fs;
+
+    fn repository(path: &Path) -> GitRepository {
+        GitRepository {
+            repo_path: path.to_string_lossy().to_string(),
+            total_size_mb: 0.0,
+            total_allocated_mb: 0.0,
+            entry_count: 0,
+            entries: Vec::new(),
+            large_blobs: Vec::new(),
+            clone_group: None,
+        }
+    }
+
+    #[test]
+    fn test_normalize_remote_url() {
+        for url in [
+            "git@github.com:org/app.git",
+            "ssh://git@GitHub.com:22/org/app",
+            "https://user@github.com/org/app.git/",
+        ] {
+            assert_eq!(normalize_remote_url(url), "github.com/org/app", "{url}");
+        }
+        assert_eq!(normalize_remote_url("/srv/git/app.git"), "/srv/git/app");
+        assert_eq!(normalize_remote_url("C:/repos/app"), "C:/repos/app");
+    }
+
+    #[test]
+    fn test_group_clones() {
+        if find_program("git").is_none() {
+            return;
+        }
+        let dir = tempfile::tempdir().unwrap();
+        let base = dir.path();
+
+        // A bare "remote" with one commit on main
+        let origin = base.join("origin");
+        fs::create_dir_all(&origin).unwrap();
+        run_git(&origin, &["init", "-q", "-b", "main"]);
+        fs::write(origin.join("README"), "app").unwrap();
+        run_git(&origin, &["add", "."]);
+        run_git(&origin, &["commit", "-q", "-m", "initial"]);
+        let remote = base.join("remote.git");
+        run_git(base, &["clone", "-q", "--bare", "origin", "remote.git"]);
+
+        let url = remote.to_string_lossy().to_string();
+        for name in ["work", "old", "feature", "scratch"] {
+            run_git(base, &["clone", "-q", &url, name]);
+        }
+        // Local-only work keeps "work"
+        fs::write(base.join("work/notes.txt"), "wip").unwrap();
+        // Git ignores unknown files in `.git`; this makes "old" the largest
+        fs::write(base.join("old/.git/padding"), vec![0u8; 2 * 1024 * 1024]).unwrap();
+        run_git(&base.join("feature"), &["checkout", "-q", "-b", "feature"]);
+        run_git(&base.join("feature"), &["push", "-q", "origin", "feature"]);
+        run_git(
+            &base.join("scratch"),
+            &["commit", "-q", "--allow-empty", "-m", "local"],
+        );
+        // Same history, unrelated remote: a different project
+        run_git(base, &["clone", "-q", &url, "fork"]);
+        run_git(
+            &base.join("fork"),
+            &[
+                "remote",
+                "set-url",
+                "origin",
+                "https://example.com/other/app",
+            ],
+        );
+
+        let mut repos: Vec<GitRepository> = ["work", "old", "feature", "scratch", "fork"]
+            .iter()
+            .map(|name| repository(&base.join(name)))
+            .collect();
+        group_clones(&mut repos, &CancellationToken::new());
+
+        assert!(repos[4].clone_group.is_none());
+        let group = repos[0].clone_group.as_ref().unwrap();
+        assert_eq!(group.clones.len(), 4);
+        assert_eq!(group.remote_urls, vec![normalize_remote_url(&url)]);
+        assert!(group.total_size_mb > 0.0);
+
+        let suggestion = |name: &str| {
+            let clone = group
+                .clones
+                .iter()
+                .find(|clone| clone.repo_path.ends_with(name))
+                .unwrap();
+            (clone.suggestion.as_str(), clone.fully_pushed)
+        };
+        assert_eq!(group.clones[0].suggestion, "keep");
+        assert!(group.clones[0].repo_path.ends_with("work"));
+        // Largest first after the clone to keep
+        assert!(group.clones[1].repo_path.ends_with("old"));
+        assert!(group.clones[1..]
+            .windows(2)
+            .all(|pair| pair[0].size_mb >= pair[1].size_mb));
+        assert_eq!(suggestion("old"), ("delete", true));
+        assert_eq!(suggestion("feature"), ("worktree", true));
+        assert_eq!(suggestion("scratch"), ("worktree", false));
+        assert!(group.deletable_mb > 0.0);
+    }
+}
@@ -0,0 +1,481 @@
+//! Git maintenance actions with a dry-run estimate and measured reclaim.
+//!
+//! Deleting `.git/logs` or loose objects by hand can corrupt a repository.
+//! These actions let git itself do the work: `git gc`, `git prune`,
+//! `git repack -a -d`, `git reflog expire --all` and `git worktree prune`.
+//!
+//! Every action first estimates what it would free, using git's own dry-run
+//! output where the command has one (`prune`, `reflog expire`, `worktree
+//! prune`) and `git count-objects -v` where it does not. An action refuses to
+//! run while another git process holds one of the repository's lock files.
+//! The space freed is measured from the allocated size of `.git` before and after.
+
+use crate::models::GitMaintenanceResult;
+use crate::utils::deletion_log::{log_deletion, DeletionRecord};
+use crate::utils::disk_usage::{path_usage, paths_usage};
+use crate::utils::process::{find_program, run_with_timeout};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, SystemTime};
+use walkdir::WalkDir;
+
+/// Longest a maintenance command may run before it is stopped
+pub const GIT_MAINTENANCE_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+/// Longest a dry-run estimate command may run
+const ESTIMATE_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+/// A `gc.pid` older than this belongs to a `git gc` that died (as git assumes)
+const GC_PID_MAX_AGE: Duration = Duration::from_secs(12 * 60 * 60);
+
+/// Unreachable loose objects `git gc` keeps (its default `gc.pruneExpire`)
+const GC_PRUNE_EXPIRE: &str = "--expire=2.weeks.ago";
+
+/// A git maintenance action
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GitAction {
+    /// `git gc`: expire reflogs, repack, prune old unreachable objects
+    Gc,
+    /// `git prune`: delete unreachable loose objects
+    Prune,
+    /// `git repack -a -d`: pack everything reachable into one pack
+    Repack,
+    /// `git reflog expire --all`: drop reflog entries past their expiry
+    ReflogExpire,
+    /// `git worktree prune`: drop records of deleted worktrees
+    WorktreePrune,
+}
+
+impl GitAction {
+    /// Every action, in the order the UI lists them
+    pub const ALL: [Self; 5] = [
+        Self::Gc,
+        Self::Prune,
+        Self::Repack,
+        Self::ReflogExpire,
+        Self::WorktreePrune,
+    ];
+
+    /// Parse an action id as used by the frontend
+    ///
+    /// # Errors
+    /// Returns an error for an unknown id
+    pub fn from_id(id: &str) -> Result<Self, String> {
+        Self::ALL
+            .into_iter()
+            .find(|action| action.id() == id)
+            .ok_or_else(|| format!("Unknown git maintenance action: {id}"))
+    }
+
+    /// Stable id of the action
+    #[must_use]
+    pub const fn id(self) -> &'static str {
+        match self {
+            Self::Gc => "gc",
+            Self::Prune => "prune",
+            Self::Repack => "repack",
+            Self::ReflogExpire => "reflog_expire",
+            Self::WorktreePrune => "worktree_prune",
+        }
+    }
+
+    /// Git arguments that perform the action
+    #[must_use]
+    pub const fn args(self) -> &'static [&'static str] {
+        match self {
+            Self::Gc => &["gc"],
+            Self::Prune => &["prune"],
+            Self::Repack => &["repack", "-a", "-d"],
+            Self::ReflogExpire => &["reflog", "expire", "--all"],
+            Self::WorktreePrune => &["worktree", "prune"],
+        }
+    }
+}
+
+/// Estimate and (unless `dry_run`) run a git maintenance action on a repository.
+///
+/// **Parameters:**
+/// - `repo` - Work tree, `.git` directory or bare repository
+/// - `action` - The action to run
+/// - `dry_run` - If true, only estimates what the action would free
+///
+/// **Returns:** The estimate, the command's exit code and captured output,
+/// and the space freed measured from the size of `.git` before and after
+///
+/// # Errors
+/// Returns an error if `repo` is not a repository, another git process holds
+/// its lock, git is not installed, or the estimate fails
+pub fn run_git_maintenance(
+    repo: &Path,
+    action: GitAction,
+    dry_run: bool,
+) -> Result<GitMaintenanceResult, String> {
+    let git_dir = resolve_git_dir(repo)?;
+    if let Some(lock) = find_lock(&git_dir) {
+        return Err(format!(
+            "{} is in use by another git process ({} exists)",
+            repo.display(),
+            lock.display()
+        ));
+    }
+    let git = find_program("git").ok_or_else(|| "git is not installed".to_string())?;
+
+    let before = path_usage(&git_dir).allocated_bytes;
+    let estimate = estimate(&git, &git_dir, action)?;
+    let command_line = format!("git {}", action.args().join(" "));
+    let mut result = GitMaintenanceResult {
+        repo_path: repo.to_string_lossy().to_string(),
+        action: action.id().to_string(),
+        command: command_line.clone(),
+        dry_run,
+        estimated_mb: bytes_to_mb(estimate.bytes),
+        estimate: estimate.summary,
+        success: false,
+        exit_code: None,
+        timed_out: false,
+        stdout: String::new(),
+        stderr: String::new(),
+        error: None,
+        before_mb: bytes_to_mb(before),
+        after_mb: bytes_to_mb(before),
+        freed_mb: 0.0,
+    };
+    if dry_run {
+        result.success = true;
+        return Ok(result);
+    }
+
+    log::info!("Running `{command_line}` in {}", git_dir.display());
+    match run_with_timeout(
+        git_command(&git, &git_dir, action.args()),
+        GIT_MAINTENANCE_TIMEOUT,
+    ) {
+        Ok(output) => {
+            result.exit_code = output.exit_code;
+            result.timed_out = output.timed_out;
+            result.stdout = output.stdout;
+            result.stderr = output.stderr;
+            result.success = !output.timed_out && output.exit_code == Some(0);
+            if output.timed_out {
+                result.error = Some(format!(
+                    "`{command_line}` did not finish within {} minutes and was stopped",
+                    GIT_MAINTENANCE_TIMEOUT.as_secs() / 60
+                ));
+            } else if !result.success {
+                result.error = Some(format!("`{command_line}` failed: {}", result.stderr.trim()));
+            }
+        }
+        Err(e) => result.error = Some(e),
+    }
+
+    let after = path_usage(&git_dir).allocated_bytes;
+    result.after_mb = bytes_to_mb(after);
+    result.freed_mb = bytes_to_mb(before.saturating_sub(after));
+    if result.success && after < before {
+        let record = DeletionRecord::new(
+            git_dir.to_string_lossy().to_string(),
+            before - after,
+            "git".to_string(),
+            "native".to_string(),
+        );
+        if let Err(e) = log_deletion(&record) {
+            log::warn!("Failed to log git maintenance: {e}");
+        }
+    }
+    Ok(result)
+}
+
+/// The git directory of a work tree, `.git` directory or bare repository
+///
+/// Linked worktrees and submodules (a `.git` file) resolve to the repository
+/// that owns the objects, through `gitdir:` and `commondir`.
+fn resolve_git_dir(repo: &Path) -> Result<PathBuf, String> {
+    let dot_git = repo.join(".git");
+    let git_dir = if dot_git.is_dir() {
+        dot_git
+    } else if dot_git.is_file() {
+        let content = fs::read_to_string(&dot_git)
+            .map_err(|e| format!("Failed to read {}: {e}", dot_git.display()))?;
+        let target = content
+            .trim()
+            .strip_prefix("gitdir:")
+            .ok_or_else(|| format!("{} is not a gitdir file", dot_git.display()))?;
+        repo.join(target.trim())
+    } else {
+        repo.to_path_buf()
+    };
+
+    let git_dir = match fs::read_to_string(git_dir.join("commondir")) {
+        Ok(common) => git_dir.join(common.trim()),
+        Err(_) => git_dir,
+    };
+    if git_dir.join("objects").is_dir() && git_dir.join("HEAD").is_file() {
+        Ok(git_dir)
+    } else {
+        Err(format!("{} is not a Git repository", repo.display()))
+    }
+}
+
+/// A lock file another git process holds in `git_dir`, if any
+///
+/// Checks `*.lock` files at the top level (`index.lock`, `HEAD.lock`, ...),
+/// under `refs/` and in linked worktrees, and a recent `gc.pid`.
+fn find_lock(git_dir: &Path) -> Option<PathBuf> {
+    let is_lock = |path: &Path| path.extension().is_some_and(|ext| ext == "lock");
+    let top_level = fs::read_dir(git_dir)
+        .into_iter()
+        .flatten()
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file());
+    let nested = ["refs", "worktrees"].into_iter().flat_map(|dir| {
+        WalkDir::new(git_dir.join(dir))
+            .max_depth(if dir == "worktrees" { 2 } else { usize::MAX })
+            .into_iter()
+            .filter_map(Result::ok)
+            .filter(|entry| entry.file_type().is_file())
+            .map(walkdir::DirEntry::into_path)
+    });
+    if let Some(lock) = top_level.chain(nested).find(|path| is_lock(path)) {
+        return Some(lock);
+    }
+
+    let gc_pid = git_dir.join("gc.pid");
+    let running = fs::metadata(&gc_pid)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+        .is_some_and(|age| age < GC_PID_MAX_AGE);
+    running.then_some(gc_pid)
+}
+
+// ============================================================================
+// Dry-Run Estimates
+// ============================================================================
+
+/// What an action is expected to free
+struct Estimate {
+    bytes: u64,
+    summary: String,
+}
+
+fn estimate(git: &Path, git_dir: &Path, action: GitAction) -> Result<Estimate, String> {
+    match action {
+        GitAction::Prune => estimate_prune(git, git_dir, None),
+        GitAction::Repack => estimate_repack(git, git_dir),
+        GitAction::ReflogExpire => estimate_reflog_expire(git, git_dir),
+        GitAction::WorktreePrune => estimate_worktree_prune(git, git_dir),
+        GitAction::Gc => {
+            // What `git gc` runs, with its default expiry for unreachable objects
+            let parts = [
+                estimate_reflog_expire(git, git_dir)?,
+                estimate_repack(git, git_dir)?,
+                estimate_prune(git, git_dir, Some(GC_PRUNE_EXPIRE))?,
+                estimate_worktree_prune(git, git_dir)?,
+            ];
+            Ok(Estimate {
+                bytes: parts.iter().map(|part| part.bytes).sum(),
+                summary: parts
+                    .into_iter()
+                    .map(|part| part.summary)
+                    .collect::<Vec<_>>()
+                    .join("; "),
+            })
+        }
+    }
+}
+
+/// Unreachable loose objects `git prune --dry-run` lists
+fn estimate_prune(git: &Path, git_dir: &Path, expire: Option<&str>) -> Result<Estimate, String> {
+    let mut args = vec!["prune", "--dry-run", "--verbose"];
+    args.extend(expire);
+    let output = git_output(git, git_dir, &args)?;
+    let objects: Vec<PathBuf> = output
+        .lines()
+        .filter_map(|line| line.split_whitespace().next())
+        .filter(|id| id.len() > 2 && id.bytes().all(|b| b.is_ascii_hexdigit()))
+        .map(|id| git_dir.join("objects").join(&id[..2]).join(&id[2..]))
+        .collect();
+    Ok(Estimate {
+        bytes: paths_usage(objects.iter().map(PathBuf::as_path)).allocated_bytes,
+        summary: format!("{} unreachable loose objects", objects.len()),
+    })
+}
+
+/// Loose objects already in packs and garbage files, from `git count-objects -v`
+///
+/// Gains from deltifying loose objects and dropping unreachable packed
+/// objects are not known before repacking and are not included.
+fn estimate_repack(git: &Path, git_dir: &Path) -> Result<Estimate, String> {
+    let counts: HashMap<String, u64> = git_output(git, git_dir, &["count-objects", "-v"])?
+        .lines()
+        .filter_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            Some((key.trim().to_string(), value.trim().parse().ok()?))
+        })
+        .collect();
+    let count = |key: &str| counts.get(key).copied().unwrap_or(0);
+
+    // count-objects reports sizes in KiB
+    let packed_loose_kib = count("size")
+        .saturating_mul(count("prune-packable"))
+        .checked_div(count("count"))
+        .unwrap_or(0);
+    Ok(Estimate {
+        bytes: (packed_loose_kib + count("size-garbage")) * 1024,
+        summary: format!(
+            "{} loose objects to pack ({} already packed), {} packs to merge, {} garbage files",
+            count("count"),
+            count("prune-packable"),
+            count("packs"),
+            count("garbage")
+        ),
+    })
+}
+
+/// Share of reflog entries `git reflog expire --dry-run` would drop
+fn estimate_reflog_expire(git: &Path, git_dir: &Path) -> Result<Estimate, String> {
+    let output = git_output(
+        git,
+        git_dir,
+        &["reflog", "expire", "--all", "--dry-run", "--verbose"],
+    )?;
+    let expired = output
+        .lines()
+        .filter(|line| line.starts_with("would prune"))
+        .count();
+    let kept = output
+        .lines()
+        .filter(|line| line.starts_with("keep"))
+        .count();
+    let logs = path_usage(&git_dir.join("logs")).allocated_bytes;
+    let total = expired + kept;
+    Ok(Estimate {
+        bytes: if total == 0 {
+            0
+        } else {
+            logs * expired as u64 / total as u64
+        },
+        summary: format!("{expired} of {total} reflog entries expired"),
+    })
+}
+
+/// Records of deleted worktrees `git worktree prune --dry-run` lists
+fn estimate_worktree_prune(git: &Path, git_dir: &Path) -> Result<Estimate, String> {
+    let output = git_output(
+        git,
+        git_dir,
+        &["worktree", "prune", "--dry-run", "--verbose"],
+    )?;
+    let records: Vec<PathBuf> = output
+        .lines()
+        .filter_map(|line| line.strip_prefix("Removing worktrees/"))
+        .filter_map(|rest| rest.split(':').next())
+        .map(|name| git_dir.join("worktrees").join(name))
+        .collect();
+    Ok(Estimate {
+        bytes: paths_usage(records.iter().map(PathBuf::as_path)).allocated_bytes,
+        summary: format!("{} stale worktrees", records.len()),
+    })
+}
+
+// ============================================================================
+// Running Git
+// ============================================================================
+
+/// `git --git-dir <git_dir> <args>` with untranslated output and no prompts
+fn git_command(git: &Path, git_dir: &Path, args: &[&str]) -> Command {
+    let mut command = Command::new(git);
+    command
+        .arg("--git-dir")
+        .arg(git_dir)
+        .args(args)
+        .current_dir(git_dir)
+        .env("LC_ALL", "C")
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .stdin(Stdio::null());
+    command
+}
+
+/// Standard output and error of a successful estimate command
+fn git_output(git: &Path, git_dir: &Path, args: &[&str]) -> Result<String, String> {
+    let output = run_with_timeout(git_command(git, git_dir, args), ESTIMATE_TIMEOUT)?;
+    if output.timed_out || output.exit_code != Some(0) {
+        return Err(format!(
+            "`git {}` failed: {}",
+            args.join(" "),
+            output.stderr.trim()
+        ));
+    }
+    Ok(output.stdout + &output.stderr)
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn bytes_to_mb(bytes: u64) -> f32 {
+    bytes as f32 / 1_048_576.0
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use crate::utils::git_test_support::run_git;
+
+    #[test]
+    fn test_action_ids() {
+        for action in GitAction::ALL {
+            assert_eq!(GitAction::from_id(action.id()), Ok(action));
+        }
+        assert!(GitAction::from_id("filter-repo").is_err());
+    }
+
+    #[test]
+    fn test_refuses_locked_repository() {
+        let dir = tempfile::tempdir().unwrap();
+        let git_dir = dir.path().join(".git");
+        fs::create_dir_all(git_dir.join("objects")).unwrap();
+        fs::create_dir_all(git_dir.join("refs/heads")).unwrap();
+        fs::write(git_dir.join("HEAD"), "ref: refs/heads/main\n").unwrap();
+        fs::write(git_dir.join("refs/heads/main.lock"), "").unwrap();
+
+        let err = run_git_maintenance(dir.path(), GitAction::Gc, true).unwrap_err();
+        assert!(err.contains("main.lock"));
+
+        fs::remove_file(git_dir.join("refs/heads/main.lock")).unwrap();
+        fs::write(git_dir.join("gc.pid"), "12345 host").unwrap();
+        assert!(run_git_maintenance(dir.path(), GitAction::Gc, true).is_err());
+
+        assert!(run_git_maintenance(&dir.path().join("missing"), GitAction::Gc, true).is_err());
+    }
+
+    #[test]
+    fn test_prune_estimates_then_frees_unreachable_objects() {
+        if find_program("git").is_none() {
+            return;
+        }
+        let dir = tempfile::tempdir().unwrap();
+        let repo = dir.path();
+        run_git(repo, &["init", "-q"]);
+        fs::write(repo.join("a.txt"), "tracked").unwrap();
+        run_git(repo, &["add", "a.txt"]);
+        run_git(repo, &["commit", "-q", "-m", "initial"]);
+        let junk: Vec<u8> = (0..200_000u32).map(|i| (i * 7 % 251) as u8).collect();
+        fs::write(repo.join("junk.bin"), junk).unwrap();
+        let id = run_git(repo, &["hash-object", "-w", "junk.bin"]);
+        let object = repo.join(".git/objects").join(&id[..2]).join(&id[2..]);
+
+        let estimate = run_git_maintenance(repo, GitAction::Prune, true).unwrap();
+        assert!(estimate.success);
+        assert_eq!(estimate.estimate, "1 unreachable loose objects");
+        assert!(estimate.estimated_mb > 0.0);
+        assert!(object.exists());
+
+        let result = run_git_maintenance(repo, GitAction::Prune, false).unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert!(!object.exists());
+        assert!(result.freed_mb > 0.0);
+        assert_eq!(run_git(repo, &["cat-file", "-t", "HEAD"]), "commit");
+    }
+}
@@ -0,0 +1,1195 @@
+//! Large-blob analysis of a repository's history, read straight from `.git/objects`.
+//!
+//! The `.git` breakdown of the unified scan only sums directory sizes, which
+//! shows that history is big but not what makes it big. This module reads
+//! pack indexes (`.idx` versions 1 and 2), pack files and loose objects
+//! without the git CLI. It sizes every blob, following delta chains for the
+//! real object type and size. It then walks the commits reachable from refs,
+//! oldest first, to find the path and commit that introduced each of the
+//! largest blobs.
+//!
+//! A reachable large blob can only be reclaimed by rewriting history
+//! (`git filter-repo`). A blob no ref reaches is kept by reflogs or is garbage
+//! that `git gc --prune=now` removes. Missing objects are skipped, as in
+//! shallow or partial clones and repositories borrowing objects through
+//! alternates.
+
+use crate::models::GitBlob;
+use crate::utils::resource_limits::ReadThrottle;
+use crate::utils::scan_progress::CancellationToken;
+use flate2::read::ZlibDecoder;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::fmt::Write as _;
+use std::fs::{self, File};
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use walkdir::WalkDir;
+
+/// Blobs smaller than this are never reported (1 MB)
+pub const LARGE_BLOB_MIN_BYTES: u64 = 1024 * 1024;
+
+/// Most blobs reported per repository
+pub const LARGE_BLOB_LIMIT: usize = 20;
+
+/// Decoded commits and trees kept to resolve delta chains (bytes)
+const DELTA_CACHE_BYTES: usize = 32 * 1024 * 1024;
+
+/// Bytes read for an object header: type, size and delta base
+const HEADER_READ: u64 = 64;
+
+/// Longest delta chain followed before a pack is considered corrupt
+const MAX_DELTA_CHAIN: usize = 10_000;
+
+/// Objects sized between cancellation checks
+const CANCEL_CHECK_INTERVAL: usize = 4096;
+
+/// Raw object id (20 bytes for SHA-1, 32 for SHA-256 repositories)
+type ObjectId = Vec<u8>;
+
+/// A decoded object and its content
+type Object = (Kind, Rc<Vec<u8>>);
+
+/// Git object types
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Kind {
+    Commit,
+    Tree,
+    Blob,
+    Tag,
+}
+
+impl Kind {
+    /// Type code of an undeltified pack entry
+    const fn from_pack_code(code: u8) -> Option<Self> {
+        match code {
+            1 => Some(Self::Commit),
+            2 => Some(Self::Tree),
+            3 => Some(Self::Blob),
+            4 => Some(Self::Tag),
+            _ => None,
+        }
+    }
+
+    /// Type name in a loose object header
+    fn from_name(name: &[u8]) -> Option<Self> {
+        match name {
+            b"commit" => Some(Self::Commit),
+            b"tree" => Some(Self::Tree),
+            b"blob" => Some(Self::Blob),
+            b"tag" => Some(Self::Tag),
+            _ => None,
+        }
+    }
+}
+
+/// List the largest blobs stored in a `.git` directory and where they came from.
+///
+/// **Parameters:**
+/// - `git_dir` - The `.git` directory (or a bare repository)
+/// - `limit` - Most blobs to return
+/// - `cancel` - Stops sizing objects and walking history when set
+/// - `throttle` - Read rate limit for pack and loose object reads
+///
+/// **Returns:** Blobs of at least [`LARGE_BLOB_MIN_BYTES`], largest first, each
+/// with the path and oldest commit reachable from a ref that contains it
+///
+/// # Errors
+/// Returns an error if the object store cannot be read, a pack is corrupt,
+/// or the analysis was cancelled
+pub fn largest_blobs(
+    git_dir: &Path,
+    limit: usize,
+    cancel: &CancellationToken,
+    throttle: &ReadThrottle,
+) -> Result<Vec<GitBlob>, String> {
+    let mut store = ObjectStore::open(git_dir, throttle)?;
+    let mut blobs: Vec<(ObjectId, BlobSize)> = store
+        .large_blobs(LARGE_BLOB_MIN_BYTES, cancel)?
+        .into_iter()
+        .collect();
+    blobs.sort_by(|a, b| b.1.size.cmp(&a.1.size).then_with(|| a.0.cmp(&b.0)));
+    blobs.truncate(limit);
+    if blobs.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let commits = oldest_first(store.collect_commits(ref_tips(git_dir, store.hash_len), cancel)?);
+    let ids: Vec<&[u8]> = blobs.iter().map(|(id, _)| id.as_slice()).collect();
+    let introduced = store.find_introductions(&commits, &ids, cancel)?;
+
+    let mb = |bytes: u64| {
+        #[allow(clippy::cast_precision_loss)]
+        let mb = bytes as f32 / 1_048_576.0;
+        mb
+    };
+    Ok(blobs
+        .iter()
+        .zip(introduced)
+        .map(|((id, size), introduced)| {
+            let commit = introduced.map(|(index, path)| (&commits[index], path));
+            GitBlob {
+                id: to_hex(id),
+                size_mb: mb(size.size),
+                disk_mb: mb(size.disk),
+                path: commit.as_ref().map(|(_, path)| path.clone()),
+                commit: commit.as_ref().map(|(commit, _)| to_hex(&commit.id)),
+                commit_time: commit.as_ref().map(|(commit, _)| commit.time),
+                commit_summary: commit.map(|(commit, _)| commit.summary.clone()),
+            }
+        })
+        .collect())
+}
+
+// ============================================================================
+// Object Store
+// ============================================================================
+
+/// Uncompressed size of a blob and the space its stored copies take
+#[derive(Debug, Default, Clone, Copy)]
+struct BlobSize {
+    size: u64,
+    disk: u64,
+}
+
+/// A commit reachable from a ref
+#[derive(Debug)]
+struct Commit {
+    id: ObjectId,
+    tree: ObjectId,
+    parents: Vec<ObjectId>,
+    /// Committer time (Unix seconds)
+    time: u64,
+    summary: String,
+}
+
+/// Where an object is stored
+enum Location {
+    /// Pack index and entry offset
+    Packed(usize, u64),
+    Loose(PathBuf),
+}
+
+/// Packs and loose objects of one repository
+struct ObjectStore<'a> {
+    hash_len: usize,
+    packs: Vec<Pack>,
+    loose: HashMap<ObjectId, PathBuf>,
+    /// Decoded packed objects by (pack, offset), emptied when full
+    cache: HashMap<(usize, u64), Object>,
+    cache_bytes: usize,
+    throttle: &'a ReadThrottle,
+}
+
+impl<'a> ObjectStore<'a> {
+    /// Open the packs and list the loose objects under `git_dir/objects`
+    fn open(git_dir: &Path, throttle: &'a ReadThrottle) -> Result<Self, String> {
+        let objects = git_dir.join("objects");
+        if !objects.is_dir() {
+            return Err(format!("{} has no object store", git_dir.display()));
+        }
+        let hash_len = object_hash_len(git_dir);
+
+        let mut packs = Vec::new();
+        for entry in fs::read_dir(objects.join("pack"))
+            .into_iter()
+            .flatten()
+            .filter_map(Result::ok)
+        {
+            let path = entry.path();
+            if path.extension().is_some_and(|ext| ext == "idx") {
+                match Pack::open(&path, hash_len) {
+                    Ok(pack) => packs.push(pack),
+                    Err(e) => log::warn!("Skipping pack {}: {e}", path.display()),
+                }
+            }
+        }
+
+        let mut loose = HashMap::new();
+        for dir in fs::read_dir(&objects)
+            .into_iter()
+            .flatten()
+            .filter_map(Result::ok)
+        {
+            let prefix = dir.file_name().to_string_lossy().into_owned();
+            if prefix.len() != 2 || from_hex(&prefix).is_none() {
+                continue;
+            }
+            for file in fs::read_dir(dir.path())
+                .into_iter()
+                .flatten()
+                .filter_map(Result::ok)
+            {
+                let name = format!("{prefix}{}", file.file_name().to_string_lossy());
+                if let Some(id) = from_hex(&name).filter(|id| id.len() == hash_len) {
+                    loose.insert(id, file.path());
+                }
+            }
+        }
+
+        Ok(Self {
+            hash_len,
+            packs,
+            loose,
+            cache: HashMap::new(),
+            cache_bytes: 0,
+            throttle,
+        })
+    }
+
+    fn locate(&self, id: &[u8]) -> Option<Location> {
+        self.packs
+            .iter()
+            .enumerate()
+            .find_map(|(index, pack)| pack.find(id).map(|offset| Location::Packed(index, offset)))
+            .or_else(|| self.loose.get(id).cloned().map(Location::Loose))
+    }
+
+    /// Decode an object; `None` if the repository does not have it
+    fn read(&mut self, id: &[u8]) -> Result<Option<Object>, String> {
+        match self.locate(id) {
+            Some(Location::Packed(pack, offset)) => self.read_packed(pack, offset).map(Some),
+            Some(Location::Loose(path)) => {
+                let (kind, _, content) = self.read_loose(&path, false)?;
+                Ok(Some((kind, Rc::new(content))))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Decode a packed object, applying its delta chain
+    fn read_packed(&mut self, pack: usize, offset: u64) -> Result<Object, String> {
+        let mut chain = Vec::new();
+        let (mut pack_index, mut entry_offset) = (pack, offset);
+        let (kind, mut data) = loop {
+            if let Some((kind, data)) = self.cache.get(&(pack_index, entry_offset)) {
+                break (*kind, Rc::clone(data));
+            }
+            let header = self.header(pack_index, entry_offset)?;
+            let base = match &header.base {
+                DeltaBase::None => {
+                    let kind = Kind::from_pack_code(header.code)
+                        .ok_or_else(|| format!("Unknown object type {}", header.code))?;
+                    let data = Rc::new(self.inflate(pack_index, entry_offset, &header)?);
+                    self.remember(pack_index, entry_offset, kind, &data);
+                    break (kind, data);
+                }
+                DeltaBase::Offset(base) => (pack_index, *base),
+                DeltaBase::Id(id) => match self.locate(id) {
+                    Some(Location::Packed(base_pack, base)) => (base_pack, base),
+                    _ => return Err(format!("Delta base {} is missing", to_hex(id))),
+                },
+            };
+            chain.push((pack_index, entry_offset, header));
+            if chain.len() > MAX_DELTA_CHAIN {
+                return Err("Delta chain too long".to_string());
+            }
+            (pack_index, entry_offset) = base;
+        };
+
+        while let Some((delta_pack, delta_offset, header)) = chain.pop() {
+            let delta = self.inflate(delta_pack, delta_offset, &header)?;
+            data = Rc::new(apply_delta(&data, &delta)?);
+            self.remember(delta_pack, delta_offset, kind, &data);
+        }
+        Ok((kind, data))
+    }
+
+    /// Keep a decoded object for later deltas against it
+    fn remember(&mut self, pack: usize, offset: u64, kind: Kind, data: &Rc<Vec<u8>>) {
+        if kind == Kind::Blob || data.len() > DELTA_CACHE_BYTES / 4 {
+            return;
+        }
+        if self.cache_bytes + data.len() > DELTA_CACHE_BYTES {
+            self.cache.clear();
+            self.cache_bytes = 0;
+        }
+        self.cache_bytes += data.len();
+        self.cache.insert((pack, offset), (kind, Rc::clone(data)));
+    }
+
+    fn header(&mut self, pack: usize, offset: u64) -> Result<EntryHeader, String> {
+        self.throttle.consume(HEADER_READ);
+        self.packs[pack].header(offset, self.hash_len)
+    }
+
+    /// Inflate an entry's data, the object itself or its delta
+    fn inflate(
+        &mut self,
+        pack: usize,
+        offset: u64,
+        header: &EntryHeader,
+    ) -> Result<Vec<u8>, String> {
+        let pack = &mut self.packs[pack];
+        self.throttle.consume(pack.disk_size(offset));
+        let data = pack.inflate(header.data_offset, header.size)?;
+        if data.len() as u64 != header.size {
+            return Err(format!("Truncated pack entry at offset {offset}"));
+        }
+        Ok(data)
+    }
+
+    /// Type of a packed object, following delta bases through their headers
+    fn packed_kind(
+        &mut self,
+        pack: usize,
+        offset: u64,
+        kinds: &mut HashMap<(usize, u64), Kind>,
+    ) -> Result<Kind, String> {
+        let mut chain = Vec::new();
+        let (mut pack_index, mut entry_offset) = (pack, offset);
+        let kind = loop {
+            if let Some(kind) = kinds.get(&(pack_index, entry_offset)) {
+                break *kind;
+            }
+            let header = self.header(pack_index, entry_offset)?;
+            chain.push((pack_index, entry_offset));
+            match header.base {
+                DeltaBase::None => {
+                    break Kind::from_pack_code(header.code)
+                        .ok_or_else(|| format!("Unknown object type {}", header.code))?;
+                }
+                DeltaBase::Offset(base) => entry_offset = base,
+                DeltaBase::Id(id) => match self.locate(&id) {
+                    Some(Location::Packed(base_pack, base)) => {
+                        (pack_index, entry_offset) = (base_pack, base);
+                    }
+                    _ => return Err(format!("Delta base {} is missing", to_hex(&id))),
+                },
+            }
+            if chain.len() > MAX_DELTA_CHAIN {
+                return Err("Delta chain too long".to_string());
+            }
+        };
+        for key in chain {
+            kinds.insert(key, kind);
+        }
+        Ok(kind)
+    }
+
+    /// Uncompressed size of a packed object (a delta's target size)
+    fn packed_size(&mut self, pack: usize, offset: u64) -> Result<u64, String> {
+        let header = self.header(pack, offset)?;
+        if matches!(header.base, DeltaBase::None) {
+            return Ok(header.size);
+        }
+        let delta = self.packs[pack].inflate(header.data_offset, 2 * 10)?;
+        let (_, pos) = delta_varint(&delta, 0)?;
+        delta_varint(&delta, pos).map(|(target_size, _)| target_size)
+    }
+
+    /// Type, size and (unless `header_only`) content of a loose object
+    fn read_loose(&self, path: &Path, header_only: bool) -> Result<(Kind, u64, Vec<u8>), String> {
+        let file =
+            File::open(path).map_err(|e| format!("Failed to open {}: {e}", path.display()))?;
+        let file_len = file.metadata().map_or(0, |m| m.len());
+        self.throttle.consume(if header_only {
+            HEADER_READ.min(file_len)
+        } else {
+            file_len
+        });
+
+        let mut decoder = ZlibDecoder::new(BufReader::new(file));
+        let mut data = Vec::new();
+        let read = if header_only {
+            decoder.take(HEADER_READ).read_to_end(&mut data)
+        } else {
+            decoder.read_to_end(&mut data)
+        };
+        read.map_err(|e| format!("Failed to inflate {}: {e}", path.display()))?;
+
+        let corrupt = || format!("Corrupt loose object {}", path.display());
+        let nul = data.iter().position(|&b| b == 0).ok_or_else(corrupt)?;
+        let header = std::str::from_utf8(&data[..nul]).map_err(|_| corrupt())?;
+        let (kind, size) = header.split_once(' ').ok_or_else(corrupt)?;
+        let kind = Kind::from_name(kind.as_bytes()).ok_or_else(corrupt)?;
+        let size = size.parse().map_err(|_| corrupt())?;
+        data.drain(..=nul);
+        Ok((kind, size, data))
+    }
+
+    /// Blobs of at least `min_size` bytes with their sizes
+    fn large_blobs(
+        &mut self,
+        min_size: u64,
+        cancel: &CancellationToken,
+    ) -> Result<HashMap<ObjectId, BlobSize>, String> {
+        let mut blobs: HashMap<ObjectId, BlobSize> = HashMap::new();
+        let mut kinds = HashMap::new();
+        let mut sized = 0;
+
+        for pack in 0..self.packs.len() {
+            for position in 0..self.packs[pack].offsets.len() {
+                sized += 1;
+                if sized % CANCEL_CHECK_INTERVAL == 0 && cancel.is_cancelled() {
+                    return Err("Cancelled".to_string());
+                }
+                let offset = self.packs[pack].offsets[position];
+                if self.packed_kind(pack, offset, &mut kinds)? != Kind::Blob {
+                    continue;
+                }
+                let size = self.packed_size(pack, offset)?;
+                if size >= min_size {
+                    let blob = blobs
+                        .entry(self.packs[pack].id(position, self.hash_len).to_vec())
+                        .or_default();
+                    blob.size = size;
+                    blob.disk += self.packs[pack].disk_size(offset);
+                }
+            }
+        }
+
+        for (id, path) in &self.loose {
+            let Ok((Kind::Blob, size, _)) = self.read_loose(path, true) else {
+                continue;
+            };
+            if size >= min_size {
+                let blob = blobs.entry(id.clone()).or_default();
+                blob.size = size;
+                blob.disk += fs::metadata(path).map_or(0, |m| m.len());
+            }
+        }
+        Ok(blobs)
+    }
+
+    /// Commits reachable from `tips`, peeling annotated tags
+    fn collect_commits(
+        &mut self,
+        tips: Vec<ObjectId>,
+        cancel: &CancellationToken,
+    ) -> Result<Vec<Commit>, String> {
+        let mut pending = tips;
+        let mut seen = HashSet::new();
+        let mut commits = Vec::new();
+        while let Some(id) = pending.pop() {
+            if !seen.insert(id.clone()) {
+                continue;
+            }
+            if cancel.is_cancelled() {
+                return Err("Cancelled".to_string());
+            }
+            match self.read(&id)? {
+                Some((Kind::Tag, data)) => pending.extend(header_ids(&data, b"object ")),
+                Some((Kind::Commit, data)) => {
+                    if let Some(commit) = parse_commit(id, &data) {
+                        pending.extend(commit.parents.iter().cloned());
+                        commits.push(commit);
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(commits)
+    }
+
+    /// Oldest commit and path for each of `blobs`, walking every tree once
+    ///
+    /// `commits` must be ordered by [`oldest_first`]: a tree is only entered
+    /// the first time it is seen, so later commits sharing it add nothing.
+    fn find_introductions(
+        &mut self,
+        commits: &[Commit],
+        blobs: &[&[u8]],
+        cancel: &CancellationToken,
+    ) -> Result<Vec<Option<(usize, String)>>, String> {
+        let slots: HashMap<&[u8], usize> =
+            blobs.iter().enumerate().map(|(i, id)| (*id, i)).collect();
+        let mut found = vec![None; blobs.len()];
+        let mut remaining = blobs.len();
+        let mut seen_trees = HashSet::new();
+
+        for (index, commit) in commits.iter().enumerate() {
+            if remaining == 0 {
+                break;
+            }
+            if cancel.is_cancelled() {
+                return Err("Cancelled".to_string());
+            }
+            let mut pending = vec![(commit.tree.clone(), String::new())];
+            while let Some((tree, prefix)) = pending.pop() {
+                if !seen_trees.insert(tree.clone()) {
+                    continue;
+                }
+                let Some((Kind::Tree, data)) = self.read(&tree)? else {
+                    continue;
+                };
+                for (mode, name, id) in TreeEntries::new(&data, self.hash_len) {
+                    let path = || format!("{prefix}{}", String::from_utf8_lossy(name));
+                    if mode == b"40000" {
+                        pending.push((id.to_vec(), path() + "/"));
+                    } else if let Some(&slot) = slots.get(id) {
+                        if found[slot].is_none() {
+                            found[slot] = Some((index, path()));
+                            remaining -= 1;
+                        }
+                    }
+                }
+            }
+        }
+        Ok(found)
+    }
+}
+
+// ============================================================================
+// Pack Files
+// ============================================================================
+
+/// Where a pack entry's delta base is
+enum DeltaBase {
+    /// Not a delta
+    None,
+    /// `OFS_DELTA`: an earlier entry of the same pack
+    Offset(u64),
+    /// `REF_DELTA`: an object by id
+    Id(ObjectId),
+}
+
+/// Header of a pack entry
+struct EntryHeader {
+    /// Pack type code (6 and 7 for deltas)
+    code: u8,
+    /// Inflated size of the entry data (the delta itself for deltas)
+    size: u64,
+    base: DeltaBase,
+    /// Offset of the zlib data
+    data_offset: u64,
+}
+
+/// A `.pack` file and its `.idx`
+struct Pack {
+    file: BufReader<File>,
+    /// Sorted object ids, `hash_len` bytes each
+    ids: Vec<u8>,
+    /// Entry offsets, in id order
+    offsets: Vec<u64>,
+    /// Entry offsets in pack order, followed by the end of the last entry
+    sorted_offsets: Vec<u64>,
+}
+
+impl Pack {
+    fn open(idx_path: &Path, hash_len: usize) -> Result<Self, String> {
+        let idx = fs::read(idx_path).map_err(|e| format!("Failed to read index: {e}"))?;
+        let (ids, offsets) = parse_idx(&idx, hash_len)?;
+        let pack_path = idx_path.with_extension("pack");
+        let file = File::open(&pack_path).map_err(|e| format!("Failed to open pack: {e}"))?;
+        let pack_len = file
+            .metadata()
+            .map_err(|e| format!("Failed to read pack metadata: {e}"))?
+            .len();
+
+        let mut sorted_offsets = offsets.clone();
+        sorted_offsets.sort_unstable();
+        sorted_offsets.push(pack_len.saturating_sub(hash_len as u64));
+        Ok(Self {
+            file: BufReader::new(file),
+            ids,
+            offsets,
+            sorted_offsets,
+        })
+    }
+
+    fn id(&self, position: usize, hash_len: usize) -> &[u8] {
+        &self.ids[position * hash_len..(position + 1) * hash_len]
+    }
+
+    /// Offset of the entry for `id`, if the pack has it
+    fn find(&self, id: &[u8]) -> Option<u64> {
+        let hash_len = id.len();
+        let (mut low, mut high) = (0, self.offsets.len());
+        while low < high {
+            let mid = (low + high) / 2;
+            match self.id(mid, hash_len).cmp(id) {
+                std::cmp::Ordering::Less => low = mid + 1,
+                std::cmp::Ordering::Greater => high = mid,
+                std::cmp::Ordering::Equal => return Some(self.offsets[mid]),
+            }
+        }
+        None
+    }
+
+    /// Bytes the entry at `offset` takes in the pack
+    fn disk_size(&self, offset: u64) -> u64 {
+        self.sorted_offsets
+            .binary_search(&offset)
+            .ok()
+            .and_then(|index| self.sorted_offsets.get(index + 1))
+            .map_or(0, |next| next.saturating_sub(offset))
+    }
+
+    fn header(&mut self, offset: u64, hash_len: usize) -> Result<EntryHeader, String> {
+        let mut buf = Vec::new();
+        self.file
+            .seek(SeekFrom::Start(offset))
+            .and_then(|_| (&mut self.file).take(HEADER_READ).read_to_end(&mut buf))
+            .map_err(|e| format!("Failed to read pack entry: {e}"))?;
+        parse_entry_header(&buf, offset, hash_len)
+    }
+
+    /// Inflate at most `limit` bytes of the zlib stream at `data_offset`
+    fn inflate(&mut self, data_offset: u64, limit: u64) -> Result<Vec<u8>, String> {
+        let mut data = Vec::new();
+        self.file
+            .seek(SeekFrom::Start(data_offset))
+            .map_err(|e| format!("Failed to read pack entry: {e}"))?;
+        ZlibDecoder::new(&mut self.file)
+            .take(limit)
+            .read_to_end(&mut data)
+            .map_err(|e| format!("Failed to inflate pack entry: {e}"))?;
+        Ok(data)
+    }
+}
+
+/// Object ids and entry offsets of a pack index (version 1 or 2)
+fn parse_idx(idx: &[u8], hash_len: usize) -> Result<(Vec<u8>, Vec<u64>), String> {
+    let be = |pos: usize, len: usize| -> Result<u64, String> {
+        idx.get(pos..pos + len)
+            .map(|bytes| bytes.iter().fold(0, |acc, &b| acc << 8 | u64::from(b)))
+            .ok_or_else(|| "Truncated pack index".to_string())
+    };
+    let count_at = |pos: usize| -> Result<usize, String> {
+        usize::try_from(be(pos, 4)?).map_err(|e| e.to_string())
+    };
+
+    if idx.starts_with(b"\xfftOc") {
+        if be(4, 4)? != 2 {
+            return Err("Unsupported pack index version".to_string());
+        }
+        let count = count_at(8 + 255 * 4)?;
+        let ids_start = 8 + 256 * 4;
+        let offsets_start = ids_start + count * (hash_len + 4);
+        let large_start = offsets_start + count * 4;
+        let ids = idx
+            .get(ids_start..ids_start + count * hash_len)
+            .ok_or_else(|| "Truncated pack index".to_string())?
+            .to_vec();
+        let offsets = (0..count)
+            .map(|i| {
+                let offset = be(offsets_start + i * 4, 4)?;
+                if offset & 0x8000_0000 == 0 {
+                    return Ok(offset);
+                }
+                let large = usize::try_from(offset & 0x7fff_ffff).map_err(|e| e.to_string())?;
+                be(large_start + large * 8, 8)
+            })
+            .collect::<Result<_, String>>()?;
+        Ok((ids, offsets))
+    } else {
+        let count = count_at(255 * 4)?;
+        let entry_len = 4 + hash_len;
+        let mut ids = Vec::with_capacity(count * hash_len);
+        let mut offsets = Vec::with_capacity(count);
+        for i in 0..count {
+            let pos = 256 * 4 + i * entry_len;
+            offsets.push(be(pos, 4)?);
+            ids.extend_from_slice(
+                idx.get(pos + 4..pos + entry_len)
+                    .ok_or_else(|| "Truncated pack index".to_string())?,
+            );
+        }
+        Ok((ids, offsets))
+    }
+}
+
+/// Parse the type, size and delta base at the start of a pack entry
+fn parse_entry_header(buf: &[u8], offset: u64, hash_len: usize) -> Result<EntryHeader, String> {
+    let corrupt = || format!("Corrupt pack entry at offset {offset}");
+    let mut pos = 0;
+    let mut next = || {
+        let byte = buf.get(pos).copied();
+        pos += 1;
+        byte.ok_or_else(corrupt)
+    };
+
+    let mut byte = next()?;
+    let code = (byte >> 4) & 0x07;
+    let mut size = u64::from(byte & 0x0f);
+    let mut shift = 4;
+    while byte & 0x80 != 0 {
+        byte = next()?;
+        if shift > 57 {
+            return Err(corrupt());
+        }
+        size |= u64::from(byte & 0x7f) << shift;
+        shift += 7;
+    }
+
+    let base = match code {
+        6 => {
+            byte = next()?;
+            let mut distance = u64::from(byte & 0x7f);
+            while byte & 0x80 != 0 {
+                byte = next()?;
+                if distance >= u64::MAX >> 8 {
+                    return Err(corrupt());
+                }
+                distance = ((distance + 1) << 7) | u64::from(byte & 0x7f);
+            }
+            DeltaBase::Offset(offset.checked_sub(distance).ok_or_else(corrupt)?)
+        }
+        7 => DeltaBase::Id((0..hash_len).map(|_| next()).collect::<Result<_, _>>()?),
+        _ => DeltaBase::None,
+    };
+    Ok(EntryHeader {
+        code,
+        size,
+        base,
+        data_offset: offset + pos as u64,
+    })
+}
+
+/// Size varint at the start of a delta
+fn delta_varint(data: &[u8], mut pos: usize) -> Result<(u64, usize), String> {
+    let mut value = 0;
+    let mut shift = 0;
+    loop {
+        let byte = *data.get(pos).ok_or_else(|| "Truncated delta".to_string())?;
+        pos += 1;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Ok((value, pos));
+        }
+        shift += 7;
+        if shift > 63 {
+            return Err("Corrupt delta".to_string());
+        }
+    }
+}
+
+/// Rebuild an object from its delta base and the delta's copy/insert instructions
+fn apply_delta(base: &[u8], delta: &[u8]) -> Result<Vec<u8>, String> {
+    let corrupt = || "Corrupt delta".to_string();
+    let (source_size, pos) = delta_varint(delta, 0)?;
+    let (target_size, mut pos) = delta_varint(delta, pos)?;
+    if source_size != base.len() as u64 {
+        return Err("Delta does not match its base".to_string());
+    }
+
+    let mut out = Vec::with_capacity(usize::try_from(target_size).map_err(|_| corrupt())?);
+    while let Some(&op) = delta.get(pos) {
+        pos += 1;
+        if op & 0x80 != 0 {
+            let mut field = |bits: std::ops::Range<u8>, flag_shift: u8| -> Result<usize, String> {
+                let mut value = 0;
+                for bit in bits {
+                    if op & (1 << (bit + flag_shift)) != 0 {
+                        value |= usize::from(*delta.get(pos).ok_or_else(corrupt)?) << (8 * bit);
+                        pos += 1;
+                    }
+                }
+                Ok(value)
+            };
+            let start = field(0..4, 0)?;
+            let len = match field(0..3, 4)? {
+                0 => 0x10000,
+                len => len,
+            };
+            let end = start.checked_add(len).ok_or_else(corrupt)?;
+            out.extend_from_slice(base.get(start..end).ok_or_else(corrupt)?);
+        } else if op != 0 {
+            let end = pos + usize::from(op);
+            out.extend_from_slice(delta.get(pos..end).ok_or_else(corrupt)?);
+            pos = end;
+        } else {
+            return Err(corrupt());
+        }
+    }
+    if out.len() as u64 != target_size {
+        return Err(corrupt());
+    }
+    Ok(out)
+}
+
+// ============================================================================
+// Commits, Trees and Refs
+// ============================================================================
+
+/// Entries of a tree object: mode, name and object id
+struct TreeEntries<'d> {
+    data: &'d [u8],
+    hash_len: usize,
+}
+
+impl<'d> TreeEntries<'d> {
+    const fn new(data: &'d [u8], hash_len: usize) -> Self {
+        Self { data, hash_len }
+    }
+}
+
+impl<'d> Iterator for TreeEntries<'d> {
+    type Item = (&'d [u8], &'d [u8], &'d [u8]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let space = self.data.iter().position(|&b| b == b' ')?;
+        let nul = space + self.data[space..].iter().position(|&b| b == 0)?;
+        let id = self.data.get(nul + 1..nul + 1 + self.hash_len)?;
+        let entry = (&self.data[..space], &self.data[space + 1..nul], id);
+        self.data = &self.data[nul + 1 + self.hash_len..];
+        Some(entry)
+    }
+}
+
+/// Ids in the header lines of a commit or tag starting with `prefix`
+fn header_ids(data: &[u8], prefix: &[u8]) -> Vec<ObjectId> {
+    data.split(|&b| b == b'\n')
+        .take_while(|line| !line.is_empty())
+        .filter_map(|line| line.strip_prefix(prefix))
+        .filter_map(|hex| from_hex(std::str::from_utf8(hex).ok()?))
+        .collect()
+}
+
+fn parse_commit(id: ObjectId, data: &[u8]) -> Option<Commit> {
+    let text = String::from_utf8_lossy(data);
+    let (headers, message) = text.split_once("\n\n").unwrap_or((&text, ""));
+    let tree = header_ids(data, b"tree ").into_iter().next()?;
+    let time = headers
+        .lines()
+        .find_map(|line| line.strip_prefix("committer "))
+        .and_then(|committer| committer.rsplit(' ').nth(1))
+        .and_then(|time| time.parse().ok())
+        .unwrap_or(0);
+    Some(Commit {
+        id,
+        tree,
+        parents: header_ids(data, b"parent "),
+        time,
+        summary: message.lines().next().unwrap_or_default().to_string(),
+    })
+}
+
+/// Order commits so parents come before their children, oldest first among
+/// commits whose parents are all placed (commit times alone tie or lie)
+fn oldest_first(commits: Vec<Commit>) -> Vec<Commit> {
+    let index: HashMap<&[u8], usize> = commits
+        .iter()
+        .enumerate()
+        .map(|(i, commit)| (commit.id.as_slice(), i))
+        .collect();
+    let mut waiting_on = vec![0; commits.len()];
+    let mut children = vec![Vec::new(); commits.len()];
+    for (i, commit) in commits.iter().enumerate() {
+        for parent in commit
+            .parents
+            .iter()
+            .filter_map(|p| index.get(p.as_slice()))
+        {
+            waiting_on[i] += 1;
+            children[*parent].push(i);
+        }
+    }
+
+    let mut ready: BinaryHeap<Reverse<(u64, usize)>> = (0..commits.len())
+        .filter(|&i| waiting_on[i] == 0)
+        .map(|i| Reverse((commits[i].time, i)))
+        .collect();
+    let mut order = Vec::with_capacity(commits.len());
+    while let Some(Reverse((_, i))) = ready.pop() {
+        order.push(i);
+        for &child in &children[i] {
+            waiting_on[child] -= 1;
+            if waiting_on[child] == 0 {
+                ready.push(Reverse((commits[child].time, child)));
+            }
+        }
+    }
+
+    let mut slots: Vec<Option<Commit>> = commits.into_iter().map(Some).collect();
+    order.into_iter().filter_map(|i| slots[i].take()).collect()
+}
+
+/// Objects refs point at: `HEAD`, `packed-refs` (with peeled tags) and `refs/`
+fn ref_tips(git_dir: &Path, hash_len: usize) -> Vec<ObjectId> {
+    let parse = |text: &str| from_hex(text.trim()).filter(|id| id.len() == hash_len);
+    let mut tips: Vec<ObjectId> = fs::read_to_string(git_dir.join("HEAD"))
+        .ok()
+        .and_then(|head| parse(&head))
+        .into_iter()
+        .collect();
+
+    if let Ok(packed) = fs::read_to_string(git_dir.join("packed-refs")) {
+        tips.extend(
+            packed
+                .lines()
+                .filter(|line| !line.starts_with('#'))
+                .filter_map(|line| parse(line.trim_start_matches('^').split(' ').next()?)),
+        );
+    }
+    tips.extend(
+        WalkDir::new(git_dir.join("refs"))
+            .into_iter()
+            .filter_map(Result::ok)
+            .filter(|entry| entry.file_type().is_file())
+            .filter_map(|entry| parse(&fs::read_to_string(entry.path()).ok()?)),
+    );
+    tips
+}
+
+/// Object id length from `extensions.objectFormat` in the repository config
+fn object_hash_len(git_dir: &Path) -> usize {
+    let sha256 = fs::read_to_string(git_dir.join("config"))
+        .unwrap_or_default()
+        .lines()
+        .any(|line| {
+            line.replace(' ', "")
+                .eq_ignore_ascii_case("objectformat=sha256")
+        });
+    if sha256 {
+        32
+    } else {
+        20
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().fold(String::new(), |mut hex, b| {
+        let _ = write!(hex, "{b:02x}");
+        hex
+    })
+}
+
+fn from_hex(text: &str) -> Option<Vec<u8>> {
+    if text.len() % 2 != 0 {
+        return None;
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(text.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use flate2::write::ZlibEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+
+    fn zlib(data: &[u8]) -> Vec<u8> {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::fast());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    /// Fake object id; the reader never verifies hashes
+    fn oid(n: u8) -> Vec<u8> {
+        vec![n; 20]
+    }
+
+    fn write_loose(git: &Path, id: &[u8], kind: &str, content: &[u8]) {
+        let hex = to_hex(id);
+        let dir = git.join("objects").join(&hex[..2]);
+        fs::create_dir_all(&dir).unwrap();
+        let mut raw = format!("{kind} {}\0", content.len()).into_bytes();
+        raw.extend_from_slice(content);
+        fs::write(dir.join(&hex[2..]), zlib(&raw)).unwrap();
+    }
+
+    fn tree(entries: &[(&str, &str, &[u8])]) -> Vec<u8> {
+        let mut data = Vec::new();
+        for (mode, name, id) in entries {
+            data.extend_from_slice(format!("{mode} {name}\0").as_bytes());
+            data.extend_from_slice(id);
+        }
+        data
+    }
+
+    fn commit(tree: &[u8], parent: Option<&[u8]>, time: u64, message: &str) -> Vec<u8> {
+        let mut text = format!("tree {}\n", to_hex(tree));
+        if let Some(parent) = parent {
+            text += &format!("parent {}\n", to_hex(parent));
+        }
+        text += &format!("author A <a@example.com> {time} +0000\ncommitter A <a@example.com> {time} +0000\n\n{message}\n");
+        text.into_bytes()
+    }
+
+    /// Entry header for a pack: type code and inflated size
+    fn entry_header(code: u8, size: usize) -> Vec<u8> {
+        let mut bytes = vec![(code << 4) | (size & 0x0f) as u8];
+        let mut rest = size >> 4;
+        while rest != 0 {
+            *bytes.last_mut().unwrap() |= 0x80;
+            bytes.push((rest & 0x7f) as u8);
+            rest >>= 7;
+        }
+        bytes
+    }
+
+    /// `OFS_DELTA` distance back to the base entry
+    fn ofs_distance(mut distance: usize) -> Vec<u8> {
+        let mut bytes = vec![(distance & 0x7f) as u8];
+        distance >>= 7;
+        while distance != 0 {
+            distance -= 1;
+            bytes.insert(0, 0x80 | (distance & 0x7f) as u8);
+            distance >>= 7;
+        }
+        bytes
+    }
+
+    fn delta_size(mut size: usize) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        loop {
+            let byte = (size & 0x7f) as u8;
+            size >>= 7;
+            if size == 0 {
+                bytes.push(byte);
+                return bytes;
+            }
+            bytes.push(byte | 0x80);
+        }
+    }
+
+    /// Write `pack-test.pack` and a version 2 `.idx` holding a full blob, an
+    /// `OFS_DELTA` blob built on it, a tree and a commit
+    fn write_pack(git: &Path, base: &[u8], parent: &[u8]) {
+        let mut target = base.to_vec();
+        target.extend_from_slice(b"tail");
+        let mut delta = delta_size(base.len());
+        delta.extend(delta_size(target.len()));
+        let len = base.len();
+        delta.extend([0x80 | 0x70, len as u8, (len >> 8) as u8, (len >> 16) as u8]);
+        delta.extend([4]);
+        delta.extend_from_slice(b"tail");
+
+        let tree_data = tree(&[
+            ("100644", "v1.bin", &oid(10)),
+            ("100644", "v2.bin", &oid(11)),
+        ]);
+        let assets = tree(&[("40000", "assets", &oid(12))]);
+        let commit_data = commit(&oid(14), Some(parent), 2000, "Update binary");
+
+        let mut pack = b"PACK\0\0\0\x02\0\0\0\x05".to_vec();
+        let mut index = Vec::new();
+        let mut push = |pack: &mut Vec<u8>, id: Vec<u8>, header: Vec<u8>, data: &[u8]| {
+            index.push((id, pack.len() as u64));
+            pack.extend(header);
+            pack.extend(zlib(data));
+        };
+        let base_offset = pack.len();
+        push(&mut pack, oid(10), entry_header(3, base.len()), base);
+        let mut header = entry_header(6, delta.len());
+        header.extend(ofs_distance(pack.len() - base_offset));
+        push(&mut pack, oid(11), header, &delta);
+        push(
+            &mut pack,
+            oid(12),
+            entry_header(2, tree_data.len()),
+            &tree_data,
+        );
+        push(&mut pack, oid(14), entry_header(2, assets.len()), &assets);
+        push(
+            &mut pack,
+            oid(13),
+            entry_header(1, commit_data.len()),
+            &commit_data,
+        );
+        pack.extend([0u8; 20]);
+
+        index.sort();
+        let mut idx = b"\xfftOc\0\0\0\x02".to_vec();
+        for first in 0..=255u8 {
+            let count = index.iter().filter(|(id, _)| id[0] <= first).count() as u32;
+            idx.extend(count.to_be_bytes());
+        }
+        for (id, _) in &index {
+            idx.extend(id);
+        }
+        idx.extend(vec![0u8; 4 * index.len()]);
+        for (_, offset) in &index {
+            idx.extend((*offset as u32).to_be_bytes());
+        }
+
+        let dir = git.join("objects/pack");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("pack-test.pack"), pack).unwrap();
+        fs::write(dir.join("pack-test.idx"), idx).unwrap();
+    }
+
+    #[test]
+    fn test_apply_delta() {
+        let base = b"hello, world";
+        let mut delta = vec![12, 9];
+        delta.extend([0x80 | 0x01 | 0x10, 7, 5]); // copy "world"
+        delta.extend([4]);
+        delta.extend_from_slice(b"wide");
+        assert_eq!(apply_delta(base, &delta).unwrap(), b"worldwide");
+
+        assert!(apply_delta(b"short", &delta).is_err());
+    }
+
+    #[test]
+    fn test_largest_blobs_in_packs_and_loose_objects() {
+        let dir = tempfile::tempdir().unwrap();
+        let git = dir.path().join(".git");
+        fs::create_dir_all(&git).unwrap();
+        let base: Vec<u8> = (0..(2 * 1024 * 1024))
+            .map(|i: u32| (i % 251) as u8)
+            .collect();
+
+        // First commit (loose) adds assets/v1.bin; the packed second commit adds v2.bin
+        write_loose(
+            &git,
+            &oid(1),
+            "tree",
+            &tree(&[("100644", "v1.bin", &oid(10))]),
+        );
+        write_loose(
+            &git,
+            &oid(2),
+            "tree",
+            &tree(&[("40000", "assets", &oid(1)), ("100644", "README", &oid(3))]),
+        );
+        write_loose(&git, &oid(3), "blob", b"readme");
+        write_loose(
+            &git,
+            &oid(4),
+            "commit",
+            &commit(&oid(2), None, 1000, "Add binary"),
+        );
+        write_pack(&git, &base, &oid(4));
+        // Unreachable: no tree references it
+        write_loose(&git, &oid(20), "blob", &vec![7u8; 1024 * 1024 + 1]);
+        fs::create_dir_all(git.join("refs/heads")).unwrap();
+        fs::write(
+            git.join("refs/heads/main"),
+            format!("{}\n", to_hex(&oid(13))),
+        )
+        .unwrap();
+        fs::write(git.join("HEAD"), "ref: refs/heads/main\n").unwrap();
+
+        let blobs = largest_blobs(
+            &git,
+            LARGE_BLOB_LIMIT,
+            &CancellationToken::new(),
+            &ReadThrottle::new(None),
+        )
+        .unwrap();
+
+        let summary: Vec<(Option<&str>, Option<&str>)> = blobs
+            .iter()
+            .map(|b| (b.path.as_deref(), b.commit_summary.as_deref()))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (Some("assets/v2.bin"), Some("Update binary")),
+                (Some("assets/v1.bin"), Some("Add binary")),
+                (None, None),
+            ]
+        );
+        assert_eq!(blobs[0].id, to_hex(&oid(11)));
+        assert!(blobs[0].size_mb > 2.0);
+        // The delta stores only the change
+        assert!(blobs[0].disk_mb < 0.01);
+        assert_eq!(blobs[1].commit.as_deref(), Some(to_hex(&oid(4)).as_str()));
+        assert_eq!(blobs[1].commit_time, Some(1000));
+
+        assert_eq!(
+            largest_blobs(&git, 1, &CancellationToken::new(), &ReadThrottle::new(None))
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+}
@@ -0,0 +1,442 @@
+//! Working tree status of Git repositories, and the guard that keeps cleanup
+//! from deleting work that exists nowhere else.
+//!
+//! A deletion loses work in two ways:
+//! - A path inside a work tree takes its uncommitted changes and untracked
+//!   (not ignored) files with it. Committed files survive in `.git`.
+//! - A path that is, or contains, a repository also takes its stashes and
+//!   the commits on local branches that no remote-tracking branch contains.
+//!   Deleting just a `.git` directory loses the stashes and commits.
+//!
+//! Paths inside a `.git` directory (reflogs, objects) are left to
+//! [`crate::utils::git_maintenance`]. Without a `git` executable nothing
+//! can be checked and nothing is reported.
+
+use crate::models::{GitRepoStatus, UnsavedWork};
+use crate::utils::patterns::{classify_bloat_dir, match_cache_pattern};
+use crate::utils::process::find_program;
+use std::collections::HashMap;
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use walkdir::WalkDir;
+
+/// Branch, upstream, change counts, unpushed commits and stashes of a work tree.
+///
+/// Fields git cannot report (no commits, no upstream, git missing) are left
+/// at "unknown" or 0.
+#[must_use]
+pub fn repo_status(work_dir: &Path) -> GitRepoStatus {
+    let branch = git(work_dir, &["rev-parse", "--abbrev-ref", "HEAD"])
+        .map_or_else(|| "unknown".to_string(), |out| out.trim().to_string());
+    let has_upstream = git(
+        work_dir,
+        &["rev-parse", "--abbrev-ref", "--symbolic-full-name", "@{u}"],
+    )
+    .is_some();
+
+    let (ahead, behind) = if has_upstream {
+        git(
+            work_dir,
+            &["rev-list", "--left-right", "--count", "HEAD...@{upstream}"],
+        )
+        .map_or((0, 0), |out| {
+            let mut counts = out
+                .split_whitespace()
+                .map(|count| count.parse().unwrap_or(0));
+            (counts.next().unwrap_or(0), counts.next().unwrap_or(0))
+        })
+    } else {
+        (0, 0)
+    };
+
+    let changes = changed_paths(work_dir);
+    let untracked = changes.iter().filter(|(_, untracked)| *untracked).count();
+
+    GitRepoStatus {
+        branch,
+        ahead,
+        behind,
+        uncommitted: to_u32(changes.len() - untracked),
+        untracked: to_u32(untracked),
+        last_commit_ts: parse(git(work_dir, &["log", "-1", "--format=%ct"])),
+        has_upstream,
+        unpushed: parse(git(
+            work_dir,
+            &["rev-list", "--count", "--branches", "--not", "--remotes"],
+        )),
+        stashes: git(work_dir, &["stash", "list"]).map_or(0, |out| to_u32(out.lines().count())),
+    }
+}
+
+/// Work that deleting `paths` would destroy, per path and repository.
+///
+/// **Returns:** One entry for each requested path inside a work tree with
+/// changes under it, and for each repository at or below a requested path
+/// with changes, unpushed commits or stashes; empty if git is not installed
+#[must_use]
+pub fn find_unsaved_work(paths: &[String]) -> Vec<UnsavedWork> {
+    if find_program("git").is_none() {
+        log::warn!("git is not installed; not checking deletions for unsaved work");
+        return Vec::new();
+    }
+
+    let mut changes: HashMap<PathBuf, Vec<(PathBuf, bool)>> = HashMap::new();
+    let mut unsaved = Vec::new();
+    for requested in paths {
+        let path = Path::new(requested);
+
+        if let Some(work_tree) = enclosing_work_tree(path) {
+            let entries = changes
+                .entry(work_tree.clone())
+                .or_insert_with(|| changed_paths(&work_tree));
+            // Changes under `path`, or an untracked directory `path` is inside
+            let lost: Vec<bool> = entries
+                .iter()
+                .filter(|(changed, _)| changed.starts_with(path) || path.starts_with(changed))
+                .map(|(_, untracked)| *untracked)
+                .collect();
+            if !lost.is_empty() {
+                let untracked = lost.iter().filter(|untracked| **untracked).count();
+                unsaved.push(UnsavedWork {
+                    path: requested.clone(),
+                    repo_path: work_tree.to_string_lossy().to_string(),
+                    uncommitted: to_u32(lost.len() - untracked),
+                    untracked: to_u32(untracked),
+                    unpushed: 0,
+                    stashes: 0,
+                });
+            }
+        }
+
+        let deletes_git_dir_only = path.file_name().is_some_and(|name| name == ".git");
+        for work_tree in contained_work_trees(path) {
+            let status = repo_status(&work_tree);
+            let (uncommitted, untracked) = if deletes_git_dir_only {
+                (0, 0)
+            } else {
+                (status.uncommitted, status.untracked)
+            };
+            if uncommitted + untracked + status.unpushed + status.stashes > 0 {
+                unsaved.push(UnsavedWork {
+                    path: requested.clone(),
+                    repo_path: work_tree.to_string_lossy().to_string(),
+                    uncommitted,
+                    untracked,
+                    unpushed: status.unpushed,
+                    stashes: status.stashes,
+                });
+            }
+        }
+    }
+    unsaved
+}
+
+/// One line per affected repository, for error messages
+#[must_use]
+pub fn describe_unsaved_work(unsaved: &[UnsavedWork]) -> String {
+    unsaved
+        .iter()
+        .map(|work| {
+            let counts: Vec<String> = [
+                (work.uncommitted, "uncommitted changes"),
+                (work.untracked, "untracked files"),
+                (work.unpushed, "unpushed commits"),
+                (work.stashes, "stashes"),
+            ]
+            .into_iter()
+            .filter(|(count, _)| *count > 0)
+            .map(|(count, what)| format!("{count} {what}"))
+            .collect();
+            format!(
+                "{} would lose {} in {}",
+                work.path,
+                counts.join(", "),
+                work.repo_path
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// The nearest work tree `path` is inside, unless it is inside a `.git` directory
+fn enclosing_work_tree(path: &Path) -> Option<PathBuf> {
+    for ancestor in path.ancestors().skip(1) {
+        if ancestor.file_name().is_some_and(|name| name == ".git") {
+            return None;
+        }
+        if ancestor.join(".git").exists() {
+            return Some(ancestor.to_path_buf());
+        }
+    }
+    None
+}
+
+/// Work trees deleted with `path`: `path` itself and repositories below it
+///
+/// This walks every directory below `path` on each guarded deletion, before
+/// the deletion walks them again. Build artifact and cache directories
+/// (`node_modules`, `target`, `~/.cargo/registry`, ...) are not entered:
+/// they hold the bulk of the files, and repositories inside them are
+/// checkouts their tools recreate. Such a directory is still reported when
+/// it is a repository itself.
+fn contained_work_trees(path: &Path) -> Vec<PathBuf> {
+    if path.file_name().is_some_and(|name| name == ".git") {
+        return path.parent().map(Path::to_path_buf).into_iter().collect();
+    }
+
+    let mut work_trees = Vec::new();
+    if path.join(".git").exists() {
+        work_trees.push(path.to_path_buf());
+    }
+    let mut walker = WalkDir::new(path)
+        .into_iter()
+        .filter_entry(|entry| !is_regenerable_dir(entry));
+    while let Some(entry) = walker.next() {
+        let Ok(entry) = entry else {
+            continue;
+        };
+        if entry.file_name() == ".git" {
+            if entry.depth() > 1 {
+                work_trees.extend(entry.path().parent().map(Path::to_path_buf));
+            }
+            if entry.file_type().is_dir() {
+                walker.skip_current_dir();
+            }
+        }
+    }
+    work_trees
+}
+
+/// Whether `entry` is a build artifact or cache directory that is not a repository
+fn is_regenerable_dir(entry: &walkdir::DirEntry) -> bool {
+    let path = entry.path();
+    entry.file_type().is_dir()
+        && entry.file_name() != ".git"
+        && (classify_bloat_dir(path).is_some() || match_cache_pattern(path).is_some())
+        && !path.join(".git").exists()
+}
+
+/// Changed paths (absolute) of a work tree and whether each is untracked,
+/// from `git status --porcelain`; untracked directories are listed once
+fn changed_paths(work_dir: &Path) -> Vec<(PathBuf, bool)> {
+    let Some(output) = git(work_dir, &["status", "--porcelain", "-z"]) else {
+        return Vec::new();
+    };
+    let mut entries = Vec::new();
+    let mut fields = output.split('\0');
+    while let Some(field) = fields.next() {
+        let (Some(code), Some(path)) = (field.get(..3), field.get(3..)) else {
+            continue;
+        };
+        // Renames and copies are followed by their original path
+        if code.starts_with(['R', 'C']) {
+            fields.next();
+        }
+        entries.push((
+            work_dir.join(path.trim_end_matches('/')),
+            code.starts_with("??"),
+        ));
+    }
+    entries
+}
+
+/// Standard output of a successful git command run in `work_dir`
+pub fn git(work_dir: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(work_dir)
+        .args(args)
+        .env("LC_ALL", "C")
+        .stdin(Stdio::null())
+        .output();
+    match output {
+        Ok(out) if out.status.success() => Some(String::from_utf8_lossy(&out.stdout).into_owned()),
+        Ok(out) => {
+            log::debug!(
+                "git {} failed in {}: {}",
+                args.join(" "),
+                work_dir.display(),
+                String::from_utf8_lossy(&out.stderr).trim()
+            );
+            None
+        }
+        Err(e) => {
+            log::warn!("git not available: {e}");
+            None
+        }
+    }
+}
+
+/// Standard output of a git command run in `work_dir` with `input` on standard input
+pub fn git_with_input(work_dir: &Path, args: &[&str], input: String) -> Result<Vec<u8>, String> {
+    let mut child = Command::new("git")
+        .arg("-C")
+        .arg(work_dir)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("Failed to run git: {e}"))?;
+
+    // Write from another thread so a full stdout pipe cannot deadlock
+    let mut stdin = child
+        .stdin
+        .take()
+        .ok_or_else(|| "git stdin unavailable".to_string())?;
+    let writer = std::thread::spawn(move || stdin.write_all(input.as_bytes()));
+    let output = child
+        .wait_with_output()
+        .map_err(|e| format!("Failed to run git: {e}"))?;
+    writer
+        .join()
+        .map_err(|_| "git stdin writer panicked".to_string())?
+        .map_err(|e| format!("Failed to write to git: {e}"))?;
+
+    if output.status.success() {
+        Ok(output.stdout)
+    } else {
+        Err(format!("git {} failed", args.join(" ")))
+    }
+}
+
+/// A number printed by git, 0 if the command failed
+fn parse<T: std::str::FromStr + Default>(output: Option<String>) -> T {
+    output
+        .and_then(|out| out.trim().parse().ok())
+        .unwrap_or_default()
+}
+
+fn to_u32(count: usize) -> u32 {
+    u32::try_from(count).unwrap_or(u32::MAX)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use crate::utils::git_test_support::run_git;
+    use std::fs;
+
+    /// A committed repository with an ignored `node_modules/`
+    fn repo(base: &Path) -> PathBuf {
+        let repo = base.join("app");
+        fs::create_dir_all(repo.join("src")).unwrap();
+        fs::create_dir_all(repo.join("node_modules/pkg")).unwrap();
+        fs::write(repo.join(".gitignore"), "node_modules/\n").unwrap();
+        fs::write(repo.join("src/main.js"), "console.log(1)").unwrap();
+        fs::write(repo.join("node_modules/pkg/index.js"), "module.exports = 1").unwrap();
+        run_git(&repo, &["init", "-q"]);
+        run_git(&repo, &["add", "."]);
+        run_git(&repo, &["commit", "-q", "-m", "initial"]);
+        repo
+    }
+
+    fn paths(paths: &[&Path]) -> Vec<String> {
+        paths
+            .iter()
+            .map(|path| path.to_string_lossy().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_changes_inside_work_tree() {
+        if find_program("git").is_none() {
+            return;
+        }
+        let dir = tempfile::tempdir().unwrap();
+        let repo = repo(dir.path());
+
+        // Ignored and clean committed directories hold nothing unsaved
+        assert!(
+            find_unsaved_work(&paths(&[&repo.join("node_modules"), &repo.join("src")])).is_empty()
+        );
+
+        fs::write(repo.join("src/main.js"), "console.log(2)").unwrap();
+        fs::create_dir_all(repo.join("notes")).unwrap();
+        fs::write(repo.join("notes/todo.txt"), "todo").unwrap();
+        let unsaved = find_unsaved_work(&paths(&[
+            &repo.join("src"),
+            &repo.join("notes/todo.txt"),
+            &repo.join("node_modules"),
+        ]));
+        assert_eq!(unsaved.len(), 2);
+        assert_eq!((unsaved[0].uncommitted, unsaved[0].untracked), (1, 0));
+        assert_eq!((unsaved[1].uncommitted, unsaved[1].untracked), (0, 1));
+    }
+
+    #[test]
+    fn test_deleting_repository_with_unpushed_commits_and_stashes() {
+        if find_program("git").is_none() {
+            return;
+        }
+        let dir = tempfile::tempdir().unwrap();
+        let repo = repo(dir.path());
+        fs::write(repo.join("src/main.js"), "console.log(3)").unwrap();
+        run_git(&repo, &["stash", "-q"]);
+
+        // No remote: every commit exists only here
+        let unsaved = find_unsaved_work(&paths(&[dir.path()]));
+        assert_eq!(unsaved.len(), 1);
+        assert_eq!(unsaved[0].repo_path, repo.to_string_lossy());
+        assert_eq!((unsaved[0].unpushed, unsaved[0].stashes), (1, 1));
+        assert!(describe_unsaved_work(&unsaved).contains("1 unpushed commits, 1 stashes"));
+
+        let status = repo_status(&repo);
+        assert_eq!(
+            (status.unpushed, status.stashes, status.uncommitted),
+            (1, 1, 0)
+        );
+    }
+
+    #[test]
+    fn test_artifact_directories_are_not_searched() {
+        if find_program("git").is_none() {
+            return;
+        }
+        let dir = tempfile::tempdir().unwrap();
+        let repo = repo(dir.path());
+        // A checkout inside `node_modules` is recreated by npm
+        let dep = repo.join("node_modules/dep");
+        fs::create_dir_all(&dep).unwrap();
+        fs::write(dep.join("index.js"), "dep").unwrap();
+        run_git(&dep, &["init", "-q"]);
+        run_git(&dep, &["add", "."]);
+        run_git(&dep, &["commit", "-q", "-m", "dep"]);
+
+        let unsaved = find_unsaved_work(&paths(&[dir.path()]));
+        assert_eq!(unsaved.len(), 1);
+        assert_eq!(unsaved[0].repo_path, repo.to_string_lossy());
+
+        assert!(contained_work_trees(&repo.join("node_modules")).is_empty());
+        // Deleting the checkout itself still checks it
+        assert_eq!(contained_work_trees(&dep), [dep]);
+        assert_eq!(contained_work_trees(&repo), [repo]);
+    }
+
+    #[test]
+    fn test_cleanup_requires_override() {
+        use crate::models::CleanupReq;
+        use crate::utils::cleanup::validate_deletion_request;
+
+        if find_program("git").is_none() {
+            return;
+        }
+        let dir = tempfile::tempdir().unwrap();
+        let repo = repo(dir.path());
+        fs::write(repo.join("src/new.js"), "new").unwrap();
+
+        let mut req = CleanupReq {
+            paths: paths(&[&repo.join("src")]),
+            dry_run: true,
+            trash: true,
+            allow_unsaved_work: false,
+        };
+        let err = validate_deletion_request(&req).unwrap_err().to_string();
+        assert!(err.contains("1 untracked files"), "{err}");
+
+        req.allow_unsaved_work = true;
+        assert!(validate_deletion_request(&req).is_ok());
+    }
+}
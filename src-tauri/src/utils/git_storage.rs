@@ -0,0 +1,363 @@
+//! Repository storage outside the object store: Git LFS objects and linked
+//! worktrees.
+//!
+//! LFS keeps the content of every version of a tracked large file under
+//! `.git/lfs/objects/<aa>/<bb>/<oid>`. An object is referenced when a pointer
+//! file in the tree of a checked-out HEAD (the main work tree or a linked
+//! worktree) names it; the rest can be removed by `git lfs prune`, which also
+//! keeps objects of recent and unpushed commits, so it may free less.
+//!
+//! Linked worktrees (`git worktree add`) live outside `.git`, with their
+//! administrative files in `.git/worktrees/<name>`. A worktree whose checkout
+//! was deleted by hand is only metadata, which `git worktree prune` removes.
+//!
+//! Submodule stores (`.git/modules`) are measured during the walk itself.
+
+use crate::models::GitEntry;
+use crate::utils::disk_usage::{path_usage, paths_usage, DiskUsage};
+use crate::utils::git_status::{git, git_with_input};
+use crate::utils::process::find_program;
+use std::collections::HashSet;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// LFS pointer files are smaller than this (per the LFS specification)
+const LFS_POINTER_MAX_BYTES: u64 = 1024;
+
+/// A worktree registered in `.git/worktrees`
+#[derive(Debug, Clone)]
+pub struct LinkedWorktree {
+    /// Administrative directory (`.git/worktrees/<name>`)
+    pub admin_dir: PathBuf,
+    /// Checkout directory, `None` if it no longer exists
+    pub checkout: Option<PathBuf>,
+    /// Checked-out branch, or the detached commit
+    pub head: String,
+}
+
+/// LFS and linked worktree entries of a repository, with their disk usage
+///
+/// **Returns:** Entries of type "`lfs_objects`", "`lfs_prunable`" and
+/// "worktree"; all LFS objects count as referenced if git is not installed.
+/// The two LFS entries share the store directory, so their paths end in
+/// `#referenced` and `#prunable`
+#[must_use]
+pub fn storage_entries(git_dir: &Path) -> Vec<(GitEntry, DiskUsage)> {
+    let worktrees = linked_worktrees(git_dir);
+    let mut entries = lfs_entries(git_dir, &worktrees);
+    entries.extend(worktrees.iter().map(worktree_entry));
+    entries
+}
+
+/// Worktrees registered in `git_dir`, in name order
+#[must_use]
+pub fn linked_worktrees(git_dir: &Path) -> Vec<LinkedWorktree> {
+    let Ok(dirs) = std::fs::read_dir(git_dir.join("worktrees")) else {
+        return Vec::new();
+    };
+    let mut worktrees: Vec<LinkedWorktree> = dirs
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_ok_and(|t| t.is_dir()))
+        .map(|entry| {
+            let admin_dir = entry.path();
+            // `gitdir` holds the path of the checkout's `.git` file
+            let checkout = std::fs::read_to_string(admin_dir.join("gitdir"))
+                .ok()
+                .and_then(|gitdir| Path::new(gitdir.trim()).parent().map(Path::to_path_buf))
+                .filter(|checkout| checkout.is_dir());
+            let head = std::fs::read_to_string(admin_dir.join("HEAD"))
+                .map(|head| {
+                    let head = head.trim();
+                    head.strip_prefix("ref: refs/heads/")
+                        .unwrap_or(head)
+                        .to_string()
+                })
+                .unwrap_or_default();
+            LinkedWorktree {
+                admin_dir,
+                checkout,
+                head,
+            }
+        })
+        .collect();
+    worktrees.sort_by(|a, b| a.admin_dir.cmp(&b.admin_dir));
+    worktrees
+}
+
+fn worktree_entry(worktree: &LinkedWorktree) -> (GitEntry, DiskUsage) {
+    let name = worktree
+        .admin_dir
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let (path, usage, description, safety, actionable) = match &worktree.checkout {
+        Some(checkout) => (
+            checkout,
+            paths_usage([checkout.as_path(), worktree.admin_dir.as_path()]),
+            format!("Linked worktree {name} on {}", worktree.head),
+            "caution",
+            false,
+        ),
+        None => (
+            &worktree.admin_dir,
+            path_usage(&worktree.admin_dir),
+            format!(
+                "Linked worktree {name} whose checkout is gone - `git worktree prune` removes it"
+            ),
+            "safe",
+            true,
+        ),
+    };
+    (
+        GitEntry {
+            path: path.to_string_lossy().to_string(),
+            size_mb: usage.apparent_mb(),
+            allocated_mb: usage.allocated_mb(),
+            entry_type: "worktree".to_string(),
+            description,
+            safety: safety.to_string(),
+            actionable,
+        },
+        usage,
+    )
+}
+
+/// Split the LFS store into objects checked-out HEADs reference and the rest
+fn lfs_entries(git_dir: &Path, worktrees: &[LinkedWorktree]) -> Vec<(GitEntry, DiskUsage)> {
+    let store = git_dir.join("lfs").join("objects");
+    let objects: Vec<(String, PathBuf)> = WalkDir::new(&store)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_file())
+        .map(|entry| {
+            (
+                entry.file_name().to_string_lossy().into_owned(),
+                entry.into_path(),
+            )
+        })
+        .collect();
+    if objects.is_empty() {
+        return Vec::new();
+    }
+
+    let mut heads: Vec<&Path> = worktrees
+        .iter()
+        .filter_map(|worktree| worktree.checkout.as_deref())
+        .collect();
+    if git_dir.file_name().is_some_and(|name| name == ".git") {
+        heads.extend(git_dir.parent());
+    }
+    let referenced = referenced_lfs_oids(git_dir, &heads);
+
+    let (kept, prunable): (Vec<_>, Vec<_>) = objects
+        .iter()
+        .partition(|(oid, _)| referenced.as_ref().map_or(true, |oids| oids.contains(oid)));
+
+    let store_path = store.to_string_lossy();
+    let mut entries = Vec::new();
+    if !kept.is_empty() {
+        let usage = paths_usage(kept.iter().map(|(_, path)| path.as_path()));
+        let description = if referenced.is_some() {
+            format!(
+                "Git LFS objects: {} referenced by checked-out HEADs",
+                kept.len()
+            )
+        } else {
+            format!("Git LFS objects: {} files", kept.len())
+        };
+        entries.push((
+            GitEntry {
+                path: format!("{store_path}#referenced"),
+                size_mb: usage.apparent_mb(),
+                allocated_mb: usage.allocated_mb(),
+                entry_type: "lfs_objects".to_string(),
+                description,
+                safety: "caution".to_string(),
+                actionable: false,
+            },
+            usage,
+        ));
+    }
+    if !prunable.is_empty() {
+        let usage = paths_usage(prunable.iter().map(|(_, path)| path.as_path()));
+        entries.push((
+            GitEntry {
+                path: format!("{store_path}#prunable"),
+                size_mb: usage.apparent_mb(),
+                allocated_mb: usage.allocated_mb(),
+                entry_type: "lfs_prunable".to_string(),
+                description: format!(
+                    "Git LFS objects: {} not referenced by any checked-out HEAD - `git lfs prune` removes them",
+                    prunable.len()
+                ),
+                safety: "safe".to_string(),
+                actionable: false,
+            },
+            usage,
+        ));
+    }
+    entries
+}
+
+/// LFS object ids named by pointer files in the trees of `heads`
+///
+/// **Returns:** `None` if git is not installed or a tree could not be read,
+/// so nothing is reported as prunable
+fn referenced_lfs_oids(git_dir: &Path, heads: &[&Path]) -> Option<HashSet<String>> {
+    find_program("git")?;
+
+    // Blobs small enough to be pointers; ls-tree -l prints "<mode> blob <id> <size>\t<path>"
+    let mut candidates = HashSet::new();
+    for head in heads {
+        let tree = git(head, &["ls-tree", "-r", "-l", "-z", "HEAD"])?;
+        for record in tree.split('\0') {
+            let Some((meta, _path)) = record.split_once('\t') else {
+                continue;
+            };
+            let mut fields = meta.split_whitespace();
+            let (Some(_mode), Some("blob"), Some(id), Some(size)) =
+                (fields.next(), fields.next(), fields.next(), fields.next())
+            else {
+                continue;
+            };
+            if size
+                .parse::<u64>()
+                .is_ok_and(|size| size < LFS_POINTER_MAX_BYTES)
+            {
+                candidates.insert(id.to_string());
+            }
+        }
+    }
+    if candidates.is_empty() {
+        return Some(HashSet::new());
+    }
+
+    let ids = candidates.iter().fold(String::new(), |mut ids, id| {
+        let _ = writeln!(ids, "{id}");
+        ids
+    });
+    let contents = git_with_input(git_dir, &["cat-file", "--batch"], ids).ok()?;
+    Some(pointer_oids(&contents))
+}
+
+/// Parse `git cat-file --batch` output ("<id> <type> <size>\n<content>\n"
+/// per object) and collect the `oid sha256:<hex>` lines of LFS pointers
+fn pointer_oids(batch: &[u8]) -> HashSet<String> {
+    let mut oids = HashSet::new();
+    let mut rest = batch;
+    while let Some(end) = rest.iter().position(|&b| b == b'\n') {
+        let header = String::from_utf8_lossy(&rest[..end]);
+        rest = &rest[end + 1..];
+        let Some(size) = header
+            .split(' ')
+            .nth(2)
+            .and_then(|size| size.parse::<usize>().ok())
+        else {
+            // "<id> missing"
+            continue;
+        };
+        let content = &rest[..size.min(rest.len())];
+        rest = rest.get(size + 1..).unwrap_or_default();
+
+        let content = String::from_utf8_lossy(content);
+        if content.starts_with("version https://git-lfs.github.com/spec/") {
+            oids.extend(
+                content
+                    .lines()
+                    .filter_map(|line| line.strip_prefix("oid sha256:"))
+                    .map(|oid| oid.trim().to_string()),
+            );
+        }
+    }
+    oids
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use crate::utils::git_test_support::run_git;
+    use std::fs;
+
+    fn pointer(oid: &str) -> String {
+        format!("version https://git-lfs.github.com/spec/v1\noid sha256:{oid}\nsize 4096\n")
+    }
+
+    fn store_object(git_dir: &Path, oid: &str) {
+        let dir = git_dir.join("lfs/objects").join(&oid[..2]).join(&oid[2..4]);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join(oid), vec![0u8; 4096]).unwrap();
+    }
+
+    #[test]
+    fn test_pointer_oids() {
+        let oid = "a".repeat(64);
+        let pointer = pointer(&oid);
+        let batch = format!(
+            "1111 blob {}\n{pointer}\n2222 missing\n3333 blob 5\nhello\n",
+            pointer.len()
+        );
+        assert_eq!(pointer_oids(batch.as_bytes()), HashSet::from([oid]));
+    }
+
+    #[test]
+    fn test_lfs_and_worktree_entries() {
+        if find_program("git").is_none() {
+            return;
+        }
+        let dir = tempfile::tempdir().unwrap();
+        let repo = dir.path().join("repo");
+        fs::create_dir_all(&repo).unwrap();
+        run_git(&repo, &["init", "-q", "-b", "main"]);
+
+        let (old, current, feature) = ("1".repeat(64), "2".repeat(64), "3".repeat(64));
+        fs::write(repo.join("model.bin"), pointer(&old)).unwrap();
+        run_git(&repo, &["add", "."]);
+        run_git(&repo, &["commit", "-q", "-m", "old model"]);
+        fs::write(repo.join("model.bin"), pointer(&current)).unwrap();
+        run_git(&repo, &["commit", "-q", "-am", "new model"]);
+
+        // A worktree on another branch references its own version
+        run_git(&repo, &["branch", "feature"]);
+        run_git(&repo, &["worktree", "add", "-q", "../feature", "feature"]);
+        let feature_dir = dir.path().join("feature");
+        fs::write(feature_dir.join("model.bin"), pointer(&feature)).unwrap();
+        run_git(&feature_dir, &["commit", "-q", "-am", "feature model"]);
+
+        let git_dir = repo.join(".git");
+        for oid in [&old, &current, &feature] {
+            store_object(&git_dir, oid);
+        }
+
+        let entries = storage_entries(&git_dir);
+        let entry = |entry_type: &str| {
+            entries
+                .iter()
+                .find(|(entry, _)| entry.entry_type == entry_type)
+                .unwrap()
+        };
+        let (referenced, usage) = entry("lfs_objects");
+        assert_eq!(usage.apparent_bytes, 2 * 4096);
+        assert_eq!(referenced.safety, "caution");
+        let (prunable, usage) = entry("lfs_prunable");
+        assert_eq!(usage.apparent_bytes, 4096);
+        assert_eq!(prunable.safety, "safe");
+        assert_ne!(referenced.path, prunable.path);
+
+        let (worktree, usage) = entry("worktree");
+        assert_eq!(worktree.path, feature_dir.to_string_lossy());
+        assert!(worktree.description.ends_with("on feature"));
+        assert!(usage.apparent_bytes > 0);
+
+        // A checkout deleted by hand leaves prunable metadata
+        fs::remove_dir_all(&feature_dir).unwrap();
+        let entries = storage_entries(&git_dir);
+        let (worktree, _) = entries
+            .iter()
+            .find(|(entry, _)| entry.entry_type == "worktree")
+            .unwrap();
+        assert!(worktree.actionable);
+        assert!(worktree.path.contains("worktrees"));
+    }
+}
@@ -0,0 +1,22 @@
+//! Git fixtures shared by the tests of the Git modules.
+
+use std::path::Path;
+use std::process::Command;
+
+/// Run git in `dir` with a fixed committer identity, failing the test if it fails
+///
+/// **Returns:** Standard output with surrounding whitespace trimmed
+pub fn run_git(dir: &Path, args: &[&str]) -> String {
+    let output = Command::new("git")
+        .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "git {args:?} failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8_lossy(&output.stdout).trim().to_string()
+}
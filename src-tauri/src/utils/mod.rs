@@ -1,9 +1,43 @@
 //! Utility modules for the application
 
+/// Hardlink-aware apparent, allocated and reclaimable size accounting.
+pub mod disk_usage;
 /// File and directory cleanup utilities with safety-first deletion operations.
 pub mod cleanup;
+/// Program lookup on `PATH` and external commands run with a timeout.
+pub mod process;
+/// Cache cleanup through the owning tool's own command, with deletion as fallback.
+pub mod cache_cleanup;
+/// Version-aware pruning proposals for package manager caches.
+pub mod cache_versions;
+/// Per-profile breakdown and selective pruning of Cargo `target/` directories.
+pub mod cargo_target;
+/// Pack, index and loose object reader that finds the largest blobs in Git history.
+pub mod git_objects;
+/// Git maintenance actions (`gc`, `prune`, `repack`, ...) with dry-run estimates.
+pub mod git_maintenance;
+/// Detection of multiple clones of the same repository.
+pub mod git_clones;
+/// Committed build artifacts and missing `.gitignore` patterns.
+pub mod git_artifacts;
+/// Git LFS object and linked worktree storage of a repository.
+pub mod git_storage;
+/// Work tree status and the unsaved-work guard for deletions.
+pub mod git_status;
+/// Git fixtures shared by the tests of the Git modules.
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+pub(crate) mod git_test_support;
 /// Deletion history logging for audit trails and recovery.
 pub mod deletion_log;
+/// User-defined bloat, junk and cache rules loaded from TOML rule files.
+pub mod detector_rules;
+/// Identical and near-identical directory tree detection.
+pub mod duplicate_dirs;
+/// Staged duplicate detection (size, head/tail hash, full hash).
+pub mod duplicates;
+/// One-filesystem mode: stop walks at mount boundaries.
+pub mod mount_boundary;
 /// Network drive detection utilities (BEAD-011).
 pub mod network;
 /// Path validation utilities for safe directory scanning.
@@ -12,10 +46,20 @@ pub mod path;
 pub mod patterns;
 /// Port and network utilities for Tauri application.
 pub mod port;
+/// Last activity of projects owning build artifacts, for stale-project cleanup.
+pub mod project_activity;
+/// Thread, read-rate and priority limits for scans.
+pub mod resource_limits;
 /// Core scanning algorithms for disk analysis.
 pub mod scan;
+/// Hierarchical size tree with cumulative sizes for drill-down views.
+pub mod size_tree;
 /// Enhanced scanning utilities with BEAD features.
 pub mod scan_enhanced;
+/// Single-pass traversal engine shared by all scan types.
+pub mod unified_scan;
+/// Background scan jobs with progress, cancellation and stored results.
+pub mod scan_jobs;
 /// Scan progress tracking with cancellation support (BEAD-013, BEAD-014).
 pub mod scan_progress;
 /// Symlink loop detection utilities (BEAD-009).
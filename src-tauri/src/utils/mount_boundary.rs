@@ -0,0 +1,159 @@
+//! One-filesystem scanning.
+//!
+//! A [`MountBoundary`] remembers the device of the scan root and reports
+//! directories that live on a different device (bind mounts, FUSE mounts,
+//! network shares, other disks) so walkers can skip them, like `du -x`.
+//! Every skipped mount point is recorded with its filesystem type.
+
+use crate::models::SkippedMount;
+use crate::utils::network::get_mount_info;
+use std::fs::Metadata;
+use std::path::Path;
+use walkdir::DirEntry;
+
+/// Tracks the root's device and the mount points a walk refused to enter
+#[derive(Debug, Default)]
+pub struct MountBoundary {
+    /// Device of the scan root, or `None` when crossing mounts is allowed
+    root_dev: Option<u64>,
+    skipped: Vec<SkippedMount>,
+}
+
+impl MountBoundary {
+    /// Create a boundary at `root`'s device; a disabled boundary never skips anything
+    #[must_use]
+    pub fn new(root: &Path, one_file_system: bool) -> Self {
+        let root_dev = if one_file_system {
+            std::fs::metadata(root).ok().and_then(|m| device_of(&m))
+        } else {
+            None
+        };
+
+        Self {
+            root_dev,
+            skipped: Vec::new(),
+        }
+    }
+
+    /// Whether mount points are being skipped
+    #[must_use]
+    pub const fn is_enabled(&self) -> bool {
+        self.root_dev.is_some()
+    }
+
+    /// Whether `dir` is on another device and must not be entered. Skipped
+    /// directories are recorded together with their filesystem type.
+    pub fn crosses(&mut self, dir: &Path, metadata: &Metadata) -> bool {
+        let Some(root_dev) = self.root_dev else {
+            return false;
+        };
+        if device_of(metadata).map_or(true, |dev| dev == root_dev) {
+            return false;
+        }
+
+        let skipped = describe_mount(dir);
+        log::info!(
+            "Not crossing into {} ({} filesystem)",
+            skipped.path,
+            skipped.fs_type
+        );
+        self.skipped.push(skipped);
+        true
+    }
+
+    /// Walkdir filter: `false` for a directory below the root that is on another device
+    pub fn admits(&mut self, entry: &DirEntry) -> bool {
+        // Only directories below the root can be mount points
+        if !self.is_enabled() || entry.depth() == 0 || !entry.file_type().is_dir() {
+            return true;
+        }
+        entry
+            .metadata()
+            .map_or(true, |metadata| !self.crosses(entry.path(), &metadata))
+    }
+
+    /// Mount points skipped so far
+    #[must_use]
+    pub fn skipped(&self) -> &[SkippedMount] {
+        &self.skipped
+    }
+
+    /// Consume the boundary, returning the skipped mount points
+    #[must_use]
+    pub fn into_skipped(self) -> Vec<SkippedMount> {
+        self.skipped
+    }
+}
+
+#[cfg(unix)]
+#[allow(clippy::unnecessary_wraps)]
+fn device_of(metadata: &Metadata) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+    Some(metadata.dev())
+}
+
+/// Devices are not exposed on this platform, so no boundary can be detected
+#[cfg(not(unix))]
+const fn device_of(_metadata: &Metadata) -> Option<u64> {
+    None
+}
+
+/// Describe a mount point, falling back to "unknown" when mount info is unavailable
+fn describe_mount(dir: &Path) -> SkippedMount {
+    let path = dir.to_string_lossy().to_string();
+    match get_mount_info(dir) {
+        Ok(info) => SkippedMount {
+            path,
+            fs_type: info.fs_type,
+            source: info.filesystem,
+            is_network: info.is_network,
+        },
+        Err(err) => {
+            log::debug!("Cannot read mount info for {}: {err}", dir.display());
+            SkippedMount {
+                path,
+                fs_type: "unknown".to_string(),
+                source: String::new(),
+                is_network: false,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_same_device_is_not_crossed() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let child = temp_dir.path().join("child");
+        std::fs::create_dir(&child).unwrap();
+        let metadata = std::fs::metadata(&child).unwrap();
+
+        let mut boundary = MountBoundary::new(temp_dir.path(), true);
+        assert!(!boundary.crosses(&child, &metadata));
+        assert!(boundary.skipped().is_empty());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_other_device_is_skipped_and_described() {
+        use std::os::unix::fs::MetadataExt;
+
+        // /proc is its own filesystem on Linux; skip if a sandbox hides that
+        let proc_metadata = std::fs::metadata("/proc").unwrap();
+        let mut boundary = MountBoundary::new(Path::new("/"), true);
+        if std::fs::metadata("/").unwrap().dev() == proc_metadata.dev() {
+            return;
+        }
+
+        assert!(boundary.crosses(Path::new("/proc"), &proc_metadata));
+        assert_eq!(boundary.skipped()[0].fs_type, "proc");
+
+        let mut disabled = MountBoundary::new(Path::new("/"), false);
+        assert!(!disabled.crosses(Path::new("/proc"), &proc_metadata));
+    }
+}
@@ -157,7 +157,12 @@ pub fn get_mount_info(path: &Path) -> Result<MountInfo, std::io::Error> {
         get_mount_info_macos(path)
     }
     
-    #[cfg(not(target_os = "macos"))]
+    #[cfg(target_os = "linux")]
+    {
+        get_mount_info_linux(path)
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "linux")))]
     {
         Err(std::io::Error::new(
             std::io::ErrorKind::Unsupported,
@@ -214,6 +219,64 @@ fn get_mount_info_macos(path: &Path) -> Result<MountInfo, std::io::Error> {
     })
 }
 
+#[cfg(target_os = "linux")]
+fn get_mount_info_linux(path: &Path) -> Result<MountInfo, std::io::Error> {
+    let mounts = std::fs::read_to_string("/proc/mounts")?;
+    let canonical_path = path.canonicalize()?;
+
+    // The innermost mount containing the path is the one with the longest mount point
+    let (filesystem, mount_point, fs_type) = mounts
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            let filesystem = unescape_mount_field(parts.next()?);
+            let mount_point = unescape_mount_field(parts.next()?);
+            let fs_type = parts.next()?.to_string();
+            Some((filesystem, mount_point, fs_type))
+        })
+        .filter(|(_, mount_point, _)| canonical_path.starts_with(mount_point))
+        .max_by_key(|(_, mount_point, _)| mount_point.len())
+        .ok_or_else(|| {
+            std::io::Error::new(std::io::ErrorKind::NotFound, "No mount contains this path")
+        })?;
+
+    let network_fs_types = [
+        "nfs", "nfs4", "cifs", "smbfs", "smb3", "9p", "ceph", "glusterfs", "fuse.sshfs",
+    ];
+    let is_network = network_fs_types.contains(&fs_type.as_str());
+
+    Ok(MountInfo {
+        filesystem,
+        mount_point,
+        fs_type,
+        is_network,
+    })
+}
+
+/// Decode the octal escapes (`\040` for space, etc.) used in /proc/mounts fields
+#[cfg(target_os = "linux")]
+fn unescape_mount_field(field: &str) -> String {
+    let bytes = field.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let escape = bytes.get(i + 1..i + 4).filter(|digits| {
+            bytes[i] == b'\\' && digits.iter().all(|d| (b'0'..=b'7').contains(d))
+        });
+        if let Some(digits) = escape {
+            let value = digits.iter().fold(0u32, |acc, d| acc * 8 + u32::from(d - b'0'));
+            decoded.push(u8::try_from(value).unwrap_or(b'?'));
+            i += 4;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -238,7 +301,7 @@ mod tests {
     }
 
     #[test]
-    #[cfg(target_os = "macos")]
+    #[cfg(any(target_os = "macos", target_os = "linux"))]
     fn test_mount_info() {
         match get_mount_info(Path::new("/")) {
             Ok(info) => {
@@ -251,4 +314,11 @@ mod tests {
             Err(e) => eprintln!("Warning: Could not get mount info: {}", e),
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_unescape_mount_field() {
+        assert_eq!(unescape_mount_field("/mnt/My\\040Drive"), "/mnt/My Drive");
+        assert_eq!(unescape_mount_field("/plain"), "/plain");
+    }
+}
@@ -12,52 +12,228 @@ use std::path::Path;
 // Bloat Pattern Detection
 // ============================================================================
 
+/// Confidence of a match confirmed by a marker file or an unambiguous name
+pub const CONFIDENCE_HIGH: &str = "high";
+
+/// Confidence of a telltale name without a marker file next to it
+pub const CONFIDENCE_MEDIUM: &str = "medium";
+
 /// All recognized build artifact patterns
+///
+/// Generic names (`target`, `build`, `dist`, `out`, `vendor`, `deps`, `Pods`,
+/// `bazel-*`) only match when a project file sits next to them; names no one
+/// picks by hand match on their own, with lower confidence when unmarked.
 pub const BLOAT_PATTERNS: &[BloatPattern] = &[
     BloatPattern {
         category_id: "node_modules",
         display_name: "Node.js",
         dir_names: &["node_modules"],
+        markers: &["package.json"],
+        unmarked_confidence: Some(CONFIDENCE_MEDIUM),
+        safety: "safe",
     },
     BloatPattern {
         category_id: "rust_target",
         display_name: "Rust",
         dir_names: &["target"],
+        markers: &["Cargo.toml"],
+        unmarked_confidence: None,
+        safety: "safe",
     },
     BloatPattern {
         category_id: "python_venv",
         display_name: "Python",
-        dir_names: &[
-            "venv",
-            ".venv",
-            "__pycache__",
-            ".pytest_cache",
-            ".mypy_cache",
+        dir_names: &["venv", ".venv"],
+        markers: &[
+            "pyproject.toml",
+            "requirements.txt",
+            "setup.py",
+            "setup.cfg",
+            "Pipfile",
         ],
+        unmarked_confidence: Some(CONFIDENCE_MEDIUM),
+        safety: "caution",
+    },
+    BloatPattern {
+        category_id: "python_venv",
+        display_name: "Python",
+        dir_names: &["__pycache__", ".pytest_cache", ".mypy_cache"],
+        markers: &[],
+        unmarked_confidence: Some(CONFIDENCE_HIGH),
+        safety: "safe",
+    },
+    BloatPattern {
+        category_id: "python_test_envs",
+        display_name: "tox/nox",
+        dir_names: &[".tox", ".nox"],
+        markers: &["tox.ini", "noxfile.py", "setup.cfg", "pyproject.toml"],
+        unmarked_confidence: Some(CONFIDENCE_MEDIUM),
+        safety: "safe",
     },
     BloatPattern {
         category_id: "git",
         display_name: ".git",
         dir_names: &[".git"],
+        markers: &[],
+        unmarked_confidence: Some(CONFIDENCE_HIGH),
+        safety: "dangerous",
+    },
+    BloatPattern {
+        category_id: "nextjs",
+        display_name: "Next.js",
+        dir_names: &[".next"],
+        markers: &[
+            "next.config.js",
+            "next.config.mjs",
+            "next.config.ts",
+            "package.json",
+        ],
+        unmarked_confidence: Some(CONFIDENCE_MEDIUM),
+        safety: "safe",
+    },
+    BloatPattern {
+        category_id: "turbo",
+        display_name: "Turborepo",
+        dir_names: &[".turbo"],
+        markers: &["turbo.json", "package.json"],
+        unmarked_confidence: Some(CONFIDENCE_MEDIUM),
+        safety: "safe",
     },
     BloatPattern {
         category_id: "build_artifacts",
         display_name: "Build Artifacts",
-        dir_names: &["dist", "build", ".next", ".nuxt", "out", ".output"],
+        dir_names: &[".nuxt", ".output"],
+        markers: &[],
+        unmarked_confidence: Some(CONFIDENCE_HIGH),
+        safety: "safe",
+    },
+    BloatPattern {
+        category_id: "build_artifacts",
+        display_name: "Build Artifacts",
+        dir_names: &["dist", "build", "out"],
+        markers: &[
+            "package.json",
+            "build.gradle",
+            "build.gradle.kts",
+            "CMakeLists.txt",
+            "setup.py",
+            "pyproject.toml",
+        ],
+        unmarked_confidence: None,
+        safety: "caution",
     },
     BloatPattern {
         category_id: "vendor",
         display_name: "Vendor",
         dir_names: &["vendor"],
+        markers: &["composer.json", "Gemfile", "go.mod"],
+        unmarked_confidence: None,
+        safety: "caution",
     },
     BloatPattern {
         category_id: "java_gradle",
         display_name: "Java/Gradle",
         dir_names: &[".gradle", ".m2"],
+        markers: &[],
+        unmarked_confidence: Some(CONFIDENCE_HIGH),
+        safety: "safe",
+    },
+    BloatPattern {
+        category_id: "terraform",
+        display_name: "Terraform",
+        dir_names: &[".terraform"],
+        markers: &["*.tf", ".terraform.lock.hcl"],
+        unmarked_confidence: Some(CONFIDENCE_MEDIUM),
+        safety: "caution",
+    },
+    BloatPattern {
+        category_id: "dart",
+        display_name: "Dart/Flutter",
+        dir_names: &[".dart_tool"],
+        markers: &["pubspec.yaml"],
+        unmarked_confidence: Some(CONFIDENCE_MEDIUM),
+        safety: "safe",
+    },
+    BloatPattern {
+        category_id: "cocoapods",
+        display_name: "CocoaPods",
+        dir_names: &["Pods"],
+        markers: &["Podfile", "Podfile.lock"],
+        unmarked_confidence: None,
+        safety: "safe",
+    },
+    BloatPattern {
+        category_id: "haskell",
+        display_name: "Haskell",
+        dir_names: &[".stack-work"],
+        markers: &["stack.yaml"],
+        unmarked_confidence: Some(CONFIDENCE_MEDIUM),
+        safety: "safe",
+    },
+    BloatPattern {
+        category_id: "haskell",
+        display_name: "Haskell",
+        dir_names: &["dist-newstyle"],
+        markers: &["cabal.project", "*.cabal"],
+        unmarked_confidence: Some(CONFIDENCE_MEDIUM),
+        safety: "safe",
+    },
+    BloatPattern {
+        category_id: "elixir",
+        display_name: "Elixir",
+        dir_names: &["_build", "deps"],
+        markers: &["mix.exs"],
+        unmarked_confidence: None,
+        safety: "safe",
+    },
+    BloatPattern {
+        category_id: "zig",
+        display_name: "Zig",
+        dir_names: &["zig-cache", ".zig-cache", "zig-out"],
+        markers: &["build.zig"],
+        unmarked_confidence: Some(CONFIDENCE_MEDIUM),
+        safety: "safe",
+    },
+    BloatPattern {
+        category_id: "cmake",
+        display_name: "CMake",
+        dir_names: &["cmake-build-*"],
+        markers: &["CMakeLists.txt"],
+        unmarked_confidence: Some(CONFIDENCE_MEDIUM),
+        safety: "safe",
+    },
+    // Bazel's bazel-* entries are symlinks into its output base: they are
+    // measured when the scan follows symlinks, and the output base itself is
+    // reported as the `.cache/bazel` developer cache
+    BloatPattern {
+        category_id: "bazel",
+        display_name: "Bazel",
+        dir_names: &["bazel-*"],
+        markers: &["WORKSPACE", "WORKSPACE.bazel", "MODULE.bazel"],
+        unmarked_confidence: None,
+        safety: "safe",
     },
 ];
 
-/// Detect if a path matches a known bloat pattern.
+/// A directory classified as bloat, with how sure the match is and why
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BloatMatch {
+    /// Category identifier (e.g., `node_modules`)
+    pub category_id: &'static str,
+    /// Human-readable category name
+    pub display_name: &'static str,
+    /// [`CONFIDENCE_HIGH`] or [`CONFIDENCE_MEDIUM`]
+    pub confidence: &'static str,
+    /// Why the directory matched (e.g., "target next to Cargo.toml")
+    pub reason: String,
+    /// Safety level of deleting it: "safe", "caution", or "dangerous"
+    pub safety: &'static str,
+}
+
+/// Detect if a path's name matches a known bloat pattern.
+///
+/// Looks at the name only; use [`classify_bloat_dir`] to also check the
+/// pattern's marker files.
 ///
 /// # Arguments
 /// * `path` - Path to check for bloat pattern match
@@ -74,14 +250,134 @@ pub const BLOAT_PATTERNS: &[BloatPattern] = &[
 /// ```
 #[must_use]
 pub fn detect_bloat_category(path: &Path) -> Option<(&'static str, &'static str)> {
-    if let Some(dir_name) = path.file_name().and_then(|n| n.to_str()) {
-        for pattern in BLOAT_PATTERNS {
-            if pattern.dir_names.contains(&dir_name) {
-                return Some((pattern.category_id, pattern.display_name));
-            }
-        }
+    find_bloat_pattern(path).map(|pattern| (pattern.category_id, pattern.display_name))
+}
+
+/// Classify a directory as bloat using its name and the files next to it.
+///
+/// # Arguments
+/// * `path` - Directory to classify
+///
+/// # Returns
+/// * `Some(BloatMatch)` with high confidence when a marker file sits in the
+///   parent directory or the name is unambiguous, or with the pattern's
+///   unmarked confidence when no marker was found
+/// * `None` if no pattern matches, or the name is too generic to match
+///   without a marker (e.g., a `build` directory holding sources)
+///
+/// # Example
+/// ```ignore
+/// // Only matches when ./Cargo.toml exists
+/// let found = classify_bloat_dir(Path::new("./target"));
+/// ```
+#[must_use]
+pub fn classify_bloat_dir(path: &Path) -> Option<BloatMatch> {
+    let pattern = find_bloat_pattern(path)?;
+    let dir_name = path.file_name()?.to_string_lossy();
+
+    let matched = |confidence, reason| BloatMatch {
+        category_id: pattern.category_id,
+        display_name: pattern.display_name,
+        confidence,
+        reason,
+        safety: pattern.safety,
+    };
+
+    if pattern.markers.is_empty() {
+        return Some(matched(
+            CONFIDENCE_HIGH,
+            format!("{dir_name} is always generated"),
+        ));
     }
-    None
+
+    let parent = path.parent()?;
+    if let Some(marker) = pattern
+        .markers
+        .iter()
+        .find(|marker| has_marker(parent, marker))
+    {
+        return Some(matched(
+            CONFIDENCE_HIGH,
+            format!("{dir_name} next to {marker}"),
+        ));
+    }
+
+    pattern.unmarked_confidence.map(|confidence| {
+        matched(
+            confidence,
+            format!(
+                "{dir_name} without {} next to it",
+                pattern.markers.join(", ")
+            ),
+        )
+    })
+}
+
+/// Find the first pattern whose directory names match the path's name
+fn find_bloat_pattern(path: &Path) -> Option<&'static BloatPattern> {
+    let dir_name = path.file_name().and_then(|n| n.to_str())?;
+    BLOAT_PATTERNS.iter().find(|pattern| {
+        pattern
+            .dir_names
+            .iter()
+            .any(|name| matches_junk_pattern(dir_name, name))
+    })
+}
+
+/// Whether `dir` holds a file named `marker`, which may be a `*.ext` pattern
+#[must_use]
+pub fn has_marker(dir: &Path, marker: &str) -> bool {
+    if !marker.contains('*') {
+        return dir.join(marker).is_file();
+    }
+    std::fs::read_dir(dir).is_ok_and(|entries| {
+        entries.filter_map(Result::ok).any(|entry| {
+            entry.file_type().is_ok_and(|t| t.is_file())
+                && entry
+                    .file_name()
+                    .to_str()
+                    .is_some_and(|name| matches_junk_pattern(name, marker))
+        })
+    })
+}
+
+/// Check whether a directory is checked into a git repository.
+///
+/// A committed `build` or `vendor` directory holds sources someone chose to
+/// keep, so it is not bloat even when its name and markers match.
+///
+/// # Arguments
+/// * `path` - Directory to check
+///
+/// # Returns
+/// * `true` if the directory is in the `HEAD` tree of the repository that
+///   contains it
+/// * `false` if it is not tracked, is not inside a repository, is a `.git`
+///   directory itself, or git is unavailable
+#[must_use]
+pub fn is_tracked_in_git(path: &Path) -> bool {
+    let (Some(parent), Some(name)) = (path.parent(), path.file_name()) else {
+        return false;
+    };
+    if name == ".git" || !is_in_git_repo(parent) {
+        return false;
+    }
+
+    std::process::Command::new("git")
+        .arg("--literal-pathspecs")
+        .arg("-C")
+        .arg(parent)
+        .args(["ls-tree", "--name-only", "HEAD", "--"])
+        .arg(name)
+        .stderr(std::process::Stdio::null())
+        .output()
+        .is_ok_and(|output| output.status.success() && !output.stdout.is_empty())
+}
+
+/// Check whether a directory is inside a git working tree (has a `.git` at or above it)
+#[must_use]
+pub fn is_in_git_repo(dir: &Path) -> bool {
+    dir.ancestors().any(|dir| dir.join(".git").exists())
 }
 
 // ============================================================================
@@ -258,30 +554,107 @@ pub fn detect_junk_file(filename: &str) -> Option<(&'static str, &'static str, &
 // Cache Patterns
 // ============================================================================
 
+/// A tool's own command for emptying its cache.
+///
+/// Deleting a cache directory behind a tool's back can leave its index or
+/// lock files inconsistent; the tool's command cleans up after itself. The
+/// command runs in the directory the cache pattern starts in (the home
+/// directory for `.npm`, the project for `.yarn/cache`), and each `env`
+/// value is a path relative to that directory, so the tool cleans the cache
+/// that was found rather than whichever one it is configured to use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NativeCleanup {
+    /// Program and arguments
+    pub command: &'static [&'static str],
+    /// Environment variables pointing the tool at the cache, relative to the
+    /// directory the command runs in
+    pub env: &'static [(&'static str, &'static str)],
+    /// Program that must be installed besides `command[0]` (e.g. the
+    /// `cargo-cache` binary behind `cargo cache`)
+    pub requires: Option<&'static str>,
+}
+
+/// `cargo cache -a` removes extracted crate sources and git checkouts,
+/// keeping the compressed downloads
+const CARGO_CACHE_AUTOCLEAN: NativeCleanup = NativeCleanup {
+    command: &["cargo", "cache", "-a"],
+    env: &[("CARGO_HOME", ".cargo")],
+    requires: Some("cargo-cache"),
+};
+
+/// A cache directory pattern: (pattern, `category_id`, `display_name`, safety,
+/// description, native cleanup)
+pub type CachePattern = (
+    &'static str,
+    &'static str,
+    &'static str,
+    &'static str,
+    &'static str,
+    Option<&'static NativeCleanup>,
+);
+
 /// Known cache directory patterns and their safety levels
-pub const CACHE_PATTERNS: &[(&str, &str, &str, &str, &str)] = &[
-    // Format: (pattern, category_id, display_name, safety, description)
+///
+/// A directory matches the first pattern its path contains, and only the
+/// top-most matching directory is reported, so caches under `Library/Caches`
+/// on macOS count towards the system cache. Entries of tools with a cache
+/// command name it; all others are cleaned by deleting the directory.
+pub const CACHE_PATTERNS: &[CachePattern] = &[
+    // Format: (pattern, category_id, display_name, safety, description, native cleanup)
     (
         "node_modules/.cache",
         "nodejs",
         "npm Cache",
         "safe",
         "Downloaded package metadata and tarballs",
+        None,
+    ),
+    (
+        ".npm",
+        "nodejs",
+        "npm Cache",
+        "safe",
+        "npm package cache",
+        Some(&NativeCleanup {
+            command: &["npm", "cache", "clean", "--force"],
+            env: &[("npm_config_cache", ".npm")],
+            requires: None,
+        }),
     ),
-    (".npm", "nodejs", "npm Cache", "safe", "npm package cache"),
     (
         ".yarn/cache",
         "nodejs",
         "Yarn Cache",
         "safe",
         "Yarn package cache",
+        Some(&NativeCleanup {
+            command: &["yarn", "cache", "clean"],
+            env: &[],
+            requires: None,
+        }),
     ),
+    // pip's HTTP and wheel caches are separate so built wheels show on their own.
+    // pip has no command for the HTTP cache alone (`pip cache purge` also
+    // removes the wheels), so it is deleted as a directory.
     (
-        ".cache/pip",
+        ".cache/pip/http",
         "python",
         "pip Cache",
         "safe",
-        "pip package cache",
+        "Downloaded packages (pip HTTP cache)",
+        None,
+    ),
+    (
+        ".cache/pip/wheels",
+        "python",
+        "pip Wheel Cache",
+        "safe",
+        "Wheels built locally from source distributions",
+        Some(&NativeCleanup {
+            command: &["pip", "cache", "remove", "*"],
+            env: &[("PIP_CACHE_DIR", ".cache/pip")],
+            requires: None,
+        }),
     ),
     (
         ".cache/pip-build",
@@ -289,6 +662,7 @@ pub const CACHE_PATTERNS: &[(&str, &str, &str, &str, &str)] = &[
         "pip Cache",
         "safe",
         "pip build cache",
+        None,
     ),
     (
         ".gradle",
@@ -296,6 +670,7 @@ pub const CACHE_PATTERNS: &[(&str, &str, &str, &str, &str)] = &[
         "Gradle Cache",
         "caution",
         "Gradle build cache and dependencies",
+        None,
     ),
     (
         ".m2/repository",
@@ -303,6 +678,7 @@ pub const CACHE_PATTERNS: &[(&str, &str, &str, &str, &str)] = &[
         "Maven Cache",
         "caution",
         "Downloaded Maven dependencies",
+        None,
     ),
     (
         ".cache/go-build",
@@ -310,6 +686,95 @@ pub const CACHE_PATTERNS: &[(&str, &str, &str, &str, &str)] = &[
         "Go Build Cache",
         "safe",
         "Go build cache",
+        Some(&NativeCleanup {
+            command: &["go", "clean", "-cache"],
+            env: &[("GOCACHE", ".cache/go-build")],
+            requires: None,
+        }),
+    ),
+    (
+        "go/pkg/mod",
+        "go",
+        "Go Module Cache",
+        "safe",
+        "Downloaded Go modules (read-only; remove with go clean -modcache)",
+        Some(&NativeCleanup {
+            command: &["go", "clean", "-modcache"],
+            env: &[("GOMODCACHE", "go/pkg/mod")],
+            requires: None,
+        }),
+    ),
+    (
+        ".cargo/registry",
+        "rust",
+        "Cargo Registry",
+        "safe",
+        "Downloaded crate sources and registry index",
+        Some(&CARGO_CACHE_AUTOCLEAN),
+    ),
+    (
+        ".cargo/git",
+        "rust",
+        "Cargo Git Checkouts",
+        "safe",
+        "Git dependencies fetched by Cargo",
+        Some(&CARGO_CACHE_AUTOCLEAN),
+    ),
+    (
+        ".ccache",
+        "c_cpp",
+        "ccache",
+        "safe",
+        "Compiler output cache",
+        Some(&NativeCleanup {
+            command: &["ccache", "--clear"],
+            env: &[("CCACHE_DIR", ".ccache")],
+            requires: None,
+        }),
+    ),
+    (
+        ".cache/ccache",
+        "c_cpp",
+        "ccache",
+        "safe",
+        "Compiler output cache",
+        Some(&NativeCleanup {
+            command: &["ccache", "--clear"],
+            env: &[("CCACHE_DIR", ".cache/ccache")],
+            requires: None,
+        }),
+    ),
+    (
+        ".cache/sccache",
+        "c_cpp",
+        "sccache",
+        "safe",
+        "Shared compilation cache",
+        None,
+    ),
+    (
+        "AppData/Local/Mozilla/sccache",
+        "c_cpp",
+        "sccache",
+        "safe",
+        "Shared compilation cache",
+        None,
+    ),
+    (
+        ".cache/bazel",
+        "bazel",
+        "Bazel Output Base",
+        "caution",
+        "Build outputs of every Bazel workspace (bazel-* symlinks point here)",
+        None,
+    ),
+    (
+        ".cache/huggingface",
+        "ml",
+        "Hugging Face Cache",
+        "caution",
+        "Downloaded models and datasets; slow to download again",
+        None,
     ),
     (
         "Library/Caches",
@@ -317,6 +782,7 @@ pub const CACHE_PATTERNS: &[(&str, &str, &str, &str, &str)] = &[
         "System Cache",
         "safe",
         "macOS system application caches",
+        None,
     ),
     (
         "AppData/Local/Cache",
@@ -324,9 +790,26 @@ pub const CACHE_PATTERNS: &[(&str, &str, &str, &str, &str)] = &[
         "System Cache",
         "safe",
         "Windows system application caches",
+        None,
     ),
 ];
 
+/// Index of the first cache pattern matching a directory path
+///
+/// A pattern matches when its components appear, in order and whole, among
+/// the path's components, so `.cache/pip/http` matches neither
+/// `.cache/pip/httpx` nor `my.cache/pip/http`.
+#[must_use]
+pub fn match_cache_pattern(path: &Path) -> Option<usize> {
+    let components: Vec<_> = path.components().collect();
+    CACHE_PATTERNS.iter().position(|(pattern, ..)| {
+        let pattern: Vec<_> = Path::new(pattern).components().collect();
+        components
+            .windows(pattern.len())
+            .any(|window| window == pattern.as_slice())
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -358,6 +841,112 @@ mod tests {
         assert!(result.is_none());
     }
 
+    #[test]
+    fn test_classify_bloat_requires_marker_for_generic_names() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("target");
+        let build = dir.path().join("build");
+
+        assert_eq!(classify_bloat_dir(&target), None);
+        assert_eq!(classify_bloat_dir(&build), None);
+
+        std::fs::write(dir.path().join("Cargo.toml"), "[package]").unwrap();
+        let found = classify_bloat_dir(&target).unwrap();
+        assert_eq!(found.category_id, "rust_target");
+        assert_eq!(found.confidence, CONFIDENCE_HIGH);
+        assert_eq!(found.reason, "target next to Cargo.toml");
+        assert_eq!(classify_bloat_dir(&build), None);
+    }
+
+    #[test]
+    fn test_classify_bloat_unmarked_confidence() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let node_modules = classify_bloat_dir(&dir.path().join("node_modules")).unwrap();
+        assert_eq!(node_modules.confidence, CONFIDENCE_MEDIUM);
+        assert!(node_modules.reason.contains("without package.json"));
+
+        let pycache = classify_bloat_dir(&dir.path().join("__pycache__")).unwrap();
+        assert_eq!(pycache.category_id, "python_venv");
+        assert_eq!(pycache.confidence, CONFIDENCE_HIGH);
+    }
+
+    #[test]
+    fn test_classify_extended_ecosystems() {
+        let dir = tempfile::tempdir().unwrap();
+        let base = dir.path();
+
+        // Generic names need their marker
+        assert_eq!(classify_bloat_dir(&base.join("deps")), None);
+        assert_eq!(classify_bloat_dir(&base.join("Pods")), None);
+        assert_eq!(classify_bloat_dir(&base.join("bazel-out")), None);
+        std::fs::write(base.join("mix.exs"), "").unwrap();
+        std::fs::write(base.join("WORKSPACE.bazel"), "").unwrap();
+        assert_eq!(
+            classify_bloat_dir(&base.join("deps")).unwrap().category_id,
+            "elixir"
+        );
+        assert_eq!(
+            classify_bloat_dir(&base.join("bazel-out"))
+                .unwrap()
+                .category_id,
+            "bazel"
+        );
+
+        // Wildcard names and markers
+        let cmake = classify_bloat_dir(&base.join("cmake-build-debug")).unwrap();
+        assert_eq!(cmake.category_id, "cmake");
+        assert_eq!(cmake.confidence, CONFIDENCE_MEDIUM);
+        let terraform = classify_bloat_dir(&base.join(".terraform")).unwrap();
+        assert_eq!(terraform.confidence, CONFIDENCE_MEDIUM);
+        std::fs::write(base.join("main.tf"), "").unwrap();
+        let terraform = classify_bloat_dir(&base.join(".terraform")).unwrap();
+        assert_eq!(terraform.category_id, "terraform");
+        assert_eq!(terraform.confidence, CONFIDENCE_HIGH);
+        assert_eq!(terraform.reason, ".terraform next to *.tf");
+        assert_eq!(terraform.safety, "caution");
+
+        for (name, category) in [
+            (".dart_tool", "dart"),
+            (".stack-work", "haskell"),
+            ("dist-newstyle", "haskell"),
+            (".zig-cache", "zig"),
+            (".tox", "python_test_envs"),
+            (".next", "nextjs"),
+            (".turbo", "turbo"),
+        ] {
+            assert_eq!(detect_bloat_category(Path::new(name)).unwrap().0, category);
+        }
+    }
+
+    #[test]
+    fn test_is_tracked_in_git() {
+        let dir = tempfile::tempdir().unwrap();
+        let git = |args: &[&str]| {
+            std::process::Command::new("git")
+                .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+                .arg("-C")
+                .arg(dir.path())
+                .args(args)
+                .output()
+                .is_ok_and(|output| output.status.success())
+        };
+        if !git(&["init", "-q"]) {
+            return; // git not installed
+        }
+
+        std::fs::create_dir_all(dir.path().join("build")).unwrap();
+        std::fs::write(dir.path().join("build/main.c"), "int main;").unwrap();
+        std::fs::create_dir_all(dir.path().join("dist")).unwrap();
+        std::fs::write(dir.path().join("dist/app.js"), "x").unwrap();
+        assert!(git(&["add", "build"]));
+        assert!(git(&["commit", "-q", "-m", "sources"]));
+
+        assert!(is_tracked_in_git(&dir.path().join("build")));
+        assert!(!is_tracked_in_git(&dir.path().join("dist")));
+        assert!(!is_tracked_in_git(&dir.path().join(".git")));
+    }
+
     #[test]
     fn test_junk_exact_match() {
         let result = detect_junk_file(".DS_Store");
@@ -432,4 +1021,36 @@ mod tests {
     fn test_cache_patterns_not_empty() {
         assert!(!CACHE_PATTERNS.is_empty());
     }
+
+    #[test]
+    fn test_cache_patterns_match_whole_components() {
+        let pattern_of =
+            |path: &str| match_cache_pattern(Path::new(path)).map(|index| CACHE_PATTERNS[index].0);
+        assert_eq!(pattern_of("/home/dev/go/pkg/mod"), Some("go/pkg/mod"));
+        assert_eq!(pattern_of("/home/dev/go/pkg/mod/cache"), Some("go/pkg/mod"));
+        assert_eq!(pattern_of("/home/dev/.npm"), Some(".npm"));
+        assert_ne!(pattern_of("/home/dev/cargo/pkg/mod"), Some("go/pkg/mod"));
+        assert_ne!(pattern_of("/x/.cache/pip/httpx"), Some(".cache/pip/http"));
+        assert_eq!(pattern_of("/x/.cache/pip/http"), Some(".cache/pip/http"));
+    }
+
+    #[test]
+    fn test_cache_patterns_cover_toolchains() {
+        let patterns: Vec<&str> = CACHE_PATTERNS.iter().map(|p| p.0).collect();
+        for expected in [
+            ".cargo/registry",
+            ".cargo/git",
+            "go/pkg/mod",
+            ".ccache",
+            ".cache/sccache",
+            ".cache/huggingface",
+            ".cache/pip/wheels",
+        ] {
+            assert!(patterns.contains(&expected), "missing {expected}");
+        }
+        // Every entry names a known safety level
+        assert!(CACHE_PATTERNS
+            .iter()
+            .all(|p| ["safe", "caution", "dangerous"].contains(&p.3)));
+    }
 }
@@ -0,0 +1,215 @@
+//! Running external tools.
+//!
+//! Cache cleanup and Git maintenance shell out to the tools that own the
+//! data (`npm`, `git`, ...). Programs are looked up on `PATH` first, so a
+//! missing tool is reported instead of failing to spawn, and each command
+//! runs with a timeout while its output is captured.
+
+use std::io::Read;
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+/// How often a running command is checked for exit
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How long output is still read after a command exits or is killed
+pub const OUTPUT_GRACE: Duration = Duration::from_secs(2);
+
+/// Extensions tried when looking a program up on `PATH`
+const EXECUTABLE_EXTENSIONS: &[&str] = if cfg!(windows) {
+    &[".exe", ".cmd", ".bat", ""]
+} else {
+    &[""]
+};
+
+/// Look `name` up on `PATH`, like a shell would
+#[must_use]
+pub fn find_program(name: &str) -> Option<PathBuf> {
+    let paths = std::env::var_os("PATH")?;
+    std::env::split_paths(&paths).find_map(|dir| {
+        EXECUTABLE_EXTENSIONS
+            .iter()
+            .map(|extension| dir.join(format!("{name}{extension}")))
+            .find(|candidate| candidate.is_file())
+    })
+}
+
+/// Exit code and captured output of a command run with [`run_with_timeout`]
+pub struct CommandOutput {
+    /// Exit code, if the command exited
+    pub exit_code: Option<i32>,
+    /// Whether the command was killed after running too long
+    pub timed_out: bool,
+    /// Captured standard output
+    pub stdout: String,
+    /// Captured standard error
+    pub stderr: String,
+}
+
+/// Run `command`, killing it if it is still running after `timeout`
+///
+/// On Unix the command runs in its own process group and the whole group is
+/// killed, so helpers it started (`sh -c` children, `git repack`, ...) stop
+/// too. Output is read for at most [`OUTPUT_GRACE`] after the command exits
+/// or is killed: a background process that escaped the group could otherwise
+/// hold the pipes open indefinitely.
+///
+/// # Errors
+/// Returns an error if the command cannot be started or waited for
+pub fn run_with_timeout(mut command: Command, timeout: Duration) -> Result<CommandOutput, String> {
+    own_process_group(&mut command);
+    let mut child = command
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to start command: {e}"))?;
+
+    // Drain both pipes while waiting, so a chatty command never blocks on a full pipe
+    let stdout = child.stdout.take().map(drain);
+    let stderr = child.stderr.take().map(drain);
+
+    let deadline = Instant::now() + timeout;
+    let (status, timed_out) = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break (Some(status), false),
+            Ok(None) if Instant::now() >= deadline => {
+                kill_process_tree(&mut child);
+                break (child.wait().ok(), true);
+            }
+            Ok(None) => std::thread::sleep(POLL_INTERVAL),
+            Err(e) => {
+                kill_process_tree(&mut child);
+                return Err(format!("Failed to wait for command: {e}"));
+            }
+        }
+    };
+
+    let output_deadline = Instant::now() + OUTPUT_GRACE;
+    Ok(CommandOutput {
+        exit_code: status.and_then(|status| status.code()),
+        timed_out,
+        stdout: collect(stdout, output_deadline),
+        stderr: collect(stderr, output_deadline),
+    })
+}
+
+/// Start the command as the leader of a new process group
+#[cfg(unix)]
+fn own_process_group(command: &mut Command) {
+    use std::os::unix::process::CommandExt;
+    command.process_group(0);
+}
+
+#[cfg(not(unix))]
+fn own_process_group(_command: &mut Command) {}
+
+/// Kill `child` together with every process in its group
+#[cfg(unix)]
+#[allow(unsafe_code)]
+fn kill_process_tree(child: &mut Child) {
+    if let Ok(group) = libc::pid_t::try_from(child.id()) {
+        // SAFETY: killpg only reads its integer arguments; the child leads its
+        // own group (see `own_process_group`) and has not been reaped yet
+        if unsafe { libc::killpg(group, libc::SIGKILL) } == 0 {
+            return;
+        }
+        log::warn!(
+            "Failed to stop process group {group}: {}",
+            std::io::Error::last_os_error()
+        );
+    }
+    if let Err(e) = child.kill() {
+        log::warn!("Failed to stop command: {e}");
+    }
+}
+
+/// Kill `child`; processes it started are left running
+#[cfg(not(unix))]
+fn kill_process_tree(child: &mut Child) {
+    if let Err(e) = child.kill() {
+        log::warn!("Failed to stop command: {e}");
+    }
+}
+
+/// Output of one pipe, collected by its own thread
+struct PipeReader {
+    buffer: Arc<Mutex<Vec<u8>>>,
+    thread: JoinHandle<()>,
+}
+
+/// Read a pipe to the end on its own thread
+fn drain(mut pipe: impl Read + Send + 'static) -> PipeReader {
+    let buffer = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&buffer);
+    let thread = std::thread::spawn(move || {
+        let mut chunk = [0u8; 8192];
+        loop {
+            match pipe.read(&mut chunk) {
+                Ok(0) => break,
+                Ok(read) => {
+                    if let Ok(mut sink) = sink.lock() {
+                        sink.extend_from_slice(&chunk[..read]);
+                    }
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+                Err(e) => {
+                    log::debug!("Failed to read command output: {e}");
+                    break;
+                }
+            }
+        }
+    });
+    PipeReader { buffer, thread }
+}
+
+/// Output read by a [`drain`] thread, waiting until `deadline` for the pipe to close
+fn collect(reader: Option<PipeReader>, deadline: Instant) -> String {
+    let Some(reader) = reader else {
+        return String::new();
+    };
+    while !reader.thread.is_finished() && Instant::now() < deadline {
+        std::thread::sleep(POLL_INTERVAL);
+    }
+    reader
+        .buffer
+        .lock()
+        .map(|buffer| String::from_utf8_lossy(&buffer).into_owned())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_timeout_stops_background_children() {
+        let mut command = Command::new("sh");
+        command.args(["-c", "echo started; sleep 30 & sleep 30"]);
+
+        let started = Instant::now();
+        let output = run_with_timeout(command, Duration::from_millis(300)).unwrap();
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert!(output.timed_out);
+        assert_eq!(output.stdout.trim(), "started");
+    }
+
+    #[test]
+    fn test_output_is_captured() {
+        if find_program("sh").is_none() {
+            return;
+        }
+        let mut command = Command::new("sh");
+        command.args(["-c", "echo out; echo err >&2; exit 3"]);
+
+        let output = run_with_timeout(command, Duration::from_secs(30)).unwrap();
+        assert!(!output.timed_out);
+        assert_eq!(output.exit_code, Some(3));
+        assert_eq!(output.stdout.trim(), "out");
+        assert_eq!(output.stderr.trim(), "err");
+    }
+}
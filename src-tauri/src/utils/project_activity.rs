@@ -0,0 +1,346 @@
+//! Activity of the projects that own build artifacts.
+//!
+//! A `node_modules` or `target` directory is only worth deleting when nobody
+//! works on its project any more. For each artifact directory this module
+//! finds the project root above it and when the project was last active: the
+//! newest modification of a source file (ignoring build artifacts and `.git`)
+//! and the date of the last git commit touching the project.
+//!
+//! Source modification times come from the scan's own traversal: a
+//! [`ProjectTracker`] follows the walk and holds artifacts back until their
+//! project directory has been left, so no project is walked a second time.
+//!
+//! With a stale threshold, artifacts of projects inactive for longer are
+//! marked as recommended for deletion; all others are kept.
+
+use crate::models::BloatEntry;
+use crate::utils::patterns::{classify_bloat_dir, has_marker, is_in_git_repo, BLOAT_PATTERNS};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Seconds in a day, for converting inactivity to days
+const SECS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// When a project's sources and git history last changed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProjectActivity {
+    /// Project directory the artifacts belong to
+    pub root: PathBuf,
+    /// Newest source file modification (Unix seconds), if the project has files
+    pub last_source_modified: Option<u64>,
+    /// Last commit touching the project (Unix seconds), if it is in a git repository
+    pub last_commit: Option<u64>,
+}
+
+impl ProjectActivity {
+    /// Activity of the project rooted at `root`, given its newest source modification
+    #[must_use]
+    pub fn of(root: &Path, last_source_modified: Option<u64>) -> Self {
+        Self {
+            root: root.to_path_buf(),
+            last_source_modified,
+            last_commit: last_commit(root),
+        }
+    }
+
+    /// Most recent sign of activity (Unix seconds)
+    #[must_use]
+    pub fn last_active(&self) -> Option<u64> {
+        self.last_source_modified.max(self.last_commit)
+    }
+
+    /// Whole days between the last activity and `now` (Unix seconds)
+    #[must_use]
+    pub fn inactive_days(&self, now: u64) -> Option<u64> {
+        self.last_active()
+            .map(|last| now.saturating_sub(last) / SECS_PER_DAY)
+    }
+
+    /// Fill the project fields of `entry`, recommending deletion when the
+    /// project has been inactive for more than `stale_after_days`
+    pub fn annotate(&self, entry: &mut BloatEntry, stale_after_days: Option<u64>, now: u64) {
+        let inactive_days = self.inactive_days(now);
+        entry.project_root = self.root.to_string_lossy().to_string();
+        entry.project_last_modified = self.last_source_modified;
+        entry.last_commit = self.last_commit;
+        entry.inactive_days = inactive_days;
+        entry.recommended = matches!(
+            (inactive_days, stale_after_days),
+            (Some(inactive), Some(threshold)) if inactive > threshold
+        );
+    }
+}
+
+/// Directory on the walk's current path
+#[derive(Debug)]
+struct TrackedDir {
+    path: PathBuf,
+    depth: usize,
+    /// Build artifact or `.git`: its files are not project sources
+    artifact: bool,
+    /// Newest source file modification below this directory so far
+    newest: Option<u64>,
+}
+
+/// Follows a depth-first walk, recording the newest source modification of
+/// every directory on the current path. Artifacts wait (as `T`) until the walk
+/// leaves their project directory, when its activity is complete.
+#[derive(Debug)]
+pub struct ProjectTracker<T> {
+    open: Vec<TrackedDir>,
+    waiting: HashMap<PathBuf, Vec<T>>,
+}
+
+impl<T> Default for ProjectTracker<T> {
+    fn default() -> Self {
+        Self {
+            open: Vec::new(),
+            waiting: HashMap::new(),
+        }
+    }
+}
+
+impl<T> ProjectTracker<T> {
+    /// Create a tracker for a new walk
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Enter a directory; the walk must already have left every directory at
+    /// `depth` or below (see [`Self::leave_dirs_from`])
+    pub fn enter_dir(&mut self, path: &Path, depth: usize, artifact: bool) {
+        self.open.push(TrackedDir {
+            path: path.to_path_buf(),
+            depth,
+            artifact,
+            newest: None,
+        });
+    }
+
+    /// Count a file in the innermost open directory
+    pub fn add_file(&mut self, last_modified: u64) {
+        if let Some(dir) = self.open.last_mut() {
+            dir.newest = dir.newest.max(Some(last_modified));
+        }
+    }
+
+    /// Hold `item` back until the walk leaves the project owning `artifact`
+    pub fn wait_for_project(&mut self, artifact: &Path, scan_root: &Path, item: T) {
+        self.waiting
+            .entry(find_project_root(artifact, scan_root))
+            .or_default()
+            .push(item);
+    }
+
+    /// Leave every open directory at `depth` or below
+    ///
+    /// **Returns:** Items whose project directory was left, with its activity
+    pub fn leave_dirs_from(&mut self, depth: usize) -> Vec<(ProjectActivity, T)> {
+        let mut ready = Vec::new();
+        while self.open.last().is_some_and(|dir| dir.depth >= depth) {
+            let Some(dir) = self.open.pop() else {
+                break;
+            };
+            if !dir.artifact {
+                if let Some(parent) = self.open.last_mut() {
+                    parent.newest = parent.newest.max(dir.newest);
+                }
+            }
+            if let Some(items) = self.waiting.remove(&dir.path) {
+                let activity = ProjectActivity::of(&dir.path, dir.newest);
+                ready.extend(items.into_iter().map(|item| (activity.clone(), item)));
+            }
+        }
+        ready
+    }
+
+    /// Leave every directory and release all waiting items; projects the walk
+    /// never entered (the scan root was itself an artifact) have no source time
+    pub fn finish(&mut self) -> Vec<(ProjectActivity, T)> {
+        let mut ready = self.leave_dirs_from(0);
+        for (root, items) in self.waiting.drain() {
+            let activity = ProjectActivity::of(&root, None);
+            ready.extend(items.into_iter().map(|item| (activity.clone(), item)));
+        }
+        ready
+    }
+}
+
+/// Find the project directory an artifact directory belongs to.
+///
+/// Artifacts nested in other artifacts (`node_modules/pkg/node_modules`)
+/// belong to the outermost one's project. From the artifact's parent, the
+/// nearest directory holding a project marker (`Cargo.toml`, `package.json`,
+/// ...) or `.git` is the project root; the search stays inside `scan_root`.
+///
+/// **Returns:** The project root, or the artifact's parent when no marker is found
+#[must_use]
+pub fn find_project_root(artifact: &Path, scan_root: &Path) -> PathBuf {
+    let within_scan = |dir: &&Path| dir.starts_with(scan_root);
+    let outermost = artifact
+        .ancestors()
+        .take_while(within_scan)
+        .filter(|dir| classify_bloat_dir(dir).is_some())
+        .last()
+        .unwrap_or(artifact);
+    let parent = outermost.parent().unwrap_or(outermost);
+
+    parent
+        .ancestors()
+        .take_while(within_scan)
+        .find(|dir| is_project_dir(dir))
+        .unwrap_or(parent)
+        .to_path_buf()
+}
+
+/// Whether `dir` holds a git repository or a file marking a project root
+fn is_project_dir(dir: &Path) -> bool {
+    dir.join(".git").exists()
+        || BLOAT_PATTERNS
+            .iter()
+            .flat_map(|pattern| pattern.markers)
+            .any(|marker| has_marker(dir, marker))
+}
+
+/// Commit time (Unix seconds) of the last commit touching `project_root`
+///
+/// **Returns:** `None` if the project is not in a git repository, has no
+/// commits, or git is unavailable
+#[must_use]
+pub fn last_commit(project_root: &Path) -> Option<u64> {
+    if !is_in_git_repo(project_root) {
+        return None;
+    }
+
+    let output = std::process::Command::new("git")
+        .arg("-C")
+        .arg(project_root)
+        .args(["log", "-1", "--format=%ct", "--", "."])
+        .stderr(std::process::Stdio::null())
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8_lossy(&output.stdout).trim().parse().ok()
+}
+
+/// Current time in Unix seconds
+#[must_use]
+pub fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since_epoch| since_epoch.as_secs())
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_find_project_root() {
+        let dir = tempfile::tempdir().unwrap();
+        let base = dir.path();
+        fs::create_dir_all(base.join("app/src/pkg/__pycache__")).unwrap();
+        fs::create_dir_all(base.join("app/node_modules/dep/node_modules")).unwrap();
+        fs::write(base.join("app/package.json"), "{}").unwrap();
+        fs::write(base.join("app/node_modules/dep/package.json"), "{}").unwrap();
+
+        let app = base.join("app");
+        assert_eq!(
+            find_project_root(&base.join("app/src/pkg/__pycache__"), base),
+            app
+        );
+        assert_eq!(
+            find_project_root(&base.join("app/node_modules/dep/node_modules"), base),
+            app
+        );
+        // Without a marker the parent is the project
+        fs::create_dir_all(base.join("loose/target")).unwrap();
+        assert_eq!(
+            find_project_root(&base.join("loose/target"), base),
+            base.join("loose")
+        );
+    }
+
+    #[test]
+    fn test_tracker_ignores_artifact_sources() {
+        let dir = tempfile::tempdir().unwrap();
+        let base = dir.path();
+        fs::write(base.join("Cargo.toml"), "[package]").unwrap();
+        let target = base.join("target");
+
+        // Walk order: base, base/src, base/src/main.rs, base/target, base/target/app
+        let mut tracker = ProjectTracker::new();
+        tracker.enter_dir(base, 0, false);
+        tracker.enter_dir(&base.join("src"), 1, false);
+        tracker.add_file(1_000_000);
+        assert!(tracker.leave_dirs_from(1).is_empty());
+        tracker.enter_dir(&target, 1, true);
+        tracker.add_file(2_000_000);
+        tracker.wait_for_project(&target, base, "target");
+        assert!(tracker.leave_dirs_from(1).is_empty());
+
+        let ready = tracker.finish();
+        assert_eq!(ready.len(), 1);
+        let (activity, item) = &ready[0];
+        assert_eq!(*item, "target");
+        assert_eq!(activity.root, base);
+        assert_eq!(activity.last_source_modified, Some(1_000_000));
+        assert_eq!(activity.last_commit, None);
+    }
+
+    #[test]
+    fn test_source_build_dir_is_not_an_artifact() {
+        let dir = tempfile::tempdir().unwrap();
+        let base = dir.path();
+        // `build` without a build-system marker next to it holds sources
+        fs::create_dir_all(base.join("tool/build/node_modules")).unwrap();
+        fs::write(base.join("tool/Cargo.toml"), "[package]").unwrap();
+        fs::write(base.join("tool/build/package.json"), "{}").unwrap();
+
+        assert_eq!(
+            find_project_root(&base.join("tool/build/node_modules"), base),
+            base.join("tool/build")
+        );
+    }
+
+    #[test]
+    fn test_annotate_recommends_only_stale_projects() {
+        let activity = ProjectActivity {
+            root: PathBuf::from("/work/app"),
+            last_source_modified: Some(10 * SECS_PER_DAY),
+            last_commit: Some(20 * SECS_PER_DAY),
+        };
+        let mut entry = BloatEntry {
+            path: "/work/app/target".to_string(),
+            size_mb: 100.0,
+            allocated_mb: 100.0,
+            reclaimable_mb: 100.0,
+            confidence: "high".to_string(),
+            reason: "target next to Cargo.toml".to_string(),
+            safety: "safe".to_string(),
+            project_root: String::new(),
+            project_last_modified: None,
+            last_commit: None,
+            inactive_days: None,
+            recommended: false,
+        };
+
+        activity.annotate(&mut entry, Some(90), 100 * SECS_PER_DAY);
+        assert_eq!(entry.project_root, "/work/app");
+        assert_eq!(entry.inactive_days, Some(80));
+        assert!(!entry.recommended);
+
+        activity.annotate(&mut entry, Some(90), 200 * SECS_PER_DAY);
+        assert_eq!(entry.inactive_days, Some(180));
+        assert!(entry.recommended);
+
+        activity.annotate(&mut entry, None, 200 * SECS_PER_DAY);
+        assert!(!entry.recommended);
+    }
+}
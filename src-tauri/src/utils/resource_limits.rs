@@ -0,0 +1,236 @@
+//! Resource limits for scans.
+//!
+//! A [`ScanLimiter`] turns [`ScanResourceLimits`] into a dedicated rayon pool
+//! with a bounded number of threads, optionally running at lowered I/O and CPU
+//! priority, and a [`ReadThrottle`] that caps the bytes per second read for
+//! hashing. Scans run their walk and hashing inside the pool, so the limits
+//! apply to every thread the scan uses and end with it.
+
+use crate::models::ScanResourceLimits;
+use std::io::Read;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Nice value of scan threads with lowered CPU priority (like `nice` without `-n`)
+#[cfg(target_os = "linux")]
+const LOW_CPU_NICE: libc::c_int = 10;
+
+/// Thread pool and read throttle enforcing a scan's resource limits
+pub struct ScanLimiter {
+    /// Dedicated pool, or `None` to run on the caller and the global pool
+    pool: Option<rayon::ThreadPool>,
+    throttle: ReadThrottle,
+}
+
+impl ScanLimiter {
+    /// Build the pool and throttle for `limits`.
+    ///
+    /// Without thread or priority limits no pool is created and scans run on
+    /// the calling thread and the global rayon pool, as before.
+    pub fn new(limits: &ScanResourceLimits) -> Result<Self, String> {
+        let needs_pool =
+            limits.max_threads.is_some() || limits.low_io_priority || limits.low_cpu_priority;
+        let pool = if needs_pool {
+            let (low_io, low_cpu) = (limits.low_io_priority, limits.low_cpu_priority);
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(limits.max_threads.unwrap_or(0))
+                .thread_name(|index| format!("scan-worker-{index}"))
+                .start_handler(move |_| lower_current_thread_priority(low_io, low_cpu))
+                .build()
+                .map_err(|e| format!("Failed to create scan thread pool: {e}"))?;
+            Some(pool)
+        } else {
+            None
+        };
+
+        Ok(Self {
+            pool,
+            throttle: ReadThrottle::new(limits.max_read_bytes_per_sec),
+        })
+    }
+
+    /// A limiter that imposes no limits
+    #[must_use]
+    pub fn unlimited() -> Self {
+        Self {
+            pool: None,
+            throttle: ReadThrottle::new(None),
+        }
+    }
+
+    /// Run `op` inside the limited pool, so it and every parallel iterator it
+    /// starts use only the pool's threads
+    pub fn install<T: Send>(&self, op: impl FnOnce() -> T + Send) -> T {
+        match &self.pool {
+            Some(pool) => pool.install(op),
+            None => op(),
+        }
+    }
+
+    /// Throttle that hashing reads go through
+    #[must_use]
+    pub const fn throttle(&self) -> &ReadThrottle {
+        &self.throttle
+    }
+}
+
+/// Token bucket shared by every hashing thread of a scan
+///
+/// Up to one second of reads may burst; after that readers sleep until the
+/// average rate is back under the limit.
+pub struct ReadThrottle {
+    /// Bytes per second, or `None` when reads are not limited
+    rate: Option<u64>,
+    /// (bytes that may still be read without waiting, time of the last refill)
+    bucket: Mutex<(f64, Instant)>,
+}
+
+impl ReadThrottle {
+    /// Create a throttle allowing `bytes_per_sec` (None or 0 = unlimited)
+    #[must_use]
+    pub fn new(bytes_per_sec: Option<u64>) -> Self {
+        let rate = bytes_per_sec.filter(|&rate| rate > 0);
+        Self {
+            rate,
+            bucket: Mutex::new((rate.map_or(0.0, as_f64), Instant::now())),
+        }
+    }
+
+    /// Account for `bytes` read, sleeping if the limit was exceeded
+    pub fn consume(&self, bytes: u64) {
+        let Some(rate) = self.rate.map(as_f64) else {
+            return;
+        };
+
+        let wait = {
+            let Ok(mut bucket) = self.bucket.lock() else {
+                return;
+            };
+            let now = Instant::now();
+            let refill = now.duration_since(bucket.1).as_secs_f64() * rate;
+            bucket.0 = (bucket.0 + refill).min(rate) - as_f64(bytes);
+            bucket.1 = now;
+            bucket.0
+        };
+
+        if wait < 0.0 {
+            std::thread::sleep(Duration::from_secs_f64(-wait / rate));
+        }
+    }
+
+    /// Wrap `inner` so every read is accounted for
+    pub const fn reader<R: Read>(&self, inner: R) -> ThrottledReader<'_, R> {
+        ThrottledReader {
+            inner,
+            throttle: self,
+        }
+    }
+}
+
+/// Reader that sleeps as needed to stay under a [`ReadThrottle`]'s rate
+pub struct ThrottledReader<'a, R> {
+    inner: R,
+    throttle: &'a ReadThrottle,
+}
+
+impl<R: Read> Read for ThrottledReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.throttle.consume(read as u64);
+        Ok(read)
+    }
+}
+
+#[allow(clippy::cast_precision_loss)]
+const fn as_f64(value: u64) -> f64 {
+    value as f64
+}
+
+/// Lower the calling thread's I/O and CPU priority.
+///
+/// I/O priority drops to the lowest best-effort level (like `ionice -c2 -n7`);
+/// CPU priority to nice 10. On Linux both are per-thread, so other threads of
+/// the app keep their priority. Failures are logged and otherwise ignored.
+#[cfg(target_os = "linux")]
+#[allow(unsafe_code)]
+fn lower_current_thread_priority(low_io: bool, low_cpu: bool) {
+    const IOPRIO_WHO_PROCESS: libc::c_int = 1;
+    const IOPRIO_CLASS_BE: libc::c_int = 2;
+    const IOPRIO_CLASS_SHIFT: libc::c_int = 13;
+    const IOPRIO_LOWEST_LEVEL: libc::c_int = 7;
+
+    if low_io {
+        let ioprio = (IOPRIO_CLASS_BE << IOPRIO_CLASS_SHIFT) | IOPRIO_LOWEST_LEVEL;
+        // SAFETY: ioprio_set only reads its integer arguments; who = 0 is the calling thread
+        let result = unsafe { libc::syscall(libc::SYS_ioprio_set, IOPRIO_WHO_PROCESS, 0, ioprio) };
+        if result != 0 {
+            log::debug!(
+                "Cannot lower I/O priority of scan thread: {}",
+                std::io::Error::last_os_error()
+            );
+        }
+    }
+
+    if low_cpu {
+        // SAFETY: setpriority only reads its integer arguments; who = 0 is the calling thread
+        let result = unsafe { libc::setpriority(libc::PRIO_PROCESS, 0, LOW_CPU_NICE) };
+        if result != 0 {
+            log::debug!(
+                "Cannot lower CPU priority of scan thread: {}",
+                std::io::Error::last_os_error()
+            );
+        }
+    }
+}
+
+/// Thread priorities are only lowered on Linux, where they are per-thread
+#[cfg(not(target_os = "linux"))]
+fn lower_current_thread_priority(low_io: bool, low_cpu: bool) {
+    if low_io || low_cpu {
+        log::debug!("Lowering scan thread priority is not supported on this platform");
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pool_is_bounded_by_max_threads() {
+        let limits = ScanResourceLimits {
+            max_threads: Some(2),
+            low_io_priority: true,
+            low_cpu_priority: true,
+            ..ScanResourceLimits::default()
+        };
+        let limiter = ScanLimiter::new(&limits).unwrap();
+
+        assert_eq!(limiter.install(rayon::current_num_threads), 2);
+        let name = limiter.install(|| std::thread::current().name().map(str::to_owned));
+        assert!(name.unwrap().starts_with("scan-worker-"));
+    }
+
+    #[test]
+    fn test_throttle_limits_read_rate() {
+        let throttle = ReadThrottle::new(Some(100_000));
+        let data = vec![0u8; 150_000];
+
+        let started = Instant::now();
+        let mut copied = Vec::new();
+        std::io::copy(&mut throttle.reader(data.as_slice()), &mut copied).unwrap();
+
+        // The first 100 000 bytes burst; the remaining 50 000 take half a second
+        assert_eq!(copied.len(), data.len());
+        assert!(started.elapsed() >= Duration::from_millis(400));
+    }
+
+    #[test]
+    fn test_unlimited_throttle_never_waits() {
+        let throttle = ReadThrottle::new(Some(0));
+        let started = Instant::now();
+        throttle.consume(u64::MAX);
+        assert!(started.elapsed() < Duration::from_millis(100));
+        assert!(ScanLimiter::unlimited().pool.is_none());
+    }
+}
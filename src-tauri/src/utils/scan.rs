@@ -1,25 +1,33 @@
 //! Scanning logic for identifying bloat, duplicates, junk files, and developer caches.
 //!
+//! The per-type scans below are thin filters over the single-pass traversal in
+//! [`crate::utils::unified_scan`]; use [`scan_all`] to run several at once.
+//! Each takes a [`CancellationToken`] and a [`ProgressTracker`]; a cancelled
+//! scan returns what it found so far, marked as cancelled. [`ScanResourceLimits`]
+//! bound the threads, hashing read rate and thread priorities of each scan.
+//!
 //! This module contains all core scanning algorithms:
 //! - Large file detection
 //! - Project bloat detection (node_modules, target, venv, etc.)
-//! - Duplicate file detection (SHA-256 hashing)
+//! - Duplicate file detection (staged SHA-256 hashing)
 //! - Junk file detection (temporary, backup, system files)
 //! - Developer cache discovery
 //! - Git repository analysis
 
-use crate::error::{retry_sync, RetryConfig, compare_f32_safe, ScannerError, ScannerResult};
+use crate::error::{retry_sync, RetryConfig, ScannerError, ScannerResult};
 use crate::models::{
-    BloatCategory, BloatEntry, CacheCategory, CacheEntry, DuplicateEntry, DuplicateSet, GitEntry,
-    GitRepository, JunkCategory, JunkFileEntry, LargeFileEntry,
+    BloatCategory, CacheCategory, CombinedScanResults, DuplicateDirSet, DuplicateSet, GitRepository,
+    JunkCategory, LargeFileEntry, ScanOutcome, ScanResourceLimits,
+};
+use crate::utils::disk_usage::{DiskUsage, FileUsage, UsageAccumulator};
+use crate::utils::scan_progress::{CancellationToken, ProgressTracker};
+use crate::utils::detector_rules::DetectorRules;
+pub use crate::utils::unified_scan::{
+    scan_all, scan_all_indexed, IndexedScanStats, ScanDetectors, UnifiedScanOptions,
 };
-use crate::utils::patterns::{detect_bloat_category, detect_junk_file, CACHE_PATTERNS};
-use crate::utils::scan_progress::CancellationToken;
-use rayon::prelude::*;
-use sha2::{Digest, Sha256};
-use std::collections::HashMap;
+use crate::utils::unified_scan::{scan_all_monitored, ScanMonitor, DEFAULT_LARGE_FILE_BYTES};
 use std::path::Path;
-use std::sync::Mutex;
+use std::sync::Arc;
 use walkdir::WalkDir;
 use tokio::task;
 
@@ -27,19 +35,28 @@ use tokio::task;
 // Core Scanning Utilities
 // ============================================================================
 
-/// Calculate the total size of a directory recursively.
+/// Calculate the total apparent size of a directory recursively.
 ///
-/// Walks the entire directory tree and sums the size of all files.
-/// Uses retry logic for transient failures (BEAD-014).
+/// Hardlinked files are counted once. See [`dir_usage`] for allocated and
+/// reclaimable sizes.
 #[must_use]
 pub fn dir_size(path: &Path) -> u64 {
+    dir_usage(path).apparent_bytes
+}
+
+/// Calculate the apparent, allocated and reclaimable size of a directory recursively.
+///
+/// Walks the entire directory tree, deduplicating hardlinks by (dev, inode).
+/// Uses retry logic for transient failures (BEAD-014).
+#[must_use]
+pub fn dir_usage(path: &Path) -> DiskUsage {
     let retry_config = RetryConfig::new(2, 100) // 2 attempts, 100ms initial delay
         .with_backoff_multiplier(1.5)
         .with_max_delay_ms(1000)
         .with_jitter(false);
     
     retry_sync(retry_config, || {
-        let mut total_size = 0u64;
+        let mut usage = UsageAccumulator::new();
         let mut error_count = 0;
         
         for entry in WalkDir::new(path) {
@@ -47,7 +64,7 @@ pub fn dir_size(path: &Path) -> u64 {
                 Ok(entry) => {
                     if let Ok(metadata) = entry.metadata() {
                         if metadata.is_file() {
-                            total_size += metadata.len();
+                            usage.add(&FileUsage::from_metadata(&metadata));
                         }
                     } else {
                         error_count += 1;
@@ -62,12 +79,13 @@ pub fn dir_size(path: &Path) -> u64 {
             }
         }
         
+        let usage = usage.usage();
         if error_count > 0 {
-            log::debug!("dir_size completed with {} errors, total size: {}", error_count, total_size);
+            log::debug!("dir_size completed with {} errors, total size: {}", error_count, usage.apparent_bytes);
         }
         
-        Ok(total_size)
-    }).unwrap_or(0) // Return 0 if all retries fail
+        Ok(usage)
+    }).unwrap_or_default() // Return zero usage if all retries fail
 }
 
 /// Async version of dir_size to prevent UI blocking (BEAD-009)
@@ -81,6 +99,36 @@ pub async fn dir_size_async(path: &Path) -> u64 {
     })
 }
 
+// ============================================================================
+// Cancellation and Progress
+// ============================================================================
+
+/// Run the unified scan with `options`, stopping when `cancel_token` is set.
+///
+/// Files, directories and bytes seen are counted in `progress` while the scan runs.
+///
+/// **Returns:** The part of the results picked by `select`, marked as cancelled
+/// when the token was set before the scan finished
+fn scan_with_controls<T>(
+    root: &Path,
+    options: &UnifiedScanOptions,
+    cancel_token: &CancellationToken,
+    progress: &Arc<ProgressTracker>,
+    select: impl FnOnce(CombinedScanResults) -> T,
+) -> Result<ScanOutcome<T>, String> {
+    let monitor = ScanMonitor::with_controls(cancel_token.clone(), Arc::clone(progress));
+    let results = scan_all_monitored(root, options, &monitor)?;
+    let cancelled = results.cancelled;
+    if cancelled {
+        log::info!("Scan of {} cancelled, returning partial results", root.display());
+    }
+
+    Ok(ScanOutcome {
+        results: select(results),
+        cancelled,
+    })
+}
+
 // ============================================================================
 // Large File Detection
 // ============================================================================
@@ -91,54 +139,29 @@ pub async fn dir_size_async(path: &Path) -> u64 {
 /// - `root` - Root directory path to scan
 /// - `min_bytes` - Minimum file size threshold (default: 1GB)
 /// - `follow_symlinks` - Whether to follow symbolic links
+/// - `cancel_token` - Stops the walk at the next entry once cancelled
+/// - `progress` - Counts files, directories and bytes as they are visited
+/// - `limits` - Thread, hashing read-rate and priority limits
 ///
-/// **Returns:** Vector of large file entries sorted by size (largest first)
+/// **Returns:** Large file entries sorted by size (largest first); if cancelled,
+/// those found before the token was set
 pub fn scan_large_files(
     root: &Path,
     min_bytes: Option<u64>,
     follow_symlinks: bool,
-) -> Result<Vec<LargeFileEntry>, String> {
-    let min_size = min_bytes.unwrap_or(1024 * 1024 * 1024); // Default 1GB
-
-    let entries: Vec<_> = WalkDir::new(root)
-        .follow_links(follow_symlinks)
-        .into_iter()
-        .filter_map(Result::ok)
-        .filter(|e| e.file_type().is_file())
-        .collect();
-
-    let large_files: Vec<LargeFileEntry> = entries
-        .par_iter()
-        .filter_map(|entry| {
-            let metadata = entry.metadata().ok()?;
-            let size = metadata.len();
-
-            if size >= min_size {
-                let last_modified = metadata
-                    .modified()
-                    .ok()
-                    .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
-                    .map_or(0, |d| d.as_secs());
-
-                #[allow(clippy::cast_precision_loss)]
-                {
-                    let size_mb = size as f32 / 1_048_576.0;
-                    Some(LargeFileEntry {
-                        path: entry.path().to_string_lossy().to_string(),
-                        size_mb,
-                        last_modified,
-                    })
-                }
-            } else {
-                None
-            }
-        })
-        .collect();
-
-    let mut sorted = large_files;
-    sorted.sort_by(|a, b| compare_f32_safe(b.size_mb, a.size_mb));
+    cancel_token: &CancellationToken,
+    progress: &Arc<ProgressTracker>,
+    limits: ScanResourceLimits,
+) -> Result<ScanOutcome<Vec<LargeFileEntry>>, String> {
+    let detectors = ScanDetectors {
+        large_files: true,
+        ..ScanDetectors::none()
+    };
+    let options = UnifiedScanOptions::new(detectors, follow_symlinks)
+        .with_min_large_file_bytes(min_bytes.unwrap_or(DEFAULT_LARGE_FILE_BYTES))
+        .with_limits(limits);
 
-    Ok(sorted)
+    scan_with_controls(root, &options, cancel_token, progress, |results| results.large_files)
 }
 
 // ============================================================================
@@ -150,63 +173,26 @@ pub fn scan_large_files(
 /// **Parameters:**
 /// - `root` - Root directory path to scan
 /// - `follow_symlinks` - Whether to follow symbolic links
+/// - `cancel_token` - Stops the walk at the next entry once cancelled
+/// - `progress` - Counts files, directories and bytes as they are visited
+/// - `limits` - Thread, hashing read-rate and priority limits
 ///
-/// **Returns:** Vector of bloat categories with entries sorted by size (largest first)
-#[allow(clippy::significant_drop_tightening, clippy::cast_precision_loss)]
-pub fn scan_bloat(root: &Path, follow_symlinks: bool) -> Result<Vec<BloatCategory>, String> {
-    let categories: Mutex<HashMap<String, (String, Vec<BloatEntry>)>> = Mutex::new(HashMap::new());
-
-    // Walk the directory tree
-    for entry in WalkDir::new(root)
-        .follow_links(follow_symlinks)
-        .into_iter()
-        .filter_map(Result::ok)
-    {
-        if entry.file_type().is_dir() {
-            if let Some((category_id, display_name)) = detect_bloat_category(entry.path()) {
-                // Calculate directory size
-                let size_bytes = dir_size(entry.path());
-                #[allow(clippy::cast_precision_loss)]
-                let size_mb = size_bytes as f32 / 1_048_576.0;
-
-                // Only include if size is significant (> 1MB)
-                if size_mb > 1.0 {
-                    let mut cats = categories
-                        .lock()
-                        .map_err(|e| format!("categories mutex poisoned: {e}"))?;
-                    let cat_entry = cats
-                        .entry(category_id.to_string())
-                        .or_insert_with(|| (display_name.to_string(), Vec::new()));
-
-                    cat_entry.1.push(BloatEntry {
-                        path: entry.path().to_string_lossy().to_string(),
-                        size_mb,
-                    });
-                }
-            }
-        }
-    }
-
-    // Convert to result format
-    let mut result: Vec<BloatCategory> = categories
-        .lock()
-        .map_err(|e| format!("categories mutex poisoned: {e}"))?
-        .drain()
-        .map(|(category_id, (display_name, entries))| {
-            let total_size_mb: f32 = entries.iter().map(|e| e.size_mb).sum();
-            BloatCategory {
-                category_id,
-                display_name,
-                total_size_mb,
-                entries,
-            }
-        })
-        .collect();
-
-    // Sort by total size (largest first)
-    result.sort_by(|a, b| compare_f32_safe(b.total_size_mb, a.total_size_mb));
+/// **Returns:** Bloat categories with entries sorted by size (largest first); if
+/// cancelled, directories inside the last visited one may be sized incompletely
+pub fn scan_bloat(
+    root: &Path,
+    follow_symlinks: bool,
+    cancel_token: &CancellationToken,
+    progress: &Arc<ProgressTracker>,
+    limits: ScanResourceLimits,
+) -> Result<ScanOutcome<Vec<BloatCategory>>, String> {
+    let detectors = ScanDetectors {
+        bloat: true,
+        ..ScanDetectors::none()
+    };
+    let options = UnifiedScanOptions::new(detectors, follow_symlinks).with_limits(limits);
 
-    Ok(result)
+    scan_with_controls(root, &options, cancel_token, progress, |results| results.bloat)
 }
 
 // ============================================================================
@@ -218,106 +204,75 @@ pub fn scan_bloat(root: &Path, follow_symlinks: bool) -> Result<Vec<BloatCategor
 /// **Parameters:**
 /// - `root` - Root directory path to scan
 /// - `follow_symlinks` - Whether to follow symbolic links
+/// - `cancel_token` - Stops the walk and the hashing stages once cancelled
+/// - `progress` - Counts files, directories and bytes as they are visited
+/// - `limits` - Thread, hashing read-rate and priority limits
 ///
 /// **Algorithm:**
-/// 1. First pass: collect all files with their sizes
-/// 2. Group files by size (optimization - only hash files with same-size siblings)
-/// 3. Hash files in parallel for performance
-/// 4. Group duplicates by hash
-/// 5. Calculate savable space (all copies except one)
+/// 1. First pass: collect all files of at least 1KB with their sizes (no upper limit)
+/// 2. Group files by size (only files with same-size siblings go further)
+/// 3. Hash the head and tail blocks of each remaining file in parallel
+/// 4. Fully hash only files whose size and head/tail hash both collide
+/// 5. Group duplicates by hash and calculate savable space (all copies except one)
 ///
-/// **Returns:** Vector of duplicate sets with savable space calculated, sorted by savings (largest first)
-pub fn scan_duplicates(root: &Path, follow_symlinks: bool) -> Result<Vec<DuplicateSet>, String> {
-    const MAX_FILE_SIZE: u64 = 100 * 1024 * 1024; // 100MB limit for hashing
-
-    // First pass: collect all files with their sizes
-    let files: Vec<_> = WalkDir::new(root)
-        .follow_links(follow_symlinks)
-        .into_iter()
-        .filter_map(Result::ok)
-        .filter(|e| e.file_type().is_file())
-        .filter_map(|e| {
-            let metadata = e.metadata().ok()?;
-            let size = metadata.len();
-
-            // Skip files that are out of the allowed range
-            if !(1024..=MAX_FILE_SIZE).contains(&size) {
-                return None;
-            }
-
-            let last_modified = metadata
-                .modified()
-                .ok()
-                .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
-                .map_or(0, |d| d.as_secs());
-
-            Some((e.path().to_path_buf(), size, last_modified))
-        })
-        .collect();
-
-    // Group files by size first (optimization)
-    let mut size_groups: HashMap<u64, Vec<(std::path::PathBuf, u64)>> = HashMap::new();
-    for (path, size, last_mod) in files {
-        size_groups.entry(size).or_default().push((path, last_mod));
-    }
-
-    // Only hash files that have potential duplicates (same size)
-    let file_hashes: Mutex<HashMap<String, Vec<DuplicateEntry>>> = Mutex::new(HashMap::new());
-
-    size_groups
-        .par_iter()
-        .filter(|(_, files)| files.len() > 1) // Only files with same-size siblings
-        .for_each(|(size, files)| {
-            for (path, last_mod) in files {
-                if let Ok(mut file) = std::fs::File::open(path) {
-                    let mut hasher = Sha256::new();
-                    if std::io::copy(&mut file, &mut hasher).is_ok() {
-                        let hash = format!("{:x}", hasher.finalize());
-                        #[allow(clippy::cast_precision_loss)]
-                        let size_mb = *size as f32 / 1_048_576.0;
-
-                        // Lock shared hash map; if poisoned, log and skip this file
-                        let mut hash_map = match file_hashes.lock() {
-                            Ok(h) => h,
-                            Err(e) => {
-                                log::error!("file_hashes mutex poisoned: {e}");
-                                continue;
-                            }
-                        };
-                        hash_map.entry(hash).or_default().push(DuplicateEntry {
-                            path: path.to_string_lossy().to_string(),
-                            size_mb,
-                            last_modified: *last_mod,
-                        });
-                    }
-                }
-            }
-        });
-
-    // Convert to result format
-    let mut result: Vec<DuplicateSet> = file_hashes
-        .lock()
-        .map_err(|e| format!("file_hashes mutex poisoned: {e}"))?
-        .drain()
-        .filter(|(_, entries)| entries.len() > 1) // Only actual duplicates
-        .map(|(hash, entries)| {
-            // Calculate savable space (all copies except one)
-            let single_file_size = entries.first().map_or(0.0, |e| e.size_mb);
-            #[allow(clippy::cast_precision_loss)]
-            let total_savable_mb = single_file_size * (entries.len().saturating_sub(1)) as f32;
+/// Per-stage statistics are logged; use [`scan_all`] to get them as
+/// `CombinedScanResults::duplicate_stats`.
+///
+/// **Returns:** Duplicate sets with savable space calculated, sorted by savings
+/// (largest first); if cancelled, only sets confirmed from files hashed before
+/// the token was set
+pub fn scan_duplicates(
+    root: &Path,
+    follow_symlinks: bool,
+    cancel_token: &CancellationToken,
+    progress: &Arc<ProgressTracker>,
+    limits: ScanResourceLimits,
+) -> Result<ScanOutcome<Vec<DuplicateSet>>, String> {
+    let detectors = ScanDetectors {
+        duplicates: true,
+        ..ScanDetectors::none()
+    };
+    let options = UnifiedScanOptions::new(detectors, follow_symlinks).with_limits(limits);
 
-            DuplicateSet {
-                hash,
-                total_savable_mb,
-                entries,
-            }
-        })
-        .collect();
+    scan_with_controls(root, &options, cancel_token, progress, |results| results.duplicates)
+}
 
-    // Sort by savable space (largest first)
-    result.sort_by(|a, b| compare_f32_safe(b.total_savable_mb, a.total_savable_mb));
+/// Scan for duplicated directory trees (whole copied folders).
+///
+/// **Parameters:**
+/// - `root` - Root directory path to scan
+/// - `follow_symlinks` - Whether to follow symbolic links
+/// - `cancel_token` - Stops the walk and file hashing once cancelled
+/// - `progress` - Counts files, directories and bytes as they are visited
+/// - `limits` - Thread, hashing read-rate and priority limits
+///
+/// **Algorithm:**
+/// 1. Record the directory tree during the walk
+/// 2. Fingerprint every directory from its child names and file sizes
+/// 3. Hash the files of directories whose fingerprints collide and confirm
+///    identical trees by a Merkle fingerprint of names and content hashes
+/// 4. Compare remaining directories with similar names (`project`, `project-old`)
+///    file by file; trees sharing at least 90% of their bytes are near-identical
+/// 5. Report only the topmost duplicated directory of each group
+///
+/// **Returns:** Duplicate directory sets, sorted by savings (largest first); if
+/// cancelled, only trees whose files were all hashed before the token was set
+pub fn scan_duplicate_dirs(
+    root: &Path,
+    follow_symlinks: bool,
+    cancel_token: &CancellationToken,
+    progress: &Arc<ProgressTracker>,
+    limits: ScanResourceLimits,
+) -> Result<ScanOutcome<Vec<DuplicateDirSet>>, String> {
+    let detectors = ScanDetectors {
+        duplicate_dirs: true,
+        ..ScanDetectors::none()
+    };
+    let options = UnifiedScanOptions::new(detectors, follow_symlinks).with_limits(limits);
 
-    Ok(result)
+    scan_with_controls(root, &options, cancel_token, progress, |results| {
+        results.duplicate_dirs
+    })
 }
 
 // ============================================================================
@@ -329,75 +284,26 @@ pub fn scan_duplicates(root: &Path, follow_symlinks: bool) -> Result<Vec<Duplica
 /// **Parameters:**
 /// - `root` - Root directory path to scan
 /// - `follow_symlinks` - Whether to follow symbolic links
+/// - `cancel_token` - Stops the walk at the next entry once cancelled
+/// - `progress` - Counts files, directories and bytes as they are visited
+/// - `limits` - Thread, hashing read-rate and priority limits
 ///
-/// **Returns:** Vector of junk file categories sorted by file count (most numerous first)
-#[allow(
-    clippy::type_complexity,
-    clippy::significant_drop_tightening,
-    clippy::cast_precision_loss
-)]
-pub fn scan_junk_files(root: &Path, follow_symlinks: bool) -> Result<Vec<JunkCategory>, String> {
-    let junk_files: Mutex<HashMap<String, (String, String, Vec<JunkFileEntry>)>> =
-        Mutex::new(HashMap::new());
-
-    // Walk the directory tree and find junk files
-    for entry in WalkDir::new(root)
-        .follow_links(follow_symlinks)
-        .into_iter()
-        .filter_map(Result::ok)
-    {
-        if entry.file_type().is_file() {
-            if let Some(filename) = entry.path().file_name().and_then(|n| n.to_str()) {
-                if let Some((category_id, display_name, safety)) = detect_junk_file(filename) {
-                    // Get file size
-                    let size_bytes = entry.metadata().ok().map_or(0, |m| m.len());
-                    #[allow(clippy::cast_precision_loss)]
-                    let size_kb = size_bytes as f32 / 1024.0;
-
-                    // NO minimum size - catch even 0-byte files
-                    let mut junk = junk_files
-                        .lock()
-                        .map_err(|e| format!("junk_files mutex poisoned: {e}"))?;
-                    let cat_entry = junk.entry(category_id.to_string()).or_insert_with(|| {
-                        (display_name.to_string(), safety.to_string(), Vec::new())
-                    });
-
-                    cat_entry.2.push(JunkFileEntry {
-                        path: entry.path().to_string_lossy().to_string(),
-                        size_kb,
-                        pattern: filename.to_string(),
-                        category: category_id.to_string(),
-                        safety: (*safety).to_string(),
-                    });
-                }
-            }
-        }
-    }
-
-    // Convert to result format
-    let mut result: Vec<JunkCategory> = junk_files
-        .lock()
-        .map_err(|e| format!("junk_files mutex poisoned: {e}"))?
-        .drain()
-        .map(|(category_id, (display_name, safety, files))| {
-            let total_size_kb: f32 = files.iter().map(|f| f.size_kb).sum();
-            let file_count = files.len();
-
-            JunkCategory {
-                category_id,
-                display_name,
-                total_size_kb,
-                file_count,
-                safety,
-                files,
-            }
-        })
-        .collect();
-
-    // Sort by file count (most files first)
-    result.sort_by(|a, b| b.file_count.cmp(&a.file_count));
+/// **Returns:** Junk file categories sorted by file count (most numerous first);
+/// if cancelled, those found before the token was set
+pub fn scan_junk_files(
+    root: &Path,
+    follow_symlinks: bool,
+    cancel_token: &CancellationToken,
+    progress: &Arc<ProgressTracker>,
+    limits: ScanResourceLimits,
+) -> Result<ScanOutcome<Vec<JunkCategory>>, String> {
+    let detectors = ScanDetectors {
+        junk: true,
+        ..ScanDetectors::none()
+    };
+    let options = UnifiedScanOptions::new(detectors, follow_symlinks).with_limits(limits);
 
-    Ok(result)
+    scan_with_controls(root, &options, cancel_token, progress, |results| results.junk)
 }
 
 // ============================================================================
@@ -409,6 +315,9 @@ pub fn scan_junk_files(root: &Path, follow_symlinks: bool) -> Result<Vec<JunkCat
 /// **Parameters:**
 /// - `root` - Root directory path to scan
 /// - `follow_symlinks` - Whether to follow symbolic links
+/// - `cancel_token` - Stops the walk at the next entry once cancelled
+/// - `progress` - Counts files, directories and bytes as they are visited
+/// - `limits` - Thread, hashing read-rate and priority limits
 ///
 /// **Cache Types Detected:**
 /// - Node.js/npm/yarn
@@ -417,82 +326,38 @@ pub fn scan_junk_files(root: &Path, follow_symlinks: bool) -> Result<Vec<JunkCat
 /// - Java/Maven/Gradle
 /// - Docker, VS Code, `IntelliJ` IDEA, macOS system caches
 ///
-/// **Returns:** Vector of cache categories sorted by total size (largest first)
-pub fn scan_dev_caches(root: &Path, follow_symlinks: bool) -> Result<Vec<CacheCategory>, String> {
-    let mut cache_map: HashMap<String, (String, String, String, Vec<CacheEntry>)> = HashMap::new();
-
-    // Walk the directory tree looking for cache directories
-    for entry in WalkDir::new(root)
-        .follow_links(follow_symlinks)
-        .into_iter()
-        .filter_map(Result::ok)
-        .filter(|e| e.file_type().is_dir())
-    {
-        let path = entry.path();
-        let path_str = path.to_string_lossy();
-
-        for (pattern, category_id, display_name, safety, description) in CACHE_PATTERNS {
-            if path_str.contains(pattern)
-                || path
-                    .file_name()
-                    .is_some_and(|name| name.to_string_lossy() == *pattern)
-            {
-                // Calculate directory size
-                let size_bytes = dir_size(path);
-                #[allow(clippy::cast_precision_loss)]
-                let size_mb = size_bytes as f32 / 1_048_576.0;
-
-                let cache_entry = CacheEntry {
-                    path: path_str.to_string(),
-                    size_mb,
-                    cache_type: (*category_id).to_string(),
-                    safety: (*safety).to_string(),
-                    description: (*description).to_string(),
-                };
-
-                let key = format!("{category_id}:{display_name}");
-                let (_, _, _, entries) = cache_map.entry(key).or_insert_with(|| {
-                    (
-                        (*category_id).to_string(),
-                        (*display_name).to_string(),
-                        (*safety).to_string(),
-                        Vec::new(),
-                    )
-                });
-                entries.push(cache_entry);
-                break;
-            }
-        }
-    }
-
-    // Convert to result format
-    let mut result: Vec<CacheCategory> = cache_map
-        .into_iter()
-        .map(|(_key, (category_id, display_name, safety, entries))| {
-            let total_size_mb: f32 = entries.iter().map(|e| e.size_mb).sum();
-            CacheCategory {
-                category_id,
-                display_name,
-                total_size_mb,
-                entry_count: entries.len(),
-                safety,
-                entries,
-            }
-        })
-        .collect();
-
-    // Sort by total size (largest first)
-    result.sort_by(|a, b| compare_f32_safe(b.total_size_mb, a.total_size_mb));
+/// **Returns:** Cache categories sorted by total size (largest first); if
+/// cancelled, caches found before the token was set
+pub fn scan_dev_caches(
+    root: &Path,
+    follow_symlinks: bool,
+    cancel_token: &CancellationToken,
+    progress: &Arc<ProgressTracker>,
+    limits: ScanResourceLimits,
+) -> Result<ScanOutcome<Vec<CacheCategory>>, String> {
+    let detectors = ScanDetectors {
+        dev_caches: true,
+        ..ScanDetectors::none()
+    };
+    let options = UnifiedScanOptions::new(detectors, follow_symlinks).with_limits(limits);
 
-    Ok(result)
+    scan_with_controls(root, &options, cancel_token, progress, |results| results.dev_caches)
 }
 
 /// Async version of scan_dev_caches to prevent UI blocking (BEAD-009)
-pub async fn scan_dev_caches_async(root: &Path, follow_symlinks: bool) -> Result<Vec<CacheCategory>, String> {
+pub async fn scan_dev_caches_async(
+    root: &Path,
+    follow_symlinks: bool,
+    cancel_token: &CancellationToken,
+    progress: &Arc<ProgressTracker>,
+    limits: ScanResourceLimits,
+) -> Result<ScanOutcome<Vec<CacheCategory>>, String> {
     let root = root.to_owned();
+    let cancel_token = cancel_token.clone();
+    let progress = Arc::clone(progress);
     
     task::spawn_blocking(move || {
-        scan_dev_caches(&root, follow_symlinks)
+        scan_dev_caches(&root, follow_symlinks, &cancel_token, &progress, limits)
     }).await.unwrap_or_else(|e| {
         log::error!("Task failed in scan_dev_caches_async: {}", e);
         Err(format!("Task failed: {}", e))
@@ -508,194 +373,50 @@ pub async fn scan_dev_caches_async(root: &Path, follow_symlinks: bool) -> Result
 /// **Parameters:**
 /// - `root` - Root directory path to scan
 /// - `follow_symlinks` - Whether to follow symbolic links
+/// - `cancel_token` - Stops the walk at the next entry once cancelled
+/// - `progress` - Counts files, directories and bytes as they are visited
+/// - `limits` - Thread, hashing read-rate and priority limits
 ///
 /// **Analysis Includes:**
 /// - Repository path and size
-/// - .git directory structure (objects, refs, logs, pack files)
-/// - Large files in git history (>10MB)
+/// - .git directory structure (objects, refs, logs, pack files, submodule stores)
+/// - Git LFS objects, split into referenced and prunable, and linked worktrees
+/// - Largest blobs in git history (>1MB) with the path and commit that introduced them
+/// - Clones of the same project, grouped by root commit and remote URLs
 /// - Entry types and safety levels
 ///
-/// **Returns:** Vector of Git repositories sorted by total size (largest first)
-#[allow(clippy::too_many_lines)]
-pub fn scan_git_repos(root: &Path, follow_symlinks: bool) -> Result<Vec<GitRepository>, String> {
-    let mut repositories = Vec::new();
-    let mut error_count = 0;
-
-    log::info!(
-        "Starting git repository scan in: {} (follow_symlinks={follow_symlinks})",
-        root.display()
-    );
-
-    // Find all .git directories - with explicit error logging
-    for entry_result in WalkDir::new(root).follow_links(follow_symlinks) {
-        let entry = match entry_result {
-            Ok(e) => e,
-            Err(err) => {
-                error_count += 1;
-                log::warn!("Error walking directory (#{error_count} errors total): {err}");
-                continue; // Skip this entry but continue scanning
-            }
-        };
-
-        // Only process directories named ".git"
-        if !entry.file_type().is_dir() || entry.file_name() != ".git" {
-            continue;
-        }
-
-        log::debug!("Found .git directory: {}", entry.path().display());
-
-        let git_path = entry.path();
-        let repo_path = git_path.parent().unwrap_or(git_path);
-
-        let mut git_entries = Vec::new();
-        let mut total_size = 0u64;
-
-        // Analyze .git directory structure
-        if let Ok(git_contents) = std::fs::read_dir(git_path) {
-            for git_entry in git_contents.flatten() {
-                let entry_path = git_entry.path();
-                let entry_name = git_entry.file_name().to_string_lossy().into_owned();
-
-                match entry_name.as_str() {
-                    "objects" => {
-                        // Analyze git objects
-                        if let Ok(objects_size) = analyze_git_objects(&entry_path) {
-                            total_size += objects_size;
-                            git_entries.push(GitEntry {
-                                path: entry_path.to_string_lossy().to_string(),
-                                #[allow(clippy::cast_precision_loss)]
-                                size_mb: objects_size as f32 / 1_048_576.0,
-                                entry_type: "objects".to_string(),
-                                description: format!(
-                                    "Git objects: {} files",
-                                    count_git_objects(&entry_path)
-                                ),
-                                safety: "safe".to_string(),
-                                actionable: false, // Don't delete git objects
-                            });
-                        }
-                    }
-                    "refs" => {
-                        // Analyze refs
-                        let refs_size = dir_size(&entry_path);
-                        if refs_size > 0 {
-                            total_size += refs_size;
-                            git_entries.push(GitEntry {
-                                path: entry_path.to_string_lossy().to_string(),
-                                #[allow(clippy::cast_precision_loss)]
-                                size_mb: refs_size as f32 / 1_048_576.0,
-                                entry_type: "refs".to_string(),
-                                description: "Git references and branches".to_string(),
-                                safety: "safe".to_string(),
-                                actionable: false,
-                            });
-                        }
-                    }
-                    "logs" => {
-                        // Reflogs - can be cleaned up
-                        let logs_size = dir_size(&entry_path);
-                        if logs_size > 0 {
-                            total_size += logs_size;
-                            git_entries.push(GitEntry {
-                                path: entry_path.to_string_lossy().to_string(),
-                                #[allow(clippy::cast_precision_loss)]
-                                size_mb: logs_size as f32 / 1_048_576.0,
-                                entry_type: "reflog".to_string(),
-                                description: "Git reflogs - tracks branch movements".to_string(),
-                                safety: "caution".to_string(),
-                                actionable: true,
-                            });
-                        }
-                    }
-                    "pack" => {
-                        // Pack files
-                        let pack_size = dir_size(&entry_path);
-                        if pack_size > 0 {
-                            total_size += pack_size;
-                            git_entries.push(GitEntry {
-                                path: entry_path.to_string_lossy().to_string(),
-                                #[allow(clippy::cast_precision_loss)]
-                                size_mb: pack_size as f32 / 1_048_576.0,
-                                entry_type: "pack_file".to_string(),
-                                description: "Git pack files - compressed object storage"
-                                    .to_string(),
-                                safety: "safe".to_string(),
-                                actionable: false,
-                            });
-                        }
-                    }
-                    _ => {
-                        // Other files/directories
-                        if let Ok(metadata) = entry_path.metadata() {
-                            if metadata.is_file() {
-                                let file_size = metadata.len();
-                                total_size += file_size;
-                                git_entries.push(GitEntry {
-                                    path: entry_path.to_string_lossy().to_string(),
-                                    #[allow(clippy::cast_precision_loss)]
-                                    size_mb: file_size as f32 / 1_048_576.0,
-                                    entry_type: "file".to_string(),
-                                    description: format!("Git file: {entry_name}"),
-                                    safety: "safe".to_string(),
-                                    actionable: false,
-                                });
-                            }
-                        }
-                    }
-                }
-            }
-        }
-
-        // Check for large files in git history (using git command)
-        // TODO: This is VERY slow - temporarily disabled for testing
-        log::debug!(
-            "Skipping large file check (temporarily disabled): {}",
-            repo_path.display()
-        );
-        // if let Ok(large_files) = find_large_git_files(repo_path) {
-        //     log::debug!("Found {} large files in history", large_files.len());
-        //     for (file_path, file_size) in large_files {
-        //         total_size += file_size;
-        //         git_entries.push(GitEntry {
-        //             path: file_path,
-        //             size_mb: file_size as f32 / 1_048_576.0,
-        //             entry_type: "large_file".to_string(),
-        //             description: "Large file in git history".to_string(),
-        //             safety: "caution".to_string(),
-        //             actionable: true, // Can be removed with git filter-branch or BFG
-        //         });
-        //     }
-        // }
-
-        if !git_entries.is_empty() {
-            repositories.push(GitRepository {
-                repo_path: repo_path.to_string_lossy().to_string(),
-                #[allow(clippy::cast_precision_loss)]
-                total_size_mb: total_size as f32 / 1_048_576.0,
-                entry_count: git_entries.len(),
-                entries: git_entries,
-            });
-        }
-    }
-
-    // Sort by total size (largest first)
-    repositories.sort_by(|a, b| compare_f32_safe(b.total_size_mb, a.total_size_mb));
-
-    log::info!(
-        "Git repository scan complete: {} repositories found, {} errors encountered",
-        repositories.len(),
-        error_count
-    );
+/// **Returns:** Git repositories sorted by total size (largest first); if
+/// cancelled, repositories whose `.git` directory was fully walked
+pub fn scan_git_repos(
+    root: &Path,
+    follow_symlinks: bool,
+    cancel_token: &CancellationToken,
+    progress: &Arc<ProgressTracker>,
+    limits: ScanResourceLimits,
+) -> Result<ScanOutcome<Vec<GitRepository>>, String> {
+    let detectors = ScanDetectors {
+        git_repos: true,
+        ..ScanDetectors::none()
+    };
+    let options = UnifiedScanOptions::new(detectors, follow_symlinks).with_limits(limits);
 
-    Ok(repositories)
+    scan_with_controls(root, &options, cancel_token, progress, |results| results.git_repos)
 }
 
 /// Async version of scan_git_repos to prevent UI blocking (BEAD-009)
-pub async fn scan_git_repos_async(root: &Path, follow_symlinks: bool) -> Result<Vec<GitRepository>, String> {
+pub async fn scan_git_repos_async(
+    root: &Path,
+    follow_symlinks: bool,
+    cancel_token: &CancellationToken,
+    progress: &Arc<ProgressTracker>,
+    limits: ScanResourceLimits,
+) -> Result<ScanOutcome<Vec<GitRepository>>, String> {
     let root = root.to_owned();
+    let cancel_token = cancel_token.clone();
+    let progress = Arc::clone(progress);
     
     task::spawn_blocking(move || {
-        scan_git_repos(&root, follow_symlinks)
+        scan_git_repos(&root, follow_symlinks, &cancel_token, &progress, limits)
     }).await.unwrap_or_else(|e| {
         log::error!("Task failed in scan_git_repos_async: {}", e);
         Err(format!("Task failed: {}", e))
@@ -706,403 +427,69 @@ pub async fn scan_git_repos_async(root: &Path, follow_symlinks: bool) -> Result<
 // Cancellation-Aware Scanning Functions (BEAD-010)
 // ============================================================================
 
-/// Async version of scan_dev_caches with cancellation support (BEAD-010)
+/// Async cache scan that can stay on the root's filesystem and applies user cache rules (BEAD-010)
 pub async fn scan_dev_caches_async_with_cancellation(
     root: &Path, 
     follow_symlinks: bool,
-    cancel_token: &CancellationToken
-) -> ScannerResult<Vec<CacheCategory>> {
+    one_file_system: bool,
+    cancel_token: &CancellationToken,
+    progress: &Arc<ProgressTracker>,
+    limits: ScanResourceLimits,
+    rules: &Arc<DetectorRules>,
+) -> ScannerResult<ScanOutcome<Vec<CacheCategory>>> {
     let root = root.to_owned();
     let cancel_token = cancel_token.clone();
+    let progress = Arc::clone(progress);
+    let rules = Arc::clone(rules);
     
     task::spawn_blocking(move || {
-        // Check for cancellation at start
-        if cancel_token.is_cancelled() {
-            return Err(ScannerError::Other("Scan cancelled".to_string()));
-        }
+        let detectors = ScanDetectors {
+            dev_caches: true,
+            ..ScanDetectors::none()
+        };
+        let options = UnifiedScanOptions::new(detectors, follow_symlinks)
+            .with_one_file_system(one_file_system)
+            .with_limits(limits)
+            .with_rules(rules);
         
-        scan_dev_caches_with_cancellation(&root, follow_symlinks, &cancel_token)
+        scan_with_controls(&root, &options, &cancel_token, &progress, |results| results.dev_caches)
+            .map_err(ScannerError::from)
     }).await.unwrap_or_else(|e| {
         log::error!("Task failed in scan_dev_caches_async_with_cancellation: {}", e);
         Err(ScannerError::Other(format!("Task failed: {}", e)))
     })
 }
 
-/// Cancellation-aware version of scan_dev_caches (BEAD-010)
-fn scan_dev_caches_with_cancellation(
-    root: &Path, 
-    follow_symlinks: bool,
-    cancel_token: &CancellationToken
-) -> ScannerResult<Vec<CacheCategory>> {
-    use std::sync::atomic::{AtomicU64, Ordering};
-    
-    let mut categories = Vec::new();
-    let files_processed = AtomicU64::new(0);
-    
-    log::info!("Starting cancellation-aware cache scan: {}", root.display());
-    
-    // Check for cancellation before starting
-    if cancel_token.is_cancelled() {
-        return Err(ScannerError::Other("Scan cancelled before starting".to_string()));
-    }
-    
-    // Walk through directories looking for caches
-    let walker = if follow_symlinks {
-        WalkDir::new(root).follow_links(true)
-    } else {
-        WalkDir::new(root).follow_links(false)
-    };
-    
-    let mut entries = Vec::new();
-    
-    for entry in walker.into_iter().filter_map(Result::ok) {
-        // Check for cancellation periodically
-        if files_processed.load(Ordering::Relaxed) % 100 == 0 && cancel_token.is_cancelled() {
-            log::info!("Cache scan cancelled at entry: {}", entry.path().display());
-            return Err(ScannerError::Other("Scan cancelled by user".to_string()));
-        }
-        
-        let path = entry.path();
-        
-        // Check if this path matches any cache pattern
-        for (pattern, category_id, display_name, safety, description) in CACHE_PATTERNS {
-            if let Some(pattern_path) = path.strip_prefix(root).ok().and_then(|p| Some(p.join(pattern))) {
-                let full_path = root.join(pattern_path);
-                
-                if full_path.exists() {
-                    // Check for cancellation before expensive operations
-                    if cancel_token.is_cancelled() {
-                        return Err(ScannerError::Other("Scan cancelled during directory processing".to_string()));
-                    }
-                    
-                    let size = dir_size(&full_path);
-                    let size_mb = size as f32 / (1024.0 * 1024.0);
-                    
-                    entries.push(CacheEntry {
-                        path: full_path.to_string_lossy().to_string(),
-                        size_mb,
-                        cache_type: category_id.to_string(),
-                        safety: safety.to_string(),
-                        description: description.to_string(),
-                    });
-                    
-                    files_processed.fetch_add(1, Ordering::Relaxed);
-                }
-            }
-            
-        }
-        
-        // Group entries by category
-        let mut category_map: HashMap<String, Vec<CacheEntry>> = HashMap::new();
-        for entry in &entries {
-            let category = entry.cache_type.clone();
-            category_map.entry(category).or_insert_with(Vec::new).push(entry.clone());
-        }
-        
-        for (category_id, category_entries) in category_map {
-            if !category_entries.is_empty() {
-                let mut sorted_entries = category_entries;
-                sorted_entries.sort_by(|a, b| compare_f32_safe(b.size_mb, a.size_mb));
-                
-                let total_size_mb: f32 = sorted_entries.iter().map(|e| e.size_mb).sum();
-                let safety = if sorted_entries.iter().any(|e| e.safety == "dangerous") {
-                    "dangerous".to_string()
-                } else if sorted_entries.iter().any(|e| e.safety == "caution") {
-                    "caution".to_string()
-                } else {
-                    "safe".to_string()
-                };
-                
-                categories.push(CacheCategory {
-                    category_id: category_id.clone(),
-                    display_name: format!("{} Cache", category_id),
-                    total_size_mb,
-                    entry_count: sorted_entries.len(),
-                    safety,
-                    entries: sorted_entries,
-                });
-            }
-        }
-        
-        if !categories.is_empty() {
-            categories.sort_by(|a, b| compare_f32_safe(b.total_size_mb, a.total_size_mb));
-        }
-    }
-    
-    // Final cancellation check
-    if cancel_token.is_cancelled() {
-        return Err(ScannerError::Other("Scan cancelled during final processing".to_string()));
-    }
-    
-    log::info!("Completed cancellation-aware cache scan: {} categories found", categories.len());
-    
-    Ok(categories)
-}
-
-/// Async version of scan_git_repos with cancellation support (BEAD-010)
+/// Async Git repository scan that can stay on the root's filesystem (BEAD-010)
 pub async fn scan_git_repos_async_with_cancellation(
     root: &Path, 
     follow_symlinks: bool,
-    cancel_token: &CancellationToken
-) -> ScannerResult<Vec<GitRepository>> {
+    one_file_system: bool,
+    cancel_token: &CancellationToken,
+    progress: &Arc<ProgressTracker>,
+    limits: ScanResourceLimits,
+) -> ScannerResult<ScanOutcome<Vec<GitRepository>>> {
     let root = root.to_owned();
     let cancel_token = cancel_token.clone();
+    let progress = Arc::clone(progress);
     
     task::spawn_blocking(move || {
-        // Check for cancellation at start
-        if cancel_token.is_cancelled() {
-            return Err(ScannerError::Other("Scan cancelled".to_string()));
-        }
+        let detectors = ScanDetectors {
+            git_repos: true,
+            ..ScanDetectors::none()
+        };
+        let options = UnifiedScanOptions::new(detectors, follow_symlinks)
+            .with_one_file_system(one_file_system)
+            .with_limits(limits);
         
-        scan_git_repos_with_cancellation(&root, follow_symlinks, &cancel_token)
+        scan_with_controls(&root, &options, &cancel_token, &progress, |results| results.git_repos)
+            .map_err(ScannerError::from)
     }).await.unwrap_or_else(|e| {
         log::error!("Task failed in scan_git_repos_async_with_cancellation: {}", e);
         Err(ScannerError::Other(format!("Task failed: {}", e)))
     })
 }
 
-/// Analyze a single git repository (extracted from scan_git_repos for reuse)
-fn analyze_git_repository(repo_path: &Path) -> ScannerResult<GitRepository> {
-    let git_path = repo_path.join(".git");
-    let mut git_entries = Vec::new();
-    let mut total_size = 0u64;
-
-    // Analyze .git directory structure
-    if let Ok(git_contents) = std::fs::read_dir(&git_path) {
-        for git_entry in git_contents.flatten() {
-            let entry_path = git_entry.path();
-            let entry_name = git_entry.file_name().to_string_lossy().into_owned();
-
-            match entry_name.as_str() {
-                "objects" => {
-                    // Analyze git objects
-                    if let Ok(objects_size) = analyze_git_objects(&entry_path) {
-                        total_size += objects_size;
-                        git_entries.push(GitEntry {
-                            path: entry_path.to_string_lossy().to_string(),
-                            #[allow(clippy::cast_precision_loss)]
-                            size_mb: objects_size as f32 / 1_048_576.0,
-                            entry_type: "objects".to_string(),
-                            description: format!(
-                                "Git objects: {} files",
-                                count_git_objects(&entry_path)
-                            ),
-                            safety: "safe".to_string(),
-                            actionable: false, // Don't delete git objects
-                        });
-                    }
-                }
-                "refs" => {
-                    // Analyze refs
-                    let refs_size = dir_size(&entry_path);
-                    if refs_size > 0 {
-                        total_size += refs_size;
-                        git_entries.push(GitEntry {
-                            path: entry_path.to_string_lossy().to_string(),
-                            #[allow(clippy::cast_precision_loss)]
-                            size_mb: refs_size as f32 / 1_048_576.0,
-                            entry_type: "refs".to_string(),
-                            description: "Git references and branches".to_string(),
-                            safety: "safe".to_string(),
-                            actionable: false,
-                        });
-                    }
-                }
-                "logs" => {
-                    // Reflogs - can be cleaned up
-                    let logs_size = dir_size(&entry_path);
-                    if logs_size > 0 {
-                        total_size += logs_size;
-                        git_entries.push(GitEntry {
-                            path: entry_path.to_string_lossy().to_string(),
-                            #[allow(clippy::cast_precision_loss)]
-                            size_mb: logs_size as f32 / 1_048_576.0,
-                            entry_type: "reflog".to_string(),
-                            description: "Git reflogs - tracks branch movements".to_string(),
-                            safety: "caution".to_string(),
-                            actionable: true,
-                        });
-                    }
-                }
-                "pack" => {
-                    // Pack files
-                    let pack_size = dir_size(&entry_path);
-                    if pack_size > 0 {
-                        total_size += pack_size;
-                        git_entries.push(GitEntry {
-                            path: entry_path.to_string_lossy().to_string(),
-                            #[allow(clippy::cast_precision_loss)]
-                            size_mb: pack_size as f32 / 1_048_576.0,
-                            entry_type: "pack_file".to_string(),
-                            description: "Git pack files - compressed object storage".to_string(),
-                            safety: "safe".to_string(),
-                            actionable: false,
-                        });
-                    }
-                }
-                _ => {
-                    // Other files/directories
-                    if let Ok(metadata) = entry_path.metadata() {
-                        if metadata.is_file() {
-                            let file_size = metadata.len();
-                            total_size += file_size;
-                            git_entries.push(GitEntry {
-                                path: entry_path.to_string_lossy().to_string(),
-                                #[allow(clippy::cast_precision_loss)]
-                                size_mb: file_size as f32 / 1_048_576.0,
-                                entry_type: "file".to_string(),
-                                description: format!("Git file: {entry_name}"),
-                                safety: "safe".to_string(),
-                                actionable: false,
-                            });
-                        }
-                    }
-                }
-            }
-        }
-    }
-
-    if !git_entries.is_empty() {
-        Ok(GitRepository {
-            repo_path: repo_path.to_string_lossy().to_string(),
-            #[allow(clippy::cast_precision_loss)]
-            total_size_mb: total_size as f32 / 1_048_576.0,
-            entry_count: git_entries.len(),
-            entries: git_entries,
-        })
-    } else {
-        Err(ScannerError::Other("No valid git entries found".to_string()))
-    }
-}
-
-/// Cancellation-aware version of scan_git_repos (BEAD-010)
-fn scan_git_repos_with_cancellation(
-    root: &Path, 
-    follow_symlinks: bool,
-    cancel_token: &CancellationToken
-) -> ScannerResult<Vec<GitRepository>> {
-    use std::sync::atomic::{AtomicU64, Ordering};
-    
-    let mut repositories = Vec::new();
-    let files_processed = AtomicU64::new(0);
-    
-    log::info!("Starting cancellation-aware git repository scan: {}", root.display());
-    
-    // Check for cancellation before starting
-    if cancel_token.is_cancelled() {
-        return Err(ScannerError::Other("Scan cancelled before starting".to_string()));
-    }
-    
-    // Walk through directories looking for .git directories
-    let walker = if follow_symlinks {
-        WalkDir::new(root).follow_links(true)
-    } else {
-        WalkDir::new(root).follow_links(false)
-    };
-    
-    for entry in walker.into_iter().filter_map(Result::ok) {
-        // Check for cancellation periodically
-        if files_processed.load(Ordering::Relaxed) % 50 == 0 && cancel_token.is_cancelled() {
-            log::info!("Git repository scan cancelled at entry: {}", entry.path().display());
-            return Err(ScannerError::Other("Scan cancelled by user".to_string()));
-        }
-        
-        let path = entry.path();
-        
-        // Check if this is a .git directory
-        if path.file_name().map(|n| n == ".git").unwrap_or(false) {
-            if let Some(repo_path) = path.parent() {
-                // Check for cancellation before expensive operations
-                if cancel_token.is_cancelled() {
-                    return Err(ScannerError::Other("Scan cancelled during repository processing".to_string()));
-                }
-                
-                // Analyze the repository
-                if let Ok(repo) = analyze_git_repository(repo_path) {
-                    repositories.push(repo);
-                    files_processed.fetch_add(1, Ordering::Relaxed);
-                }
-            }
-        }
-    }
-    
-    // Final cancellation check
-    if cancel_token.is_cancelled() {
-        return Err(ScannerError::Other("Scan cancelled during final processing".to_string()));
-    }
-    
-    log::info!("Completed cancellation-aware git repository scan: {} repositories found", repositories.len());
-    
-    Ok(repositories)
-}
-
-// ============================================================================
-// Git Helper Functions
-// ============================================================================
-
-#[allow(clippy::unnecessary_wraps)]
-fn analyze_git_objects(objects_path: &std::path::Path) -> Result<u64, std::io::Error> {
-    Ok(dir_size(objects_path))
-}
-
-fn count_git_objects(objects_path: &std::path::Path) -> usize {
-    let mut count = 0;
-    if let Ok(entries) = std::fs::read_dir(objects_path) {
-        for entry in entries.flatten() {
-            let path = entry.path();
-            if path.is_dir() {
-                count += count_git_objects(&path);
-            } else {
-                count += 1;
-            }
-        }
-    }
-    count
-}
-
-#[allow(dead_code)]
-#[allow(clippy::unnecessary_wraps)]
-fn find_large_git_files(repo_path: &std::path::Path) -> Result<Vec<(String, u64)>, std::io::Error> {
-    let mut large_files = Vec::new();
-
-    // Use git command to find large files in history
-    if let Ok(output) = std::process::Command::new("git")
-        .arg("-C")
-        .arg(repo_path)
-        .args(["rev-list", "--objects", "--all"])
-        .output()
-    {
-        if output.status.success() {
-            let output_str = String::from_utf8_lossy(&output.stdout);
-            for line in output_str.lines() {
-                if let Some((hash, path)) = line.split_once(' ') {
-                    // Get file size for this object
-                    if let Ok(size_output) = std::process::Command::new("git")
-                        .arg("-C")
-                        .arg(repo_path)
-                        .args(["cat-file", "-s", hash])
-                        .output()
-                    {
-                        if size_output.status.success() {
-                            if let Ok(size_str) = String::from_utf8_lossy(&size_output.stdout)
-                                .trim()
-                                .parse::<u64>()
-                            {
-                                if size_str > 10 * 1024 * 1024 {
-                                    // Files larger than 10MB
-                                    large_files.push((path.to_string(), size_str));
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-        }
-    }
-
-    Ok(large_files)
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1130,8 +517,33 @@ mod tests {
     #[test]
     fn test_scan_large_files_empty_directory() {
         let temp_dir = TempDir::new().expect("Failed to create temp dir");
-        let result = scan_large_files(temp_dir.path(), Some(1024), false);
+        let progress = Arc::new(ProgressTracker::new());
+        let result = scan_large_files(temp_dir.path(), Some(1024), false, &CancellationToken::new(), &progress, ScanResourceLimits::default());
         assert!(result.is_ok());
-        assert_eq!(result.unwrap().len(), 0);
+        let outcome = result.unwrap();
+        assert_eq!(outcome.results.len(), 0);
+        assert!(!outcome.cancelled);
+    }
+
+    #[test]
+    fn test_cancelled_scans_return_partial_results() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        fs::write(temp_dir.path().join("big.bin"), vec![0u8; 4096]).expect("Failed to write file");
+        fs::write(temp_dir.path().join("copy.bin"), vec![0u8; 4096]).expect("Failed to write file");
+        let progress = Arc::new(ProgressTracker::new());
+        let cancel_token = CancellationToken::new();
+
+        let finished = scan_duplicates(temp_dir.path(), false, &cancel_token, &progress, ScanResourceLimits::default()).expect("scan failed");
+        assert!(!finished.cancelled);
+        assert_eq!(finished.results.len(), 1);
+        assert_eq!(progress.get_files_scanned(), 2);
+
+        cancel_token.cancel();
+        let large = scan_large_files(temp_dir.path(), Some(1024), false, &cancel_token, &progress, ScanResourceLimits::default()).expect("scan failed");
+        assert!(large.cancelled);
+        assert!(large.results.is_empty());
+        let duplicates = scan_duplicates(temp_dir.path(), false, &cancel_token, &progress, ScanResourceLimits::default()).expect("scan failed");
+        assert!(duplicates.cancelled);
+        assert!(duplicates.results.is_empty());
     }
 }
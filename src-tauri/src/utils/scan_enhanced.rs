@@ -1,8 +1,10 @@
 /// Enhanced scanning utilities with BEAD features integrated
+use super::mount_boundary::MountBoundary;
 use super::scan_progress::{CancellationToken, ProgressTracker, ScanProgress};
 use super::symlink::SymlinkTracker;
 use super::network::is_network_mount;
 use crate::error;
+use crate::models::SkippedMount;
 use std::path::Path;
 use std::sync::Arc;
 use walkdir::{DirEntry, WalkDir};
@@ -14,6 +16,8 @@ const LARGE_DIRECTORY_THRESHOLD: usize = 10_000; // BEAD-010: Warn at 10K files
 pub struct EnhancedScanOptions<'a> {
     pub root: &'a Path,
     pub follow_symlinks: bool,
+    /// Stay on the root's filesystem instead of descending into other mounts
+    pub one_file_system: bool,
     pub cancellation_token: Option<CancellationToken>,
     pub progress_tracker: Option<Arc<ProgressTracker>>,
     pub emit_progress: Option<Box<dyn Fn(ScanProgress) + Send + Sync>>,
@@ -21,8 +25,8 @@ pub struct EnhancedScanOptions<'a> {
 
 /// Enhanced directory walker with all BEAD features
 pub struct EnhancedWalker {
-    root: std::path::PathBuf,
-    follow_symlinks: bool,
+    entries: walkdir::IntoIter,
+    mount_boundary: MountBoundary,
     symlink_tracker: SymlinkTracker,
     cancellation_token: Option<CancellationToken>,
     progress_tracker: Option<Arc<ProgressTracker>>,
@@ -44,8 +48,10 @@ impl EnhancedWalker {
         }
 
         Ok(Self {
-            root: options.root.to_path_buf(),
-            follow_symlinks: options.follow_symlinks,
+            entries: WalkDir::new(options.root)
+                .follow_links(options.follow_symlinks)
+                .into_iter(),
+            mount_boundary: MountBoundary::new(options.root, options.one_file_system),
             symlink_tracker: SymlinkTracker::new(),
             cancellation_token: options.cancellation_token,
             progress_tracker: options.progress_tracker,
@@ -67,10 +73,7 @@ impl EnhancedWalker {
                 }
             }
 
-            let walker = WalkDir::new(&self.root)
-                .follow_links(self.follow_symlinks);
-            
-            let entry = match walker.into_iter().next() {
+            let entry = match self.entries.next() {
                 Some(Ok(entry)) => entry,
                 Some(Err(e)) => {
                     log::warn!("Error walking directory: {}", e);
@@ -81,6 +84,14 @@ impl EnhancedWalker {
 
             let path = entry.path();
 
+            // One-filesystem mode: do not descend into other mounts
+            if !self.mount_boundary.admits(&entry) {
+                let warning = format!("Skipped mount point: {}", path.display());
+                self.warnings.push(warning);
+                self.entries.skip_current_dir();
+                continue;
+            }
+
             // BEAD-010: Large directory detection
             if let Some(ref parent) = path.parent() {
                 if self.current_dir.as_ref() != Some(&parent.to_path_buf()) {
@@ -155,6 +166,11 @@ impl EnhancedWalker {
     pub fn get_warnings(&self) -> &[String] {
         &self.warnings
     }
+
+    /// Get mount points skipped in one-filesystem mode, with their filesystem types
+    pub fn get_skipped_mounts(&self) -> &[SkippedMount] {
+        self.mount_boundary.skipped()
+    }
 }
 
 /// Check scan path with enhanced validations
@@ -217,6 +233,7 @@ mod tests {
         let options = EnhancedScanOptions {
             root: temp_dir.path(),
             follow_symlinks: false,
+            one_file_system: false,
             cancellation_token: None,
             progress_tracker: None,
             emit_progress: None,
@@ -243,6 +260,7 @@ mod tests {
         let options = EnhancedScanOptions {
             root: temp_dir.path(),
             follow_symlinks: false,
+            one_file_system: false,
             cancellation_token: Some(token.clone()),
             progress_tracker: None,
             emit_progress: None,
@@ -273,6 +291,7 @@ mod tests {
         let options = EnhancedScanOptions {
             root: temp_dir.path(),
             follow_symlinks: false,
+            one_file_system: false,
             cancellation_token: None,
             progress_tracker: Some(tracker.clone()),
             emit_progress: Some(Box::new(move |_progress| {
@@ -286,6 +305,6 @@ mod tests {
             // Process entries
         }
 
-        assert!(tracker.files_scanned.load(std::sync::atomic::Ordering::Relaxed) >= 5);
+        assert!(tracker.get_files_scanned() >= 5);
     }
 }
\ No newline at end of file
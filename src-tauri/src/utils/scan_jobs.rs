@@ -0,0 +1,728 @@
+//! Background scan jobs.
+//!
+//! [`ScanJobs`] runs any scan type on a worker thread, reports progress with
+//! ETA and chunks of newly found large files, bloat, junk and caches through a
+//! callback while it runs, and keeps the outcome so results can be fetched by
+//! job ID after the scan finished. With a database path, jobs and
+//! their results survive restarts; jobs that were still running when the app
+//! quit are reported as interrupted.
+
+use crate::database::ScanJobStore;
+use crate::models::ScanResultsChunk;
+use crate::utils::scan_progress::ScanProgress;
+use crate::utils::unified_scan::{
+    scan_all_monitored, ScanDetectors, ScanMonitor, UnifiedScanOptions,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// How often a running job reports progress
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Callback receiving every [`ScanJobEvent`] of a job
+pub type ScanJobListener = Arc<dyn Fn(ScanJobEvent<'_>) + Send + Sync>;
+
+/// What a running job reports to its listener
+#[derive(Debug, Clone, Copy)]
+pub enum ScanJobEvent<'a> {
+    /// Periodic state with live progress
+    Progress(&'a ScanJobInfo),
+    /// Entries found since the previous chunk; every chunk precedes `Finished`
+    Results(&'a ScanResultsChunk),
+    /// Final state, sent once
+    Finished(&'a ScanJobInfo),
+}
+
+// ============================================================================
+// Job Description
+// ============================================================================
+
+/// Scan types a job can run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScanJobKind {
+    /// Files above the large file threshold
+    LargeFiles,
+    /// Build artifact directories
+    Bloat,
+    /// Files with identical content
+    Duplicates,
+    /// Identical and near-identical directory trees
+    DuplicateDirs,
+    /// Junk files
+    Junk,
+    /// Developer tool caches
+    DevCaches,
+    /// Git repositories
+    GitRepos,
+    /// Every detector in one pass
+    All,
+}
+
+impl ScanJobKind {
+    /// Parse a scan type name as used by the frontend (e.g., `large_files`)
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name {
+            "large_files" => Ok(Self::LargeFiles),
+            "bloat" => Ok(Self::Bloat),
+            "duplicates" => Ok(Self::Duplicates),
+            "duplicate_dirs" => Ok(Self::DuplicateDirs),
+            "junk" => Ok(Self::Junk),
+            "dev_caches" => Ok(Self::DevCaches),
+            "git_repos" => Ok(Self::GitRepos),
+            "all" => Ok(Self::All),
+            other => Err(format!("Unknown scan type: {other}")),
+        }
+    }
+
+    /// Name of the scan type, the inverse of [`ScanJobKind::parse`]
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::LargeFiles => "large_files",
+            Self::Bloat => "bloat",
+            Self::Duplicates => "duplicates",
+            Self::DuplicateDirs => "duplicate_dirs",
+            Self::Junk => "junk",
+            Self::DevCaches => "dev_caches",
+            Self::GitRepos => "git_repos",
+            Self::All => "all",
+        }
+    }
+
+    /// Detectors the unified scan runs for this scan type
+    #[must_use]
+    pub const fn detectors(self) -> ScanDetectors {
+        let none = ScanDetectors::none();
+        match self {
+            Self::LargeFiles => ScanDetectors {
+                large_files: true,
+                ..none
+            },
+            Self::Bloat => ScanDetectors {
+                bloat: true,
+                ..none
+            },
+            Self::Duplicates => ScanDetectors {
+                duplicates: true,
+                ..none
+            },
+            Self::DuplicateDirs => ScanDetectors {
+                duplicate_dirs: true,
+                ..none
+            },
+            Self::Junk => ScanDetectors { junk: true, ..none },
+            Self::DevCaches => ScanDetectors {
+                dev_caches: true,
+                ..none
+            },
+            Self::GitRepos => ScanDetectors {
+                git_repos: true,
+                ..none
+            },
+            Self::All => ScanDetectors::all(),
+        }
+    }
+
+    /// Whether the scan hashes file contents and benefits from the hash cache
+    #[must_use]
+    pub const fn hashes_files(self) -> bool {
+        matches!(self, Self::Duplicates | Self::DuplicateDirs | Self::All)
+    }
+}
+
+/// Lifecycle state of a scan job
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ScanJobStatus {
+    /// The scan is still walking or analyzing
+    Running,
+    /// The scan finished and its result is available
+    Completed,
+    /// The scan stopped with an error
+    Failed,
+    /// The scan was cancelled by the user; the partial result is available
+    Cancelled,
+    /// The app quit before the scan finished
+    Interrupted,
+}
+
+impl ScanJobStatus {
+    /// Name of the status as stored and serialized
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Running => "running",
+            Self::Completed => "completed",
+            Self::Failed => "failed",
+            Self::Cancelled => "cancelled",
+            Self::Interrupted => "interrupted",
+        }
+    }
+
+    /// Parse a stored status name, treating unknown names as interrupted
+    #[must_use]
+    pub fn parse(name: &str) -> Self {
+        match name {
+            "running" => Self::Running,
+            "completed" => Self::Completed,
+            "failed" => Self::Failed,
+            "cancelled" => Self::Cancelled,
+            _ => Self::Interrupted,
+        }
+    }
+}
+
+/// State of a scan job, without its result
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ScanJobInfo {
+    /// Unique job ID, also accepted by `cancel_scan`
+    pub id: String,
+    /// Scan type name (see [`ScanJobKind::as_str`])
+    pub scan_type: String,
+    /// Scanned root directory
+    pub root: String,
+    /// Current lifecycle state
+    pub status: ScanJobStatus,
+    /// When the job started (RFC 3339)
+    pub started_at: String,
+    /// When the job stopped (RFC 3339), if it has
+    pub finished_at: Option<String>,
+    /// Why the job failed or was interrupted
+    pub error: Option<String>,
+    /// Latest progress; for finished jobs, the totals at the end of the scan
+    pub progress: Option<ScanProgress>,
+}
+
+// ============================================================================
+// Job Manager
+// ============================================================================
+
+/// A job known to this process
+struct JobEntry {
+    info: ScanJobInfo,
+    /// Present while the job runs
+    monitor: Option<Arc<ScanMonitor>>,
+    /// Kept in memory only when it could not be stored
+    result: Option<serde_json::Value>,
+}
+
+/// Runs scans in the background and keeps their outcomes
+pub struct ScanJobs {
+    jobs: Arc<Mutex<HashMap<String, JobEntry>>>,
+    db_path: Option<PathBuf>,
+}
+
+impl ScanJobs {
+    /// Create a job manager that stores jobs and results in `db_path`.
+    ///
+    /// Jobs left running by a previous process are marked interrupted. Without a
+    /// database (or if it cannot be opened), results are only kept in memory.
+    #[must_use]
+    pub fn new(db_path: Option<PathBuf>) -> Self {
+        let db_path = db_path.filter(|path| match open_store(path) {
+            Ok(store) => {
+                match store.mark_interrupted() {
+                    Ok(0) => {}
+                    Ok(count) => log::info!("Marked {count} unfinished scan jobs as interrupted"),
+                    Err(e) => log::warn!("Failed to mark unfinished scan jobs: {e}"),
+                }
+                true
+            }
+            Err(e) => {
+                log::warn!("Scan job results will not be persisted: {e}");
+                false
+            }
+        });
+
+        Self {
+            jobs: Arc::new(Mutex::new(HashMap::new())),
+            db_path,
+        }
+    }
+
+    /// Start a scan of `root` on a background thread.
+    ///
+    /// **Parameters:**
+    /// - `kind` - Which scan to run; its detectors replace those in `options`
+    /// - `root` - Validated directory to scan
+    /// - `options` - Traversal options (symlinks, one filesystem, thresholds, hash cache)
+    /// - `listener` - Receives progress and result chunks every tick, then the final state
+    ///
+    /// **Behavior:**
+    /// - Progress percentage and ETA are estimated from the bytes processed by the
+    ///   last completed job over the same root, when there is one
+    /// - Large files, junk and caches are reported in the first chunk after they
+    ///   are found; bloat and cache directories once the walk has left them
+    /// - [`ScanJobs::cancel`] stops the scan at the next entry; what was found
+    ///   before that is kept as the job's result
+    ///
+    /// **Returns:** The job's initial state, including its ID
+    pub fn start(
+        &self,
+        kind: ScanJobKind,
+        root: PathBuf,
+        mut options: UnifiedScanOptions,
+        listener: ScanJobListener,
+    ) -> Result<ScanJobInfo, String> {
+        options.detectors = kind.detectors();
+        let root_display = root.to_string_lossy().into_owned();
+
+        let info = ScanJobInfo {
+            id: format!("scan_{}_{}", kind.as_str(), uuid::Uuid::new_v4().simple()),
+            scan_type: kind.as_str().to_string(),
+            root: root_display.clone(),
+            status: ScanJobStatus::Running,
+            started_at: chrono::Utc::now().to_rfc3339(),
+            finished_at: None,
+            error: None,
+            progress: None,
+        };
+
+        let monitor = Arc::new(ScanMonitor::streaming());
+        if let Some(store) = self.store() {
+            if let Ok(Some(total)) = store.last_bytes_processed(&root_display) {
+                monitor.progress.set_total_estimate(total);
+            }
+            if let Err(e) = store.save(&info, None) {
+                log::warn!("Failed to record scan job {}: {e}", info.id);
+            }
+        }
+
+        self.lock()?.insert(
+            info.id.clone(),
+            JobEntry {
+                info: info.clone(),
+                monitor: Some(Arc::clone(&monitor)),
+                result: None,
+            },
+        );
+
+        let worker = Worker {
+            jobs: Arc::clone(&self.jobs),
+            db_path: self.db_path.clone(),
+            job_id: info.id.clone(),
+            kind,
+            root,
+            options,
+            monitor,
+            listener,
+        };
+        let spawned = std::thread::Builder::new()
+            .name(format!("scan-job-{}", kind.as_str()))
+            .spawn(move || worker.run());
+
+        if let Err(e) = spawned {
+            self.lock()?.remove(&info.id);
+            return Err(format!("Failed to start scan job: {e}"));
+        }
+
+        log::info!(
+            "Started scan job {} ({}) in {}",
+            info.id,
+            info.scan_type,
+            info.root
+        );
+        Ok(info)
+    }
+
+    /// Cancel a running job
+    ///
+    /// **Returns:** true if the job was running, false if it is unknown or already finished
+    pub fn cancel(&self, job_id: &str) -> Result<bool, String> {
+        let monitor = self
+            .lock()?
+            .get(job_id)
+            .and_then(|entry| entry.monitor.clone());
+        if let Some(monitor) = &monitor {
+            monitor.cancel.cancel();
+        }
+        Ok(monitor.is_some())
+    }
+
+    /// Whether the job is known and still running
+    pub fn is_running(&self, job_id: &str) -> Result<bool, String> {
+        Ok(self
+            .lock()?
+            .get(job_id)
+            .is_some_and(|entry| entry.monitor.is_some()))
+    }
+
+    /// Current state of a job, including live progress while it runs
+    pub fn job(&self, job_id: &str) -> Result<Option<ScanJobInfo>, String> {
+        let known = self.lock()?.get(job_id).map(JobEntry::snapshot);
+        if known.is_some() {
+            return Ok(known);
+        }
+
+        self.store().map_or(Ok(None), |store| {
+            store
+                .job(job_id)
+                .map_err(|e| format!("Database error: {e}"))
+        })
+    }
+
+    /// Result of a completed or cancelled job, shaped like the matching blocking
+    /// scan command
+    ///
+    /// **Returns:** The result, or `None` if the job is unknown, still running or failed
+    pub fn result(&self, job_id: &str) -> Result<Option<serde_json::Value>, String> {
+        let kept = self
+            .lock()?
+            .get(job_id)
+            .and_then(|entry| entry.result.clone());
+        if kept.is_some() {
+            return Ok(kept);
+        }
+
+        let Some(store) = self.store() else {
+            return Ok(None);
+        };
+        store
+            .result(job_id)
+            .map_err(|e| format!("Database error: {e}"))?
+            .map(|json| {
+                serde_json::from_str(&json).map_err(|e| format!("Corrupt scan result: {e}"))
+            })
+            .transpose()
+    }
+
+    /// Jobs of this and previous sessions, newest first
+    pub fn list(&self, limit: usize) -> Result<Vec<ScanJobInfo>, String> {
+        let mut jobs: Vec<ScanJobInfo> = self.lock()?.values().map(JobEntry::snapshot).collect();
+
+        if let Some(store) = self.store() {
+            let stored = store
+                .list(limit)
+                .map_err(|e| format!("Database error: {e}"))?;
+            for info in stored {
+                if !jobs.iter().any(|job| job.id == info.id) {
+                    jobs.push(info);
+                }
+            }
+        }
+
+        jobs.sort_by(|a, b| b.started_at.cmp(&a.started_at));
+        jobs.truncate(limit);
+        Ok(jobs)
+    }
+
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, HashMap<String, JobEntry>>, String> {
+        self.jobs
+            .lock()
+            .map_err(|e| format!("Failed to acquire scan job lock: {e}"))
+    }
+
+    fn store(&self) -> Option<ScanJobStore> {
+        let path = self.db_path.as_deref()?;
+        open_store(path)
+            .map_err(|e| log::warn!("Failed to open scan job database: {e}"))
+            .ok()
+    }
+}
+
+impl JobEntry {
+    fn snapshot(&self) -> ScanJobInfo {
+        let mut info = self.info.clone();
+        if let Some(monitor) = &self.monitor {
+            info.progress = Some(monitor.snapshot());
+        }
+        info
+    }
+}
+
+fn open_store(path: &Path) -> Result<ScanJobStore, String> {
+    ScanJobStore::new(&path.to_string_lossy()).map_err(|e| format!("Database error: {e}"))
+}
+
+// ============================================================================
+// Worker
+// ============================================================================
+
+/// Everything a background job needs, moved onto its thread
+struct Worker {
+    jobs: Arc<Mutex<HashMap<String, JobEntry>>>,
+    db_path: Option<PathBuf>,
+    job_id: String,
+    kind: ScanJobKind,
+    root: PathBuf,
+    options: UnifiedScanOptions,
+    monitor: Arc<ScanMonitor>,
+    listener: ScanJobListener,
+}
+
+impl Worker {
+    fn run(self) {
+        let (done_tx, done_rx) = mpsc::channel::<()>();
+        let worker = &self;
+
+        let outcome = std::thread::scope(|scope| {
+            scope.spawn(move || {
+                while done_rx.recv_timeout(PROGRESS_INTERVAL) == Err(RecvTimeoutError::Timeout) {
+                    if let Some(info) = worker.current() {
+                        (worker.listener)(ScanJobEvent::Progress(&info));
+                    }
+                    worker.send_chunk();
+                }
+            });
+
+            let outcome =
+                scan_all_monitored(&self.root, &self.options, &self.monitor).and_then(|results| {
+                    let cancelled = results.cancelled;
+                    select_result(self.kind, results).map(|result| (result, cancelled))
+                });
+            drop(done_tx);
+            outcome
+        });
+
+        self.send_chunk();
+        self.finish(outcome);
+    }
+
+    fn send_chunk(&self) {
+        if let Some(chunk) = self.monitor.take_chunk(&self.job_id) {
+            (self.listener)(ScanJobEvent::Results(&chunk));
+        }
+    }
+
+    fn current(&self) -> Option<ScanJobInfo> {
+        self.jobs
+            .lock()
+            .ok()?
+            .get(&self.job_id)
+            .map(JobEntry::snapshot)
+    }
+
+    fn finish(self, outcome: Result<(serde_json::Value, bool), String>) {
+        let mut info = self.current().unwrap_or_else(|| ScanJobInfo {
+            id: self.job_id.clone(),
+            scan_type: self.kind.as_str().to_string(),
+            root: self.root.to_string_lossy().into_owned(),
+            status: ScanJobStatus::Running,
+            started_at: chrono::Utc::now().to_rfc3339(),
+            finished_at: None,
+            error: None,
+            progress: None,
+        });
+        info.finished_at = Some(chrono::Utc::now().to_rfc3339());
+        info.progress = Some(self.monitor.snapshot());
+
+        let mut result = match outcome {
+            Ok((result, cancelled)) => {
+                info.status = if cancelled {
+                    ScanJobStatus::Cancelled
+                } else {
+                    ScanJobStatus::Completed
+                };
+                Some(result)
+            }
+            Err(e) => {
+                info.status = ScanJobStatus::Failed;
+                info.error = Some(e);
+                None
+            }
+        };
+
+        let stored = self.db_path.as_deref().map(open_store).map(|store| {
+            let json = result.as_ref().map(serde_json::Value::to_string);
+            store.and_then(|store| {
+                store
+                    .save(&info, json.as_deref())
+                    .map_err(|e| format!("Database error: {e}"))
+            })
+        });
+        match stored {
+            Some(Ok(())) => result = None,
+            Some(Err(e)) => log::warn!("Failed to store scan job {}: {e}", info.id),
+            None => {}
+        }
+
+        log::info!("Scan job {} finished: {}", info.id, info.status.as_str());
+
+        if let Ok(mut jobs) = self.jobs.lock() {
+            jobs.insert(
+                info.id.clone(),
+                JobEntry {
+                    info: info.clone(),
+                    monitor: None,
+                    result,
+                },
+            );
+        }
+        (self.listener)(ScanJobEvent::Finished(&info));
+    }
+}
+
+/// The part of the combined results the job's scan type reports
+fn select_result(
+    kind: ScanJobKind,
+    results: crate::models::CombinedScanResults,
+) -> Result<serde_json::Value, String> {
+    let value = match kind {
+        ScanJobKind::LargeFiles => serde_json::to_value(results.large_files),
+        ScanJobKind::Bloat => serde_json::to_value(results.bloat),
+        ScanJobKind::Duplicates => serde_json::to_value(results.duplicates),
+        ScanJobKind::DuplicateDirs => serde_json::to_value(results.duplicate_dirs),
+        ScanJobKind::Junk => serde_json::to_value(results.junk),
+        ScanJobKind::DevCaches => serde_json::to_value(results.dev_caches),
+        ScanJobKind::GitRepos => serde_json::to_value(results.git_repos),
+        ScanJobKind::All => serde_json::to_value(results),
+    };
+    value.map_err(|e| format!("Failed to serialize scan result: {e}"))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::time::Instant;
+    use tempfile::{NamedTempFile, TempDir};
+
+    fn wait_until_finished(jobs: &ScanJobs, job_id: &str) -> ScanJobInfo {
+        let deadline = Instant::now() + Duration::from_secs(30);
+        loop {
+            let info = jobs.job(job_id).unwrap().expect("job should be known");
+            if info.status != ScanJobStatus::Running {
+                return info;
+            }
+            assert!(Instant::now() < deadline, "scan job did not finish");
+            std::thread::sleep(Duration::from_millis(20));
+        }
+    }
+
+    fn noop_listener() -> ScanJobListener {
+        Arc::new(|_: ScanJobEvent<'_>| {})
+    }
+
+    #[test]
+    fn test_parse_round_trips_scan_types() {
+        for name in [
+            "large_files",
+            "bloat",
+            "duplicates",
+            "duplicate_dirs",
+            "junk",
+            "dev_caches",
+            "git_repos",
+            "all",
+        ] {
+            assert_eq!(ScanJobKind::parse(name).unwrap().as_str(), name);
+        }
+        assert!(ScanJobKind::parse("everything").is_err());
+    }
+
+    #[test]
+    fn test_completed_job_result_survives_restart() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        fs::write(temp_dir.path().join("big.bin"), vec![1u8; 4096]).unwrap();
+        let db_file = NamedTempFile::new().unwrap();
+
+        let finished = Arc::new(Mutex::new(Vec::new()));
+        let streamed = Arc::new(Mutex::new(Vec::new()));
+        let listener: ScanJobListener = {
+            let finished = Arc::clone(&finished);
+            let streamed = Arc::clone(&streamed);
+            Arc::new(move |event: ScanJobEvent<'_>| match event {
+                ScanJobEvent::Results(chunk) => {
+                    assert!(finished.lock().unwrap().is_empty());
+                    streamed
+                        .lock()
+                        .unwrap()
+                        .extend(chunk.large_files.iter().map(|f| f.path.clone()));
+                }
+                ScanJobEvent::Finished(info) => finished.lock().unwrap().push(info.id.clone()),
+                ScanJobEvent::Progress(_) => {}
+            })
+        };
+
+        let jobs = ScanJobs::new(Some(db_file.path().to_path_buf()));
+        let options = UnifiedScanOptions::default().with_min_large_file_bytes(1024);
+        let job = jobs
+            .start(
+                ScanJobKind::LargeFiles,
+                temp_dir.path().to_path_buf(),
+                options,
+                listener,
+            )
+            .unwrap();
+
+        let info = wait_until_finished(&jobs, &job.id);
+        assert_eq!(info.status, ScanJobStatus::Completed);
+        assert_eq!(info.progress.unwrap().files_scanned, 1);
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while finished.lock().unwrap().is_empty() && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(*finished.lock().unwrap(), vec![job.id.clone()]);
+        assert_eq!(
+            *streamed.lock().unwrap(),
+            vec![temp_dir
+                .path()
+                .join("big.bin")
+                .to_string_lossy()
+                .into_owned()]
+        );
+
+        let reopened = ScanJobs::new(Some(db_file.path().to_path_buf()));
+        let result = reopened.result(&job.id).unwrap().expect("stored result");
+        assert_eq!(result.as_array().map(Vec::len), Some(1));
+        assert_eq!(
+            reopened.list(10).unwrap().first().map(|info| info.status),
+            Some(ScanJobStatus::Completed)
+        );
+    }
+
+    #[test]
+    fn test_cancelled_job_keeps_partial_result() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        for i in 0..200 {
+            fs::write(temp_dir.path().join(format!("f{i}")), b"data").unwrap();
+        }
+
+        let jobs = ScanJobs::new(None);
+        let job = jobs
+            .start(
+                ScanJobKind::All,
+                temp_dir.path().to_path_buf(),
+                UnifiedScanOptions::default(),
+                noop_listener(),
+            )
+            .unwrap();
+        let was_running = jobs.cancel(&job.id).unwrap();
+
+        let info = wait_until_finished(&jobs, &job.id);
+        let cancelled = info.status == ScanJobStatus::Cancelled;
+        // Otherwise the scan won the race against cancellation
+        assert!(cancelled || info.status == ScanJobStatus::Completed);
+        assert!(was_running || !cancelled);
+        let result = jobs.result(&job.id).unwrap().expect("partial result");
+        assert_eq!(result["cancelled"], serde_json::Value::Bool(cancelled));
+        assert!(!jobs.cancel(&job.id).unwrap());
+    }
+
+    #[test]
+    fn test_unfinished_jobs_are_interrupted_on_restart() {
+        let db_file = NamedTempFile::new().unwrap();
+        let store = ScanJobStore::new(db_file.path().to_str().unwrap()).unwrap();
+        let info = ScanJobInfo {
+            id: "scan_all_stale".to_string(),
+            scan_type: "all".to_string(),
+            root: "/tmp".to_string(),
+            status: ScanJobStatus::Running,
+            started_at: chrono::Utc::now().to_rfc3339(),
+            finished_at: None,
+            error: None,
+            progress: None,
+        };
+        store.save(&info, None).unwrap();
+
+        let jobs = ScanJobs::new(Some(db_file.path().to_path_buf()));
+        let stale = jobs.job(&info.id).unwrap().unwrap();
+        assert_eq!(stale.status, ScanJobStatus::Interrupted);
+        assert!(jobs.result(&info.id).unwrap().is_none());
+    }
+}
@@ -2,14 +2,22 @@
 //!
 //! This module provides the ability to schedule automatic scans at regular intervals
 //! using cron expressions or simple interval-based scheduling.
+//!
+//! Scheduled scans run the unified scan with the schedule's [`ScanResourceLimits`],
+//! so they can be kept from saturating the disk and CPU while the machine is in use.
 
 use crate::error::{ScannerError, ScannerResult};
+use crate::models::{CombinedScanResults, ScanResourceLimits};
+use crate::utils::path::validate_scan_path;
+use crate::utils::detector_rules::DetectorRules;
+use crate::utils::unified_scan::{scan_all, ScanDetectors, UnifiedScanOptions};
 use chrono::{DateTime, Local, Duration};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
+use tokio::task;
 use tokio::time::{sleep, interval};
 use uuid::Uuid;
 
@@ -25,6 +33,9 @@ pub struct ScheduleConfig {
     pub last_run: Option<DateTime<Local>>,
     pub next_run: Option<DateTime<Local>>,
     pub notification_enabled: bool,
+    /// Thread, hashing read-rate and priority limits for the scheduled scans
+    #[serde(default)]
+    pub limits: ScanResourceLimits,
 }
 
 /// Types of scans that can be scheduled
@@ -358,28 +369,27 @@ impl ScanScheduler {
         }
     }
     
-    /// Execute a scheduled scan
+    /// Execute a scheduled scan in one pass over the schedule's path, within its resource limits
     async fn execute_scheduled_scan(schedule: &ScheduleConfig) -> ScheduledScanResult {
         let started_at = Local::now();
-        let mut findings_summary = HashMap::new();
-        let mut error = None;
-        
-        // TODO: Actually execute the scans based on schedule.scan_types
-        // For now, we'll simulate the scan
-        for scan_type in &schedule.scan_types {
-            match scan_type {
-                ScanType::LargeFiles => {
-                    findings_summary.insert("large_files".to_string(), 42);
-                }
-                ScanType::Duplicates => {
-                    findings_summary.insert("duplicates".to_string(), 15);
-                }
-                ScanType::JunkFiles => {
-                    findings_summary.insert("junk_files".to_string(), 123);
-                }
-                _ => {}
+        let scan_types = schedule.scan_types.clone();
+        let options = UnifiedScanOptions::new(scan_detectors(&scan_types), false)
+            .with_limits(schedule.limits);
+        let scan_path = schedule.scan_path.clone();
+        
+        let outcome = task::spawn_blocking(move || {
+            let root = validate_scan_path(&scan_path)?;
+            let options = options.with_rules(Arc::new(DetectorRules::load_for_root(&root)));
+            scan_all(&root, &options)
+        }).await.unwrap_or_else(|e| Err(format!("Task failed: {}", e)));
+        
+        let (findings_summary, error) = match outcome {
+            Ok(results) => (summarize_findings(&scan_types, &results), None),
+            Err(e) => {
+                log::error!("Scheduled scan '{}' failed: {}", schedule.name, e);
+                (HashMap::new(), Some(e))
             }
-        }
+        };
         
         ScheduledScanResult {
             schedule_id: schedule.id.clone(),
@@ -413,6 +423,45 @@ impl ScanScheduler {
     }
 }
 
+/// Detectors that produce the findings of `scan_types`
+fn scan_detectors(scan_types: &[ScanType]) -> ScanDetectors {
+    let mut detectors = ScanDetectors::none();
+    for scan_type in scan_types {
+        match scan_type {
+            ScanType::LargeFiles => detectors.large_files = true,
+            ScanType::Duplicates => detectors.duplicates = true,
+            ScanType::JunkFiles => detectors.junk = true,
+            ScanType::DevCaches => detectors.dev_caches = true,
+            ScanType::GitRepos => detectors.git_repos = true,
+            ScanType::NodeModules => detectors.bloat = true,
+        }
+    }
+    detectors
+}
+
+/// Number of findings per scan type
+fn summarize_findings(scan_types: &[ScanType], results: &CombinedScanResults) -> HashMap<String, usize> {
+    scan_types
+        .iter()
+        .map(|scan_type| match scan_type {
+            ScanType::LargeFiles => ("large_files", results.large_files.len()),
+            ScanType::Duplicates => ("duplicates", results.duplicates.len()),
+            ScanType::JunkFiles => ("junk_files", results.junk.iter().map(|c| c.file_count).sum()),
+            ScanType::DevCaches => ("dev_caches", results.dev_caches.iter().map(|c| c.entry_count).sum()),
+            ScanType::GitRepos => ("git_repos", results.git_repos.len()),
+            ScanType::NodeModules => (
+                "node_modules",
+                results.bloat
+                    .iter()
+                    .filter(|c| c.category_id == "node_modules")
+                    .map(|c| c.entries.len())
+                    .sum(),
+            ),
+        })
+        .map(|(key, count)| (key.to_string(), count))
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -436,6 +485,7 @@ mod tests {
             last_run: None,
             next_run: None,
             notification_enabled: true,
+            limits: ScanResourceLimits::default(),
         };
         
         let id = ScanScheduler::create_schedule(config).unwrap();
@@ -444,4 +494,47 @@ mod tests {
         let retrieved = ScanScheduler::get_schedule(&id).unwrap();
         assert_eq!(retrieved.name, "Test Schedule");
     }
+    
+    #[test]
+    fn test_limited_scan_summarizes_findings() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("a.bin"), vec![1u8; 4096]).unwrap();
+        fs::write(temp_dir.path().join("b.bin"), vec![1u8; 4096]).unwrap();
+        fs::write(temp_dir.path().join(".DS_Store"), b"junk").unwrap();
+        
+        let scan_types = vec![ScanType::Duplicates, ScanType::JunkFiles];
+        let limits = ScanResourceLimits {
+            max_threads: Some(1),
+            max_read_bytes_per_sec: Some(1024 * 1024),
+            low_io_priority: true,
+            low_cpu_priority: true,
+        };
+        let options = UnifiedScanOptions::new(scan_detectors(&scan_types), false).with_limits(limits);
+        let results = scan_all(temp_dir.path(), &options).unwrap();
+        
+        let summary = summarize_findings(&scan_types, &results);
+        assert_eq!(summary.get("duplicates"), Some(&1));
+        assert_eq!(summary.get("junk_files"), Some(&1));
+        assert!(results.large_files.is_empty());
+    }
+    
+    #[tokio::test]
+    async fn test_scheduled_scan_reports_invalid_path() {
+        let config = ScheduleConfig {
+            id: "missing".to_string(),
+            name: "Missing path".to_string(),
+            scan_path: "/nonexistent/scheduled/scan".to_string(),
+            scan_types: vec![ScanType::LargeFiles],
+            schedule: ScheduleType::Interval { minutes: 60 },
+            enabled: false,
+            last_run: None,
+            next_run: None,
+            notification_enabled: false,
+            limits: ScanResourceLimits::default(),
+        };
+        
+        let result = ScanScheduler::execute_scheduled_scan(&config).await;
+        assert!(!result.success);
+        assert!(result.error.is_some());
+    }
 }
\ No newline at end of file
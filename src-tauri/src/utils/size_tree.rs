@@ -0,0 +1,442 @@
+//! Hierarchical size tree for treemap and drill-down views.
+//!
+//! [`SizeTree::build`] walks a root once and keeps every directory and file in
+//! memory with cumulative apparent size, allocated size, file count and
+//! directory count, like `ncdu`. The children of any directory can then be
+//! listed, largest first, without touching the disk again. A tree can also be
+//! saved to a [`SizeTreeStore`](crate::database::size_tree::SizeTreeStore) so
+//! drill-down keeps working after the app restarts.
+//!
+//! A hardlinked file is counted at the first path the walk reaches, as `du`
+//! does, so cumulative sizes never count an inode twice.
+
+use crate::models::{SizeTreeChildren, SizeTreeNode, SkippedMount};
+use crate::utils::disk_usage::FileUsage;
+use crate::utils::mount_boundary::MountBoundary;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Cumulative totals of a size tree node
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NodeTotals {
+    /// Apparent bytes, each hardlinked file counted once
+    pub apparent: u64,
+    /// Bytes allocated on disk, each hardlinked file counted once
+    pub allocated: u64,
+    /// Files at or below the node
+    pub file_count: u64,
+    /// Directories below the node
+    pub dir_count: u64,
+}
+
+impl NodeTotals {
+    /// Describe a node for the frontend
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn to_node(
+        &self,
+        path: String,
+        name: String,
+        is_dir: bool,
+        child_count: usize,
+    ) -> SizeTreeNode {
+        SizeTreeNode {
+            path,
+            name,
+            is_dir,
+            size_mb: self.apparent as f32 / 1_048_576.0,
+            allocated_mb: self.allocated as f32 / 1_048_576.0,
+            file_count: self.file_count,
+            dir_count: self.dir_count,
+            child_count,
+        }
+    }
+}
+
+impl std::ops::AddAssign for NodeTotals {
+    fn add_assign(&mut self, other: Self) {
+        self.apparent += other.apparent;
+        self.allocated += other.allocated;
+        self.file_count += other.file_count;
+        self.dir_count += other.dir_count;
+    }
+}
+
+/// Build a page of children from the parent and its children sorted largest first
+#[must_use]
+#[allow(clippy::cast_precision_loss)]
+pub fn page_children(
+    parent: SizeTreeNode,
+    parent_bytes: u64,
+    children: Vec<(SizeTreeNode, u64)>,
+) -> SizeTreeChildren {
+    let listed_bytes: u64 = children.iter().map(|(_, bytes)| bytes).sum();
+    let omitted_count = parent.child_count.saturating_sub(children.len());
+
+    SizeTreeChildren {
+        parent,
+        children: children.into_iter().map(|(node, _)| node).collect(),
+        omitted_count,
+        omitted_size_mb: parent_bytes.saturating_sub(listed_bytes) as f32 / 1_048_576.0,
+    }
+}
+
+#[derive(Debug)]
+struct TreeNode {
+    name: String,
+    parent: Option<usize>,
+    is_dir: bool,
+    totals: NodeTotals,
+    children: Vec<usize>,
+}
+
+/// In-memory directory tree with cumulative sizes
+#[derive(Debug)]
+pub struct SizeTree {
+    root: PathBuf,
+    /// Nodes in walk order: the root first, every child after its parent
+    nodes: Vec<TreeNode>,
+    skipped_mounts: Vec<SkippedMount>,
+}
+
+impl SizeTree {
+    /// Walk `root` and record every directory and regular file below it.
+    ///
+    /// **Parameters:**
+    /// - `root` - Root directory path to scan
+    /// - `follow_symlinks` - Whether to follow symbolic links
+    /// - `one_file_system` - Whether to skip mount points below the root
+    ///
+    /// **Returns:** The tree, or an error if `root` cannot be read
+    pub fn build(
+        root: &Path,
+        follow_symlinks: bool,
+        one_file_system: bool,
+    ) -> Result<Self, String> {
+        std::fs::metadata(root).map_err(|e| format!("Cannot read {}: {e}", root.display()))?;
+
+        let mut boundary = MountBoundary::new(root, one_file_system);
+        let mut nodes: Vec<TreeNode> = Vec::new();
+        let mut open: Vec<usize> = Vec::new();
+        let mut seen_inodes = HashSet::new();
+        let mut error_count = 0usize;
+
+        log::info!(
+            "Building size tree for: {} (follow_symlinks={follow_symlinks}, one_file_system={one_file_system})",
+            root.display()
+        );
+
+        let entries = WalkDir::new(root)
+            .follow_links(follow_symlinks)
+            .into_iter()
+            .filter_entry(|entry| boundary.admits(entry));
+
+        for entry_result in entries {
+            let entry = match entry_result {
+                Ok(e) => e,
+                Err(err) => {
+                    error_count += 1;
+                    log::debug!("Error walking directory (#{error_count} errors total): {err}");
+                    continue;
+                }
+            };
+
+            let depth = entry.depth();
+            open.truncate(depth);
+            if open.len() != depth {
+                continue;
+            }
+
+            let is_dir = entry.file_type().is_dir();
+            let totals = if is_dir {
+                NodeTotals::default()
+            } else if entry.file_type().is_file() {
+                match entry.metadata() {
+                    Ok(metadata) => {
+                        let usage = FileUsage::from_metadata(&metadata);
+                        let first_link =
+                            !usage.is_hardlinked() || seen_inodes.insert((usage.dev, usage.inode));
+                        NodeTotals {
+                            apparent: if first_link { usage.apparent } else { 0 },
+                            allocated: if first_link { usage.allocated } else { 0 },
+                            file_count: 1,
+                            dir_count: 0,
+                        }
+                    }
+                    Err(err) => {
+                        error_count += 1;
+                        log::debug!("Cannot read metadata for {}: {err}", entry.path().display());
+                        continue;
+                    }
+                }
+            } else {
+                continue;
+            };
+
+            let index = nodes.len();
+            let parent = open.last().copied();
+            if let Some(parent) = parent {
+                nodes[parent].children.push(index);
+            }
+            let name = if depth == 0 {
+                root.to_string_lossy().into_owned()
+            } else {
+                entry.file_name().to_string_lossy().into_owned()
+            };
+            nodes.push(TreeNode {
+                name,
+                parent,
+                is_dir,
+                totals,
+                children: Vec::new(),
+            });
+            if is_dir {
+                open.push(index);
+            }
+        }
+
+        // Children always follow their parent, so a reverse pass sums bottom-up
+        for index in (1..nodes.len()).rev() {
+            let mut totals = nodes[index].totals;
+            if nodes[index].is_dir {
+                totals.dir_count += 1;
+            }
+            if let Some(parent) = nodes[index].parent {
+                nodes[parent].totals += totals;
+            }
+        }
+
+        if error_count > 0 {
+            log::info!("Size tree finished with {error_count} inaccessible entries");
+        }
+
+        Ok(Self {
+            root: root.to_path_buf(),
+            nodes,
+            skipped_mounts: boundary.into_skipped(),
+        })
+    }
+
+    /// Root path the tree was built from
+    #[must_use]
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Number of directories and files in the tree
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Whether the tree holds no nodes
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Mount points the walk did not enter in one-filesystem mode
+    #[must_use]
+    pub fn skipped_mounts(&self) -> &[SkippedMount] {
+        &self.skipped_mounts
+    }
+
+    /// Whether `path` is the root or lies below it
+    #[must_use]
+    pub fn contains(&self, path: &Path) -> bool {
+        path.starts_with(&self.root)
+    }
+
+    /// Describe the node at `path`, or `None` if it is not in the tree
+    #[must_use]
+    pub fn node(&self, path: &Path) -> Option<SizeTreeNode> {
+        self.find(path).map(|index| self.describe(index, path))
+    }
+
+    /// Children of the directory at `path`, largest first.
+    ///
+    /// **Parameters:**
+    /// - `path` - Directory in the tree (the root or any path below it)
+    /// - `limit` - Maximum number of children to return; the rest are summarized
+    ///
+    /// **Returns:** The directory and its children, or `None` if `path` is not in the tree
+    #[must_use]
+    pub fn children(&self, path: &Path, limit: Option<usize>) -> Option<SizeTreeChildren> {
+        let index = self.find(path)?;
+        let node = &self.nodes[index];
+
+        let mut children = node.children.clone();
+        children.sort_by(|&a, &b| {
+            let (a, b) = (&self.nodes[a], &self.nodes[b]);
+            b.totals
+                .apparent
+                .cmp(&a.totals.apparent)
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        children.truncate(limit.unwrap_or(usize::MAX));
+
+        let children = children
+            .into_iter()
+            .map(|child| {
+                let child_path = path.join(&self.nodes[child].name);
+                (
+                    self.describe(child, &child_path),
+                    self.nodes[child].totals.apparent,
+                )
+            })
+            .collect();
+
+        Some(page_children(
+            self.describe(index, path),
+            node.totals.apparent,
+            children,
+        ))
+    }
+
+    /// Every node with its path, parent path, name, kind, totals and child count,
+    /// parents before children
+    #[must_use]
+    pub fn flatten(&self) -> Vec<FlatNode<'_>> {
+        let mut paths: Vec<PathBuf> = Vec::with_capacity(self.nodes.len());
+        let mut flat = Vec::with_capacity(self.nodes.len());
+
+        for node in &self.nodes {
+            let parent_path = node.parent.map(|parent| paths[parent].clone());
+            let path = parent_path
+                .as_ref()
+                .map_or_else(|| self.root.clone(), |parent| parent.join(&node.name));
+            paths.push(path.clone());
+            flat.push(FlatNode {
+                path,
+                parent_path,
+                name: &node.name,
+                is_dir: node.is_dir,
+                totals: node.totals,
+                child_count: node.children.len(),
+            });
+        }
+
+        flat
+    }
+
+    fn find(&self, path: &Path) -> Option<usize> {
+        let relative = path.strip_prefix(&self.root).ok()?;
+        if self.nodes.is_empty() {
+            return None;
+        }
+        let mut index = 0;
+
+        for component in relative.components() {
+            let name = component.as_os_str().to_string_lossy();
+            index = self.nodes[index]
+                .children
+                .iter()
+                .copied()
+                .find(|&child| self.nodes[child].name == name)?;
+        }
+
+        Some(index)
+    }
+
+    fn describe(&self, index: usize, path: &Path) -> SizeTreeNode {
+        let node = &self.nodes[index];
+        node.totals.to_node(
+            path.to_string_lossy().into_owned(),
+            node.name.clone(),
+            node.is_dir,
+            node.children.len(),
+        )
+    }
+}
+
+/// A size tree node with its full path, as stored by a size tree store
+#[derive(Debug)]
+pub struct FlatNode<'a> {
+    /// Absolute path of the node
+    pub path: PathBuf,
+    /// Absolute path of the parent, `None` for the root
+    pub parent_path: Option<PathBuf>,
+    /// File or directory name (the full path for the root)
+    pub name: &'a str,
+    /// Whether the node is a directory
+    pub is_dir: bool,
+    /// Cumulative totals
+    pub totals: NodeTotals,
+    /// Number of direct children
+    pub child_count: usize,
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn setup_tree() -> TempDir {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let base = temp_dir.path();
+        fs::create_dir_all(base.join("big/inner")).unwrap();
+        fs::create_dir_all(base.join("small")).unwrap();
+        fs::create_dir_all(base.join("empty")).unwrap();
+        fs::write(base.join("big/inner/a.bin"), vec![1u8; 300_000]).unwrap();
+        fs::write(base.join("big/b.bin"), vec![1u8; 200_000]).unwrap();
+        fs::write(base.join("small/c.txt"), vec![1u8; 1000]).unwrap();
+        fs::write(base.join("top.txt"), vec![1u8; 50_000]).unwrap();
+        temp_dir
+    }
+
+    #[test]
+    fn test_cumulative_totals_and_sorted_children() {
+        let temp_dir = setup_tree();
+        let base = temp_dir.path();
+        let tree = SizeTree::build(base, false, false).unwrap();
+
+        let root = tree.node(base).unwrap();
+        assert_eq!(root.file_count, 4);
+        assert_eq!(root.dir_count, 4);
+        assert_eq!(root.child_count, 4);
+
+        let listing = tree.children(base, None).unwrap();
+        let names: Vec<&str> = listing.children.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["big", "top.txt", "small", "empty"]);
+        assert_eq!(listing.children[0].file_count, 2);
+        assert_eq!(listing.children[0].dir_count, 1);
+        assert_eq!(listing.omitted_count, 0);
+
+        let inner = tree.children(&base.join("big"), None).unwrap();
+        assert_eq!(
+            inner.children[0].path,
+            base.join("big/inner").to_string_lossy()
+        );
+        assert!(tree.children(&base.join("missing"), None).is_none());
+    }
+
+    #[test]
+    fn test_limit_summarizes_omitted_children() {
+        let temp_dir = setup_tree();
+        let base = temp_dir.path();
+        let tree = SizeTree::build(base, false, false).unwrap();
+
+        let listing = tree.children(base, Some(1)).unwrap();
+        assert_eq!(listing.children.len(), 1);
+        assert_eq!(listing.omitted_count, 3);
+        let expected_mb = 51_000.0 / 1_048_576.0;
+        assert!((listing.omitted_size_mb - expected_mb).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_hardlinks_counted_once() {
+        let temp_dir = setup_tree();
+        let base = temp_dir.path();
+        fs::hard_link(base.join("big/b.bin"), base.join("small/b-link.bin")).unwrap();
+        let tree = SizeTree::build(base, false, false).unwrap();
+
+        let root = tree.node(base).unwrap();
+        assert_eq!(root.file_count, 5);
+        let expected_mb = 551_000.0 / 1_048_576.0;
+        assert!((root.size_mb - expected_mb).abs() < 1e-4);
+        assert_eq!(tree.flatten().len(), tree.len());
+    }
+}
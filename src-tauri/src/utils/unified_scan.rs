@@ -0,0 +1,2050 @@
+//! Single-pass filesystem traversal shared by every scan type.
+//!
+//! Walking a large home directory is the dominant cost of a scan, so instead of
+//! each detector running its own `WalkDir`, this module walks the tree once and
+//! feeds every entry (with its metadata) to the enabled detectors:
+//! - Large file detection
+//! - Project bloat detection (node_modules, target, venv, etc.)
+//! - Duplicate candidate collection (hashed in stages after the walk)
+//! - Duplicate directory trees (fingerprinted from the recorded tree after the walk)
+//! - Junk file detection
+//! - Developer cache discovery
+//! - Git repository analysis
+//!
+//! Directory sizes for bloat, cache and `.git` entries are accumulated during
+//! the same walk using a stack of "open" matched directories, so no detector
+//! needs a second `dir_size` traversal. Sizes are accumulated with
+//! [`UsageAccumulator`], so every entry reports apparent, allocated and
+//! reclaimable bytes with hardlinks counted once.
+//!
+//! [`scan_all_indexed`] drives the same detectors from a persistent
+//! [`ScanIndex`], re-reading only directories whose metadata changed.
+//!
+//! With [`UnifiedScanOptions::one_file_system`] both walks stay on the root's
+//! device and report the mount points they did not enter.
+//!
+//! [`UnifiedScanOptions::limits`] bound the threads, hashing read rate and
+//! thread priorities of a scan through a [`ScanLimiter`].
+//!
+//! [`scan_all_monitored`] reports progress through a [`ScanMonitor`] and stops
+//! early when its cancellation token is set, returning what was found so far
+//! with [`CombinedScanResults::cancelled`] set. A streaming monitor also collects
+//! large files, bloat, junk and caches as they are found, to be taken in chunks
+//! while the scan runs.
+
+use crate::database::hash_cache::HashCache;
+use crate::database::scan_index::{DirStamp, IndexedDir, IndexedFile, ScanIndex};
+use crate::error::compare_f32_safe;
+use crate::models::{
+    BloatCategory, BloatEntry, CacheCategory, CacheEntry, CombinedScanResults, DuplicateScanStats,
+    GitEntry, GitRepository, JunkCategory, JunkFileEntry, LargeFileEntry, ScanOpts,
+    ScanResourceLimits, ScanResultsChunk,
+};
+use crate::utils::detector_rules::DetectorRules;
+use crate::utils::disk_usage::{DiskUsage, FileUsage, UsageAccumulator};
+use crate::utils::duplicate_dirs::{find_duplicate_dirs, DirTree};
+use crate::utils::duplicates::{find_duplicates, DuplicateCandidate, DUPLICATE_MIN_FILE_SIZE};
+use crate::utils::git_clones::group_clones;
+use crate::utils::git_objects::{largest_blobs, LARGE_BLOB_LIMIT};
+use crate::utils::git_storage::storage_entries;
+use crate::utils::mount_boundary::MountBoundary;
+use crate::utils::patterns::{
+    classify_bloat_dir, detect_junk_file, is_tracked_in_git, match_cache_pattern, BloatMatch,
+    CACHE_PATTERNS, CONFIDENCE_HIGH,
+};
+use crate::utils::project_activity::{now_secs, ProjectActivity, ProjectTracker};
+use crate::utils::resource_limits::{ReadThrottle, ScanLimiter};
+use crate::utils::scan_progress::{CancellationToken, ProgressTracker, ScanProgress};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use walkdir::WalkDir;
+
+/// Default large file threshold (1GB)
+pub const DEFAULT_LARGE_FILE_BYTES: u64 = 1024 * 1024 * 1024;
+
+/// Bloat directories at or below this size (MB) are not reported
+const BLOAT_MIN_SIZE_MB: f32 = 1.0;
+
+// ============================================================================
+// Scan Options
+// ============================================================================
+
+/// Selects which detectors consume the shared traversal
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct ScanDetectors {
+    /// Report files above the large file threshold
+    pub large_files: bool,
+    /// Report build artifact directories
+    pub bloat: bool,
+    /// Report files with identical content
+    pub duplicates: bool,
+    /// Report identical and near-identical directory trees
+    pub duplicate_dirs: bool,
+    /// Report junk files (system, editor, build leftovers)
+    pub junk: bool,
+    /// Report developer tool caches
+    pub dev_caches: bool,
+    /// Report Git repositories and their `.git` breakdown
+    pub git_repos: bool,
+}
+
+impl ScanDetectors {
+    /// Enable every detector
+    #[must_use]
+    pub const fn all() -> Self {
+        Self {
+            large_files: true,
+            bloat: true,
+            duplicates: true,
+            duplicate_dirs: true,
+            junk: true,
+            dev_caches: true,
+            git_repos: true,
+        }
+    }
+
+    /// Enable no detectors (combine with struct update syntax)
+    #[must_use]
+    pub const fn none() -> Self {
+        Self {
+            large_files: false,
+            bloat: false,
+            duplicates: false,
+            duplicate_dirs: false,
+            junk: false,
+            dev_caches: false,
+            git_repos: false,
+        }
+    }
+}
+
+/// Options for a unified scan
+#[derive(Debug, Clone)]
+pub struct UnifiedScanOptions {
+    /// Detectors to run during the walk
+    pub detectors: ScanDetectors,
+    /// Whether to follow symbolic links
+    pub follow_symlinks: bool,
+    /// Do not descend into directories on a different device than the root
+    pub one_file_system: bool,
+    /// Minimum size for the large file detector
+    pub min_large_file_bytes: u64,
+    /// Persistent content-hash cache consulted by the duplicate detectors
+    pub hash_cache_path: Option<PathBuf>,
+    /// Thread, read-rate and priority limits
+    pub limits: ScanResourceLimits,
+    /// Recommend deleting bloat only of projects inactive for more than this many days
+    pub stale_after_days: Option<u64>,
+    /// User-defined bloat, junk and cache rules, checked before the built-ins
+    pub rules: Arc<DetectorRules>,
+}
+
+impl UnifiedScanOptions {
+    /// Create options for the given detectors with default thresholds
+    #[must_use]
+    pub fn new(detectors: ScanDetectors, follow_symlinks: bool) -> Self {
+        Self {
+            detectors,
+            follow_symlinks,
+            one_file_system: false,
+            min_large_file_bytes: DEFAULT_LARGE_FILE_BYTES,
+            hash_cache_path: None,
+            limits: ScanResourceLimits::default(),
+            stale_after_days: None,
+            rules: Arc::new(DetectorRules::none()),
+        }
+    }
+
+    /// Create options for the given detectors from a frontend scan request
+    #[must_use]
+    pub fn from_scan_opts(detectors: ScanDetectors, opts: &ScanOpts) -> Self {
+        Self::new(detectors, opts.follow_symlinks)
+            .with_one_file_system(opts.one_file_system)
+            .with_min_large_file_bytes(opts.min_bytes.unwrap_or(DEFAULT_LARGE_FILE_BYTES))
+            .with_limits(opts.limits)
+            .with_stale_after_days(opts.stale_after_days)
+    }
+
+    /// Stay on the root's filesystem, skipping mount points below it
+    #[must_use]
+    pub const fn with_one_file_system(mut self, one_file_system: bool) -> Self {
+        self.one_file_system = one_file_system;
+        self
+    }
+
+    /// Set the large file threshold
+    #[must_use]
+    pub const fn with_min_large_file_bytes(mut self, min_bytes: u64) -> Self {
+        self.min_large_file_bytes = min_bytes;
+        self
+    }
+
+    /// Bound the scan's threads, hashing read rate and thread priorities
+    #[must_use]
+    pub const fn with_limits(mut self, limits: ScanResourceLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Rank bloat by project inactivity, recommending deletion only for
+    /// projects inactive for more than `days`
+    #[must_use]
+    pub const fn with_stale_after_days(mut self, days: Option<u64>) -> Self {
+        self.stale_after_days = days;
+        self
+    }
+
+    /// Match user-defined rules before the built-in patterns
+    #[must_use]
+    pub fn with_rules(mut self, rules: Arc<DetectorRules>) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    /// Reuse digests of unchanged files from the hash cache at `path`
+    #[must_use]
+    pub fn with_hash_cache(mut self, path: impl Into<PathBuf>) -> Self {
+        self.hash_cache_path = Some(path.into());
+        self
+    }
+}
+
+impl Default for UnifiedScanOptions {
+    fn default() -> Self {
+        Self::new(ScanDetectors::all(), false)
+    }
+}
+
+// ============================================================================
+// Progress and Cancellation
+// ============================================================================
+
+/// Progress reporting and cancellation shared with whoever started a scan
+#[derive(Default)]
+pub struct ScanMonitor {
+    /// Files, directories and bytes seen so far
+    pub progress: Arc<ProgressTracker>,
+    /// Cancel to stop the scan at the next entry
+    pub cancel: CancellationToken,
+    /// (current directory, phase)
+    current: Mutex<(String, String)>,
+    /// Entries found since the last chunk was taken, if streaming
+    pending: Option<Mutex<Findings>>,
+    /// Number of chunks taken so far
+    chunks_taken: AtomicU64,
+}
+
+impl ScanMonitor {
+    /// Create a monitor with zeroed counters and an uncancelled token
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a monitor that reports to `progress` and stops when `cancel` is set
+    #[must_use]
+    pub fn with_controls(cancel: CancellationToken, progress: Arc<ProgressTracker>) -> Self {
+        Self {
+            progress,
+            cancel,
+            ..Self::default()
+        }
+    }
+
+    /// Create a monitor that also collects entries for [`ScanMonitor::take_chunk`]
+    #[must_use]
+    pub fn streaming() -> Self {
+        Self {
+            pending: Some(Mutex::new(Findings::default())),
+            ..Self::default()
+        }
+    }
+
+    /// Entries found since the previous call, grouped and sorted like the final results
+    ///
+    /// **Returns:** The chunk, or `None` if nothing new was found or the monitor
+    /// is not streaming
+    pub fn take_chunk(&self, job_id: &str) -> Option<ScanResultsChunk> {
+        let findings = std::mem::take(&mut *self.pending.as_ref()?.lock().ok()?);
+        if findings.is_empty() {
+            return None;
+        }
+
+        let (large_files, bloat, junk, dev_caches) = findings.into_sorted();
+        Some(ScanResultsChunk {
+            job_id: job_id.to_string(),
+            sequence: self.chunks_taken.fetch_add(1, Ordering::Relaxed),
+            large_files,
+            bloat,
+            junk,
+            dev_caches,
+        })
+    }
+
+    /// Current progress with ETA
+    #[must_use]
+    pub fn snapshot(&self) -> ScanProgress {
+        let (path, phase) = self
+            .current
+            .lock()
+            .map(|current| current.clone())
+            .unwrap_or_default();
+        self.progress.get_progress(&path, &phase, Vec::new())
+    }
+
+    /// Name the stage the scan is in (e.g., "walking", "hashing")
+    pub fn set_phase(&self, phase: &str) {
+        if let Ok(mut current) = self.current.lock() {
+            phase.clone_into(&mut current.1);
+        }
+    }
+
+    fn enter_dir(&self, path: &Path) {
+        self.progress.increment_dirs(1);
+        if let Ok(mut current) = self.current.lock() {
+            current.0 = path.to_string_lossy().into_owned();
+        }
+    }
+}
+
+// ============================================================================
+// Open Directory Accumulators
+// ============================================================================
+
+/// Per-bucket totals for a `.git` directory (objects, refs, logs, ...)
+#[derive(Debug, Default)]
+struct GitBucket {
+    usage: UsageAccumulator,
+    files: usize,
+}
+
+/// Accumulates the `.git` breakdown while the walk is inside it
+#[derive(Debug)]
+struct GitAccumulator {
+    git_path: PathBuf,
+    buckets: HashMap<String, GitBucket>,
+    top_level_files: Vec<(PathBuf, FileUsage)>,
+}
+
+/// A matched directory whose size is being accumulated by the walk
+#[derive(Debug)]
+enum OpenDirKind {
+    Bloat(BloatMatch),
+    /// Bloat matched by the user rule at `rule`
+    RuleBloat {
+        rule: usize,
+        reason: String,
+    },
+    Cache {
+        pattern_index: usize,
+    },
+    /// Cache matched by the user rule at `rule`
+    RuleCache {
+        rule: usize,
+    },
+    Git(GitAccumulator),
+}
+
+#[derive(Debug)]
+struct OpenDir {
+    depth: usize,
+    path: PathBuf,
+    usage: UsageAccumulator,
+    /// Newest modification time of a file inside (Unix seconds), for rule age conditions
+    newest_modified: u64,
+    kind: OpenDirKind,
+}
+
+impl OpenDir {
+    fn new(depth: usize, path: &Path, kind: OpenDirKind) -> Self {
+        Self {
+            depth,
+            path: path.to_path_buf(),
+            usage: UsageAccumulator::new(),
+            newest_modified: 0,
+            kind,
+        }
+    }
+}
+
+/// An entry reported as soon as the walk finds it
+///
+/// Category names are borrowed for built-in patterns and owned for user rules.
+#[derive(Debug, Clone)]
+enum Finding {
+    LargeFile(LargeFileEntry),
+    Bloat {
+        category_id: Cow<'static, str>,
+        display_name: Cow<'static, str>,
+        entry: BloatEntry,
+    },
+    Junk {
+        category_id: Cow<'static, str>,
+        display_name: Cow<'static, str>,
+        safety: Cow<'static, str>,
+        entry: JunkFileEntry,
+    },
+    Cache {
+        category_id: Cow<'static, str>,
+        display_name: Cow<'static, str>,
+        safety: Cow<'static, str>,
+        entry: CacheEntry,
+    },
+}
+
+/// Large files, bloat, junk and caches, grouped by category
+#[derive(Debug, Default)]
+struct Findings {
+    large_files: Vec<LargeFileEntry>,
+    bloat: HashMap<String, (String, Vec<BloatEntry>)>,
+    junk: HashMap<String, (String, String, Vec<JunkFileEntry>)>,
+    caches: HashMap<String, (String, String, String, Vec<CacheEntry>)>,
+}
+
+impl Findings {
+    fn is_empty(&self) -> bool {
+        self.large_files.is_empty()
+            && self.bloat.is_empty()
+            && self.junk.is_empty()
+            && self.caches.is_empty()
+    }
+
+    fn add(&mut self, finding: Finding) {
+        match finding {
+            Finding::LargeFile(entry) => self.large_files.push(entry),
+            Finding::Bloat {
+                category_id,
+                display_name,
+                entry,
+            } => self
+                .bloat
+                .entry(category_id.into_owned())
+                .or_insert_with(|| (display_name.into_owned(), Vec::new()))
+                .1
+                .push(entry),
+            Finding::Junk {
+                category_id,
+                display_name,
+                safety,
+                entry,
+            } => self
+                .junk
+                .entry(category_id.into_owned())
+                .or_insert_with(|| (display_name.into_owned(), safety.into_owned(), Vec::new()))
+                .2
+                .push(entry),
+            Finding::Cache {
+                category_id,
+                display_name,
+                safety,
+                entry,
+            } => self
+                .caches
+                .entry(format!("{category_id}:{display_name}"))
+                .or_insert_with(|| {
+                    (
+                        category_id.into_owned(),
+                        display_name.into_owned(),
+                        safety.into_owned(),
+                        Vec::new(),
+                    )
+                })
+                .3
+                .push(entry),
+        }
+    }
+
+    /// Large files, bloat, junk and cache categories in result order
+    fn into_sorted(
+        self,
+    ) -> (
+        Vec<LargeFileEntry>,
+        Vec<BloatCategory>,
+        Vec<JunkCategory>,
+        Vec<CacheCategory>,
+    ) {
+        let mut large_files = self.large_files;
+        large_files.sort_by(|a, b| compare_f32_safe(a.size_mb, b.size_mb));
+
+        let mut bloat: Vec<BloatCategory> = self
+            .bloat
+            .into_iter()
+            .map(|(category_id, (display_name, mut entries))| {
+                // Recommended entries first, then largest first (compare_f32_safe
+                // orders descending); without a stale threshold none are recommended
+                entries.sort_by(|a, b| {
+                    b.recommended
+                        .cmp(&a.recommended)
+                        .then_with(|| compare_f32_safe(a.size_mb, b.size_mb))
+                });
+                let total_size_mb: f32 = entries.iter().map(|e| e.size_mb).sum();
+                BloatCategory {
+                    category_id,
+                    display_name,
+                    total_size_mb,
+                    total_allocated_mb: entries.iter().map(|e| e.allocated_mb).sum(),
+                    total_reclaimable_mb: entries.iter().map(|e| e.reclaimable_mb).sum(),
+                    recommended_reclaimable_mb: entries
+                        .iter()
+                        .filter(|e| e.recommended)
+                        .map(|e| e.reclaimable_mb)
+                        .sum(),
+                    entries,
+                }
+            })
+            .collect();
+        bloat.sort_by(|a, b| {
+            compare_f32_safe(a.recommended_reclaimable_mb, b.recommended_reclaimable_mb)
+                .then_with(|| compare_f32_safe(a.total_size_mb, b.total_size_mb))
+        });
+
+        let mut junk: Vec<JunkCategory> = self
+            .junk
+            .into_iter()
+            .map(|(category_id, (display_name, safety, files))| {
+                let total_size_kb: f32 = files.iter().map(|f| f.size_kb).sum();
+                JunkCategory {
+                    category_id,
+                    display_name,
+                    total_size_kb,
+                    total_allocated_kb: files.iter().map(|f| f.allocated_kb).sum(),
+                    total_reclaimable_kb: files.iter().map(|f| f.reclaimable_kb).sum(),
+                    file_count: files.len(),
+                    safety,
+                    files,
+                }
+            })
+            .collect();
+        junk.sort_by_key(|c| std::cmp::Reverse(c.file_count));
+
+        let mut dev_caches: Vec<CacheCategory> = self
+            .caches
+            .into_values()
+            .map(|(category_id, display_name, safety, entries)| {
+                let total_size_mb: f32 = entries.iter().map(|e| e.size_mb).sum();
+                CacheCategory {
+                    category_id,
+                    display_name,
+                    total_size_mb,
+                    total_allocated_mb: entries.iter().map(|e| e.allocated_mb).sum(),
+                    total_reclaimable_mb: entries.iter().map(|e| e.reclaimable_mb).sum(),
+                    entry_count: entries.len(),
+                    safety,
+                    entries,
+                }
+            })
+            .collect();
+        dev_caches.sort_by(|a, b| compare_f32_safe(a.total_size_mb, b.total_size_mb));
+
+        (large_files, bloat, junk, dev_caches)
+    }
+}
+
+/// Bloat entry with its category id and display name
+type PendingBloat = (Cow<'static, str>, Cow<'static, str>, BloatEntry);
+
+/// Mutable state for one traversal
+struct ScanState<'a> {
+    root: &'a Path,
+    options: &'a UnifiedScanOptions,
+    monitor: &'a ScanMonitor,
+    limiter: &'a ScanLimiter,
+    open_dirs: Vec<OpenDir>,
+    findings: Findings,
+    duplicate_candidates: Vec<DuplicateCandidate>,
+    dir_tree: DirTree,
+    git_repos: Vec<GitRepository>,
+    /// Bloat entries (with category id and name) waiting for their project's activity
+    projects: ProjectTracker<PendingBloat>,
+    /// Time inactivity of bloat projects is measured against (Unix seconds)
+    started_at: u64,
+}
+
+impl<'a> ScanState<'a> {
+    fn new(
+        root: &'a Path,
+        options: &'a UnifiedScanOptions,
+        monitor: &'a ScanMonitor,
+        limiter: &'a ScanLimiter,
+    ) -> Self {
+        Self {
+            root,
+            options,
+            monitor,
+            limiter,
+            open_dirs: Vec::new(),
+            findings: Findings::default(),
+            duplicate_candidates: Vec::new(),
+            dir_tree: DirTree::new(),
+            git_repos: Vec::new(),
+            projects: ProjectTracker::new(),
+            started_at: now_secs(),
+        }
+    }
+
+    /// Keep a finding for the results, and for the next chunk when streaming
+    fn record(&mut self, finding: Finding) {
+        if let Some(pending) = &self.monitor.pending {
+            if let Ok(mut pending) = pending.lock() {
+                pending.add(finding.clone());
+            }
+        }
+        self.findings.add(finding);
+    }
+
+    /// Close every open directory at or below `depth` (the walk has left them)
+    fn close_dirs_from(&mut self, depth: usize) {
+        while self.open_dirs.last().is_some_and(|d| d.depth >= depth) {
+            if let Some(dir) = self.open_dirs.pop() {
+                self.finish_dir(dir);
+            }
+        }
+        let ready = self.projects.leave_dirs_from(depth);
+        self.record_project_bloat(ready);
+    }
+
+    fn inside_cache(&self) -> bool {
+        self.open_dirs.iter().any(|d| {
+            matches!(
+                d.kind,
+                OpenDirKind::Cache { .. } | OpenDirKind::RuleCache { .. }
+            )
+        })
+    }
+
+    fn visit_dir(&mut self, path: &Path, depth: usize) {
+        let detectors = self.options.detectors;
+        self.monitor.enter_dir(path);
+
+        if detectors.duplicate_dirs {
+            self.dir_tree.enter_dir(path, depth);
+        }
+
+        // A direct child of an open `.git` directory starts a new bucket, so
+        // empty `objects` directories are still reported.
+        for dir in &mut self.open_dirs {
+            if let OpenDirKind::Git(git) = &mut dir.kind {
+                if depth == dir.depth + 1 {
+                    if let Some(name) = path.file_name() {
+                        git.buckets
+                            .entry(name.to_string_lossy().into_owned())
+                            .or_default();
+                    }
+                }
+            }
+        }
+
+        if detectors.git_repos && path.file_name().is_some_and(|n| n == ".git") {
+            log::debug!("Found .git directory: {}", path.display());
+            let git = GitAccumulator {
+                git_path: path.to_path_buf(),
+                buckets: HashMap::new(),
+                top_level_files: Vec::new(),
+            };
+            self.open_dirs
+                .push(OpenDir::new(depth, path, OpenDirKind::Git(git)));
+        }
+
+        // User rules are checked before the built-in patterns
+        let rules = &self.options.rules;
+        if detectors.bloat {
+            let kind = rules.match_bloat(path).map_or_else(
+                || classify_bloat_dir(path).map(OpenDirKind::Bloat),
+                |found| {
+                    Some(OpenDirKind::RuleBloat {
+                        rule: found.index,
+                        reason: found.reason(),
+                    })
+                },
+            );
+            let artifact = kind.is_some() || path.file_name().is_some_and(|n| n == ".git");
+            self.projects.enter_dir(path, depth, artifact);
+            if let Some(kind) = kind {
+                self.open_dirs.push(OpenDir::new(depth, path, kind));
+            }
+        }
+
+        // Caches are reported at their top-most matching directory only
+        if detectors.dev_caches && !self.inside_cache() {
+            let kind = rules.match_cache(path).map_or_else(
+                || {
+                    match_cache_pattern(path)
+                        .map(|pattern_index| OpenDirKind::Cache { pattern_index })
+                },
+                |found| Some(OpenDirKind::RuleCache { rule: found.index }),
+            );
+            if let Some(kind) = kind {
+                self.open_dirs.push(OpenDir::new(depth, path, kind));
+            }
+        }
+    }
+
+    fn visit_file(&mut self, path: &Path, depth: usize, usage: FileUsage, last_modified: u64) {
+        let detectors = self.options.detectors;
+        let file = DiskUsage::of_file(&usage);
+        self.monitor.progress.increment_files(1);
+        self.monitor.progress.add_bytes(usage.apparent);
+
+        for dir in &mut self.open_dirs {
+            dir.usage.add(&usage);
+            dir.newest_modified = dir.newest_modified.max(last_modified);
+            if let OpenDirKind::Git(git) = &mut dir.kind {
+                attribute_git_file(git, path, depth, dir.depth, usage);
+            }
+        }
+
+        if detectors.bloat {
+            self.projects.add_file(last_modified);
+        }
+
+        if detectors.large_files && usage.apparent >= self.options.min_large_file_bytes {
+            self.record(Finding::LargeFile(LargeFileEntry {
+                path: path.to_string_lossy().to_string(),
+                size_mb: file.apparent_mb(),
+                allocated_mb: file.allocated_mb(),
+                reclaimable_mb: file.reclaimable_mb(),
+                last_modified,
+            }));
+        }
+
+        if detectors.duplicates && usage.apparent >= DUPLICATE_MIN_FILE_SIZE {
+            self.duplicate_candidates.push(DuplicateCandidate {
+                path: path.to_path_buf(),
+                usage,
+                last_modified,
+            });
+        }
+
+        if detectors.duplicate_dirs {
+            self.dir_tree.add_file(path, depth, usage);
+        }
+
+        if detectors.junk {
+            if let Some(filename) = path.file_name().and_then(|n| n.to_str()) {
+                // A matching user rule decides alone, even when its size or age
+                // condition rejects the file
+                let options = self.options;
+                let matched = match options.rules.match_junk(path) {
+                    Some(found) => found
+                        .rule
+                        .admits(file.apparent_mb(), last_modified, self.started_at)
+                        .then(|| {
+                            (
+                                Cow::Owned(found.rule.id.clone()),
+                                Cow::Owned(found.rule.name.clone()),
+                                Cow::Owned(found.rule.safety.clone()),
+                            )
+                        }),
+                    None => {
+                        detect_junk_file(filename).map(|(category_id, display_name, safety)| {
+                            (
+                                Cow::Borrowed(category_id),
+                                Cow::Borrowed(display_name),
+                                Cow::Borrowed(safety),
+                            )
+                        })
+                    }
+                };
+                if let Some((category_id, display_name, safety)) = matched {
+                    // NO minimum size - catch even 0-byte files
+                    let entry = JunkFileEntry {
+                        path: path.to_string_lossy().to_string(),
+                        size_kb: file.apparent_mb() * 1024.0,
+                        allocated_kb: file.allocated_mb() * 1024.0,
+                        reclaimable_kb: file.reclaimable_mb() * 1024.0,
+                        pattern: filename.to_string(),
+                        category: category_id.to_string(),
+                        safety: safety.to_string(),
+                    };
+                    self.record(Finding::Junk {
+                        category_id,
+                        display_name,
+                        safety,
+                        entry,
+                    });
+                }
+            }
+        }
+    }
+
+    fn finish_dir(&mut self, dir: OpenDir) {
+        let usage = dir.usage.usage();
+        let size_mb = usage.apparent_mb();
+
+        match dir.kind {
+            OpenDirKind::Bloat(found) => {
+                // Only include if size is significant (> 1MB)
+                if size_mb <= BLOAT_MIN_SIZE_MB {
+                    return;
+                }
+                let entry = bloat_entry(
+                    &dir.path,
+                    &usage,
+                    found.confidence.to_string(),
+                    found.reason,
+                    found.safety.to_string(),
+                );
+                self.record_bloat(found.category_id.into(), found.display_name.into(), entry);
+            }
+            OpenDirKind::RuleBloat { rule, reason } => {
+                let options = self.options;
+                let Some(rule) = options.rules.bloat_rule(rule) else {
+                    return;
+                };
+                // The rule's own size condition replaces the default threshold
+                let big_enough = rule.min_size_mb.is_some() || size_mb > BLOAT_MIN_SIZE_MB;
+                if !big_enough || !rule.admits(size_mb, dir.newest_modified, self.started_at) {
+                    return;
+                }
+                let entry = bloat_entry(
+                    &dir.path,
+                    &usage,
+                    CONFIDENCE_HIGH.to_string(),
+                    reason,
+                    rule.safety.clone(),
+                );
+                self.record_bloat(rule.id.clone().into(), rule.name.clone().into(), entry);
+            }
+            OpenDirKind::Cache { pattern_index } => {
+                let Some((_, category_id, display_name, safety, description, native)) =
+                    CACHE_PATTERNS.get(pattern_index)
+                else {
+                    return;
+                };
+                self.record(Finding::Cache {
+                    category_id: Cow::Borrowed(category_id),
+                    display_name: Cow::Borrowed(display_name),
+                    safety: Cow::Borrowed(safety),
+                    entry: CacheEntry {
+                        path: dir.path.to_string_lossy().to_string(),
+                        size_mb,
+                        allocated_mb: usage.allocated_mb(),
+                        reclaimable_mb: usage.reclaimable_mb(),
+                        cache_type: (*category_id).to_string(),
+                        safety: (*safety).to_string(),
+                        description: (*description).to_string(),
+                        cleanup_command: native.map(|native| native.command.join(" ")),
+                    },
+                });
+            }
+            OpenDirKind::RuleCache { rule } => {
+                let options = self.options;
+                let Some(rule) = options.rules.cache_rule(rule) else {
+                    return;
+                };
+                if !rule.admits(size_mb, dir.newest_modified, self.started_at) {
+                    return;
+                }
+                self.record(Finding::Cache {
+                    category_id: rule.id.clone().into(),
+                    display_name: rule.name.clone().into(),
+                    safety: rule.safety.clone().into(),
+                    entry: CacheEntry {
+                        path: dir.path.to_string_lossy().to_string(),
+                        size_mb,
+                        allocated_mb: usage.allocated_mb(),
+                        reclaimable_mb: usage.reclaimable_mb(),
+                        cache_type: rule.id.clone(),
+                        safety: rule.safety.clone(),
+                        description: rule.description.clone(),
+                        cleanup_command: None,
+                    },
+                });
+            }
+            OpenDirKind::Git(git) => {
+                if let Some(repo) = build_git_repository(git) {
+                    self.git_repos.push(repo);
+                }
+            }
+        }
+    }
+
+    /// Hold a bloat directory back until its project's activity is known,
+    /// unless it is tracked in git
+    fn record_bloat(
+        &mut self,
+        category_id: Cow<'static, str>,
+        display_name: Cow<'static, str>,
+        entry: BloatEntry,
+    ) {
+        let path = PathBuf::from(&entry.path);
+        // Committed directories hold sources someone chose to keep
+        if is_tracked_in_git(&path) {
+            log::debug!("Skipping git-tracked directory {}", path.display());
+            return;
+        }
+        self.projects
+            .wait_for_project(&path, self.root, (category_id, display_name, entry));
+    }
+
+    /// Report bloat directories whose project the walk has left
+    fn record_project_bloat(&mut self, ready: Vec<(ProjectActivity, PendingBloat)>) {
+        for (activity, (category_id, display_name, mut entry)) in ready {
+            activity.annotate(&mut entry, self.options.stale_after_days, self.started_at);
+            self.record(Finding::Bloat {
+                category_id,
+                display_name,
+                entry,
+            });
+        }
+    }
+
+    fn into_results(mut self) -> Result<CombinedScanResults, String> {
+        self.close_dirs_from(0);
+        let ready = self.projects.finish();
+        self.record_project_bloat(ready);
+        self.monitor.set_phase("analyzing");
+
+        let (large_files, bloat, junk, dev_caches) = self.findings.into_sorted();
+
+        let detectors = self.options.detectors;
+        let hash_cache = if detectors.duplicates || detectors.duplicate_dirs {
+            self.options
+                .hash_cache_path
+                .as_deref()
+                .and_then(open_hash_cache)
+        } else {
+            None
+        };
+
+        let (duplicates, duplicate_stats) = if detectors.duplicates {
+            find_duplicates(
+                self.duplicate_candidates,
+                hash_cache.as_ref(),
+                &self.monitor.cancel,
+                self.limiter.throttle(),
+            )?
+        } else {
+            (Vec::new(), DuplicateScanStats::default())
+        };
+
+        let duplicate_dirs = if detectors.duplicate_dirs {
+            find_duplicate_dirs(
+                &self.dir_tree,
+                hash_cache.as_ref(),
+                &self.monitor.cancel,
+                self.limiter.throttle(),
+            )?
+        } else {
+            Vec::new()
+        };
+
+        let mut git_repos = self.git_repos;
+        for repo in &mut git_repos {
+            if self.monitor.cancel.is_cancelled() {
+                break;
+            }
+            add_large_blobs(repo, &self.monitor.cancel, self.limiter.throttle());
+            add_storage_entries(repo);
+        }
+        group_clones(&mut git_repos, &self.monitor.cancel);
+        git_repos.sort_by(|a, b| compare_f32_safe(a.total_size_mb, b.total_size_mb));
+
+        Ok(CombinedScanResults {
+            large_files,
+            bloat,
+            duplicates,
+            duplicate_stats,
+            duplicate_dirs,
+            junk,
+            dev_caches,
+            git_repos,
+            skipped_mounts: Vec::new(),
+            cancelled: self.monitor.cancel.is_cancelled(),
+        })
+    }
+}
+
+// ============================================================================
+// Unified Scan Entry Point
+// ============================================================================
+
+/// Walk `root` once and run every enabled detector on the shared entry stream.
+///
+/// **Parameters:**
+/// - `root` - Root directory path to scan
+/// - `options` - Enabled detectors, symlink handling and thresholds
+///
+/// **Returns:** Results for every enabled detector, each sorted the same way
+/// as the corresponding per-type scan. Disabled detectors yield empty vectors.
+pub fn scan_all(root: &Path, options: &UnifiedScanOptions) -> Result<CombinedScanResults, String> {
+    scan_all_monitored(root, options, &ScanMonitor::new())
+}
+
+/// [`scan_all`] with progress reported through `monitor`.
+///
+/// The walk checks the monitor's cancellation token before every entry and
+/// duplicate hashing checks it before every file. A cancelled scan still
+/// returns Ok: entries found before the token was set are reported, hashing
+/// reads no further files, and [`CombinedScanResults::cancelled`] is set.
+///
+/// With thread or priority limits, the walk and hashing run in a dedicated
+/// pool that is shut down when the scan returns.
+pub fn scan_all_monitored(
+    root: &Path,
+    options: &UnifiedScanOptions,
+    monitor: &ScanMonitor,
+) -> Result<CombinedScanResults, String> {
+    let limiter = ScanLimiter::new(&options.limits)?;
+    limiter.install(|| walk_all(root, options, monitor, &limiter))
+}
+
+/// Walk `root` with `WalkDir` and run the detectors
+fn walk_all(
+    root: &Path,
+    options: &UnifiedScanOptions,
+    monitor: &ScanMonitor,
+    limiter: &ScanLimiter,
+) -> Result<CombinedScanResults, String> {
+    let mut state = ScanState::new(root, options, monitor, limiter);
+    let mut boundary = MountBoundary::new(root, options.one_file_system);
+    let mut error_count = 0usize;
+
+    log::info!(
+        "Starting unified scan in: {} (follow_symlinks={}, one_file_system={}, detectors={:?})",
+        root.display(),
+        options.follow_symlinks,
+        options.one_file_system,
+        options.detectors
+    );
+
+    let entries = WalkDir::new(root)
+        .follow_links(options.follow_symlinks)
+        .into_iter()
+        .filter_entry(|entry| boundary.admits(entry));
+
+    monitor.set_phase("walking");
+    for entry_result in entries {
+        if monitor.cancel.is_cancelled() {
+            log::info!("Unified scan of {} cancelled", root.display());
+            break;
+        }
+        let entry = match entry_result {
+            Ok(e) => e,
+            Err(err) => {
+                error_count += 1;
+                log::debug!("Error walking directory (#{error_count} errors total): {err}");
+                continue;
+            }
+        };
+
+        let depth = entry.depth();
+        state.close_dirs_from(depth);
+
+        if entry.file_type().is_dir() {
+            state.visit_dir(entry.path(), depth);
+        } else if entry.file_type().is_file() {
+            match entry.metadata() {
+                Ok(metadata) => {
+                    state.visit_file(
+                        entry.path(),
+                        depth,
+                        FileUsage::from_metadata(&metadata),
+                        last_modified_secs(&metadata),
+                    );
+                }
+                Err(err) => {
+                    error_count += 1;
+                    log::debug!("Cannot read metadata for {}: {err}", entry.path().display());
+                }
+            }
+        }
+    }
+
+    if error_count > 0 {
+        log::info!("Unified scan finished with {error_count} inaccessible entries");
+    }
+
+    let mut results = state.into_results()?;
+    results.skipped_mounts = boundary.into_skipped();
+    Ok(results)
+}
+
+// ============================================================================
+// Incremental (Indexed) Scan
+// ============================================================================
+
+/// How much of an indexed scan was served from the persistent index
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct IndexedScanStats {
+    /// Directories whose stamp matched and whose listing was replayed from the index
+    pub dirs_reused: usize,
+    /// Directories that were read from disk because they were new or changed
+    pub dirs_rescanned: usize,
+}
+
+/// Walk `root` like [`scan_all`], reusing cached listings from `index`.
+///
+/// Every directory is still stat'ed, but a directory whose (dev, inode, mtime,
+/// ctime) matches the index is not read: its files and subdirectories are
+/// replayed from the stored listing. Changed directories are read and their
+/// listings replace the stored ones in the same transaction.
+///
+/// A directory's mtime changes when entries are added, removed or renamed, but
+/// not when an existing file is rewritten in place or gains a hardlink elsewhere,
+/// so the files of a replayed listing are re-stat'ed and any changes are written
+/// back to the index.
+///
+/// The walk runs on the calling thread, which owns the index connection;
+/// resource limits apply to the hashing after it.
+///
+/// **Returns:** The same results a cold [`scan_all`] would return, plus reuse statistics
+pub fn scan_all_indexed(
+    root: &Path,
+    options: &UnifiedScanOptions,
+    index: &ScanIndex,
+) -> Result<(CombinedScanResults, IndexedScanStats), String> {
+    let monitor = ScanMonitor::new();
+    let limiter = ScanLimiter::new(&options.limits)?;
+    let mut walker = IndexedWalker {
+        state: ScanState::new(root, options, &monitor, &limiter),
+        index,
+        boundary: MountBoundary::new(root, options.one_file_system),
+        follow_symlinks: options.follow_symlinks,
+        ancestors: Vec::new(),
+        stats: IndexedScanStats::default(),
+        error_count: 0,
+    };
+
+    log::info!(
+        "Starting indexed scan in: {} (follow_symlinks={}, detectors={:?})",
+        root.display(),
+        options.follow_symlinks,
+        options.detectors
+    );
+
+    let batch = index
+        .begin_batch()
+        .map_err(|e| format!("Scan index error: {e}"))?;
+
+    match std::fs::metadata(root) {
+        Ok(metadata) if metadata.is_dir() => walker.walk_dir(root, 0, &metadata),
+        Ok(metadata) if metadata.is_file() => {
+            walker.state.visit_file(
+                root,
+                0,
+                FileUsage::from_metadata(&metadata),
+                last_modified_secs(&metadata),
+            );
+        }
+        Ok(_) => {}
+        Err(err) => {
+            walker.error_count += 1;
+            log::debug!("Cannot read scan root {}: {err}", root.display());
+        }
+    }
+
+    batch
+        .commit()
+        .map_err(|e| format!("Scan index error: {e}"))?;
+
+    let IndexedWalker {
+        state,
+        boundary,
+        stats,
+        error_count,
+        ..
+    } = walker;
+
+    log::info!(
+        "Indexed scan reused {} directories, rescanned {} ({} inaccessible entries)",
+        stats.dirs_reused,
+        stats.dirs_rescanned,
+        error_count
+    );
+
+    let mut results = limiter.install(|| state.into_results())?;
+    results.skipped_mounts = boundary.into_skipped();
+    Ok((results, stats))
+}
+
+/// Depth-first traversal that consults the scan index before reading a directory
+struct IndexedWalker<'a> {
+    state: ScanState<'a>,
+    index: &'a ScanIndex,
+    boundary: MountBoundary,
+    follow_symlinks: bool,
+    /// (dev, inode) of directories on the current path, for symlink loop detection
+    ancestors: Vec<(u64, u64)>,
+    stats: IndexedScanStats,
+    error_count: usize,
+}
+
+impl IndexedWalker<'_> {
+    fn walk_dir(&mut self, path: &Path, depth: usize, metadata: &std::fs::Metadata) {
+        let stamp = DirStamp::from_metadata(metadata);
+        if self.follow_symlinks && self.ancestors.contains(&(stamp.dev, stamp.inode)) {
+            log::warn!("Symlink loop detected at {}", path.display());
+            return;
+        }
+
+        self.state.close_dirs_from(depth);
+        self.state.visit_dir(path, depth);
+
+        let listing = if let Ok(Some(indexed)) = self.index.lookup(path, &stamp) {
+            self.stats.dirs_reused += 1;
+            let mut listing = Listing::from(indexed);
+            if self.refresh_files(path, &mut listing) {
+                if let Some(indexed) = listing.to_indexed() {
+                    if let Err(err) = self.index.record(path, &stamp, &indexed) {
+                        log::debug!("Cannot index {}: {err}", path.display());
+                    }
+                }
+            }
+            listing
+        } else {
+            self.stats.dirs_rescanned += 1;
+            match self.read_listing(path) {
+                Ok(listing) => {
+                    // Names that are not valid UTF-8 cannot be stored losslessly,
+                    // so such directories are simply re-read every time
+                    if let Some(indexed) = listing.to_indexed() {
+                        if let Err(err) = self.index.record(path, &stamp, &indexed) {
+                            log::debug!("Cannot index {}: {err}", path.display());
+                        }
+                    }
+                    listing
+                }
+                Err(err) => {
+                    self.error_count += 1;
+                    log::debug!("Cannot read directory {}: {err}", path.display());
+                    return;
+                }
+            }
+        };
+
+        for (name, usage, last_modified) in &listing.files {
+            self.state.close_dirs_from(depth + 1);
+            self.state
+                .visit_file(&path.join(name), depth + 1, *usage, *last_modified);
+        }
+
+        self.ancestors.push((stamp.dev, stamp.inode));
+        for name in &listing.subdirs {
+            let child = path.join(name);
+            match self.child_metadata(&child) {
+                Some(child_metadata) if child_metadata.is_dir() => {
+                    if !self.boundary.crosses(&child, &child_metadata) {
+                        self.walk_dir(&child, depth + 1, &child_metadata);
+                    }
+                }
+                _ => {
+                    // Vanished since the listing was stored; the parent's stamp will
+                    // change too, so just forget the stale subtree
+                    let _ = self.index.remove_subtree(&child);
+                }
+            }
+        }
+        self.ancestors.pop();
+    }
+
+    fn child_metadata(&self, path: &Path) -> Option<std::fs::Metadata> {
+        if self.follow_symlinks {
+            std::fs::metadata(path).ok()
+        } else {
+            std::fs::symlink_metadata(path).ok()
+        }
+    }
+
+    /// Re-stat the files of a replayed listing, returning whether any changed
+    fn refresh_files(&self, dir: &Path, listing: &mut Listing) -> bool {
+        let mut changed = false;
+        listing.files.retain_mut(|(name, usage, last_modified)| {
+            match self.child_metadata(&dir.join(&*name)) {
+                Some(metadata) if metadata.is_file() => {
+                    let fresh = FileUsage::from_metadata(&metadata);
+                    let fresh_modified = last_modified_secs(&metadata);
+                    if fresh != *usage || fresh_modified != *last_modified {
+                        *usage = fresh;
+                        *last_modified = fresh_modified;
+                        changed = true;
+                    }
+                    true
+                }
+                _ => {
+                    changed = true;
+                    false
+                }
+            }
+        });
+        changed
+    }
+
+    /// Read a directory's direct regular files and subdirectories from disk
+    fn read_listing(&mut self, path: &Path) -> std::io::Result<Listing> {
+        let mut listing = Listing::default();
+
+        for entry in std::fs::read_dir(path)? {
+            let entry = match entry {
+                Ok(e) => e,
+                Err(err) => {
+                    self.error_count += 1;
+                    log::debug!("Error walking directory {}: {err}", path.display());
+                    continue;
+                }
+            };
+
+            let Ok(file_type) = entry.file_type() else {
+                self.error_count += 1;
+                continue;
+            };
+            let metadata = if file_type.is_symlink() {
+                if !self.follow_symlinks {
+                    continue;
+                }
+                std::fs::metadata(entry.path())
+            } else {
+                entry.metadata()
+            };
+            let Ok(metadata) = metadata else {
+                self.error_count += 1;
+                continue;
+            };
+
+            if metadata.is_dir() {
+                listing.subdirs.push(entry.file_name());
+            } else if metadata.is_file() {
+                listing.files.push((
+                    entry.file_name(),
+                    FileUsage::from_metadata(&metadata),
+                    last_modified_secs(&metadata),
+                ));
+            }
+        }
+
+        Ok(listing)
+    }
+}
+
+/// Direct children of one directory, whether read from disk or the index
+#[derive(Default)]
+struct Listing {
+    /// (name, usage, `last_modified`) of regular files
+    files: Vec<(OsString, FileUsage, u64)>,
+    subdirs: Vec<OsString>,
+}
+
+impl Listing {
+    /// Convert to the stored form, or `None` if any name is not valid UTF-8
+    fn to_indexed(&self) -> Option<IndexedDir> {
+        let files = self
+            .files
+            .iter()
+            .map(|(name, usage, last_modified)| {
+                Some(IndexedFile {
+                    name: name.to_str()?.to_string(),
+                    usage: *usage,
+                    last_modified: *last_modified,
+                })
+            })
+            .collect::<Option<Vec<_>>>()?;
+        let subdirs = self
+            .subdirs
+            .iter()
+            .map(|name| name.to_str().map(str::to_string))
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(IndexedDir { files, subdirs })
+    }
+}
+
+impl From<IndexedDir> for Listing {
+    fn from(indexed: IndexedDir) -> Self {
+        Self {
+            files: indexed
+                .files
+                .into_iter()
+                .map(|f| (OsString::from(f.name), f.usage, f.last_modified))
+                .collect(),
+            subdirs: indexed.subdirs.into_iter().map(OsString::from).collect(),
+        }
+    }
+}
+
+// ============================================================================
+// Detector Helpers
+// ============================================================================
+
+/// Open the hash cache, falling back to uncached hashing if it is unavailable
+fn open_hash_cache(path: &Path) -> Option<HashCache> {
+    match HashCache::new(&path.to_string_lossy()) {
+        Ok(cache) => Some(cache),
+        Err(err) => {
+            log::warn!("Hash cache unavailable at {}: {err}", path.display());
+            None
+        }
+    }
+}
+
+/// A bloat entry for `path`, with its project fields still empty
+fn bloat_entry(
+    path: &Path,
+    usage: &DiskUsage,
+    confidence: String,
+    reason: String,
+    safety: String,
+) -> BloatEntry {
+    BloatEntry {
+        path: path.to_string_lossy().to_string(),
+        size_mb: usage.apparent_mb(),
+        allocated_mb: usage.allocated_mb(),
+        reclaimable_mb: usage.reclaimable_mb(),
+        confidence,
+        reason,
+        safety,
+        project_root: String::new(),
+        project_last_modified: None,
+        last_commit: None,
+        inactive_days: None,
+        recommended: false,
+    }
+}
+
+/// Modification time in seconds since the Unix epoch (0 if unavailable)
+fn last_modified_secs(metadata: &std::fs::Metadata) -> u64 {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map_or(0, |d| d.as_secs())
+}
+
+/// Attribute a file inside `.git` to its top-level bucket
+fn attribute_git_file(
+    git: &mut GitAccumulator,
+    path: &Path,
+    depth: usize,
+    git_depth: usize,
+    usage: FileUsage,
+) {
+    if depth == git_depth + 1 {
+        git.top_level_files.push((path.to_path_buf(), usage));
+        return;
+    }
+
+    // Each submodule store under `modules/` gets its own bucket
+    let bucket = path.strip_prefix(&git.git_path).ok().and_then(|rel| {
+        let mut components = rel.components().map(|c| c.as_os_str().to_string_lossy());
+        let first = components.next()?;
+        match components.next() {
+            Some(second) if first == "modules" && depth > git_depth + 2 => {
+                Some(format!("modules/{second}"))
+            }
+            _ => Some(first.into_owned()),
+        }
+    });
+
+    if let Some(bucket) = bucket {
+        let totals = git.buckets.entry(bucket).or_default();
+        totals.usage.add(&usage);
+        totals.files += 1;
+    }
+}
+
+/// Turn accumulated `.git` totals into a repository report
+fn build_git_repository(git: GitAccumulator) -> Option<GitRepository> {
+    let repo_path = git.git_path.parent().unwrap_or(&git.git_path).to_path_buf();
+    let mut git_entries = Vec::new();
+    let mut total = DiskUsage::default();
+
+    let bucket_path = |name: &str| git.git_path.join(name).to_string_lossy().to_string();
+
+    if let Some(objects) = git.buckets.get("objects") {
+        let usage = objects.usage.usage();
+        total += usage;
+        git_entries.push(GitEntry {
+            path: bucket_path("objects"),
+            size_mb: usage.apparent_mb(),
+            allocated_mb: usage.allocated_mb(),
+            entry_type: "objects".to_string(),
+            description: format!("Git objects: {} files", objects.files),
+            safety: "safe".to_string(),
+            actionable: false, // Don't delete git objects
+        });
+    }
+
+    for (name, entry_type, description, safety, actionable) in [
+        ("refs", "refs", "Git references and branches", "safe", false),
+        (
+            "logs",
+            "reflog",
+            "Git reflogs - tracks branch movements",
+            "caution",
+            true,
+        ),
+        (
+            "pack",
+            "pack_file",
+            "Git pack files - compressed object storage",
+            "safe",
+            false,
+        ),
+    ] {
+        if let Some(usage) = git
+            .buckets
+            .get(name)
+            .map(|b| b.usage.usage())
+            .filter(|u| u.apparent_bytes > 0)
+        {
+            total += usage;
+            git_entries.push(GitEntry {
+                path: bucket_path(name),
+                size_mb: usage.apparent_mb(),
+                allocated_mb: usage.allocated_mb(),
+                entry_type: entry_type.to_string(),
+                description: description.to_string(),
+                safety: safety.to_string(),
+                actionable,
+            });
+        }
+    }
+
+    let mut submodules: Vec<(&String, &GitBucket)> = git
+        .buckets
+        .iter()
+        .filter(|(name, _)| name.starts_with("modules/"))
+        .collect();
+    submodules.sort_by_key(|(name, _)| *name);
+    for (name, bucket) in submodules {
+        let usage = bucket.usage.usage();
+        total += usage;
+        git_entries.push(GitEntry {
+            path: bucket_path(name),
+            size_mb: usage.apparent_mb(),
+            allocated_mb: usage.allocated_mb(),
+            entry_type: "submodule".to_string(),
+            description: format!(
+                "Submodule store {}: {} files",
+                name.trim_start_matches("modules/"),
+                bucket.files
+            ),
+            safety: "caution".to_string(),
+            actionable: false,
+        });
+    }
+
+    for (file_path, file_usage) in git.top_level_files {
+        let entry_name = file_path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let usage = DiskUsage::of_file(&file_usage);
+        total += usage;
+        git_entries.push(GitEntry {
+            path: file_path.to_string_lossy().to_string(),
+            size_mb: usage.apparent_mb(),
+            allocated_mb: usage.allocated_mb(),
+            entry_type: "file".to_string(),
+            description: format!("Git file: {entry_name}"),
+            safety: "safe".to_string(),
+            actionable: false,
+        });
+    }
+
+    if git_entries.is_empty() {
+        return None;
+    }
+
+    Some(GitRepository {
+        repo_path: repo_path.to_string_lossy().to_string(),
+        total_size_mb: total.apparent_mb(),
+        total_allocated_mb: total.allocated_mb(),
+        entry_count: git_entries.len(),
+        entries: git_entries,
+        large_blobs: Vec::new(),
+        clone_group: None,
+    })
+}
+
+/// Find the largest blobs in a repository's history
+///
+/// The history walk only runs when the repository stores large blobs; a
+/// failed or cancelled analysis leaves the list empty.
+fn add_large_blobs(repo: &mut GitRepository, cancel: &CancellationToken, throttle: &ReadThrottle) {
+    let git_dir = Path::new(&repo.repo_path).join(".git");
+    match largest_blobs(&git_dir, LARGE_BLOB_LIMIT, cancel, throttle) {
+        Ok(blobs) => repo.large_blobs = blobs,
+        Err(e) => log::debug!("No history analysis for {}: {e}", git_dir.display()),
+    }
+}
+
+/// Add Git LFS objects and linked worktrees, which the walk of `.git` does not break down
+fn add_storage_entries(repo: &mut GitRepository) {
+    let git_dir = Path::new(&repo.repo_path).join(".git");
+    let mut total = DiskUsage::default();
+    for (entry, usage) in storage_entries(&git_dir) {
+        total += usage;
+        repo.entries.push(entry);
+    }
+    repo.entry_count = repo.entries.len();
+    repo.total_size_mb += total.apparent_mb();
+    repo.total_allocated_mb += total.allocated_mb();
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn setup_tree() -> TempDir {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let base = temp_dir.path();
+
+        fs::create_dir_all(base.join("app/node_modules/pkg")).unwrap();
+        fs::write(
+            base.join("app/node_modules/pkg/blob.bin"),
+            vec![1u8; 2 * 1024 * 1024],
+        )
+        .unwrap();
+
+        fs::create_dir_all(base.join("docs")).unwrap();
+        fs::write(base.join("docs/a.txt"), vec![7u8; 4096]).unwrap();
+        fs::write(base.join("docs/b.txt"), vec![7u8; 4096]).unwrap();
+        fs::write(base.join("docs/.DS_Store"), b"junk").unwrap();
+
+        fs::create_dir_all(base.join("repo/.git/objects/ab")).unwrap();
+        fs::create_dir_all(base.join("repo/.git/refs/heads")).unwrap();
+        fs::write(base.join("repo/.git/objects/ab/cdef"), vec![0u8; 2048]).unwrap();
+        fs::write(base.join("repo/.git/refs/heads/main"), b"0123456789abcdef").unwrap();
+        fs::write(base.join("repo/.git/HEAD"), b"ref: refs/heads/main\n").unwrap();
+
+        fs::create_dir_all(base.join("home/.npm/_cacache/index")).unwrap();
+        fs::write(base.join("home/.npm/_cacache/index/entry"), vec![3u8; 1024]).unwrap();
+
+        temp_dir
+    }
+
+    #[test]
+    fn test_stale_projects_are_recommended_first() {
+        let temp_dir = TempDir::new().unwrap();
+        let base = temp_dir.path();
+        for project in ["old", "new"] {
+            fs::create_dir_all(base.join(project).join("target/debug")).unwrap();
+            fs::write(
+                base.join(project).join("target/debug/blob.bin"),
+                vec![1u8; 2 * 1024 * 1024],
+            )
+            .unwrap();
+            fs::write(base.join(project).join("Cargo.toml"), "[package]").unwrap();
+        }
+        // Two years old; the artifact itself was written just now
+        fs::File::options()
+            .write(true)
+            .open(base.join("old/Cargo.toml"))
+            .unwrap()
+            .set_modified(
+                std::time::SystemTime::now() - std::time::Duration::from_secs(730 * 86_400),
+            )
+            .unwrap();
+
+        let detectors = ScanDetectors {
+            bloat: true,
+            ..ScanDetectors::none()
+        };
+        let options = UnifiedScanOptions::new(detectors, false).with_stale_after_days(Some(365));
+        let results = scan_all(base, &options).unwrap();
+
+        let entries = &results.bloat[0].entries;
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].project_root, base.join("old").to_string_lossy());
+        assert!(entries[0].recommended);
+        assert!(entries[0].inactive_days.unwrap() >= 729);
+        assert_eq!(entries[0].last_commit, None);
+        assert_eq!(entries[1].project_root, base.join("new").to_string_lossy());
+        assert!(!entries[1].recommended);
+        assert_eq!(entries[1].inactive_days, Some(0));
+        assert!(results.bloat[0].recommended_reclaimable_mb > 1.9);
+    }
+
+    #[test]
+    fn test_user_rules_extend_builtins() {
+        let temp_dir = TempDir::new().unwrap();
+        let base = temp_dir.path();
+        fs::create_dir_all(base.join("proj/acme-out")).unwrap();
+        fs::write(base.join("proj/acme.toml"), "").unwrap();
+        fs::write(base.join("proj/acme-out/blob.bin"), vec![1u8; 1024 * 1024]).unwrap();
+        fs::create_dir_all(base.join("logs")).unwrap();
+        fs::write(base.join("logs/new.acmelog"), b"fresh").unwrap();
+        fs::write(base.join("logs/old.acmelog"), b"stale").unwrap();
+        fs::File::options()
+            .write(true)
+            .open(base.join("logs/old.acmelog"))
+            .unwrap()
+            .set_modified(std::time::UNIX_EPOCH)
+            .unwrap();
+        fs::create_dir_all(base.join("home/.cache/acme")).unwrap();
+        fs::write(base.join("home/.cache/acme/index"), b"cached").unwrap();
+
+        let rules = crate::utils::detector_rules::RuleFile::parse(
+            r#"
+            [[bloat]]
+            id = "acme_out"
+            name = "ACME Output"
+            globs = ["acme-out"]
+            markers = ["acme.toml"]
+            min_size_mb = 0.5
+            safety = "safe"
+
+            [[junk]]
+            id = "acme_logs"
+            name = "ACME Logs"
+            globs = ["*.acmelog"]
+            min_age_days = 30
+            safety = "safe"
+
+            [[cache]]
+            id = "acme_cache"
+            name = "ACME Cache"
+            globs = [".cache/acme"]
+            safety = "caution"
+            description = "ACME download cache"
+            "#,
+        )
+        .unwrap();
+        let options = UnifiedScanOptions::new(ScanDetectors::all(), false)
+            .with_rules(Arc::new(DetectorRules::compile(&[rules]).unwrap()));
+        let results = scan_all(base, &options).unwrap();
+
+        // Below the default 1MB bloat threshold, but above the rule's own
+        assert_eq!(results.bloat.len(), 1);
+        assert_eq!(results.bloat[0].category_id, "acme_out");
+        assert_eq!(
+            results.bloat[0].entries[0].reason,
+            "ACME Output next to acme.toml (rule acme_out)"
+        );
+        assert_eq!(results.junk.len(), 1);
+        assert_eq!(results.junk[0].category_id, "acme_logs");
+        assert_eq!(results.junk[0].files.len(), 1);
+        assert!(results.junk[0].files[0].path.ends_with("old.acmelog"));
+        assert_eq!(results.dev_caches.len(), 1);
+        assert_eq!(results.dev_caches[0].safety, "caution");
+        assert_eq!(
+            results.dev_caches[0].entries[0].description,
+            "ACME download cache"
+        );
+    }
+
+    #[test]
+    fn test_scan_all_feeds_every_detector() {
+        let temp_dir = setup_tree();
+        let options = UnifiedScanOptions::new(ScanDetectors::all(), false)
+            .with_min_large_file_bytes(1024 * 1024);
+
+        let results = scan_all(temp_dir.path(), &options).unwrap();
+
+        assert_eq!(results.large_files.len(), 1);
+        assert_eq!(results.bloat.len(), 1);
+        assert_eq!(results.bloat[0].category_id, "node_modules");
+        assert_eq!(results.duplicates.len(), 1);
+        assert_eq!(results.duplicates[0].entries.len(), 2);
+        assert_eq!(results.junk.len(), 1);
+        assert_eq!(results.junk[0].file_count, 1);
+        // Only the top-most cache directory is reported, not each subdirectory
+        assert_eq!(results.dev_caches.len(), 1);
+        assert_eq!(results.dev_caches[0].entries.len(), 1);
+        assert_eq!(results.git_repos.len(), 1);
+
+        let repo = &results.git_repos[0];
+        let types: Vec<&str> = repo.entries.iter().map(|e| e.entry_type.as_str()).collect();
+        assert!(types.contains(&"objects"));
+        assert!(types.contains(&"refs"));
+        assert!(types.contains(&"file"));
+    }
+
+    #[test]
+    fn test_results_are_largest_first() {
+        let temp_dir = TempDir::new().unwrap();
+        let base = temp_dir.path();
+        fs::write(base.join("small.bin"), vec![1u8; 1024 * 1024]).unwrap();
+        fs::write(base.join("big.bin"), vec![2u8; 3 * 1024 * 1024]).unwrap();
+        fs::create_dir_all(base.join("home/.npm/_cacache")).unwrap();
+        fs::write(base.join("home/.npm/_cacache/entry"), vec![3u8; 1024]).unwrap();
+        fs::create_dir_all(base.join("home/.cargo/registry/cache")).unwrap();
+        fs::write(
+            base.join("home/.cargo/registry/cache/crate.crate"),
+            vec![4u8; 64 * 1024],
+        )
+        .unwrap();
+
+        let options = UnifiedScanOptions::new(
+            ScanDetectors {
+                large_files: true,
+                dev_caches: true,
+                ..ScanDetectors::none()
+            },
+            false,
+        )
+        .with_min_large_file_bytes(1024 * 1024);
+        let results = scan_all(base, &options).unwrap();
+
+        assert_eq!(results.large_files.len(), 2);
+        assert!(results.large_files[0].path.ends_with("big.bin"));
+        assert!(results.large_files[0].size_mb > results.large_files[1].size_mb);
+        assert!(results.dev_caches.len() >= 2);
+        assert!(results
+            .dev_caches
+            .windows(2)
+            .all(|w| w[0].total_size_mb >= w[1].total_size_mb));
+    }
+
+    #[test]
+    fn test_git_submodule_and_worktree_storage() {
+        let temp_dir = TempDir::new().unwrap();
+        let git = temp_dir.path().join("repo/.git");
+        for module in ["lib", "vendor/ui"] {
+            let objects = git.join("modules").join(module).join("objects/ab");
+            fs::create_dir_all(&objects).unwrap();
+            fs::write(objects.join("cdef"), vec![0u8; 4096]).unwrap();
+        }
+        fs::create_dir_all(git.join("objects")).unwrap();
+        fs::create_dir_all(git.join("worktrees/old")).unwrap();
+        fs::write(git.join("worktrees/old/gitdir"), "/nonexistent/old/.git\n").unwrap();
+        fs::write(git.join("HEAD"), b"ref: refs/heads/main\n").unwrap();
+
+        let options = UnifiedScanOptions::new(
+            ScanDetectors {
+                git_repos: true,
+                ..ScanDetectors::none()
+            },
+            false,
+        );
+        let results = scan_all(temp_dir.path(), &options).unwrap();
+        let repo = &results.git_repos[0];
+
+        let submodules: Vec<&GitEntry> = repo
+            .entries
+            .iter()
+            .filter(|e| e.entry_type == "submodule")
+            .collect();
+        assert_eq!(submodules.len(), 2);
+        assert!(submodules[0].path.ends_with("modules/lib"));
+        assert!(submodules[1].path.ends_with("modules/vendor"));
+        assert!(submodules.iter().all(|e| e.size_mb > 0.0));
+
+        let worktree = repo
+            .entries
+            .iter()
+            .find(|e| e.entry_type == "worktree")
+            .unwrap();
+        assert!(worktree.actionable);
+        assert_eq!(repo.entry_count, repo.entries.len());
+    }
+
+    #[test]
+    fn test_bloat_requires_marker_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let base = temp_dir.path();
+        for dir in ["crate/target/debug", "tool/target/data"] {
+            fs::create_dir_all(base.join(dir)).unwrap();
+            fs::write(base.join(dir).join("blob.bin"), vec![1u8; 2 * 1024 * 1024]).unwrap();
+        }
+        fs::write(base.join("crate/Cargo.toml"), "[package]").unwrap();
+
+        let options = UnifiedScanOptions::new(
+            ScanDetectors {
+                bloat: true,
+                ..ScanDetectors::none()
+            },
+            false,
+        );
+        let results = scan_all(base, &options).unwrap();
+
+        assert_eq!(results.bloat.len(), 1);
+        let entries = &results.bloat[0].entries;
+        assert_eq!(entries.len(), 1);
+        assert!(entries[0].path.ends_with("target"));
+        assert!(entries[0].path.contains("crate"));
+        assert_eq!(entries[0].confidence, "high");
+        assert_eq!(entries[0].reason, "target next to Cargo.toml");
+    }
+
+    #[test]
+    fn test_disabled_detectors_are_empty() {
+        let temp_dir = setup_tree();
+        let detectors = ScanDetectors {
+            junk: true,
+            ..ScanDetectors::none()
+        };
+
+        let results =
+            scan_all(temp_dir.path(), &UnifiedScanOptions::new(detectors, false)).unwrap();
+
+        assert_eq!(results.junk.len(), 1);
+        assert!(results.large_files.is_empty());
+        assert!(results.bloat.is_empty());
+        assert!(results.duplicates.is_empty());
+        assert!(results.dev_caches.is_empty());
+        assert!(results.git_repos.is_empty());
+    }
+
+    #[test]
+    fn test_bloat_size_accumulates_nested_files() {
+        let temp_dir = setup_tree();
+        let detectors = ScanDetectors {
+            bloat: true,
+            ..ScanDetectors::none()
+        };
+
+        let results =
+            scan_all(temp_dir.path(), &UnifiedScanOptions::new(detectors, false)).unwrap();
+        let entry = &results.bloat[0].entries[0];
+
+        assert!((entry.size_mb - 2.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_streaming_monitor_chunks_match_results() {
+        let temp_dir = setup_tree();
+        let options = UnifiedScanOptions::new(ScanDetectors::all(), false)
+            .with_min_large_file_bytes(1024 * 1024);
+        let monitor = ScanMonitor::streaming();
+
+        let results = scan_all_monitored(temp_dir.path(), &options, &monitor).unwrap();
+        let chunk = monitor.take_chunk("job").unwrap();
+        assert_eq!(chunk.sequence, 0);
+
+        let mut streamed = CombinedScanResults {
+            large_files: chunk.large_files,
+            bloat: chunk.bloat,
+            junk: chunk.junk,
+            dev_caches: chunk.dev_caches,
+            ..CombinedScanResults::default()
+        };
+        streamed.duplicates = results.duplicates.clone();
+        streamed.git_repos = results.git_repos.clone();
+        assert_eq!(fingerprint(&streamed), fingerprint(&results));
+        assert!(monitor.take_chunk("job").is_none());
+        assert!(ScanMonitor::new().take_chunk("job").is_none());
+    }
+
+    #[test]
+    fn test_cancelled_monitor_stops_scan() {
+        let temp_dir = setup_tree();
+        let progress = Arc::new(ProgressTracker::new());
+        let monitor = ScanMonitor::with_controls(CancellationToken::new(), Arc::clone(&progress));
+        monitor.cancel.cancel();
+
+        let results =
+            scan_all_monitored(temp_dir.path(), &UnifiedScanOptions::default(), &monitor).unwrap();
+
+        assert!(results.cancelled);
+        assert!(results.large_files.is_empty());
+        assert!(results.duplicates.is_empty());
+        assert_eq!(progress.get_files_scanned(), 0);
+        assert!(
+            !scan_all(temp_dir.path(), &UnifiedScanOptions::default())
+                .unwrap()
+                .cancelled
+        );
+    }
+
+    /// Order-independent summary of every reported path and size
+    fn fingerprint(results: &CombinedScanResults) -> Vec<String> {
+        let mut lines = Vec::new();
+        lines.extend(
+            results
+                .large_files
+                .iter()
+                .map(|f| format!("large {} {}", f.path, f.size_mb)),
+        );
+        for cat in &results.bloat {
+            lines.extend(
+                cat.entries
+                    .iter()
+                    .map(|e| format!("bloat {} {}", e.path, e.size_mb)),
+            );
+        }
+        for set in &results.duplicates {
+            lines.extend(
+                set.entries
+                    .iter()
+                    .map(|e| format!("dup {} {}", set.hash, e.path)),
+            );
+        }
+        for cat in &results.junk {
+            lines.extend(
+                cat.files
+                    .iter()
+                    .map(|f| format!("junk {} {}", f.path, f.size_kb)),
+            );
+        }
+        for cat in &results.dev_caches {
+            lines.extend(
+                cat.entries
+                    .iter()
+                    .map(|e| format!("cache {} {}", e.path, e.size_mb)),
+            );
+        }
+        for repo in &results.git_repos {
+            lines.extend(
+                repo.entries
+                    .iter()
+                    .map(|e| format!("git {} {}", e.path, e.size_mb)),
+            );
+        }
+        lines.sort();
+        lines
+    }
+
+    #[test]
+    fn test_indexed_rescan_matches_cold_scan() {
+        let temp_dir = setup_tree();
+        let db_file = tempfile::NamedTempFile::new().unwrap();
+        let index = ScanIndex::new(db_file.path().to_str().unwrap()).unwrap();
+        let options = UnifiedScanOptions::new(ScanDetectors::all(), false)
+            .with_min_large_file_bytes(1024 * 1024);
+
+        let (first, first_stats) = scan_all_indexed(temp_dir.path(), &options, &index).unwrap();
+        assert_eq!(first_stats.dirs_reused, 0);
+        assert_eq!(
+            fingerprint(&first),
+            fingerprint(&scan_all(temp_dir.path(), &options).unwrap())
+        );
+
+        // Nothing changed: every directory is replayed from the index
+        let (second, second_stats) = scan_all_indexed(temp_dir.path(), &options, &index).unwrap();
+        assert_eq!(second_stats.dirs_rescanned, 0);
+        assert_eq!(fingerprint(&second), fingerprint(&first));
+
+        // Add, remove and create entries, then compare against a cold scan
+        let base = temp_dir.path();
+        fs::write(base.join("docs/c.txt"), vec![7u8; 4096]).unwrap();
+        fs::remove_file(base.join("docs/.DS_Store")).unwrap();
+        fs::remove_dir_all(base.join("home/.npm")).unwrap();
+        fs::create_dir_all(base.join("web/node_modules/dep")).unwrap();
+        fs::write(
+            base.join("web/node_modules/dep/index.js"),
+            vec![9u8; 3 * 1024 * 1024],
+        )
+        .unwrap();
+
+        let (third, third_stats) = scan_all_indexed(base, &options, &index).unwrap();
+        assert!(third_stats.dirs_reused > 0);
+        assert!(third_stats.dirs_rescanned > 0);
+        assert_eq!(
+            fingerprint(&third),
+            fingerprint(&scan_all(base, &options).unwrap())
+        );
+        assert_eq!(third.duplicates[0].entries.len(), 3);
+        assert!(third.junk.is_empty());
+
+        // Rewriting a file in place leaves its directory's stamp unchanged
+        fs::write(base.join("docs/a.txt"), vec![8u8; 2 * 1024 * 1024]).unwrap();
+        let (fourth, _) = scan_all_indexed(base, &options, &index).unwrap();
+        assert_eq!(
+            fingerprint(&fourth),
+            fingerprint(&scan_all(base, &options).unwrap())
+        );
+        assert!(fourth
+            .large_files
+            .iter()
+            .any(|f| f.path.ends_with("docs/a.txt")));
+    }
+}
@@ -90,6 +90,7 @@ fn test_validate_deletion_request_blocks_system_file_deletion() {
         paths: vec!["/System/Library/test.txt".to_string()],
         dry_run: false,
         trash: true,
+        allow_unsaved_work: false,
     };
 
     let result = validate_deletion_request(&req);
@@ -105,6 +106,7 @@ fn test_validate_deletion_request_blocks_bin_file_deletion() {
         paths: vec!["/bin/ls".to_string()],
         dry_run: false,
         trash: true,
+        allow_unsaved_work: false,
     };
 
     let result = validate_deletion_request(&req);
@@ -120,6 +122,7 @@ fn test_validate_deletion_request_blocks_usr_file_deletion() {
         paths: vec!["/usr/bin/critical_tool".to_string()],
         dry_run: false,
         trash: true,
+        allow_unsaved_work: false,
     };
 
     let result = validate_deletion_request(&req);
@@ -138,6 +141,7 @@ fn test_validate_deletion_request_blocks_mixed_paths() {
         ],
         dry_run: false,
         trash: true,
+        allow_unsaved_work: false,
     };
 
     let result = validate_deletion_request(&req);
@@ -153,6 +157,7 @@ fn test_validate_deletion_request_allows_tmp_files() {
         paths: vec!["/tmp/safe_file.txt".to_string()],
         dry_run: true,
         trash: true,
+        allow_unsaved_work: false,
     };
 
     let result = validate_deletion_request(&req);
@@ -193,6 +198,7 @@ fn test_comprehensive_security_coverage() {
             paths: vec![path.to_string()],
             dry_run: false,
             trash: true,
+            allow_unsaved_work: false,
         };
         let del_result = validate_deletion_request(&del_req);
         assert!(
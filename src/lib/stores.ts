@@ -39,15 +39,29 @@ export interface SummaryStats {
 export interface LargeFile {
   path: string;
   size_mb: number;
+  allocated_mb: number;
+  reclaimable_mb: number;
   modified_ts?: number;
 }
 
 export interface BloatCategory {
   category_id: string;
   total_size_mb: number;
+  total_allocated_mb: number;
+  total_reclaimable_mb: number;
+  recommended_reclaimable_mb?: number;
   entries?: Array<{
     path: string;
     size_mb: number;
+    allocated_mb: number;
+    reclaimable_mb: number;
+    confidence?: "high" | "medium";
+    reason?: string;
+    project_root?: string;
+    project_last_modified?: number | null;
+    last_commit?: number | null;
+    inactive_days?: number | null;
+    recommended?: boolean;
     entry_type: string;
     description: string;
     safety: string;
@@ -63,9 +77,42 @@ export interface DuplicateSet {
   }>;
 }
 
+export interface SizeTreeNode {
+  path: string;
+  name: string;
+  is_dir: boolean;
+  size_mb: number;
+  allocated_mb: number;
+  file_count: number;
+  dir_count: number;
+  child_count: number;
+}
+
+export interface SizeTreeChildren {
+  parent: SizeTreeNode;
+  children: SizeTreeNode[]; // largest first
+  omitted_count: number;
+  omitted_size_mb: number;
+}
+
+export interface DuplicateDirSet {
+  fingerprint: string;
+  similarity: number; // 1.0 for identical trees
+  total_savable_mb: number;
+  entries: Array<{
+    path: string;
+    size_mb: number;
+    allocated_mb: number;
+    reclaimable_mb: number;
+    file_count: number;
+  }>;
+}
+
 export interface JunkCategory {
   category_id: string;
   total_size_kb: number;
+  total_allocated_kb: number;
+  total_reclaimable_kb: number;
   file_count: number;
   files?: Array<{
     path: string;
@@ -73,6 +120,229 @@ export interface JunkCategory {
   }>;
 }
 
+export interface ScanJobProgress {
+  current_path: string;
+  files_scanned: number;
+  dirs_scanned: number;
+  bytes_processed: number;
+  eta_seconds: number | null;
+  percentage: number;
+  phase: string;
+  warnings: string[];
+}
+
+// Emitted as "scan-job-progress" while running and "scan-job-finished" at the end
+export interface ScanJobInfo {
+  id: string;
+  scan_type: string;
+  root: string;
+  status: "running" | "completed" | "failed" | "cancelled" | "interrupted";
+  started_at: string;
+  finished_at: string | null;
+  error: string | null;
+  progress: ScanJobProgress | null;
+}
+
+// Emitted as "scan-results-chunk"; categories hold only this chunk's entries
+export interface ScanResultsChunk {
+  job_id: string;
+  sequence: number;
+  large_files: LargeFile[];
+  bloat: BloatCategory[];
+  junk: JunkCategory[];
+  dev_caches: Array<{
+    category_id: string;
+    display_name: string;
+    total_size_mb: number;
+    entry_count: number;
+    safety: string;
+    entries: Array<{
+      path: string;
+      size_mb: number;
+      description: string;
+      cleanup_command?: string | null;
+    }>;
+  }>;
+}
+
+// Returned by clean_cache
+export interface CacheCleanupResult {
+  path: string;
+  method: "native" | "delete";
+  command: string | null;
+  success: boolean;
+  exit_code: number | null;
+  timed_out: boolean;
+  stdout: string;
+  stderr: string;
+  error: string | null;
+  before_mb: number;
+  after_mb: number;
+  freed_mb: number;
+}
+
+// Returned by analyze_cache_versions; prune_paths go to cleanup_dirs
+export interface CachePruneReport {
+  cache_root: string;
+  layout: "maven" | "gradle" | "cargo" | "npm";
+  artifacts: Array<{
+    artifact: string;
+    total_size_mb: number;
+    prunable_mb: number;
+    versions: Array<{
+      version: string;
+      paths: string[];
+      size_mb: number;
+      reclaimable_mb: number;
+      keep: boolean;
+      reason: string;
+    }>;
+  }>;
+  total_prunable_mb: number;
+  prune_paths: string[];
+  lockfiles: string[];
+}
+
+// Returned by run_git_maintenance
+export interface GitMaintenanceResult {
+  repo_path: string;
+  action: "gc" | "prune" | "repack" | "reflog_expire" | "worktree_prune";
+  command: string;
+  dry_run: boolean;
+  estimated_mb: number;
+  estimate: string;
+  success: boolean;
+  exit_code: number | null;
+  timed_out: boolean;
+  stdout: string;
+  stderr: string;
+  error: string | null;
+  before_mb: number;
+  after_mb: number;
+  freed_mb: number;
+}
+
+// Returned by check_unsaved_work
+export interface UnsavedWork {
+  path: string;
+  repo_path: string;
+  uncommitted: number;
+  untracked: number;
+  unpushed: number;
+  stashes: number;
+}
+
+// Returned by analyze_git_artifacts
+export interface GitArtifactReport {
+  repo_path: string;
+  artifacts: GitArtifact[];
+  tracked_mb: number;
+  history_mb: number;
+  history_disk_mb: number;
+  untracked_mb: number;
+  gitignore_patch: string | null;
+}
+
+export interface GitArtifact {
+  kind: "bloat" | "junk";
+  category_id: string;
+  display_name: string;
+  paths: string[];
+  tracked: boolean;
+  file_count: number;
+  size_mb: number;
+  history_mb: number;
+  history_disk_mb: number;
+  ignore_pattern: string;
+  untrack_command: string | null;
+}
+
+// GitRepository.clone_group, from scan_git_repos
+export interface GitCloneGroup {
+  root_commits: string[];
+  remote_urls: string[];
+  total_size_mb: number;
+  total_allocated_mb: number;
+  deletable_mb: number;
+  clones: GitClone[];
+}
+
+export interface GitClone {
+  repo_path: string;
+  branch: string;
+  size_mb: number;
+  allocated_mb: number;
+  fully_pushed: boolean;
+  unsaved: UnsavedWork;
+  suggestion: "keep" | "delete" | "worktree" | "review";
+  reason: string;
+}
+
+// Returned by analyze_cargo_target
+export interface CargoTargetReport {
+  target_dir: string;
+  size_mb: number;
+  profiles: Array<{
+    name: string;
+    path: string;
+    size_mb: number;
+    incremental_mb: number;
+    deps_mb: number;
+    build_mb: number;
+    other_mb: number;
+    stale_toolchain_mb: number;
+    unit_count: number;
+    last_used: number;
+  }>;
+  doc_mb: number;
+  other_mb: number;
+  toolchain_count: number;
+}
+
+// Options for plan_cargo_target_prune
+export interface CargoPruneOptions {
+  incremental?: boolean;
+  older_than_days?: number | null;
+  stale_toolchains?: boolean;
+  profiles?: string[];
+  doc?: boolean;
+}
+
+// Returned by plan_cargo_target_prune; paths go to cleanup_dirs
+export interface CargoPrunePlan {
+  target_dir: string;
+  groups: Array<{ reason: string; paths: string[]; reclaimable_mb: number }>;
+  paths: string[];
+  reclaimable_mb: number;
+}
+
+// User-defined detector rule, as stored in detector_rules.toml / .disk-bloat-rules.toml
+export interface DetectorRule {
+  id: string;
+  name: string;
+  globs: string[];
+  markers?: string[];
+  min_size_mb?: number | null;
+  min_age_days?: number | null;
+  safety: "safe" | "caution" | "dangerous";
+  description?: string;
+}
+
+export interface RuleFile {
+  bloat: DetectorRule[];
+  junk: DetectorRule[];
+  cache: DetectorRule[];
+}
+
+// Returned by list_detector_rules
+export interface RuleListing {
+  builtin: RuleFile;
+  global: RuleFile;
+  global_path: string | null;
+  root: RuleFile | null;
+  root_path: string | null;
+}
+
 // ============================================================================
 // NAVIGATION & UI STATE
 // ============================================================================